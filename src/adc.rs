@@ -0,0 +1,160 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::gpio::{Gpio, IoPin, Mode, OutputPin};
+use crate::registry::{self, PinClaim};
+use std::{thread::sleep, time::Duration};
+
+// Pins used by the ADC0832 in the kit's lessons.
+const ADC_CS: u8 = 17;
+const ADC_CLK: u8 = 18;
+const ADC_DIO: u8 = 27;
+// Clock half period in µs. The ADC0832 is good up to 400kHz.
+const CLOCK_DELAY: u64 = 2;
+
+/// Common interface for analog to digital converters so sensor code doesn't
+/// care which chip is used.
+pub trait Adc {
+    /// Number of input channels.
+    fn channels(&self) -> u8;
+    /// Largest value [Adc::read()] can return.
+    fn max_value(&self) -> u16;
+    /// Reads the raw value of one of the channels starting from 0.
+    fn read(&mut self, channel: u8) -> Result<u16>;
+    /// Reads one of the channels as a fraction of full scale from 0.0 to 1.0.
+    fn read_ratio(&mut self, channel: u8) -> Result<f64> {
+        Ok(f64::from(self.read(channel)?) / f64::from(self.max_value()))
+    }
+}
+
+/// Structure used to model an ADC0832 dual channel 8 bit analog to digital
+/// converter using its 3 wire serial interface.
+///
+/// DI and DO are tied together so one pin is switched between output and
+/// input during each conversion.
+pub struct Adc0832 {
+    cs: OutputPin,
+    clk: OutputPin,
+    dio: IoPin,
+    _claim: PinClaim,
+}
+
+impl Adc0832 {
+    /// Gets the GPIO pins used in the kit's lessons.
+    pub fn new() -> Result<Self> {
+        Self::with_pins(ADC_CS, ADC_CLK, ADC_DIO)
+    }
+    /// Gets the given GPIO pins for chip select, clock, and data.
+    ///
+    /// Useful when the lesson pins are already taken by something like the
+    /// LCD1602.
+    pub fn with_pins(cs: u8, clk: u8, dio: u8) -> Result<Self> {
+        let claim = registry::claim("Adc0832", &[cs, clk, dio])?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        let mut cs = gpio
+            .get(cs)
+            .context("Failed to get chip select pin")?
+            .into_output();
+        cs.set_high();
+        let mut clk = gpio
+            .get(clk)
+            .context("Failed to get clock pin")?
+            .into_output();
+        clk.set_low();
+        let dio = gpio
+            .get(dio)
+            .context("Failed to get data pin")?
+            .into_io(Mode::Output);
+        Ok(Adc0832 {
+            cs,
+            clk,
+            dio,
+            _claim: claim,
+        })
+    }
+    /// Internal method to give one clock pulse.
+    fn pulse(&mut self) {
+        self.clk.set_high();
+        sleep(Duration::from_micros(CLOCK_DELAY));
+        self.clk.set_low();
+        sleep(Duration::from_micros(CLOCK_DELAY));
+    }
+}
+
+impl Adc for Adc0832 {
+    fn channels(&self) -> u8 {
+        2
+    }
+    fn max_value(&self) -> u16 {
+        u8::MAX.into()
+    }
+    /// Does a single ended conversion of the channel.
+    ///
+    /// The result is sent MSB first followed by the same bits LSB first and
+    /// the two are compared to catch any glitches.
+    fn read(&mut self, channel: u8) -> Result<u16> {
+        if channel >= self.channels() {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "ADC0832 channel {} is out of range",
+                channel
+            )));
+        }
+        self.dio.set_mode(Mode::Output);
+        self.cs.set_low();
+        self.clk.set_low();
+        // Start bit, single ended mode, then the channel.
+        for bit in [true, true, channel == 1].iter() {
+            if *bit {
+                self.dio.set_high();
+            } else {
+                self.dio.set_low();
+            }
+            sleep(Duration::from_micros(CLOCK_DELAY));
+            self.pulse();
+        }
+        // Once the multiplexer settles the chip takes over the data line.
+        self.dio.set_mode(Mode::Input);
+        self.pulse();
+        let mut msb_first = 0u8;
+        for _ in 0..8 {
+            msb_first = msb_first << 1 | self.dio.is_high() as u8;
+            self.pulse();
+        }
+        let mut lsb_first = 0u8;
+        for bit in 1..8 {
+            lsb_first |= (self.dio.is_high() as u8) << bit;
+            self.pulse();
+        }
+        // The LSB is shared by both halves.
+        lsb_first |= msb_first & 0x01;
+        self.cs.set_high();
+        self.dio.set_mode(Mode::Output);
+        if msb_first != lsb_first {
+            return Err(SuperKitError::Device(format!(
+                "ADC0832 conversion check failed: {:#04x} != {:#04x}",
+                msb_first, lsb_first
+            )));
+        }
+        Ok(msb_first.into())
+    }
+}
@@ -0,0 +1,380 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::{
+    gpio::{Gpio, InputPin, Trigger},
+    i2c::I2c,
+    spi::{Bus, Mode, SlaveSelect, Spi},
+};
+use crate::registry::{self, PinClaim};
+use log::error;
+use std::{
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+/// Default I2C address with SDO low.
+pub const ADDRESS: u16 = 0x53;
+// Registers.
+const DEVID: u8 = 0x00;
+const THRESH_TAP: u8 = 0x1d;
+const DUR: u8 = 0x21;
+const LATENT: u8 = 0x22;
+const WINDOW: u8 = 0x23;
+const TAP_AXES: u8 = 0x2a;
+const BW_RATE: u8 = 0x2c;
+const POWER_CTL: u8 = 0x2d;
+const INT_ENABLE: u8 = 0x2e;
+const INT_MAP: u8 = 0x2f;
+const INT_SOURCE: u8 = 0x30;
+const DATA_FORMAT: u8 = 0x31;
+const DATAX0: u8 = 0x32;
+// What DEVID reads back.
+const IDENTITY: u8 = 0xe5;
+// Register bits.
+const MEASURE: u8 = 0x08;
+const FULL_RES: u8 = 0x08;
+const SINGLE_TAP: u8 = 0x40;
+const DOUBLE_TAP: u8 = 0x20;
+const TAP_XYZ: u8 = 0x07;
+// SPI read and multiple byte bits added to the register address.
+const SPI_READ: u8 = 0x80;
+const SPI_MULTIPLE: u8 = 0x40;
+// The chip is good up to 5MHz.
+const SPI_CLOCK_SPEED: u32 = 5_000_000;
+/// Scale in g per count which in full resolution mode is the same for every
+/// range.
+pub const G_PER_LSB: f64 = 0.0039;
+
+/// How often the chip takes a reading.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataRate {
+    Hz6_25 = 0x06,
+    Hz12_5 = 0x07,
+    Hz25 = 0x08,
+    Hz50 = 0x09,
+    Hz100 = 0x0a,
+    Hz200 = 0x0b,
+    Hz400 = 0x0c,
+    Hz800 = 0x0d,
+    Hz1600 = 0x0e,
+    Hz3200 = 0x0f,
+}
+
+/// Full scale range of the chip.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Range {
+    G2,
+    G4,
+    G8,
+    G16,
+}
+
+/// Taps the chip can detect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TapEvent {
+    Single,
+    Double,
+}
+
+/// Settings used to detect taps.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TapConfig {
+    /// How hard a tap has to be in g, up to 16g.
+    pub threshold: f64,
+    /// Longest a tap can stay above the threshold, up to 159ms.
+    pub duration: Duration,
+    /// Quiet time after a tap before a second can start, up to 318ms.
+    pub latent: Duration,
+    /// Time after the latent time in which a second tap makes a double tap,
+    /// up to 318ms.
+    pub window: Duration,
+}
+
+/// Values from the datasheet's application examples which suit a finger tap.
+impl Default for TapConfig {
+    fn default() -> Self {
+        TapConfig {
+            threshold: 3.0,
+            duration: Duration::from_millis(10),
+            latent: Duration::from_millis(100),
+            window: Duration::from_millis(300),
+        }
+    }
+}
+
+/// How the chip is connected.
+enum Interface {
+    I2c(I2c),
+    Spi(Spi),
+}
+
+impl Interface {
+    /// Internal method to read one or more registers starting at the given
+    /// one.
+    fn read(&mut self, register: u8, buffer: &mut [u8]) -> Result<()> {
+        match self {
+            Interface::I2c(i2c) => i2c.write_read(&[register], buffer).context(format!(
+                "Failed to read ADXL345 register: {:#04x}",
+                register
+            )),
+            Interface::Spi(spi) => {
+                let mut command = register | SPI_READ;
+                if buffer.len() > 1 {
+                    command |= SPI_MULTIPLE;
+                }
+                let mut write = vec![0u8; buffer.len() + 1];
+                write[0] = command;
+                let mut read = vec![0u8; write.len()];
+                spi.transfer(&mut read, &write).context(format!(
+                    "Failed to read ADXL345 register: {:#04x}",
+                    register
+                ))?;
+                buffer.copy_from_slice(&read[1..]);
+                Ok(())
+            }
+        }
+    }
+    /// Internal method to write a register.
+    fn write(&mut self, register: u8, value: u8) -> Result<()> {
+        let context = || format!("Failed to write ADXL345 register: {:#04x}", register);
+        match self {
+            Interface::I2c(i2c) => i2c.smbus_write_byte(register, value).with_context(context),
+            Interface::Spi(spi) => spi
+                .write(&[register, value])
+                .map(|_| ())
+                .with_context(context),
+        }
+    }
+}
+
+/// Structure used to model an ADXL345 3 axis accelerometer on either the I2C
+/// or SPI bus.
+///
+/// Readings are in full resolution mode so they always have the same scale
+/// whatever the range.
+pub struct Adxl345 {
+    interface: Arc<Mutex<Interface>>,
+    range: Range,
+    data_rate: DataRate,
+    int_pin: Option<(InputPin, PinClaim)>,
+}
+
+impl Adxl345 {
+    /// Gets the chip at the default address on I2C bus 1.
+    pub fn new() -> Result<Self> {
+        Self::with_i2c_address(ADDRESS)
+    }
+    /// Gets the chip at the given I2C address, 0x1d if SDO is high.
+    pub fn with_i2c_address(address: u16) -> Result<Self> {
+        let mut i2c = I2c::new().context("Failed to get I2C bus, is I2C enabled")?;
+        i2c.set_slave_address(address)
+            .context(format!("Failed to set ADXL345 address: {:#04x}", address))?;
+        Self::setup(Interface::I2c(i2c))
+    }
+    /// Gets the chip on the given SPI bus and chip select using 4 wire SPI.
+    pub fn with_spi(bus: Bus, slave_select: SlaveSelect) -> Result<Self> {
+        let spi = Spi::new(bus, slave_select, SPI_CLOCK_SPEED, Mode::Mode3)
+            .context("Failed to get SPI instance")?;
+        Self::setup(Interface::Spi(spi))
+    }
+    /// Internal associative method (function) to check it really is an
+    /// ADXL345 then start measuring at 100Hz and ±2g.
+    fn setup(interface: Interface) -> Result<Self> {
+        let mut adxl = Adxl345 {
+            interface: Arc::new(Mutex::new(interface)),
+            range: Range::G2,
+            data_rate: DataRate::Hz100,
+            int_pin: None,
+        };
+        let identity = adxl.read_register(DEVID)?;
+        if identity != IDENTITY {
+            return Err(SuperKitError::Device(format!(
+                "Expected DEVID of {:#04x} but found {:#04x}, is it an ADXL345",
+                IDENTITY, identity
+            )));
+        }
+        adxl.set_range(Range::G2)?;
+        adxl.set_data_rate(DataRate::Hz100)?;
+        adxl.write_register(INT_ENABLE, 0)?;
+        adxl.write_register(POWER_CTL, MEASURE)?;
+        Ok(adxl)
+    }
+    /// Gives the range.
+    pub fn range(&self) -> Range {
+        self.range
+    }
+    /// Changes the range. Larger ranges don't clip as soon but the scale stays
+    /// the same.
+    pub fn set_range(&mut self, range: Range) -> Result<()> {
+        self.write_register(DATA_FORMAT, FULL_RES | range as u8)?;
+        self.range = range;
+        Ok(())
+    }
+    /// Gives the data rate.
+    pub fn data_rate(&self) -> DataRate {
+        self.data_rate
+    }
+    /// Changes how often the chip takes a reading. The default is 100Hz.
+    pub fn set_data_rate(&mut self, data_rate: DataRate) -> Result<()> {
+        self.write_register(BW_RATE, data_rate as u8)?;
+        self.data_rate = data_rate;
+        Ok(())
+    }
+    /// Reads all three axes at once so they are from the same sample.
+    pub fn read_raw(&mut self) -> Result<[i16; 3]> {
+        let mut buffer = [0u8; 6];
+        self.lock().read(DATAX0, &mut buffer)?;
+        let word = |i: usize| i16::from_le_bytes([buffer[i], buffer[i + 1]]);
+        Ok([word(0), word(2), word(4)])
+    }
+    /// Reads all three axes in g.
+    pub fn read(&mut self) -> Result<[f64; 3]> {
+        let raw = self.read_raw()?;
+        Ok([
+            f64::from(raw[0]) * G_PER_LSB,
+            f64::from(raw[1]) * G_PER_LSB,
+            f64::from(raw[2]) * G_PER_LSB,
+        ])
+    }
+    /// Turns on single and double tap detection on all axes using INT1.
+    pub fn enable_taps(&mut self, config: TapConfig) -> Result<()> {
+        // Scales from the datasheet.
+        let threshold = (config.threshold / 0.0625).round().clamp(1.0, 255.0) as u8;
+        let duration = (config.duration.as_secs_f64() / 0.000625)
+            .round()
+            .clamp(1.0, 255.0) as u8;
+        let latent = (config.latent.as_secs_f64() / 0.00125)
+            .round()
+            .clamp(1.0, 255.0) as u8;
+        let window = (config.window.as_secs_f64() / 0.00125)
+            .round()
+            .clamp(1.0, 255.0) as u8;
+        self.write_register(THRESH_TAP, threshold)?;
+        self.write_register(DUR, duration)?;
+        self.write_register(LATENT, latent)?;
+        self.write_register(WINDOW, window)?;
+        self.write_register(TAP_AXES, TAP_XYZ)?;
+        // Everything on INT1.
+        self.write_register(INT_MAP, 0)?;
+        self.write_register(INT_ENABLE, SINGLE_TAP | DOUBLE_TAP)?;
+        // Clear anything left over.
+        self.read_register(INT_SOURCE)?;
+        Ok(())
+    }
+    /// Turns off tap detection and stops any callback.
+    pub fn disable_taps(&mut self) -> Result<()> {
+        self.clear_tap()?;
+        self.write_register(INT_ENABLE, 0)?;
+        self.write_register(TAP_AXES, 0)
+    }
+    /// Checks if there has been a tap since the last check.
+    ///
+    /// A double tap also sets the single tap flag so is given instead.
+    pub fn poll_tap(&mut self) -> Result<Option<TapEvent>> {
+        let source = self.read_register(INT_SOURCE)?;
+        Ok(tap_event(source))
+    }
+    /// Calls the callback for each tap using an interrupt on the GPIO pin
+    /// connected to the chip's INT1 pin.
+    ///
+    /// Needs [Adxl345::enable_taps()] first and replaces any callback given
+    /// before.
+    pub fn on_tap<C>(&mut self, pin: u8, mut callback: C) -> Result<()>
+    where
+        C: FnMut(TapEvent) + Send + 'static,
+    {
+        self.clear_tap()?;
+        let claim = registry::claim("Adxl345", &[pin])?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        let mut pin = gpio
+            .get(pin)
+            .context(format!("Failed to get INT1 pin: {}", pin))?
+            .into_input_pulldown();
+        let interface = self.interface.clone();
+        pin.set_async_interrupt(Trigger::RisingEdge, move |_| {
+            // Reading the source also clears the interrupt.
+            let mut source = [0u8];
+            let result = interface
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .read(INT_SOURCE, &mut source);
+            match result {
+                Ok(()) => {
+                    if let Some(event) = tap_event(source[0]) {
+                        callback(event);
+                    }
+                }
+                Err(e) => error!(target: "adxl345", "Failed to read INT_SOURCE: {:?}", e),
+            }
+        })
+        .context("Failed to set INT1 interrupt")?;
+        self.int_pin = Some((pin, claim));
+        // Clear anything from before so the pin can go low for the next edge.
+        self.read_register(INT_SOURCE)?;
+        Ok(())
+    }
+    /// Stops calling the callback given to [Adxl345::on_tap()].
+    pub fn clear_tap(&mut self) -> Result<()> {
+        if let Some((mut pin, _claim)) = self.int_pin.take() {
+            pin.clear_async_interrupt()
+                .context("Failed to clear INT1 interrupt")?;
+        }
+        Ok(())
+    }
+    /// Internal method to read a single register.
+    fn read_register(&mut self, register: u8) -> Result<u8> {
+        let mut buffer = [0u8];
+        self.lock().read(register, &mut buffer)?;
+        Ok(buffer[0])
+    }
+    /// Internal method to write a register.
+    fn write_register(&mut self, register: u8, value: u8) -> Result<()> {
+        self.lock().write(register, value)
+    }
+    /// Internal method used to get at the interface shared with the interrupt.
+    fn lock(&self) -> MutexGuard<'_, Interface> {
+        self.interface
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Stop any interrupt and put the chip in standby before exiting.
+impl Drop for Adxl345 {
+    fn drop(&mut self) {
+        let _ = self.clear_tap();
+        let _ = self.write_register(POWER_CTL, 0);
+    }
+}
+
+/// Internal function to get the tap from the INT_SOURCE bits.
+fn tap_event(source: u8) -> Option<TapEvent> {
+    if source & DOUBLE_TAP != 0 {
+        Some(TapEvent::Double)
+    } else if source & SINGLE_TAP != 0 {
+        Some(TapEvent::Single)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,83 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::MatrixDisplay;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path, time::Duration};
+
+/// Structure holding a sequence of frames for the 8x8 dot matrix.
+///
+/// Animations are normally loaded from a TOML file like this so they can be
+/// changed without recompiling:
+///
+/// ```toml
+/// [[frames]]
+/// rows = [0x00, 0x66, 0xff, 0xff, 0xff, 0x7e, 0x3c, 0x18]
+/// duration = 400
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Animation {
+    pub frames: Vec<Frame>,
+}
+
+/// A single image in an animation and how long it is shown.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Frame {
+    /// Same format as [MatrixDisplay::draw_rows()].
+    pub rows: [u8; 8],
+    /// Time in milliseconds the frame is shown.
+    #[serde(default = "default_duration")]
+    pub duration: u64,
+}
+
+fn default_duration() -> u64 {
+    100
+}
+
+impl Animation {
+    /// Loads an animation from a TOML file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .context(format!("Failed to read animation file: {}", path.display()))?;
+        Self::from_toml(&text)
+    }
+    /// Parses an animation from TOML text.
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let animation: Animation = toml::from_str(text).context("Failed to parse animation")?;
+        if animation.frames.is_empty() {
+            return Err(SuperKitError::InvalidArgument(
+                "Animation has no frames".to_string(),
+            ));
+        }
+        Ok(animation)
+    }
+    /// Plays all the frames once in order.
+    pub fn play<M: MatrixDisplay>(&self, matrix: &mut M) -> Result<()> {
+        for frame in self.frames.iter() {
+            matrix.draw_rows(&frame.rows);
+            matrix.refresh_for(Duration::from_millis(frame.duration))?;
+        }
+        Ok(())
+    }
+}
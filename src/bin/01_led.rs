@@ -20,54 +20,61 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use anyhow::{Context, Result};
-use rppal::{
-    gpio::{Gpio, OutputPin},
-    system::DeviceInfo,
-};
-use std::{
-    sync::atomic::{AtomicBool, Ordering},
-    sync::Arc,
-    thread::sleep,
-    time::Duration,
-};
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{cli::Common, config::Lesson, run_lesson, say, Led};
 
 const LED_PIN: u8 = 17;
+// Led on and off time in milliseconds.
+const DELAY: u64 = 500;
+
+/// Blinks an LED on and off.
+#[derive(StructOpt)]
+#[structopt(name = "01_led")]
+struct Opt {
+    /// Gpio pin for the LED (default 17).
+    #[structopt(long)]
+    led: Option<u8>,
+    /// Led on and off time in milliseconds (default 500).
+    #[structopt(long)]
+    delay: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
+}
 
 fn main() -> Result<()> {
-    println!(
-        "01_LED started on a {}",
-        DeviceInfo::new()
-            .context("Failed to get new DeviceInfo")?
-            .model()
-    );
-    let mut led = setup()?;
-    // Stuff needed to nicely handle Ctrl-C from user.
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-    ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
+    let opt = Opt::from_args();
+    let config = with_options(&opt, opt.common.lesson("01_led")?)?;
+    run_lesson("01_LED", |ctx| {
+        // Saving a new delay in the config file takes effect right away.
+        ctx.watch_config(&opt.common);
+        let led_pin = config.pin("led", LED_PIN)?;
+        let mut delay = Duration::from_millis(config.get("delay", DELAY)?);
+        let mut led = Led::active_low(led_pin)?;
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            if let Some(new) = ctx.reload(|| -> Result<_> {
+                let new = with_options(&opt, opt.common.reload("01_led")?)?;
+                Ok(new.keep_from(&config, &["led"]).get("delay", DELAY)?)
+            }) {
+                delay = Duration::from_millis(new);
+                say!("delay = {:?}", delay);
+            }
+            say!("... led on");
+            led.on()?;
+            ctx.sleep(delay);
+            say!("led off ...");
+            led.off()?;
+            ctx.sleep(delay);
+        }
+        Ok(())
     })
-    .context("Error setting Ctrl-C handler")?;
-    // Loop until Ctrl-C is received.
-    while running.load(Ordering::SeqCst) {
-        println!("... led on");
-        led.set_low();
-        sleep(Duration::from_millis(500));
-        println!("led off ...");
-        led.set_high();
-        sleep(Duration::from_millis(500));
-    }
-    println!("\n01_LED stopped");
-    Ok(())
 }
 
-fn setup() -> Result<OutputPin> {
-    let gpio = Gpio::new().context("Failed to get GPIO instance")?;
-    let mut led = gpio
-        .get(LED_PIN)
-        .context("Failed to get led pin")?
-        .into_output();
-    led.set_high();
-    Ok(led)
+/// Puts any settings given on the command line over the ones from the config
+/// file.
+fn with_options(opt: &Opt, config: Lesson) -> Result<Lesson> {
+    Ok(config.with("led", opt.led)?.with("delay", opt.delay)?)
 }
@@ -20,63 +20,79 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use anyhow::{Context, Result};
-use rppal::{
-    gpio::{Gpio, InputPin, OutputPin},
-    system::DeviceInfo,
-};
-use std::{
-    sync::atomic::{AtomicBool, Ordering},
-    sync::Arc,
-    thread::sleep,
-    time::Duration,
-};
+// Run with `--toggle` to have each press switch the LED on or off and leave it
+// that way like a table lamp instead of it only being on while the button is
+// held down.
+
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{cli::Common, run_lesson, say, Button, ButtonEvent, Led};
 
 const BTN_PIN: u8 = 18;
 const LED_PIN: u8 = 17;
+// Button polling delay in milliseconds.
+const DELAY: u64 = 5;
 
-fn main() -> Result<()> {
-    println!(
-        "02_BtnAndLed started on a {}",
-        DeviceInfo::new()
-            .context("Failed to get new DeviceInfo")?
-            .model()
-    );
-    let (button, mut led) = setup()?;
-    // Stuff needed to nicely handle Ctrl-C from user.
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-    ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
-    })
-    .context("Error setting Ctrl-C handler")?;
-    // Loop until Ctrl-C is received.
-    while running.load(Ordering::SeqCst) {
-        // Like the C code the button acts as a momentary switch with no latching.
-        if button.is_high() {
-            println!("led off ...");
-            led.set_high();
-        } else {
-            println!("... led on");
-            led.set_low();
-        }
-        // Acts as a crude form of debounce.
-        sleep(Duration::from_millis(200));
-    }
-    println!("\n02_BtnAndLed stopped");
-    Ok(())
+/// Turns an LED on with a button.
+#[derive(StructOpt)]
+#[structopt(name = "02_btn_and_led")]
+struct Opt {
+    /// Gpio pin for the button (default 18).
+    #[structopt(long)]
+    button: Option<u8>,
+    /// Gpio pin for the LED (default 17).
+    #[structopt(long)]
+    led: Option<u8>,
+    /// Button polling delay in milliseconds (default 5).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Have each press switch the LED on or off and leave it that way.
+    #[structopt(long)]
+    toggle: bool,
+    #[structopt(flatten)]
+    common: Common,
 }
 
-fn setup() -> Result<(InputPin, OutputPin)> {
-    let gpio = Gpio::new().context("Failed to get GPIO instance")?;
-    let button = gpio
-        .get(BTN_PIN)
-        .context("Failed to get button pin")?
-        .into_input_pullup();
-    let mut led = gpio
-        .get(LED_PIN)
-        .context("Failed to get led pin")?
-        .into_output();
-    led.set_high();
-    Ok((button, led))
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("02_btn_and_led")?
+        .with("button", opt.button)?
+        .with("led", opt.led)?
+        .with("delay", opt.delay)?;
+    run_lesson("02_BtnAndLed", |ctx| {
+        let button_pin = config.pin("button", BTN_PIN)?;
+        let led_pin = config.pin("led", LED_PIN)?;
+        let delay = Duration::from_millis(config.get("delay", DELAY)?);
+        let mut button = Button::new(button_pin)?;
+        let mut led = Led::active_low(led_pin)?;
+        let toggle = config.get("toggle", false)? || opt.toggle;
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            match button.poll() {
+                // Latches on each press so releases are ignored.
+                Some(ButtonEvent::Pressed) if toggle => {
+                    led.toggle()?;
+                    say!("led {}", if led.is_on() { "on" } else { "off" });
+                }
+                Some(ButtonEvent::Released) if toggle => {}
+                // Like the C code the button acts as a momentary switch with no
+                // latching.
+                Some(ButtonEvent::Pressed) => {
+                    say!("... led on");
+                    led.on()?;
+                }
+                Some(ButtonEvent::Released) => {
+                    say!("led off ...");
+                    led.off()?;
+                }
+                None => {}
+            }
+            ctx.sleep(delay);
+        }
+        Ok(())
+    })
 }
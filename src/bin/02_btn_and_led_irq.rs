@@ -0,0 +1,94 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Same as 02_btn_and_led but driven by an interrupt instead of polling the
+// button. The LED is changed from the button's callback as soon as a debounced
+// press or release happens and nothing runs at all while the button is left
+// alone, so the CPU use is close to zero.
+// The main loop only wakes up now and then to check for Ctrl-C.
+
+use anyhow::Result;
+use log::error;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{cli::Common, run_lesson, say, Button, ButtonEvent, Led};
+
+const BTN_PIN: u8 = 18;
+const LED_PIN: u8 = 17;
+// Ctrl-C check delay in milliseconds.
+const DELAY: u64 = 100;
+
+/// Turns an LED on with a button using an interrupt.
+#[derive(StructOpt)]
+#[structopt(name = "02_btn_and_led_irq")]
+struct Opt {
+    /// Gpio pin for the button (default 18).
+    #[structopt(long)]
+    button: Option<u8>,
+    /// Gpio pin for the LED (default 17).
+    #[structopt(long)]
+    led: Option<u8>,
+    /// Ctrl-C check delay in milliseconds (default 100).
+    #[structopt(long)]
+    delay: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("02_btn_and_led_irq")?
+        .with("button", opt.button)?
+        .with("led", opt.led)?
+        .with("delay", opt.delay)?;
+    run_lesson("02_BtnAndLedIrq", |ctx| {
+        let btn_pin = config.pin("button", BTN_PIN)?;
+        let led_pin = config.pin("led", LED_PIN)?;
+        let delay = config.get("delay", DELAY)?;
+        let mut button = Button::new(btn_pin)?;
+        let mut led = Led::active_low(led_pin)?;
+        // Like the C code the button acts as a momentary switch with no latching.
+        button.on_event(move |event| {
+            let result = match event {
+                ButtonEvent::Pressed => {
+                    say!("... led on");
+                    led.on()
+                }
+                ButtonEvent::Released => {
+                    say!("led off ...");
+                    led.off()
+                }
+            };
+            if let Err(e) = result {
+                error!("{:?}", e);
+            }
+        })?;
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            ctx.sleep(Duration::from_millis(delay));
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,148 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Logs a reed switch door sensor on GPIO 18 to door_log.csv with a timestamp
+// for each time the door is opened or closed and when it has been left open
+// for more than 30 seconds.
+// Run with `--file=<path>` to log somewhere else and `--alarm=<seconds>` to
+// change how long the door can be left open.
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use log::error;
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{cli::Common, run_lesson, say, DoorEvent, ReedSwitch};
+
+const REED_PIN: u8 = 18;
+const FILE: &str = "door_log.csv";
+// Time in seconds the door can be left open before it is logged.
+const ALARM: u64 = 30;
+// Ctrl-C check delay in milliseconds.
+const DELAY: u64 = 100;
+
+/// Logs a door opening and closing with a reed switch.
+#[derive(StructOpt)]
+#[structopt(name = "02_door_log")]
+struct Opt {
+    /// Gpio pin for the reed switch (default 18).
+    #[structopt(long)]
+    reed: Option<u8>,
+    /// Ctrl-C check delay in milliseconds (default 100).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// File the door events are logged to.
+    #[structopt(long, default_value = FILE)]
+    file: String,
+    /// Seconds the door can be open before the alarm goes off (default 30).
+    #[structopt(long)]
+    alarm: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("02_door_log")?
+        .with("reed", opt.reed)?
+        .with("delay", opt.delay)?;
+    run_lesson("02_DoorLog", |ctx| {
+        let reed_pin = config.pin("reed", REED_PIN)?;
+        let delay = config.get("delay", DELAY)?;
+        let path = opt.file;
+        let alarm = opt.alarm.unwrap_or(ALARM);
+        let file = open(&path)?;
+        let mut door = ReedSwitch::new(reed_pin)?;
+        say!("logging to {}, door is {}", path, state(door.is_open()));
+        let log = file.clone();
+        door.set_open_alarm(Duration::from_secs(alarm), move |open_for| {
+            write(&log, "left_open", Some(open_for));
+        });
+        let log = file.clone();
+        let mut opened_at = None;
+        door.on_event(move |event| match event {
+            DoorEvent::Opened => {
+                opened_at = Some(Instant::now());
+                write(&log, "opened", None);
+            }
+            DoorEvent::Closed => {
+                let open_for = opened_at.take().map(|at: Instant| at.elapsed());
+                write(&log, "closed", open_for);
+            }
+        })?;
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            ctx.sleep(Duration::from_millis(delay));
+        }
+        Ok(())
+    })
+}
+
+/// Opens the log for appending, adding the header if it is new.
+fn open(path: &str) -> Result<Arc<Mutex<File>>> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .context(format!("Failed to open log file: {}", path))?;
+    let is_new = file
+        .metadata()
+        .context("Failed to read log file size")?
+        .len()
+        == 0;
+    if is_new {
+        writeln!(file, "timestamp,event,open_seconds").context("Failed to write log header")?;
+    }
+    Ok(Arc::new(Mutex::new(file)))
+}
+
+/// Adds a line to the log and shows it.
+///
+/// Errors are only shown since they happen in the callbacks.
+fn write(file: &Mutex<File>, event: &str, open_for: Option<Duration>) {
+    let time = Local::now().format("%Y-%m-%d %H:%M:%S");
+    let open_for = open_for
+        .map(|d| format!("{:.1}", d.as_secs_f64()))
+        .unwrap_or_default();
+    let line = format!("{},{},{}", time, event, open_for);
+    say!("{}", line);
+    let mut file = file.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Err(e) = writeln!(file, "{}", line).and_then(|_| file.flush()) {
+        error!("Failed to write log: {}", e);
+    }
+}
+
+fn state(open: bool) -> &'static str {
+    if open {
+        "open"
+    } else {
+        "closed"
+    }
+}
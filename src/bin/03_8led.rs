@@ -20,16 +20,15 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use anyhow::{Context, Result};
-use rppal::{
-    gpio::{Gpio, OutputPin},
-    system::DeviceInfo,
-};
-use std::{
-    sync::atomic::{AtomicBool, Ordering},
-    sync::Arc,
-    thread::sleep,
-    time::Duration,
+// Run with `--pattern=<pattern>` to show one of the chaser patterns instead
+// where the pattern is knight_rider, ping_pong, sparkle, fill_drain, or a comma
+// separated list of masks like `0x81,0x42,0x24,0x18`.
+
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, config::Lesson, run_lesson, say, Led, LedBar, Pattern,
 };
 
 // Gpio pin numbers.
@@ -37,53 +36,110 @@ const PINS: [u8; 8] = [17, 18, 27, 22, 23, 24, 25, 4];
 // Led on time in milliseconds.
 const DELAY: u64 = 50;
 
+/// Flashes 8 LEDs in sequence.
+#[derive(StructOpt)]
+#[structopt(name = "03_8led")]
+struct Opt {
+    /// Gpio pins for the LEDs separated by commas (default 17, 18, 27, 22, 23, 24, 25, 4).
+    #[structopt(long, use_delimiter = true)]
+    pins: Option<Vec<u8>>,
+    /// Led on time in milliseconds (default 50).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Show a chaser pattern instead which is knight_rider, ping_pong, sparkle,
+    /// fill_drain, or a list of masks like 0x81,0x42,0x24,0x18.
+    #[structopt(long)]
+    pattern: Option<String>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
 fn main() -> Result<()> {
-    println!(
-        "03_8Led started on a {}",
-        DeviceInfo::new()
-            .context("Failed to get new DeviceInfo")?
-            .model()
-    );
-    let mut leds = setup(PINS)?;
-    // Stuff needed to nicely handle Ctrl-C from user.
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-    ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
-    })
-    .context("Error setting Ctrl-C handler")?;
-    // Loop until Ctrl-C is received.
-    'outer: while running.load(Ordering::SeqCst) {
-        // Flash LEDs in sequence.
-        println!("forward ...");
-        for led in leds.iter_mut() {
-            led.set_low();
-            sleep(Duration::from_millis(DELAY));
-            led.set_high();
-        }
-        // Improves Ctrl-C responsiveness.
-        if !running.load(Ordering::SeqCst) {
-            break 'outer;
+    let opt = Opt::from_args();
+    let config = with_options(&opt, opt.common.lesson("03_8led")?)?;
+    // Checked before starting so a typo in the pattern is caught right away.
+    let (delay, pattern) = timing(&config)?;
+    run_lesson("03_8Led", |ctx| {
+        // Saving a new delay or pattern in the config file takes effect right
+        // away.
+        ctx.watch_config(&opt.common);
+        let pins = config.pins("pins", PINS)?;
+        let mut delay = delay;
+        let mut iterations = opt.common.iterations();
+        if let Some(mut pattern) = pattern {
+            let mut bar = LedBar::with_pins(pins)?;
+            say!("pattern = {:?}", pattern);
+            while ctx.is_running() && iterations.again() {
+                if let Some((new_delay, new_pattern)) = ctx.reload(|| -> Result<_> {
+                    let new = with_options(&opt, opt.common.reload("03_8led")?)?;
+                    timing(&new.keep_from(&config, &["pins"]))
+                }) {
+                    delay = new_delay;
+                    // Going back to the plain sequence needs a restart.
+                    if let Some(new_pattern) = new_pattern {
+                        pattern = new_pattern;
+                    }
+                    say!("pattern = {:?}, delay = {:?}", pattern, delay);
+                }
+                bar.play(&pattern, delay)?;
+            }
+            return Ok(());
         }
-        // Flash LEDs in reverse sequence.
-        println!("... reverse");
-        for led in leds.iter_mut().rev() {
-            led.set_low();
-            sleep(Duration::from_millis(DELAY));
-            led.set_high();
+        let mut leds = setup(pins)?;
+        // Loop until Ctrl-C is received.
+        'outer: while ctx.is_running() && iterations.again() {
+            if let Some((new_delay, _)) = ctx.reload(|| -> Result<_> {
+                let new = with_options(&opt, opt.common.reload("03_8led")?)?;
+                timing(&new.keep_from(&config, &["pins", "pattern"]))
+            }) {
+                delay = new_delay;
+                say!("delay = {:?}", delay);
+            }
+            // Flash LEDs in sequence.
+            say!("forward ...");
+            for led in leds.iter_mut() {
+                led.on()?;
+                ctx.sleep(delay);
+                led.off()?;
+            }
+            // Improves Ctrl-C responsiveness.
+            if !ctx.is_running() {
+                break 'outer;
+            }
+            // Flash LEDs in reverse sequence.
+            say!("... reverse");
+            for led in leds.iter_mut().rev() {
+                led.on()?;
+                ctx.sleep(delay);
+                led.off()?;
+            }
         }
-    }
-    println!("\n03_8Led stopped");
-    Ok(())
+        Ok(())
+    })
+}
+
+/// Puts any settings given on the command line over the ones from the config
+/// file.
+fn with_options(opt: &Opt, config: Lesson) -> Result<Lesson> {
+    Ok(config
+        .with("pins", opt.pins.clone())?
+        .with("delay", opt.delay)?
+        .with("pattern", opt.pattern.clone())?)
+}
+
+/// Gets the delay and any pattern from the settings.
+fn timing(config: &Lesson) -> Result<(Duration, Option<Pattern>)> {
+    let delay = Duration::from_millis(config.get("delay", DELAY)?);
+    let pattern = config
+        .get::<Option<String>>("pattern", None)?
+        .map(|pattern| pattern.parse::<Pattern>())
+        .transpose()?;
+    Ok((delay, pattern))
 }
 
-fn setup(pins: [u8; 8]) -> Result<Vec<OutputPin>> {
-    let gpio = Gpio::new().context("Failed to get GPIO instance")?;
-    let mut outputs = Vec::new();
-    for pin in pins.iter() {
-        let mut led = gpio.get(*pin).context("Failed to get pin")?.into_output();
-        led.set_high();
-        outputs.push(led);
-    }
-    Ok(outputs)
+fn setup(pins: [u8; 8]) -> Result<Vec<Led>> {
+    Ok(pins
+        .iter()
+        .map(|pin| Led::active_low(*pin))
+        .collect::<Result<_, _>>()?)
 }
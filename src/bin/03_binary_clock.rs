@@ -0,0 +1,78 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Reuses the 8 LEDs from lesson 3 to show part of the current time in binary
+// with LED 0 being the lowest bit.
+// Shows the seconds by default but can be given one of seconds, minutes,
+// hours, day, or month as an argument to show that instead.
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Datelike, Local, Timelike};
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{cli::Common, run_lesson, say, LedBar};
+
+/// Shows the time in binary on the 8 LEDs.
+#[derive(StructOpt)]
+#[structopt(name = "03_binary_clock")]
+struct Opt {
+    /// What to show which is seconds, minutes, hours, day, or month.
+    #[structopt(default_value = "seconds")]
+    field: String,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    opt.common.start()?;
+    run_lesson("03_BinaryClock", |ctx| {
+        let field = opt.field;
+        let value: fn(&DateTime<Local>) -> u32 = match field.as_str() {
+            "seconds" => |now| now.second(),
+            "minutes" => |now| now.minute(),
+            "hours" => |now| now.hour(),
+            "day" => |now| now.day(),
+            "month" => |now| now.month(),
+            _ => bail!(
+                "Unknown field \"{}\", use seconds, minutes, hours, day, or month",
+                field
+            ),
+        };
+        let mut bar = LedBar::new()?;
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            let now = Local::now();
+            let shown = value(&now) as u8;
+            if shown != bar.mask() {
+                say!("{} = {:3} = {:#010b}", field, shown, shown);
+                bar.set_mask(shown)?;
+            }
+            // Sleep until just after the start of the next second so the change
+            // lines up with the clock ticking over.
+            let nanos = u64::from(now.nanosecond().min(999_999_999));
+            ctx.sleep(Duration::from_nanos(1_000_000_000 - nanos));
+        }
+        Ok(())
+    })
+}
@@ -20,71 +20,68 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+// The brighter/dimmer ramp that used to be here has become the Breather in the
+// library which follows a smooth sine wave instead of straight ramps.
+
 use anyhow::{Context, Result};
-use rppal::{
-    gpio::{Gpio, OutputPin},
-    system::DeviceInfo,
-};
-use std::{
-    sync::atomic::{AtomicBool, Ordering},
-    sync::Arc,
-    thread::sleep,
-    time::Duration,
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::hal::gpio::{Gpio, OutputPin};
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, pwm::SOFT_FREQUENCY, run_lesson, say, Breather,
 };
 
 const LED_PIN: u8 = 18;
-const FREQUENCY: f64 = 1000.0;
+// Time in milliseconds for the LED to go from off to fully on and back.
+const PERIOD: u64 = 4000;
 const DELAY: u64 = 50;
 
+/// Makes an LED breathe with PWM.
+#[derive(StructOpt)]
+#[structopt(name = "04_pwm_led")]
+struct Opt {
+    /// Gpio pin for the LED (default 18).
+    #[structopt(long)]
+    led: Option<u8>,
+    /// Time in milliseconds for the LED to go from off to fully on and back (default 4000).
+    #[structopt(long)]
+    period: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
 fn main() -> Result<()> {
-    println!(
-        "04_PwmLed started on a {}",
-        DeviceInfo::new()
-            .context("Failed to get new DeviceInfo")?
-            .model()
-    );
-    let mut led = setup()?;
-    // Stuff needed to nicely handle Ctrl-C from user.
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-    ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
-    })
-    .context("Error setting Ctrl-C handler")?;
-    // Loop until Ctrl-C is received.
-    'outer: while running.load(Ordering::SeqCst) {
-        println!("brighter ...");
-        // Using inclusive end point for range.
-        for i in (0..=100).step_by(4) {
-            led.set_pwm_frequency(FREQUENCY, i as f64 / 100.0)
-                .context("Failed to change duty cycle")?;
-            sleep(Duration::from_millis(DELAY));
-        }
-        sleep(Duration::from_secs(1));
-        // Improves Ctrl-C responsiveness.
-        if !running.load(Ordering::SeqCst) {
-            break 'outer;
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("04_pwm_led")?
+        .with("led", opt.led)?
+        .with("period", opt.period)?;
+    run_lesson("04_PwmLed", |ctx| {
+        let led_pin = config.pin("led", LED_PIN)?;
+        let period = Duration::from_millis(config.get("period", PERIOD)?);
+        let led = setup(led_pin)?;
+        let breather = Breather::new(led, period)?;
+        let mut iterations = opt.common.iterations();
+        say!("breathing ...");
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            ctx.sleep(Duration::from_millis(DELAY));
         }
-        println!("... dimmer");
-        for i in (0..=100).rev().step_by(4) {
-            led.set_pwm_frequency(FREQUENCY, i as f64 / 100.0)
-                .context("Failed to change duty cycle")?;
-            sleep(Duration::from_millis(DELAY));
-        }
-        sleep(Duration::from_secs(1));
-    }
-    println!("\n04_PwmLed stopped");
-    Ok(())
+        // Turns the LED off.
+        drop(breather);
+        Ok(())
+    })
 }
 
-fn setup() -> Result<OutputPin> {
+fn setup(pin: u8) -> Result<OutputPin> {
     let gpio = Gpio::new().context("Failed to get GPIO instance")?;
     let mut led = gpio
-        .get(LED_PIN)
+        .get(pin)
         .context("Failed to get led pin")?
         .into_output();
     led.set_low();
-    led.set_pwm_frequency(FREQUENCY, 0.0)
+    led.set_pwm_frequency(SOFT_FREQUENCY, 0.0)
         .context("Failed to initialize PWM for led pin")?;
     Ok(led)
 }
@@ -0,0 +1,98 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Shows the built-in effects on a strip of WS2812 NeoPixels with its data line
+// on the SPI MOSI pin (GPIO 10). Needs SPI enabled with raspi-config.
+// Run with `--pixels=<count>` for strips that aren't 8 pixels long,
+// `--brightness=<0.0-1.0>` to change the brightness, and `--rgbw` for SK6812
+// RGBW strips.
+
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, neopixel::Effect, run_lesson, say, Color, NeoPixel,
+};
+
+const PIXELS: usize = 8;
+const BRIGHTNESS: f64 = 0.2;
+// Time in milliseconds each frame is shown.
+const DELAY: u64 = 50;
+// Number of cycles of each effect before moving on.
+const CYCLES: usize = 3;
+
+/// Plays effects on a NeoPixel strip.
+#[derive(StructOpt)]
+#[structopt(name = "05_neopixel")]
+struct Opt {
+    /// Time in milliseconds each frame is shown (default 50).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Number of pixels on the strip (default 8).
+    #[structopt(long)]
+    pixels: Option<usize>,
+    /// Brightness from 0.0 to 1.0 (default 0.2).
+    #[structopt(long)]
+    brightness: Option<f64>,
+    /// The strip has a white LED in each pixel as well.
+    #[structopt(long)]
+    rgbw: bool,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt.common.lesson("05_neopixel")?.with("delay", opt.delay)?;
+    run_lesson("05_NeoPixel", |ctx| {
+        let delay = config.get("delay", DELAY)?;
+        let pixels = opt.pixels.unwrap_or(PIXELS);
+        let brightness = opt.brightness.unwrap_or(BRIGHTNESS);
+        let mut strip = NeoPixel::new(pixels)?;
+        strip.set_rgbw(opt.rgbw)?;
+        strip.set_brightness(brightness);
+        let effects = [
+            Effect::Rainbow,
+            Effect::Chase {
+                color: Color::ORANGE,
+                length: 3,
+            },
+            Effect::Theater(Color::WHITE),
+            Effect::Wipe(Color::BLUE),
+        ];
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        'outer: while ctx.is_running() && iterations.again() {
+            for effect in effects.iter() {
+                say!("{:?}", effect);
+                for _ in 0..CYCLES {
+                    strip.play(effect, Duration::from_millis(delay))?;
+                    // Improves Ctrl-C responsiveness.
+                    if !ctx.is_running() {
+                        break 'outer;
+                    }
+                }
+            }
+        }
+        Ok(())
+    })
+}
@@ -23,21 +23,24 @@
 // Since the Python code went with global mutable variables everywhere and the
 // C code is doing its own very different thing here I've decided it time to
 // show a much more idiomatic Rust way of doing things.
-// I'm introducing a structure with an implantation to contain what up to now
-// would have been just global scope functions.
-// The constants have been left in global scope as there is no real benefit to
-// doing something different with them.
+// The RgbPwm structure that started out here now lives in the library along
+// with a Color type so other lessons can share them.
+// Run with `--hardware` to use hardware PWM for the green LED on GPIO 18 which
+// needs the pwm overlay enabled in /boot/config.txt.
+// Run with `--rainbow` to smoothly sweep through all the hues instead of
+// stepping through the COLORS table.
+// Run with `--fade` to smoothly fade from each color in the table to the next
+// instead of jumping.
+// Run with `--common-anode` if your RGB LED has a common anode instead of a
+// common cathode.
+// Run with `--color=<color>` to just show one color like `--color=orange` or
+// `--color=#FF8800` until Ctrl-C.
 
-use anyhow::{Context, Result};
-use rppal::{
-    gpio::{Gpio, OutputPin},
-    system::DeviceInfo,
-};
-use std::{
-    sync::atomic::{AtomicBool, Ordering},
-    sync::Arc,
-    thread::sleep,
-    time::Duration,
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, rgb::Polarity, run_lesson, say, Color, Easing, RgbPwm,
 };
 
 const COLORS: [u32; 55] = [
@@ -54,106 +57,94 @@ const COLORS: [u32; 55] = [
     0xFFFFFF, 0xBFBFBF, 0x7F7F7F, 0x3F3F3F, 0x000000, // dim white
 ];
 const DELAY: u64 = 500;
-const FREQUENCY: f64 = 2000.0;
+// Time in seconds to go once around the color wheel.
+const RAINBOW_TIME: u64 = 5;
 // Gpio pin numbers.
 const PINS: [u8; 3] = [17, 18, 27];
 
-/// Structure for Pulse Width Modulated RGB led.
-pub struct RgbPwm {
-    red: OutputPin,
-    green: OutputPin,
-    blue: OutputPin,
-}
-
-impl RgbPwm {
-    /// More idiomatic way of doing setup.
-    pub fn new() -> Result<Self> {
-        let gpio = Gpio::new().context("Failed to get GPIO instance")?;
-        let mut red = gpio
-            .get(PINS[0])
-            .context("Failed to get red LED")?
-            .into_output();
-        red.set_high();
-        red.set_pwm_frequency(FREQUENCY, 0.0)
-            .context("Failed to initialize PWM for red LED")?;
-        let mut green = gpio
-            .get(PINS[1])
-            .context("Failed to get green LED")?
-            .into_output();
-        green.set_high();
-        green
-            .set_pwm_frequency(FREQUENCY, 0.0)
-            .context("Failed to initialize PWM for green LED")?;
-        let mut blue = gpio
-            .get(PINS[2])
-            .context("Failed to get blue LED")?
-            .into_output();
-        blue.set_high();
-        blue.set_pwm_frequency(FREQUENCY, 0.0)
-            .context("Failed to initialize PWM for blue LED")?;
-        Ok(RgbPwm { red, green, blue })
-    }
-    /// Internal associative method (function).
-    fn scale(x: u32) -> f64 {
-        // (x - in_min) * (out_max - out_min) / (in_max - in_min) + out_min
-        // Better (more accurate) to just pre-calculate multiplier where minimums
-        // are all zero.
-        x as f64 * 3.92156862745098e-3f64
-    }
-    /// Externally accessible method of instance used to modify state.
-    pub fn set_color(&mut self, color: u32) -> Result<()> {
-        // Extract each value from given color.
-        // Showing explicit type info only on the first variable.
-        let red: u32 = (color & 0xFF0000) >> 16;
-        let green = (color & 0x00FF00) >> 8;
-        let blue = color & 0x0000FF;
-        // Scale from 0-255 range to 0-100 duty cycle.
-        // Showing explicit type info only on the first shadow variable.
-        let red: f64 = Self::scale(red);
-        let green = Self::scale(green);
-        let blue = Self::scale(blue);
-        // Set the new duty cycles.
-        self.red
-            .set_pwm_frequency(FREQUENCY, red)
-            .context("Failed to change red duty cycle")?;
-        self.green
-            .set_pwm_frequency(FREQUENCY, green)
-            .context("Failed to change green duty cycle")?;
-        self.blue
-            .set_pwm_frequency(FREQUENCY, blue)
-            .context("Failed to change blue duty cycle")?;
-        Ok(())
-    }
+/// Shows colors on a RGB LED.
+#[derive(StructOpt)]
+#[structopt(name = "05_rgb")]
+struct Opt {
+    /// Gpio pins for the red, green, and blue LEDs separated by commas (default 17, 18, 27).
+    #[structopt(long, use_delimiter = true)]
+    pins: Option<Vec<u8>>,
+    /// Delay in milliseconds (default 500).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Use hardware PWM for the green LED on GPIO 18.
+    #[structopt(long)]
+    hardware: bool,
+    /// The RGB LED has a common anode instead of a common cathode.
+    #[structopt(long)]
+    common_anode: bool,
+    /// Just show one color like orange or #FF8800.
+    #[structopt(long)]
+    color: Option<Color>,
+    /// Smoothly sweep through all the hues.
+    #[structopt(long)]
+    rainbow: bool,
+    /// Smoothly fade from each color to the next.
+    #[structopt(long)]
+    fade: bool,
+    #[structopt(flatten)]
+    common: Common,
 }
 
 fn main() -> Result<()> {
-    println!(
-        "05_RGB started on a {}",
-        DeviceInfo::new()
-            .context("Failed to get new DeviceInfo")?
-            .model()
-    );
-    let mut leds: RgbPwm = RgbPwm::new()?;
-    // Stuff needed to nicely handle Ctrl-C from user.
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-    ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
-    })
-    .context("Error setting Ctrl-C handler")?;
-    // Loop until Ctrl-C is received.
-    'outer: while running.load(Ordering::SeqCst) {
-        for color in COLORS.iter() {
-            println!("color = {:#08X?}", color);
-            leds.set_color(*color)?;
-            sleep(Duration::from_millis(DELAY));
-            // Improves Ctrl-C responsiveness.
-            if !running.load(Ordering::SeqCst) {
-                break 'outer;
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("05_rgb")?
+        .with("pins", opt.pins.clone())?
+        .with("delay", opt.delay)?;
+    run_lesson("05_RGB", |ctx| {
+        let pins = config.pins("pins", PINS)?;
+        let delay = Duration::from_millis(config.get("delay", DELAY)?);
+        let polarity = if config.get("common_anode", false)? || opt.common_anode {
+            Polarity::CommonAnode
+        } else {
+            Polarity::CommonCathode
+        };
+        let mut leds: RgbPwm = if opt.hardware {
+            RgbPwm::with_hardware_pwm(pins[0], pins[1], pins[2], polarity)?
+        } else {
+            RgbPwm::with_pins(pins[0], pins[1], pins[2], polarity)?
+        };
+        let mut iterations = opt.common.iterations();
+        if let Some(color) = opt.color {
+            say!("color = {:#08X?}", color.to_hex());
+            leds.set_color(color)?;
+            while ctx.is_running() && iterations.again() {
+                ctx.sleep(delay);
             }
+            return Ok(());
         }
-        sleep(Duration::from_secs(1));
-    }
-    println!("\n05_RGB stopped");
-    Ok(())
+        if opt.rainbow {
+            while ctx.is_running() && iterations.again() {
+                leds.rainbow(Duration::from_secs(RAINBOW_TIME))?;
+            }
+            return Ok(());
+        }
+        let fade = opt.fade;
+        // Loop until Ctrl-C is received.
+        'outer: while ctx.is_running() && iterations.again() {
+            for color in COLORS.iter() {
+                say!("color = {:#08X?}", color);
+                if fade {
+                    leds.fade_to(Color::from_hex(*color), delay, Easing::Sine)?;
+                    leds.wait_fade()?;
+                } else {
+                    leds.set_color(Color::from_hex(*color))?;
+                    ctx.sleep(delay);
+                }
+                // Improves Ctrl-C responsiveness.
+                if !ctx.is_running() {
+                    break 'outer;
+                }
+            }
+            ctx.sleep(Duration::from_secs(1));
+        }
+        Ok(())
+    })
 }
@@ -0,0 +1,101 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Uses a TTP223 touch sensor on GPIO 22 as an on/off switch for the RGB LED
+// from lesson 5. Each time the LED is turned on it shows the next color.
+// Run with `--momentary` to only light the LED while the pad is touched.
+
+use anyhow::Result;
+use log::error;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, rgb::Polarity, run_lesson, say, Color, RgbPwm, TouchEvent, TouchSensor,
+};
+
+const TOUCH_PIN: u8 = 22;
+// Gpio pin numbers.
+const PINS: [u8; 3] = [17, 18, 27];
+// Ctrl-C check delay in milliseconds.
+const DELAY: u64 = 100;
+
+/// Turns a RGB LED on and off with a touch sensor.
+#[derive(StructOpt)]
+#[structopt(name = "05_touch_rgb")]
+struct Opt {
+    /// Gpio pin for the touch sensor (default 22).
+    #[structopt(long)]
+    touch: Option<u8>,
+    /// Gpio pins for the red, green, and blue LEDs separated by commas (default 17, 18, 27).
+    #[structopt(long, use_delimiter = true)]
+    pins: Option<Vec<u8>>,
+    /// Ctrl-C check delay in milliseconds (default 100).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Only light the LED while the sensor is touched.
+    #[structopt(long)]
+    momentary: bool,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("05_touch_rgb")?
+        .with("touch", opt.touch)?
+        .with("pins", opt.pins.clone())?
+        .with("delay", opt.delay)?;
+    run_lesson("05_TouchRgb", |ctx| {
+        let touch_pin = config.pin("touch", TOUCH_PIN)?;
+        let pins = config.pins("pins", PINS)?;
+        let delay = config.get("delay", DELAY)?;
+        let mut leds = RgbPwm::with_pins(pins[0], pins[1], pins[2], Polarity::CommonCathode)?;
+        let mut touch = TouchSensor::new(touch_pin)?;
+        touch.set_toggle_mode(!opt.momentary);
+        // Skip black and off.
+        let mut colors = Color::NAMED.iter().skip(2).cycle();
+        touch.on_event(move |event| {
+            let color = match event {
+                TouchEvent::Touched | TouchEvent::Toggled(true) => {
+                    let (name, color) = colors.next().copied().unwrap_or(("white", Color::WHITE));
+                    say!("on: {}", name);
+                    color
+                }
+                TouchEvent::Released | TouchEvent::Toggled(false) => {
+                    say!("off");
+                    Color::BLACK
+                }
+            };
+            if let Err(e) = leds.set_color(color) {
+                error!("{:?}", e);
+            }
+        })?;
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            ctx.sleep(Duration::from_millis(delay));
+        }
+        Ok(())
+    })
+}
@@ -20,53 +20,76 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+// Run with `--alarm=<alarm>` to play one of the alarm patterns instead where
+// the alarm is single, sos, fast, or slow.
+
 use anyhow::{Context, Result};
-use rppal::{
-    gpio::{Gpio, OutputPin},
-    system::DeviceInfo,
-};
-use std::{
-    sync::atomic::{AtomicBool, Ordering},
-    sync::Arc,
-    thread::sleep,
-    time::Duration,
-};
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::hal::gpio::{Gpio, OutputPin};
+use superkit_rust_code_for_raspberrypi::{cli::Common, run_lesson, say, ActiveBuzzer, Alarm};
 
 const PIN: u8 = 17;
 const DELAY: u64 = 100;
 
+/// Beeps an active buzzer.
+#[derive(StructOpt)]
+#[structopt(name = "06_beep")]
+struct Opt {
+    /// Gpio pin for the buzzer (default 17).
+    #[structopt(long)]
+    pin: Option<u8>,
+    /// Delay in milliseconds (default 100).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Play an alarm pattern instead which is single, sos, fast, or slow.
+    #[structopt(long)]
+    alarm: Option<Alarm>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
 fn main() -> Result<()> {
-    println!(
-        "06_Beep started on a {}",
-        DeviceInfo::new()
-            .context("Failed to get new DeviceInfo")?
-            .model()
-    );
-    let mut pin = setup()?;
-    // Stuff needed to nicely handle Ctrl-C from user.
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-    ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("06_beep")?
+        .with("pin", opt.pin)?
+        .with("delay", opt.delay)?;
+    run_lesson("06_Beep", |ctx| {
+        let pin_number = config.pin("pin", PIN)?;
+        let delay = Duration::from_millis(config.get("delay", DELAY)?);
+        let alarm = opt.alarm;
+        let mut iterations = opt.common.iterations();
+        if let Some(alarm) = alarm {
+            let buzzer = ActiveBuzzer::new(pin_number)?;
+            say!("alarm = {:?}", alarm);
+            buzzer.start(alarm);
+            // A single beep ends by itself so stop waiting once it is done.
+            while ctx.is_running() && buzzer.is_sounding() {
+                ctx.sleep(delay);
+            }
+            buzzer.stop();
+            return Ok(());
+        }
+        let mut pin = setup(pin_number)?;
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            say!("beep");
+            pin.set_low();
+            ctx.sleep(delay);
+            say!("silent");
+            pin.set_high();
+            ctx.sleep(delay);
+        }
+        Ok(())
     })
-    .context("Error setting Ctrl-C handler")?;
-    // Loop until Ctrl-C is received.
-    while running.load(Ordering::SeqCst) {
-        println!("beep");
-        pin.set_low();
-        sleep(Duration::from_millis(DELAY));
-        println!("silent");
-        pin.set_high();
-        sleep(Duration::from_millis(DELAY));
-    }
-    println!("\n06_Beep stopped");
-    Ok(())
 }
 
-fn setup() -> Result<OutputPin> {
+fn setup(pin: u8) -> Result<OutputPin> {
     let gpio = Gpio::new().context("Failed to get GPIO instance")?;
     let mut pin = gpio
-        .get(PIN)
+        .get(pin)
         .context("Failed to get led pin")?
         .into_output();
     pin.set_high();
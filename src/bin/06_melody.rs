@@ -0,0 +1,89 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Plays a tune on a passive buzzer wired like the active one in lesson 6.
+// Run with `--hardware` to use hardware PWM on GPIO 18 which needs
+// `dtoverlay=pwm,pin=18,func=2` in /boot/config.txt and the buzzer driven
+// through a NPN transistor.
+
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::hal::pwm::Channel;
+use superkit_rust_code_for_raspberrypi::{cli::Common, run_lesson, Buzzer, Note};
+
+const PIN: u8 = 17;
+// Time in milliseconds of one beat.
+const BEAT: u64 = 400;
+// Twinkle Twinkle Little Star as note names and beats.
+#[rustfmt::skip]
+const TUNE: [(&str, u32); 28] = [
+    ("C4", 1), ("C4", 1), ("G4", 1), ("G4", 1), ("A4", 1), ("A4", 1), ("G4", 2),
+    ("F4", 1), ("F4", 1), ("E4", 1), ("E4", 1), ("D4", 1), ("D4", 1), ("C4", 2),
+    ("G4", 1), ("G4", 1), ("F4", 1), ("F4", 1), ("E4", 1), ("E4", 1), ("D4", 2),
+    ("G4", 1), ("G4", 1), ("F4", 1), ("F4", 1), ("E4", 1), ("E4", 1), ("D4", 2),
+];
+
+/// Plays a tune on a passive buzzer.
+#[derive(StructOpt)]
+#[structopt(name = "06_melody")]
+struct Opt {
+    /// Gpio pin for the buzzer (default 17).
+    #[structopt(long)]
+    pin: Option<u8>,
+    /// Use hardware PWM on GPIO 18 instead.
+    #[structopt(long)]
+    hardware: bool,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt.common.lesson("06_melody")?.with("pin", opt.pin)?;
+    run_lesson("06_Melody", |ctx| {
+        let pin = config.pin("pin", PIN)?;
+        let mut buzzer = if opt.hardware {
+            Buzzer::hardware(Channel::Pwm0)?
+        } else {
+            Buzzer::new(pin)?
+        };
+        let melody = TUNE
+            .iter()
+            .map(|(name, beats)| Ok((name.parse::<Note>()?, Duration::from_millis(BEAT) * *beats)))
+            .collect::<Result<Vec<(Note, Duration)>>>()?;
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        'outer: while ctx.is_running() && iterations.again() {
+            // A bar at a time improves Ctrl-C responsiveness.
+            for bar in melody.chunks(7) {
+                buzzer.play_melody(bar)?;
+                if !ctx.is_running() {
+                    break 'outer;
+                }
+            }
+            ctx.sleep(Duration::from_millis(BEAT * 2));
+        }
+        buzzer.stop()?;
+        Ok(())
+    })
+}
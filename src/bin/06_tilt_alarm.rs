@@ -0,0 +1,88 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Sounds the active buzzer from lesson 6 while a ball tilt switch on GPIO 18
+// is tilted and goes quiet again once it is back upright.
+
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, run_lesson, say, ActiveBuzzer, Alarm, Orientation, TiltSwitch,
+};
+
+const BUZZER_PIN: u8 = 17;
+const TILT_PIN: u8 = 18;
+// Ctrl-C check delay in milliseconds.
+const DELAY: u64 = 100;
+
+/// Sounds an alarm when a tilt switch is tipped over.
+#[derive(StructOpt)]
+#[structopt(name = "06_tilt_alarm")]
+struct Opt {
+    /// Gpio pin for the buzzer (default 17).
+    #[structopt(long)]
+    buzzer: Option<u8>,
+    /// Gpio pin for the tilt switch (default 18).
+    #[structopt(long)]
+    tilt: Option<u8>,
+    /// Ctrl-C check delay in milliseconds (default 100).
+    #[structopt(long)]
+    delay: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("06_tilt_alarm")?
+        .with("buzzer", opt.buzzer)?
+        .with("tilt", opt.tilt)?
+        .with("delay", opt.delay)?;
+    run_lesson("06_TiltAlarm", |ctx| {
+        let buzzer_pin = config.pin("buzzer", BUZZER_PIN)?;
+        let tilt_pin = config.pin("tilt", TILT_PIN)?;
+        let delay = config.get("delay", DELAY)?;
+        let buzzer = ActiveBuzzer::new(buzzer_pin)?;
+        let mut tilt = TiltSwitch::new(tilt_pin)?;
+        say!("orientation = {:?}", tilt.orientation());
+        if tilt.orientation() == Orientation::Tilted {
+            buzzer.start(Alarm::Fast);
+        }
+        let alarm = buzzer.handle();
+        tilt.on_change(move |orientation| {
+            say!("orientation = {:?}", orientation);
+            match orientation {
+                Orientation::Tilted => alarm.start(Alarm::Fast),
+                Orientation::Upright => alarm.stop(),
+            }
+        })?;
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            ctx.sleep(Duration::from_millis(delay));
+        }
+        Ok(())
+    })
+}
@@ -20,88 +20,80 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use anyhow::{Context, Result};
-use rppal::{
-    gpio::{Gpio, OutputPin},
-    system::DeviceInfo,
-};
-use std::{
-    sync::atomic::{AtomicBool, Ordering},
-    sync::Arc,
-    thread::sleep,
-    time::Duration,
-};
+// The motor is ramped up to speed instead of being switched straight on so
+// small motors don't draw enough current to brown out the Pi.
 
-const MOTOR_PIN1: u8 = 17;
-const MOTOR_PIN2: u8 = 18;
-const MOTOR_ENABLE: u8 = 27;
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{cli::Common, run_lesson, say, DcMotor};
+
+// Gpio pin numbers for IN1, IN2, and ENABLE.
+const PINS: [u8; 3] = [17, 18, 27];
 const DELAY: u64 = 5000;
+// Time in milliseconds to get up to full speed.
+const RAMP_TIME: u64 = 1000;
 
-fn main() -> Result<()> {
-    println!(
-        "07_Motor started on a {}",
-        DeviceInfo::new()
-            .context("Failed to get new DeviceInfo")?
-            .model()
-    );
-    let (mut motor1, mut motor2, mut enable) = setup()?;
-    // Stuff needed to nicely handle Ctrl-C from user.
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-    ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
-    })
-    .context("Error setting Ctrl-C handler")?;
-    // Loop until Ctrl-C is received.
-    'outer: while running.load(Ordering::SeqCst) {
-        println!("motor clockwise ...");
-        motor1.set_high();
-        motor2.set_low();
-        enable.set_high();
-        sleep(Duration::from_millis(DELAY));
-        // Improves Ctrl-C responsiveness.
-        if !running.load(Ordering::SeqCst) {
-            break 'outer;
-        }
-        println!("stopped");
-        enable.set_low();
-        sleep(Duration::from_millis(DELAY));
-        // Improves Ctrl-C responsiveness.
-        if !running.load(Ordering::SeqCst) {
-            break 'outer;
-        }
-        println!("motor counter-clockwise ...");
-        motor1.set_low();
-        motor2.set_high();
-        enable.set_high();
-        sleep(Duration::from_millis(DELAY));
-        // Improves Ctrl-C responsiveness.
-        if !running.load(Ordering::SeqCst) {
-            break 'outer;
-        }
-        println!("stopped");
-        enable.set_low();
-        sleep(Duration::from_millis(DELAY));
-    }
-    enable.set_low();
-    println!("\n07_Motor ended");
-    Ok(())
+/// Runs a DC motor one way and then the other.
+#[derive(StructOpt)]
+#[structopt(name = "07_motor")]
+struct Opt {
+    /// Gpio pins for the motor's IN1, IN2, and ENABLE separated by commas (default 17, 18, 27).
+    #[structopt(long, use_delimiter = true)]
+    pins: Option<Vec<u8>>,
+    /// Delay in milliseconds (default 5000).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Time in milliseconds to get up to full speed (default 1000).
+    #[structopt(long)]
+    ramp_time: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
 }
 
-fn setup() -> Result<(OutputPin, OutputPin, OutputPin)> {
-    let gpio = Gpio::new().context("Failed to get GPIO instance")?;
-    let mut enable = gpio
-        .get(MOTOR_ENABLE)
-        .context("Failed to get enable pin")?
-        .into_output();
-    enable.set_low();
-    let motor1 = gpio
-        .get(MOTOR_PIN1)
-        .context("Failed to get motor1 pin")?
-        .into_output();
-    let motor2 = gpio
-        .get(MOTOR_PIN2)
-        .context("Failed to get motor2 pin")?
-        .into_output();
-    Ok((motor1, motor2, enable))
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("07_motor")?
+        .with("pins", opt.pins.clone())?
+        .with("delay", opt.delay)?
+        .with("ramp_time", opt.ramp_time)?;
+    run_lesson("07_Motor", |ctx| {
+        let pins = config.pins("pins", PINS)?;
+        let delay = Duration::from_millis(config.get("delay", DELAY)?);
+        let ramp_time = Duration::from_millis(config.get("ramp_time", RAMP_TIME)?);
+        let mut motor = DcMotor::with_pins(pins[0], pins[1], pins[2])?;
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        'outer: while ctx.is_running() && iterations.again() {
+            say!("motor clockwise ...");
+            motor.ramp_to(1.0, ramp_time)?;
+            ctx.sleep(delay);
+            // Improves Ctrl-C responsiveness.
+            if !ctx.is_running() {
+                break 'outer;
+            }
+            say!("stopped");
+            motor.coast()?;
+            ctx.sleep(delay);
+            // Improves Ctrl-C responsiveness.
+            if !ctx.is_running() {
+                break 'outer;
+            }
+            say!("motor counter-clockwise ...");
+            motor.ramp_to(-1.0, ramp_time)?;
+            ctx.sleep(delay);
+            // Improves Ctrl-C responsiveness.
+            if !ctx.is_running() {
+                break 'outer;
+            }
+            say!("stopped");
+            motor.coast()?;
+            ctx.sleep(delay);
+        }
+        motor.coast()?;
+        say!("\n07_Motor ended");
+        Ok(())
+    })
 }
@@ -0,0 +1,184 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Drives the lesson 7 motor live from the keyboard instead of the fixed
+// forward/reverse loop.
+//   Up or +     speed up by 10% in the current direction
+//   Down or -   slow down by 10%, keep going to reverse
+//   Right       run forward at the current speed
+//   Left        run in reverse at the current speed
+//   Space       brake
+//   c           coast
+//   q or Esc    quit
+// The terminal is put in raw mode so keys are seen without waiting for Enter
+// which also means Ctrl-C shows up as a key press instead of a signal.
+
+use anyhow::{Context, Result};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    terminal,
+};
+use std::{io::Write, time::Duration};
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{cli::Common, motor::Direction, run_lesson, say, DcMotor};
+
+// Speed change for each key press.
+const STEP: f64 = 0.1;
+// Time in milliseconds to wait for a key before checking again.
+const POLL_TIME: u64 = 100;
+// Time in milliseconds to ramp between speeds.
+const RAMP_TIME: u64 = 200;
+
+/// Puts the terminal back into normal mode even on errors.
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> Result<Self> {
+        terminal::enable_raw_mode().context("Failed to put terminal in raw mode")?;
+        Ok(RawMode)
+    }
+}
+
+/// Insure the terminal is usable again before exiting.
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Drives a DC motor from the keyboard.
+#[derive(StructOpt)]
+#[structopt(name = "07_motor_keys")]
+struct Opt {
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    opt.common.start()?;
+    run_lesson("07_Motor_Keys", |ctx| {
+        say!("Up/+ faster, Down/- slower, Right forward, Left reverse,");
+        say!("Space brake, c coast, q or Esc to quit");
+        let mut motor = DcMotor::new()?;
+        let raw = RawMode::enable()?;
+        let mut speed = 0.0;
+        show(&motor, speed)?;
+        while ctx.is_running() {
+            if !event::poll(Duration::from_millis(POLL_TIME)).context("Failed to poll keyboard")? {
+                continue;
+            }
+            let key = match event::read().context("Failed to read keyboard")? {
+                Event::Key(key) => key,
+                _ => continue,
+            };
+            match key {
+                KeyEvent {
+                    code: KeyCode::Char('c'),
+                    modifiers: KeyModifiers::CONTROL,
+                }
+                | KeyEvent {
+                    code: KeyCode::Char('q'),
+                    ..
+                }
+                | KeyEvent {
+                    code: KeyCode::Esc, ..
+                } => break,
+                KeyEvent {
+                    code: KeyCode::Up, ..
+                }
+                | KeyEvent {
+                    code: KeyCode::Char('+'),
+                    ..
+                } => speed = step(speed, STEP),
+                KeyEvent {
+                    code: KeyCode::Down,
+                    ..
+                }
+                | KeyEvent {
+                    code: KeyCode::Char('-'),
+                    ..
+                } => speed = step(speed, -STEP),
+                KeyEvent {
+                    code: KeyCode::Right,
+                    ..
+                } => speed = speed.abs(),
+                KeyEvent {
+                    code: KeyCode::Left,
+                    ..
+                } => speed = -speed.abs(),
+                KeyEvent {
+                    code: KeyCode::Char(' '),
+                    ..
+                } => {
+                    speed = 0.0;
+                    motor.stop()?;
+                    show(&motor, speed)?;
+                    continue;
+                }
+                KeyEvent {
+                    code: KeyCode::Char('c'),
+                    ..
+                } => {
+                    speed = 0.0;
+                    motor.coast()?;
+                    show(&motor, speed)?;
+                    continue;
+                }
+                _ => continue,
+            }
+            motor.ramp_to(speed, Duration::from_millis(RAMP_TIME))?;
+            show(&motor, speed)?;
+        }
+        motor.coast()?;
+        drop(raw);
+        Ok(())
+    })
+}
+
+/// Changes the speed by a step while keeping it in range and dodging
+/// rounding errors around zero.
+fn step(speed: f64, by: f64) -> f64 {
+    let speed = ((speed + by) / STEP).round() * STEP;
+    speed.clamp(-1.0, 1.0)
+}
+
+/// Overwrites the status line with the new target speed.
+fn show(motor: &DcMotor, speed: f64) -> Result<()> {
+    // The motor is still ramping so use where it is heading instead.
+    let heading = if speed > 0.0 {
+        Direction::Forward
+    } else if speed < 0.0 {
+        Direction::Reverse
+    } else if motor.is_ramping() {
+        // Ramping down to zero leaves the motor coasting.
+        Direction::Coasting
+    } else {
+        motor.direction()
+    };
+    print!(
+        "\rspeed = {:>4.0}%  {:<10}",
+        speed.abs() * 100.0,
+        format!("{:?}", heading)
+    );
+    std::io::stdout().flush().context("Failed to flush stdout")
+}
@@ -0,0 +1,97 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Sweeps a SG90 style servo back and forth between 0° and 180°.
+// The servo's signal wire goes to GPIO 18 with its power from the 5V pin.
+// Run with `--hardware` to use hardware PWM which needs
+// `dtoverlay=pwm,pin=18,func=2` in /boot/config.txt.
+
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::hal::pwm::Channel;
+use superkit_rust_code_for_raspberrypi::{cli::Common, run_lesson, say, Servo};
+
+const SERVO_PIN: u8 = 18;
+// Degrees moved each step.
+const STEP: usize = 5;
+// Time in milliseconds between steps.
+const DELAY: u64 = 30;
+
+/// Sweeps a servo back and forth.
+#[derive(StructOpt)]
+#[structopt(name = "07_servo")]
+struct Opt {
+    /// Gpio pin for the servo (default 18).
+    #[structopt(long)]
+    servo: Option<u8>,
+    /// Time in milliseconds between steps (default 30).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Use hardware PWM on GPIO 18 instead.
+    #[structopt(long)]
+    hardware: bool,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("07_servo")?
+        .with("servo", opt.servo)?
+        .with("delay", opt.delay)?;
+    run_lesson("07_Servo", |ctx| {
+        let servo_pin = config.pin("servo", SERVO_PIN)?;
+        let delay = config.get("delay", DELAY)?;
+        let mut servo = if opt.hardware {
+            Servo::hardware(Channel::Pwm0)?
+        } else {
+            Servo::software(servo_pin)?
+        };
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        'outer: while ctx.is_running() && iterations.again() {
+            say!("sweep up ...");
+            for angle in (0..=180).step_by(STEP) {
+                servo.set_angle(angle as f64)?;
+                ctx.sleep(Duration::from_millis(delay));
+                // Improves Ctrl-C responsiveness.
+                if !ctx.is_running() {
+                    break 'outer;
+                }
+            }
+            say!("... sweep down");
+            for angle in (0..=180).rev().step_by(STEP) {
+                servo.set_angle(angle as f64)?;
+                ctx.sleep(Duration::from_millis(delay));
+                // Improves Ctrl-C responsiveness.
+                if !ctx.is_running() {
+                    break 'outer;
+                }
+            }
+        }
+        servo.detach()?;
+        Ok(())
+    })
+}
@@ -0,0 +1,110 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// For kit owners that have built a small two wheeled robot chassis using both
+// channels of the L293D from lesson 7. Drives around a square by going
+// straight then spinning on the spot a quarter turn, four times over.
+// The left motor uses the lesson's pins and the right motor is on the L293D's
+// second channel with IN3 = 22, IN4 = 23, and EN2 = 24.
+// The turn time will need adjusting to suit the motors and the floor.
+
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, run_lesson, say, DcMotor, DifferentialDrive,
+};
+
+// Right motor pins.
+const RIGHT_PIN1: u8 = 22;
+const RIGHT_PIN2: u8 = 23;
+const RIGHT_ENABLE: u8 = 24;
+// Forward speed along each side.
+const SPEED: f64 = 0.6;
+// Time in milliseconds to drive along one side.
+const SIDE_TIME: u64 = 2000;
+// Time in milliseconds a spin on the spot takes to turn 90°.
+const TURN_TIME: u64 = 600;
+// Time in milliseconds for speed changes.
+const RAMP_TIME: u64 = 300;
+
+/// Drives a two motor robot around a square.
+#[derive(StructOpt)]
+#[structopt(name = "07_square")]
+struct Opt {
+    /// Gpio pin for the right motor's IN1 (default 22).
+    #[structopt(long)]
+    right_pin1: Option<u8>,
+    /// Gpio pin for the right motor's IN2 (default 23).
+    #[structopt(long)]
+    right_pin2: Option<u8>,
+    /// Gpio pin for the right motor's ENABLE (default 24).
+    #[structopt(long)]
+    right_enable: Option<u8>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("07_square")?
+        .with("right_pin1", opt.right_pin1)?
+        .with("right_pin2", opt.right_pin2)?
+        .with("right_enable", opt.right_enable)?;
+    run_lesson("07_Square", |ctx| {
+        let right_pin1 = config.pin("right_pin1", RIGHT_PIN1)?;
+        let right_pin2 = config.pin("right_pin2", RIGHT_PIN2)?;
+        let right_enable = config.pin("right_enable", RIGHT_ENABLE)?;
+        let left = DcMotor::new()?;
+        let right = DcMotor::with_pins(right_pin1, right_pin2, right_enable)?;
+        let mut drive = DifferentialDrive::new(left, right);
+        let ramp = Duration::from_millis(RAMP_TIME);
+        let mut iterations = opt.common.iterations();
+        // Each time round is one side and turn.
+        for side in 1..=4 {
+            if !iterations.again() {
+                break;
+            }
+            say!("side {} ...", side);
+            drive.ramp_drive(SPEED, 0.0, ramp)?;
+            ctx.sleep(Duration::from_millis(SIDE_TIME));
+            drive.ramp_drive(0.0, 0.0, ramp)?;
+            // Improves Ctrl-C responsiveness.
+            if !ctx.is_running() {
+                break;
+            }
+            say!("... turn");
+            drive.drive(0.0, SPEED)?;
+            ctx.sleep(Duration::from_millis(TURN_TIME));
+            drive.stop()?;
+            // Improves Ctrl-C responsiveness.
+            if !ctx.is_running() {
+                break;
+            }
+        }
+        drive.coast()?;
+        say!("\n07_Square ended");
+        Ok(())
+    })
+}
@@ -0,0 +1,111 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Uses the rotary encoder from lesson 8 as a dimmer for a LED wired like the
+// one in lesson 1 but on GPIO 22.
+// Turning the knob changes the brightness from 0% to 100%, clicking the switch
+// turns the LED on or off, and a long press goes back to 50%.
+
+use anyhow::{anyhow, Result};
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common,
+    encoder::{Acceleration, Bounds},
+    run_lesson, say, Gesture, Led, PwmOutput, RotaryEncoder,
+};
+
+const LED_PIN: u8 = 22;
+// Brightness in percent to start with and go back to on a long press.
+const HALF: i32 = 50;
+// Time in milliseconds a press needs to be held for a long press.
+const LONG_PRESS: u64 = 800;
+// Time in milliseconds between checks of the knob.
+const DELAY: u64 = 20;
+
+/// Dims an LED with a rotary encoder.
+#[derive(StructOpt)]
+#[structopt(name = "08_dimmer")]
+struct Opt {
+    /// Gpio pin for the LED (default 22).
+    #[structopt(long)]
+    led: Option<u8>,
+    /// Time in milliseconds between checks of the knob (default 20).
+    #[structopt(long)]
+    delay: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("08_dimmer")?
+        .with("led", opt.led)?
+        .with("delay", opt.delay)?;
+    run_lesson("08_Dimmer", |ctx| {
+        let led_pin = config.pin("led", LED_PIN)?;
+        let delay = config.get("delay", DELAY)?;
+        let mut led = Led::active_low(led_pin)?;
+        let mut encoder = RotaryEncoder::new()?;
+        // One percent per detent with fast turns counting for more.
+        encoder.set_divider(4)?;
+        encoder.set_acceleration(Some(Acceleration::default()));
+        encoder.set_range(0, 100, Bounds::Clamp)?;
+        encoder.set_value(HALF);
+        // No double clicks so a click doesn't have to wait to see if another
+        // follows.
+        encoder
+            .switch()
+            .ok_or_else(|| anyhow!("Rotary encoder has no push switch"))?
+            .set_gesture_times(Duration::from_secs(0), Duration::from_millis(LONG_PRESS));
+        let gestures = encoder.switch_gestures()?;
+        let mut iterations = opt.common.iterations();
+        let mut on = true;
+        let mut shown = None;
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            match gestures.recv_timeout(Duration::from_millis(delay)) {
+                Ok(Gesture::Click) => on = !on,
+                Ok(Gesture::LongPress) => {
+                    encoder.set_value(HALF);
+                    on = true;
+                }
+                _ => {}
+            }
+            let level = encoder.value();
+            if shown != Some((on, level)) {
+                shown = Some((on, level));
+                if on {
+                    say!("brightness = {}%", level);
+                    // Squaring makes the steps look more even to the eye.
+                    led.set_duty_cycle((level as f64 / 100.0).powi(2))?;
+                } else {
+                    say!("off");
+                    led.off()?;
+                }
+            }
+        }
+        Ok(())
+    })
+}
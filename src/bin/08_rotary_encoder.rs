@@ -20,93 +20,99 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use anyhow::{Context, Result};
-use rppal::gpio::Trigger;
-use rppal::{
-    gpio::{Gpio, InputPin, Level},
-    system::DeviceInfo,
-};
-use std::{
-    sync::atomic::{AtomicBool, AtomicI32, Ordering},
-    sync::Arc,
-    thread::sleep,
-    time::Duration,
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, encoder::Acceleration, run_lesson, say, Gesture, RotaryEncoder,
 };
 // Once again Python code is using all global mutable state which doesn't really
-// work well in Rust. Python code made decoding of rotary encoder overly hard so
-// changed to something simpler with only one piece of shared state for counter.
+// work well in Rust. The decoding now lives in the library as a RotaryEncoder
+// so other lessons can use it and uses a proper state table that counts every
+// edge on both pins which gives 4 counts per detent.
+// Run with `--divider=4` to get one count per detent instead.
+// Run with `--accelerate` to have fast turns count for more.
+// Clicking the switch zeroes the counter like the clear() from the Python code
+// but is now debounced, and a long press shows how fast the knob was turning.
 
 const DT_PIN: u8 = 17;
 const CLK_PIN: u8 = 18;
 const SW_PIN: u8 = 27;
-const DELAY: u64 = 10;
+// Time in milliseconds between checks of the switch.
+const DELAY: u64 = 100;
+
+/// Counts turns of a rotary encoder.
+#[derive(StructOpt)]
+#[structopt(name = "08_rotary_encoder")]
+struct Opt {
+    /// Gpio pin for the encoder's CLK (default 18).
+    #[structopt(long)]
+    clk: Option<u8>,
+    /// Gpio pin for the encoder's DT (default 17).
+    #[structopt(long)]
+    dt: Option<u8>,
+    /// Gpio pin for the switch (default 27).
+    #[structopt(long)]
+    sw: Option<u8>,
+    /// Time in milliseconds between checks of the switch (default 100).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Number of edges per count, use 4 for one count per detent.
+    #[structopt(long)]
+    divider: Option<u8>,
+    /// Have fast turns count for more.
+    #[structopt(long)]
+    accelerate: bool,
+    #[structopt(flatten)]
+    common: Common,
+}
 
 fn main() -> Result<()> {
-    println!(
-        "08_RotaryEncoder started on a {}",
-        DeviceInfo::new()
-            .context("Failed to get new DeviceInfo")?
-            .model()
-    );
-    let (clk, dt, mut sw) = setup()?;
-    // Used to access counter in main().
-    let counter = Arc::new(AtomicI32::new(0));
-    // Used in interrupt callback function to update counter.
-    let c = counter.clone();
-    // Declare an anonymous closure (function) that acts like the clear() from
-    // the Python code.
-    let clear = move |_: Level| {
-        c.store(0, Ordering::SeqCst);
-        println!("counter = {}", c.load(Ordering::SeqCst));
-    };
-    sw.set_async_interrupt(Trigger::FallingEdge, clear)?;
-    // Stuff needed to nicely handle Ctrl-C from user.
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-    ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
-    })
-    .context("Error setting Ctrl-C handler")?;
-    // Initialize current clk as last clk.
-    let mut last_clk = clk.read();
-    println!("counter = {}", counter.load(Ordering::SeqCst));
-    // Loop until Ctrl-C is received.
-    while running.load(Ordering::SeqCst) {
-        // Read the current pin values.
-        let current_clk = clk.read();
-        let current_dt = dt.read();
-        // Check if clk has change value.
-        if current_clk != last_clk {
-            // If clk and dt aren't the same encoder was rotated clockwise else
-            // rotated counter-clockwise.
-            if current_dt != current_clk {
-                counter.fetch_add(1, Ordering::SeqCst);
-            } else {
-                counter.fetch_add(-1, Ordering::SeqCst);
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("08_rotary_encoder")?
+        .with("clk", opt.clk)?
+        .with("dt", opt.dt)?
+        .with("sw", opt.sw)?
+        .with("delay", opt.delay)?;
+    run_lesson("08_RotaryEncoder", |ctx| {
+        let clk_pin = config.pin("clk", CLK_PIN)?;
+        let dt_pin = config.pin("dt", DT_PIN)?;
+        let sw_pin = config.pin("sw", SW_PIN)?;
+        let delay = Duration::from_millis(config.get("delay", DELAY)?);
+        let divider = opt.divider;
+        let mut encoder = RotaryEncoder::with_switch(clk_pin, dt_pin, sw_pin)?;
+        if let Some(divider) = divider {
+            encoder.set_divider(divider)?;
+        }
+        if opt.accelerate {
+            encoder.set_acceleration(Some(Acceleration::default()));
+        }
+        encoder.on_turn(|_, count| say!("counter = {}", count));
+        // The switch callback can't get at the encoder so main() gets the
+        // gestures through a channel instead.
+        let gestures = encoder.switch_gestures()?;
+        let mut iterations = opt.common.iterations();
+        say!("counter = {}", encoder.value());
+        let mut peak: f64 = 0.0;
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            match gestures.recv_timeout(delay) {
+                Ok(Gesture::Click) => {
+                    encoder.reset();
+                    say!("counter = {}", encoder.value());
+                }
+                Ok(Gesture::LongPress) => {
+                    say!("peak velocity = {:.1} steps/s", peak);
+                    peak = 0.0;
+                }
+                _ => {}
             }
-            println!("counter = {}", counter.load(Ordering::SeqCst));
+            peak = peak.max(encoder.velocity().abs());
         }
-        // Copy current clock value to last clock to use for next loop.
-        last_clk = current_clk;
-        sleep(Duration::from_millis(DELAY));
-    }
-    println!("\n08_RotaryEncoder stopped");
-    Ok(())
-}
-
-fn setup() -> Result<(InputPin, InputPin, InputPin)> {
-    let gpio = Gpio::new().context("Failed to get GPIO instance")?;
-    let dt = gpio
-        .get(DT_PIN)
-        .context("Failed to get dt pin")?
-        .into_input();
-    let clk = gpio
-        .get(CLK_PIN)
-        .context("Failed to get clk pin")?
-        .into_input();
-    let sw = gpio
-        .get(SW_PIN)
-        .context("Failed to get sw pin")?
-        .into_input_pullup();
-    Ok((clk, dt, sw))
+        say!("\ninvalid transitions = {}", encoder.invalid_transitions());
+        say!("08_RotaryEncoder stopped");
+        Ok(())
+    })
 }
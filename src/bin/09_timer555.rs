@@ -20,61 +20,107 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use anyhow::{Context, Result};
-use rppal::gpio::Trigger;
-use rppal::{
-    gpio::{Gpio, InputPin, Level},
-    system::DeviceInfo,
-};
-use std::{
-    sync::atomic::{AtomicBool, AtomicU64, Ordering},
-    sync::Arc,
-    thread::sleep,
-    time::Duration,
+// The counting that used to be done here now lives in the library as a
+// FrequencyCounter which turns the count into a live frequency readout along
+// with the high and low times and duty cycle.
+// Run with `--gate=<ms>` to change how long each reading counts for.
+// Every few readings a summary of the last `--window=<n>` readings, 60 by
+// default, is shown to make any drift in the circuit easy to spot.
+
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, frequency::Stats, run_lesson, say, FrequencyCounter,
 };
 
 const SIG_PIN: u8 = 17;
-const DELAY: u64 = 50;
+// Default number of readings the summary covers.
+const WINDOW: usize = 60;
+// Number of readings between summaries.
+const SUMMARY_EVERY: usize = 10;
 
-fn main() -> Result<()> {
-    println!(
-        "09_timer555 started on a {}",
-        DeviceInfo::new()
-            .context("Failed to get new DeviceInfo")?
-            .model()
-    );
-    let mut sig = setup()?;
-    // Used to access counter in main().
-    let counter = Arc::new(AtomicU64::new(0));
-    // Used in interrupt callback function to update counter.
-    let c = counter.clone();
-    // Declare an anonymous closure (function) that acts like the count() from
-    // the Python code.
-    let count = move |_: Level| {
-        c.fetch_add(1, Ordering::SeqCst);
-    };
-    sig.set_async_interrupt(Trigger::RisingEdge, count)?;
-    // Stuff needed to nicely handle Ctrl-C from user.
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-    ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
-    })
-    .context("Error setting Ctrl-C handler")?;
-    // Loop until Ctrl-C is received.
-    while running.load(Ordering::SeqCst) {
-        println!("counter = {}", counter.load(Ordering::SeqCst));
-        sleep(Duration::from_millis(DELAY));
-    }
-    println!("\n09_timer555 stopped");
-    Ok(())
+/// Measures the frequency from a 555 timer.
+#[derive(StructOpt)]
+#[structopt(name = "09_timer555")]
+struct Opt {
+    /// Gpio pin for the 555's output (default 17).
+    #[structopt(long)]
+    signal: Option<u8>,
+    /// Number of readings the summary covers (default 60).
+    #[structopt(long)]
+    window: Option<usize>,
+    /// Time in milliseconds each reading counts for.
+    #[structopt(long)]
+    gate: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
 }
 
-fn setup() -> Result<InputPin> {
-    let gpio = Gpio::new().context("Failed to get GPIO instance")?;
-    let sig = gpio
-        .get(SIG_PIN)
-        .context("Failed to get led pin")?
-        .into_input_pullup();
-    Ok(sig)
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("09_timer555")?
+        .with("signal", opt.signal)?
+        .with("window", opt.window)?;
+    run_lesson("09_timer555", |ctx| {
+        let sig_pin = config.pin("signal", SIG_PIN)?;
+        let gate = opt.gate;
+        let window = config.get("window", WINDOW)?;
+        let mut hz = Stats::new(window);
+        let mut duty = Stats::new(window);
+        let mut readings = 0;
+        let mut counter = FrequencyCounter::with_pin(sig_pin)?;
+        if let Some(gate) = gate {
+            counter.set_gate_time(Duration::from_millis(gate));
+        }
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            let frequency = counter.measure();
+            match frequency.period() {
+                Some(period) => say!(
+                    "frequency = {:.2} Hz, period = {:.3} ms, counter = {}",
+                    frequency.hz(),
+                    period.as_secs_f64() * 1000.0,
+                    counter.count()
+                ),
+                None => say!("no signal, counter = {}", counter.count()),
+            }
+            if let (Some(high), Some(low), Some(duty)) =
+                (frequency.high, frequency.low, frequency.duty_cycle())
+            {
+                say!(
+                    "  high = {:.3} ms, low = {:.3} ms, duty cycle = {:.1}%",
+                    high.as_secs_f64() * 1000.0,
+                    low.as_secs_f64() * 1000.0,
+                    duty * 100.0
+                );
+            }
+            hz.push(frequency.hz());
+            if let Some(cycle) = frequency.duty_cycle() {
+                duty.push(cycle * 100.0);
+            }
+            readings += 1;
+            if readings % SUMMARY_EVERY == 0 {
+                if let Some(hz) = hz.summary() {
+                    say!(
+                    "last {} readings: min = {:.2} Hz, max = {:.2} Hz, mean = {:.2} Hz, stddev = {:.3} Hz",
+                    hz.count, hz.min, hz.max, hz.mean, hz.std_dev
+                );
+                }
+                if let Some(duty) = duty.summary() {
+                    say!(
+                        "  duty cycle: min = {:.1}%, max = {:.1}%, mean = {:.1}%, stddev = {:.2}%",
+                        duty.min,
+                        duty.max,
+                        duty.mean,
+                        duty.std_dev
+                    );
+                }
+            }
+        }
+        Ok(())
+    })
 }
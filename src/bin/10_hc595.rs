@@ -26,16 +26,13 @@
 // and provide ways to change that state. The Python code could have done the
 // same but for whatever reason they chose not to.
 
-use anyhow::{Context, Result};
-use rppal::system::DeviceInfo;
-use std::{
-    sync::atomic::{AtomicBool, Ordering},
-    sync::Arc,
-    thread::sleep,
-    time::Duration,
-};
-use superkit_rust_code_for_raspberrypi::HC595;
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{cli::Common, run_lesson, say, HC595};
 
+// Gpio pin numbers for SDI, RCLK, and SRCLK.
+const PINS: [u8; 3] = [17, 18, 27];
 const DELAY: u64 = 100;
 // Use a two dimensional array to hold several sequences of LED modes.
 const MODES: [[u8; 8]; 4] = [
@@ -45,45 +42,56 @@ const MODES: [[u8; 8]; 4] = [
     [0x02, 0x03, 0x0b, 0x0f, 0x2f, 0x3f, 0xbf, 0xff], // blink mode 3
 ];
 
+/// Shows patterns on 8 LEDs through a 74HC595.
+#[derive(StructOpt)]
+#[structopt(name = "10_hc595")]
+struct Opt {
+    /// Gpio pins for the 74HC595's SDI, RCLK, and SRCLK separated by commas (default 17, 18, 27).
+    #[structopt(long, use_delimiter = true)]
+    pins: Option<Vec<u8>>,
+    /// Delay in milliseconds (default 100).
+    #[structopt(long)]
+    delay: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
 fn main() -> Result<()> {
-    println!(
-        "10_74HC595_LED started on a {}",
-        DeviceInfo::new()
-            .context("Failed to get new DeviceInfo")?
-            .model()
-    );
-    let mut hc595 = HC595::new()?;
-    // Stuff needed to nicely handle Ctrl-C from user.
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-    ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
-    })
-    .context("Error setting Ctrl-C handler")?;
-    // Loop until Ctrl-C is received.
-    'outer: while running.load(Ordering::SeqCst) {
-        // Unlike the Python code this code cycles through all the mode patterns.
-        for (row, mode) in MODES.iter().enumerate() {
-            println!("mode = {}", row);
-            println!("forward ...");
-            for data in mode.iter() {
-                hc595.serial_in(*data);
-                hc595.parallel_out();
-                sleep(Duration::from_millis(DELAY));
-            }
-            // Improves Ctrl-C responsiveness.
-            if !running.load(Ordering::SeqCst) {
-                break 'outer;
-            }
-            sleep(Duration::from_millis(DELAY));
-            println!("... reverse");
-            for data in mode.iter().rev() {
-                hc595.serial_in(*data);
-                hc595.parallel_out();
-                sleep(Duration::from_millis(DELAY));
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("10_hc595")?
+        .with("pins", opt.pins.clone())?
+        .with("delay", opt.delay)?;
+    run_lesson("10_74HC595_LED", |ctx| {
+        let pins = config.pins("pins", PINS)?;
+        let delay = Duration::from_millis(config.get("delay", DELAY)?);
+        let mut hc595 = HC595::with_pins(pins[0], pins[1], pins[2])?;
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        'outer: while ctx.is_running() && iterations.again() {
+            // Unlike the Python code this code cycles through all the mode patterns.
+            for (row, mode) in MODES.iter().enumerate() {
+                say!("mode = {}", row);
+                say!("forward ...");
+                for data in mode.iter() {
+                    hc595.serial_in(*data);
+                    hc595.parallel_out();
+                    ctx.sleep(delay);
+                }
+                // Improves Ctrl-C responsiveness.
+                if !ctx.is_running() {
+                    break 'outer;
+                }
+                ctx.sleep(delay);
+                say!("... reverse");
+                for data in mode.iter().rev() {
+                    hc595.serial_in(*data);
+                    hc595.parallel_out();
+                    ctx.sleep(delay);
+                }
             }
         }
-    }
-    println!("\n10_74HC595_LED stopped");
-    Ok(())
+        Ok(())
+    })
 }
@@ -28,69 +28,103 @@
 
 use anyhow::{Context, Result};
 use rand::{thread_rng, Rng};
-use rppal::{
-    gpio::{Gpio, InputPin},
-    system::DeviceInfo,
-};
 use std::{
     sync::atomic::{AtomicBool, Ordering},
     sync::Arc,
-    thread::sleep,
     time::Duration,
 };
-use superkit_rust_code_for_raspberrypi::HC595;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::hal::gpio::{Gpio, InputPin, Level, Trigger};
+use superkit_rust_code_for_raspberrypi::{cli::Common, run_lesson, say, SharedHC595};
 
 const BUTTON: u8 = 22;
+// Gpio pin numbers for SDI, RCLK, and SRCLK.
+const PINS: [u8; 3] = [17, 18, 27];
 const DELAY: u64 = 10;
 // Digits 1-6
 const SEG_CODES: [u8; 6] = [0x06, 0x5b, 0x4f, 0x66, 0x6d, 0x7d];
 
+/// Rolls a dice on a segment display.
+#[derive(StructOpt)]
+#[structopt(name = "11_dice")]
+struct Opt {
+    /// Gpio pin for the button (default 22).
+    #[structopt(long)]
+    button: Option<u8>,
+    /// Gpio pins for the 74HC595's SDI, RCLK, and SRCLK separated by commas (default 17, 18, 27).
+    #[structopt(long, use_delimiter = true)]
+    pins: Option<Vec<u8>>,
+    /// Delay in milliseconds (default 10).
+    #[structopt(long)]
+    delay: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
 fn main() -> Result<()> {
-    println!(
-        "11_Dice started on a {}",
-        DeviceInfo::new()
-            .context("Failed to get new DeviceInfo")?
-            .model()
-    );
-    // Random number generator.
-    let mut rng = thread_rng();
-    let (button, mut hc595) = setup()?;
-    // Stuff needed to nicely handle Ctrl-C from user.
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-    ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
-    })
-    .context("Error setting Ctrl-C handler")?;
-    println!("Press button to roll ...");
-    // Loop until Ctrl-C is received.
-    while running.load(Ordering::SeqCst) {
-        // Flash numbers in sequence.
-        for code in SEG_CODES.iter() {
-            hc595.serial_in(*code);
-            hc595.parallel_out();
-            if button.is_low() {
-                // New random number between 0 and 5 for index into SEG_CODES.
-                // Also displayed for user after adding 1 to it.
-                let num = rng.gen_range(0, 6);
-                hc595.serial_in(SEG_CODES[num]);
-                hc595.parallel_out();
-                println!("number = {}", num + 1);
-                sleep(Duration::from_secs(2));
-            } else {
-                sleep(Duration::from_millis(DELAY));
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("11_dice")?
+        .with("button", opt.button)?
+        .with("pins", opt.pins.clone())?
+        .with("delay", opt.delay)?;
+    run_lesson("11_Dice", |ctx| {
+        let button_pin = config.pin("button", BUTTON)?;
+        let pins = config.pins("pins", PINS)?;
+        let delay = Duration::from_millis(config.get("delay", DELAY)?);
+        let (mut button, hc595) = setup(button_pin, pins)?;
+        // Set by the button callback so the main loop knows to leave the rolled
+        // number showing for a while.
+        let rolled = Arc::new(AtomicBool::new(false));
+        let rl = rolled.clone();
+        // The callback gets its own handle to the same shift register.
+        let hc = hc595.clone();
+        let roll = move |_: Level| {
+            // Ignore extra presses (and switch bounce) while a roll is showing.
+            if rl.swap(true, Ordering::SeqCst) {
+                return;
+            }
+            // New random number between 0 and 5 for index into SEG_CODES.
+            // Also displayed for user after adding 1 to it.
+            let num = thread_rng().gen_range(0, 6);
+            hc.write(SEG_CODES[num]);
+            say!("number = {}", num + 1);
+        };
+        button
+            .set_async_interrupt(Trigger::FallingEdge, roll)
+            .context("Failed to set button interrupt")?;
+        let mut iterations = opt.common.iterations();
+        say!("Press button to roll ...");
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            // Flash numbers in sequence.
+            for code in SEG_CODES.iter() {
+                if rolled.load(Ordering::SeqCst) {
+                    // Leave the rolled number up so it can be seen.
+                    ctx.sleep(Duration::from_secs(2));
+                    rolled.store(false, Ordering::SeqCst);
+                }
+                // Checking the flag while holding the lock keeps a roll from being
+                // overwritten between the check and the write.
+                let mut chip = hc595.lock();
+                if !rolled.load(Ordering::SeqCst) {
+                    chip.serial_in(*code);
+                    chip.parallel_out();
+                }
+                drop(chip);
+                ctx.sleep(delay);
             }
         }
-    }
-    println!("\n11_Dice stopped");
-    Ok(())
+        Ok(())
+    })
 }
 
-fn setup() -> Result<(InputPin, HC595)> {
-    let hc595 = HC595::new()?;
+fn setup(button: u8, pins: [u8; 3]) -> Result<(InputPin, SharedHC595)> {
+    let hc595 = SharedHC595::with_pins(pins[0], pins[1], pins[2])?;
     let gpio = Gpio::new().context("Failed to get GPIO instance")?;
     let button = gpio
-        .get(BUTTON)
+        .get(button)
         .context("Failed to get button pin")?
         .into_input_pullup();
     Ok((button, hc595))
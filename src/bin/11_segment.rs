@@ -26,60 +26,84 @@
 // and provide ways to change that state. The Python code could have done the
 // same but for whatever reason they chose not to.
 
-use anyhow::{Context, Result};
-use rppal::system::DeviceInfo;
-use std::{
-    sync::atomic::{AtomicBool, Ordering},
-    sync::Arc,
-    thread::sleep,
-    time::Duration,
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, run_lesson, say, segment::encode_str, HC595,
 };
-use superkit_rust_code_for_raspberrypi::HC595;
 
+// Gpio pin numbers for SDI, RCLK, and SRCLK.
+const PINS: [u8; 3] = [17, 18, 27];
 const DELAY: u64 = 500;
 // Hexadecimal digits 0-F and decimal point.
 const SEG_CODES: [u8; 17] = [
     0x3f, 0x06, 0x5b, 0x4f, 0x66, 0x6d, 0x7d, 0x07, 0x7f, 0x6f, 0x77, 0x7c, 0x39, 0x5e, 0x79, 0x71,
     0x80,
 ];
+// Word spelled out one letter at a time after each pass.
+const WORD: &str = "HELP";
+
+/// Counts through the hex digits on a segment display.
+#[derive(StructOpt)]
+#[structopt(name = "11_segment")]
+struct Opt {
+    /// Gpio pins for the 74HC595's SDI, RCLK, and SRCLK separated by commas (default 17, 18, 27).
+    #[structopt(long, use_delimiter = true)]
+    pins: Option<Vec<u8>>,
+    /// Delay in milliseconds (default 500).
+    #[structopt(long)]
+    delay: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
+}
 
 fn main() -> Result<()> {
-    println!(
-        "11_Segment started on a {}",
-        DeviceInfo::new()
-            .context("Failed to get new DeviceInfo")?
-            .model()
-    );
-    let mut hc595 = HC595::new()?;
-    // Stuff needed to nicely handle Ctrl-C from user.
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-    ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
-    })
-    .context("Error setting Ctrl-C handler")?;
-    // Loop until Ctrl-C is received.
-    'outer: while running.load(Ordering::SeqCst) {
-        println!("forward ...");
-        for code in SEG_CODES.iter() {
-            println!("code = {:04X?}", code);
-            hc595.serial_in(*code);
-            hc595.parallel_out();
-            sleep(Duration::from_millis(DELAY));
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("11_segment")?
+        .with("pins", opt.pins.clone())?
+        .with("delay", opt.delay)?;
+    run_lesson("11_Segment", |ctx| {
+        let pins = config.pins("pins", PINS)?;
+        let delay = Duration::from_millis(config.get("delay", DELAY)?);
+        let mut hc595 = HC595::with_pins(pins[0], pins[1], pins[2])?;
+        // Let the library work out the segment codes instead of doing it by hand.
+        let letters = encode_str(WORD)?;
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        'outer: while ctx.is_running() && iterations.again() {
+            say!("forward ...");
+            for code in SEG_CODES.iter() {
+                say!("code = {:04X?}", code);
+                hc595.serial_in(*code);
+                hc595.parallel_out();
+                ctx.sleep(delay);
+            }
+            // Improves Ctrl-C responsiveness.
+            if !ctx.is_running() {
+                break 'outer;
+            }
+            say!("... reverse");
+            for code in SEG_CODES.iter().rev() {
+                say!("code = {:04X?}", code);
+                hc595.serial_in(*code);
+                hc595.parallel_out();
+                ctx.sleep(delay);
+            }
+            // Improves Ctrl-C responsiveness.
+            if !ctx.is_running() {
+                break 'outer;
+            }
+            say!("word = {}", WORD);
+            for code in letters.iter() {
+                hc595.serial_in(*code);
+                hc595.parallel_out();
+                ctx.sleep(delay);
+            }
+            ctx.sleep(delay);
         }
-        // Improves Ctrl-C responsiveness.
-        if !running.load(Ordering::SeqCst) {
-            break 'outer;
-        }
-        println!("... reverse");
-        for code in SEG_CODES.iter().rev() {
-            println!("code = {:04X?}", code);
-            hc595.serial_in(*code);
-            hc595.parallel_out();
-            sleep(Duration::from_millis(DELAY));
-        }
-        sleep(Duration::from_millis(DELAY));
-    }
-    println!("\n11_Segment stopped");
-    Ok(())
+        Ok(())
+    })
 }
@@ -0,0 +1,108 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Shows the time as HH.MM on the 4 digit seven segment display from the
+// stopwatch with the decimal point blinking each second.
+// Run with `--rtc=<chip>` to read the time from a ds3231 or ds1302 real time
+// clock so it is right even without a network connection, and add `--set-rtc`
+// to first copy the system time into the clock.
+
+use anyhow::Result;
+use chrono::{Local, NaiveDateTime, Timelike};
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{cli::Common, rtc, run_lesson, say, Rtc, SegmentDisplay};
+
+// Digit select pins left to right.
+const DIGIT_PINS: [u8; 4] = [23, 24, 25, 12];
+// Time in milliseconds between checking the time.
+const DELAY: u64 = 100;
+
+/// Shows the time on a 4 digit segment display.
+#[derive(StructOpt)]
+#[structopt(name = "11_segment_clock")]
+struct Opt {
+    /// Gpio pins for the digits separated by commas (default 23, 24, 25, 12).
+    #[structopt(long, use_delimiter = true)]
+    digits: Option<Vec<u8>>,
+    /// Time in milliseconds between checking the time (default 100).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Keep time with a real time clock chip which is ds3231 or ds1302.
+    #[structopt(long)]
+    rtc: Option<String>,
+    /// Set the real time clock from the system time.
+    #[structopt(long)]
+    set_rtc: bool,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("11_segment_clock")?
+        .with("digits", opt.digits.clone())?
+        .with("delay", opt.delay)?;
+    run_lesson("11_SegmentClock", |ctx| {
+        let digit_pins = config.pins("digits", DIGIT_PINS)?;
+        let delay = config.get("delay", DELAY)?;
+        let mut rtc = opt.rtc.as_deref().map(rtc::open).transpose()?;
+        if let Some(rtc) = rtc.as_mut() {
+            if opt.set_rtc {
+                rtc.set(&Local::now().naive_local())?;
+                say!("clock set from the system time");
+            } else if rtc.lost_time()? {
+                say!("clock has stopped at some point, run with --set-rtc to fix it");
+            }
+        }
+        let mut display = SegmentDisplay::new(digit_pins)?;
+        let mut iterations = opt.common.iterations();
+        let mut shown = None;
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            let now = now(&mut rtc)?;
+            if shown != Some(now) {
+                // The point between the hours and minutes ticks like a colon.
+                let point = if now.second() % 2 == 0 { "." } else { "" };
+                display.show_text(&format!("{:02}{}{:02}", now.hour(), point, now.minute()))?;
+                shown = Some(now);
+            }
+            ctx.sleep(Duration::from_millis(delay));
+        }
+        display.clear();
+        Ok(())
+    })
+}
+
+/// Gets the time to the second from the real time clock if there is one or
+/// the system if not.
+fn now(rtc: &mut Option<Box<dyn Rtc>>) -> Result<NaiveDateTime> {
+    match rtc {
+        Some(rtc) => Ok(rtc.now()?),
+        None => Ok(Local::now()
+            .naive_local()
+            .with_nanosecond(0)
+            .unwrap_or_default()),
+    }
+}
@@ -0,0 +1,124 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// A more realistic use for the seven segment display than cycling through the
+// hex digits. Uses a 4 digit multiplexed display and the push button from the
+// dice lesson to make a stopwatch with tenths of a second.
+// A short press starts or stops the stopwatch and holding the button down for
+// a second resets it back to zero.
+
+use anyhow::Result;
+use std::time::{Duration, Instant};
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, run_lesson, say, Button, Gesture, SegmentDisplay,
+};
+
+const BUTTON: u8 = 22;
+// Digit select pins left to right.
+const DIGIT_PINS: [u8; 4] = [23, 24, 25, 12];
+// Button polling delay in milliseconds.
+const DELAY: u64 = 10;
+// How long the button has to be held to reset the stopwatch.
+const RESET_TIME: Duration = Duration::from_secs(1);
+// Largest time the display can show in tenths of a second.
+const MAX_TENTHS: u128 = 9999;
+
+/// Stopwatch on a 4 digit segment display.
+#[derive(StructOpt)]
+#[structopt(name = "11_stopwatch")]
+struct Opt {
+    /// Gpio pin for the button (default 22).
+    #[structopt(long)]
+    button: Option<u8>,
+    /// Gpio pins for the digits separated by commas (default 23, 24, 25, 12).
+    #[structopt(long, use_delimiter = true)]
+    digits: Option<Vec<u8>>,
+    /// Button polling delay in milliseconds (default 10).
+    #[structopt(long)]
+    delay: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("11_stopwatch")?
+        .with("button", opt.button)?
+        .with("digits", opt.digits.clone())?
+        .with("delay", opt.delay)?;
+    run_lesson("11_Stopwatch", |ctx| {
+        let button = config.pin("button", BUTTON)?;
+        let digit_pins = config.pins("digits", DIGIT_PINS)?;
+        let delay = config.get("delay", DELAY)?;
+        let mut display = SegmentDisplay::new(digit_pins)?;
+        let mut button = Button::new(button)?;
+        // No double clicks so a click is seen as soon as the button is released.
+        button.set_gesture_times(Duration::from_secs(0), RESET_TIME);
+        let mut iterations = opt.common.iterations();
+        // Time from earlier runs and when the current run started if running.
+        let mut elapsed = Duration::from_secs(0);
+        let mut started: Option<Instant> = None;
+        say!("Press button to start/stop, hold to reset ...");
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            let gesture = button.poll_gesture();
+            let now = Instant::now();
+            match gesture {
+                Some(Gesture::LongPress) => {
+                    say!("reset");
+                    elapsed = Duration::from_secs(0);
+                    started = None;
+                }
+                Some(Gesture::Click) => match started.take() {
+                    Some(start) => {
+                        elapsed += now - start;
+                        say!("stopped at {:.1}s", elapsed.as_secs_f64());
+                    }
+                    None => {
+                        say!("started");
+                        started = Some(now);
+                    }
+                },
+                _ => {}
+            }
+            let total = match started {
+                Some(start) => elapsed + (now - start),
+                None => elapsed,
+            };
+            // Blink the display while stopped so it's clear the time isn't running.
+            display.set_blink_all(started.is_none() && total > Duration::from_secs(0));
+            show_tenths(&mut display, total)?;
+            ctx.sleep(Duration::from_millis(delay));
+        }
+        Ok(())
+    })
+}
+
+/// Shows the time as seconds with one decimal place, wrapping back around to
+/// zero after 999.9 seconds.
+fn show_tenths(display: &mut SegmentDisplay<4>, time: Duration) -> Result<()> {
+    let tenths = (time.as_millis() / 100) % (MAX_TENTHS + 1);
+    Ok(display.show_float(tenths as f64 / 10.0, 1)?)
+}
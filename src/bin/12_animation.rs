@@ -0,0 +1,67 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Plays an animation from a TOML file on the dot matrix from lesson 12.
+// The file is loaded again before each pass so it can be edited while the
+// animation is running to see the changes right away.
+// Usage: 12_animation [file]
+
+use anyhow::Result;
+use log::error;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{cli::Common, run_lesson, say, Animation, DotMatrix8x8};
+
+const DEFAULT_FILE: &str = "animations/heart.toml";
+
+/// Plays an animation file on the dot matrix.
+#[derive(StructOpt)]
+#[structopt(name = "12_animation")]
+struct Opt {
+    /// Animation file to play.
+    #[structopt(default_value = DEFAULT_FILE)]
+    file: String,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    opt.common.start()?;
+    run_lesson("12_Animation", |ctx| {
+        let file = opt.file;
+        let mut animation = Animation::load(&file)?;
+        say!("playing {} ...", file);
+        let mut matrix = DotMatrix8x8::new()?;
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            animation.play(&mut matrix)?;
+            // Keep playing the last good version if the file is broken while being
+            // edited.
+            match Animation::load(&file) {
+                Ok(a) => animation = a,
+                Err(e) => error!("{:#}", e),
+            }
+        }
+        Ok(())
+    })
+}
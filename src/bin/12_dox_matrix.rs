@@ -26,64 +26,105 @@
 // and provide ways to change that state. The Python code could have done the
 // same but for whatever reason they chose not to.
 
-use anyhow::{Context, Result};
-use rppal::system::DeviceInfo;
-use std::{
-    sync::atomic::{AtomicBool, Ordering},
-    sync::Arc,
-    thread::sleep,
-    time::Duration,
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, run_lesson, say, DotMatrix8x8, MatrixDisplay, HC595,
 };
-use superkit_rust_code_for_raspberrypi::HC595;
 
+// Gpio pin numbers for SDI, RCLK, and SRCLK.
+const PINS: [u8; 3] = [17, 18, 27];
 const DELAY: u64 = 100;
 
-const CODE_H: [u8; 20] = [
-    0x01, 0xff, 0x80, 0xff, 0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0xff, 0xff, 0xff, 0xff,
-    0xff, 0xff, 0xff, 0xff,
-];
-const CODE_L: [u8; 20] = [
-    0x00, 0x7f, 0x00, 0xfe, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xfe, 0xfd, 0xfb, 0xf7,
-    0xef, 0xdf, 0xbf, 0x7f,
+/// A full line of LEDs across the matrix.
+#[derive(Clone, Copy)]
+enum Line {
+    Row(usize),
+    Column(usize),
+}
+use Line::{Column, Row};
+
+// Same sequence the raw CODE_H and CODE_L bytes in the Python code make.
+const SEQUENCE: [Line; 20] = [
+    Row(0),
+    Column(7),
+    Row(7),
+    Column(0),
+    Row(0),
+    Row(1),
+    Row(2),
+    Row(3),
+    Row(4),
+    Row(5),
+    Row(6),
+    Row(7),
+    Column(0),
+    Column(1),
+    Column(2),
+    Column(3),
+    Column(4),
+    Column(5),
+    Column(6),
+    Column(7),
 ];
 
+/// Draws lines on the 8x8 dot matrix.
+#[derive(StructOpt)]
+#[structopt(name = "12_dox_matrix")]
+struct Opt {
+    /// Gpio pins for the 74HC595's SDI, RCLK, and SRCLK separated by commas (default 17, 18, 27).
+    #[structopt(long, use_delimiter = true)]
+    pins: Option<Vec<u8>>,
+    /// Delay in milliseconds (default 100).
+    #[structopt(long)]
+    delay: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
 fn main() -> Result<()> {
-    println!(
-        "12_DotMatrix started on a {}",
-        DeviceInfo::new()
-            .context("Failed to get new DeviceInfo")?
-            .model()
-    );
-    let mut hc595 = HC595::new()?;
-    // Stuff needed to nicely handle Ctrl-C from user.
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-    ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
-    })
-    .context("Error setting Ctrl-C handler")?;
-    // Loop until Ctrl-C is received.
-    'outer: while running.load(Ordering::SeqCst) {
-        println!("forward ...");
-        for i in 0..CODE_H.len() {
-            hc595.serial_in(CODE_L[i]);
-            hc595.serial_in(CODE_H[i]);
-            hc595.parallel_out();
-            sleep(Duration::from_millis(DELAY));
-        }
-        // Improves Ctrl-C responsiveness.
-        if !running.load(Ordering::SeqCst) {
-            break 'outer;
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("12_dox_matrix")?
+        .with("pins", opt.pins.clone())?
+        .with("delay", opt.delay)?;
+    run_lesson("12_DotMatrix", |ctx| {
+        let pins = config.pins("pins", PINS)?;
+        let delay = Duration::from_millis(config.get("delay", DELAY)?);
+        let mut matrix = DotMatrix8x8::with_hc595(HC595::with_pins(pins[0], pins[1], pins[2])?, 1)?;
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        'outer: while ctx.is_running() && iterations.again() {
+            say!("forward ...");
+            for line in SEQUENCE.iter() {
+                draw_line(&mut matrix, *line);
+                matrix.refresh_for(delay)?;
+            }
+            // Improves Ctrl-C responsiveness.
+            if !ctx.is_running() {
+                break 'outer;
+            }
+            say!("... reverse");
+            for line in SEQUENCE.iter().rev() {
+                draw_line(&mut matrix, *line);
+                matrix.refresh_for(delay)?;
+            }
+            matrix.clear();
+            matrix.refresh_for(delay)?;
         }
-        println!("... reverse");
-        for i in (0..CODE_H.len()).rev() {
-            hc595.serial_in(CODE_L[i]);
-            hc595.serial_in(CODE_H[i]);
-            hc595.parallel_out();
-            sleep(Duration::from_millis(DELAY));
+        Ok(())
+    })
+}
+
+/// Replaces what is on the matrix with a single full row or column.
+fn draw_line(matrix: &mut DotMatrix8x8, line: Line) {
+    matrix.clear();
+    for i in 0..8 {
+        match line {
+            Row(y) => matrix.set_pixel(i, y, true),
+            Column(x) => matrix.set_pixel(x, i, true),
         }
-        sleep(Duration::from_millis(DELAY));
     }
-    println!("\n12_DotMatrix stopped");
-    Ok(())
 }
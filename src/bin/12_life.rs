@@ -0,0 +1,131 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Conway's Game of Life on the dot matrix from lesson 12.
+// The board wraps around at the edges so gliders keep going. When the board
+// dies out, stops changing, or gets stuck blinking between two states a new
+// random board is started.
+
+use anyhow::Result;
+use rand::{thread_rng, Rng};
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, run_lesson, say, DotMatrix8x8, MatrixDisplay,
+};
+
+// Time between generations in milliseconds.
+const DELAY: u64 = 300;
+// Chance each cell starts alive.
+const DENSITY: f64 = 0.35;
+// Reseed even if still changing to stop longer cycles going on forever.
+const MAX_GENERATIONS: u32 = 200;
+
+/// Plays Conway's Game of Life on the dot matrix.
+#[derive(StructOpt)]
+#[structopt(name = "12_life")]
+struct Opt {
+    /// Time between generations in milliseconds (default 300).
+    #[structopt(long)]
+    delay: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt.common.lesson("12_life")?.with("delay", opt.delay)?;
+    run_lesson("12_Life", |ctx| {
+        let delay = config.get("delay", DELAY)?;
+        let mut matrix = DotMatrix8x8::new()?;
+        let mut iterations = opt.common.iterations();
+        let mut board = seed();
+        let mut previous = [0u8; 8];
+        let mut generation = 0;
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            matrix.draw_rows(&board);
+            matrix.refresh_for(Duration::from_millis(delay))?;
+            let next = step(&board);
+            generation += 1;
+            let reason = if next == [0; 8] {
+                Some("extinct")
+            } else if next == board {
+                Some("still life")
+            } else if next == previous {
+                Some("oscillating")
+            } else if generation >= MAX_GENERATIONS {
+                Some("too many generations")
+            } else {
+                None
+            };
+            previous = board;
+            board = next;
+            if let Some(reason) = reason {
+                say!("{} after {} generations, reseeding", reason, generation);
+                matrix.draw_rows(&board);
+                matrix.refresh_for(Duration::from_millis(4 * delay))?;
+                board = seed();
+                previous = [0; 8];
+                generation = 0;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Makes a new random board.
+fn seed() -> [u8; 8] {
+    let mut rng = thread_rng();
+    let mut board = [0u8; 8];
+    for row in board.iter_mut() {
+        for x in 0..8 {
+            if rng.gen_bool(DENSITY) {
+                *row |= 1 << x;
+            }
+        }
+    }
+    board
+}
+
+/// Works out the next generation with the edges wrapping around.
+fn step(board: &[u8; 8]) -> [u8; 8] {
+    let alive = |x: usize, y: usize| board[y % 8] & (1 << (x % 8)) != 0;
+    let mut next = [0u8; 8];
+    for (y, row) in next.iter_mut().enumerate() {
+        for x in 0..8 {
+            // Adding 7 is the same as subtracting 1 once wrapped.
+            let mut neighbors = 0;
+            for dy in [7, 0, 1].iter() {
+                for dx in [7, 0, 1].iter() {
+                    if (*dx, *dy) != (0, 0) && alive(x + dx, y + dy) {
+                        neighbors += 1;
+                    }
+                }
+            }
+            if neighbors == 3 || (neighbors == 2 && alive(x, y)) {
+                *row |= 1 << x;
+            }
+        }
+    }
+    next
+}
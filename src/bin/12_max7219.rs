@@ -0,0 +1,71 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Same scrolling text as 12_scroll_text but on MAX7219 matrix modules connected
+// to SPI0 instead of the 74HC595 based matrix. Many of the MAX7219 modules come
+// as 4 cascaded matrices so the text can span all of them.
+// The brightness is stepped up after each message to show intensity control.
+
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, max7219::MAX_INTENSITY, run_lesson, say, MatrixDisplay, Max7219,
+};
+
+// Number of cascaded modules.
+const MODULES: usize = 4;
+// Time in milliseconds before text moves one column.
+const SPEED: u64 = 40;
+const MESSAGES: [&str; 2] = ["Hello World!", "May the Rust be with you"];
+
+/// Scrolls messages across MAX7219 dot matrix modules.
+#[derive(StructOpt)]
+#[structopt(name = "12_max7219")]
+struct Opt {
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    opt.common.start()?;
+    run_lesson("12_Max7219", |ctx| {
+        let mut matrix = Max7219::new(MODULES)?;
+        let mut iterations = opt.common.iterations();
+        let mut intensity = 0;
+        // Loop until Ctrl-C is received.
+        'outer: while ctx.is_running() && iterations.again() {
+            for message in MESSAGES.iter() {
+                say!("intensity = {}, {}", intensity, message);
+                matrix.set_intensity(intensity)?;
+                matrix.scroll_text(message, Duration::from_millis(SPEED))?;
+                intensity = (intensity + 5) % (MAX_INTENSITY + 1);
+                // Improves Ctrl-C responsiveness.
+                if !ctx.is_running() {
+                    break 'outer;
+                }
+            }
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,67 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Scrolls a message across the dot matrix from lesson 12 using the built-in
+// font instead of working out the row and column bytes by hand. Change MODULES
+// if more matrices have been chained together to make a wider display.
+
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, run_lesson, say, DotMatrix8x8, MatrixDisplay,
+};
+
+// Number of cascaded matrices.
+const MODULES: usize = 1;
+// Time in milliseconds before text moves one column.
+const SPEED: u64 = 80;
+const MESSAGES: [&str; 3] = ["Hello World!", "May the Rust be with you", "rust-lang.org"];
+
+/// Scrolls messages across the dot matrix.
+#[derive(StructOpt)]
+#[structopt(name = "12_scroll_text")]
+struct Opt {
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    opt.common.start()?;
+    run_lesson("12_ScrollText", |ctx| {
+        let mut matrix = DotMatrix8x8::with_modules(MODULES)?;
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        'outer: while ctx.is_running() && iterations.again() {
+            for message in MESSAGES.iter() {
+                say!("{}", message);
+                matrix.scroll_text(message, Duration::from_millis(SPEED))?;
+                // Improves Ctrl-C responsiveness.
+                if !ctx.is_running() {
+                    break 'outer;
+                }
+            }
+        }
+        Ok(())
+    })
+}
@@ -21,119 +21,100 @@
 // SOFTWARE.
 
 use anyhow::{Context, Result};
-use hd44780_ntb::{DisplayMode, EntryMode, FunctionMode, GpioDriver, HD44780};
-use linux_embedded_hal::{sysfs_gpio::Direction, Delay, Pin};
-use rppal::system::DeviceInfo;
-use std::{io::Write, thread::sleep, time::Duration};
+use std::{fmt::Write, time::Duration};
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common,
+    lcd1602::{Geometry, Wrap},
+    run_lesson, say, Lcd1602, LessonContext,
+};
 
-// The 4 bit data bus pins.
-const PIN_D4: u64 = 25;
-const PIN_D5: u64 = 24;
-const PIN_D6: u64 = 23;
-const PIN_D7: u64 = 18;
-// The control pins.
-const PIN_E: u64 = 22;
-const PIN_RS: u64 = 27;
-// Message delay.
+// Gpio pin numbers for register select, enable, and D4 to D7.
+const RS_PIN: u8 = 27;
+const E_PIN: u8 = 22;
+const DATA_PINS: [u8; 4] = [25, 24, 23, 18];
+// Message delay in seconds.
 const DELAY: u64 = 2;
+// Times through the messages.
+const PASSES: u64 = 3;
 // Messages to be displayed.
-const MESSAGES: [&str; 5] = [
+// Heart shape for custom character 0.
+const HEART: [u8; 8] = [0x00, 0x0a, 0x1f, 0x1f, 0x0e, 0x04, 0x00, 0x00];
+const MESSAGES: [&str; 6] = [
     " LCD 1602 Test \n123456789ABCDEF",
     "   SUNFOUNDER \nHello World ! :)",
     "Welcome to --->\n  sunfounder.com",
     "May the Rust ...\n... be with you!",
     "Ferris says \"Hi\"\n   rust-lang.org",
+    "Made with \x00 by\n  Rustaceans",
 ];
 
-fn main() -> Result<()> {
-    println!(
-        "13_LCD1602 started on a {}",
-        DeviceInfo::new()
-            .context("Failed to get new DeviceInfo")?
-            .model()
-    );
-    let mut lcd = setup()?;
-    display_loop(&mut lcd)?;
-    // lcd.return_home().context("Failed to home the display")?;
-    println!("\n12_DotMatrix stopped");
-    destroy()
+/// Shows messages on a LCD1602.
+#[derive(StructOpt)]
+#[structopt(name = "13_lcd1602")]
+struct Opt {
+    /// Gpio pin for the LCD's register select (default 27).
+    #[structopt(long)]
+    rs: Option<u8>,
+    /// Gpio pin for the LCD's enable (default 22).
+    #[structopt(long)]
+    e: Option<u8>,
+    /// Gpio pins for the LCD's D4 to D7 separated by commas (default 25, 24, 23, 18).
+    #[structopt(long, use_delimiter = true)]
+    data: Option<Vec<u8>>,
+    /// Message delay in seconds (default 2).
+    #[structopt(long)]
+    delay: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
 }
 
-/// Resets GPIO pins as inputs and releases them back to the OS.
-fn destroy() -> Result<()> {
-    let rs = Pin::new(PIN_RS);
-    let e = Pin::new(PIN_E);
-    rs.set_direction(Direction::In)
-        .context("Failed to set direction on register select pin")?;
-    e.set_direction(Direction::In)
-        .context("Failed to set direction on enable pin")?;
-    rs.unexport()
-        .context("Failed to un-export register select pin")?;
-    e.unexport().context("Failed to un-export enable pin")?;
-    let mut pin: Pin;
-    let pin_numbers = [PIN_D4, PIN_D5, PIN_D6, PIN_D7];
-    for num in pin_numbers.iter() {
-        pin = Pin::new(*num);
-        pin.set_direction(Direction::In)
-            .context(format!("Failed to set direction on data pin: {}", num))?;
-        pin.unexport()
-            .context(format!("Failed to export data pin: {}", num))?;
-    }
-    Ok(())
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("13_lcd1602")?
+        .with("rs", opt.rs)?
+        .with("e", opt.e)?
+        .with("data", opt.data.clone())?
+        .with("delay", opt.delay)?;
+    run_lesson("13_LCD1602", |ctx| {
+        let rs = config.pin("rs", RS_PIN)?;
+        let e = config.pin("e", E_PIN)?;
+        let data = config.pins("data", DATA_PINS)?;
+        let delay = Duration::from_secs(config.get("delay", DELAY)?);
+        let mut lcd = Lcd1602::with_pins(rs, e, data, Geometry::Lcd16x2, Wrap::Off)?;
+        lcd.create_char(0, HEART)?;
+        display_loop(
+            ctx,
+            &mut lcd,
+            delay,
+            opt.common.iterations.unwrap_or(PASSES),
+        )?;
+        Ok(())
+    })
 }
 
 /// Main display loop for messages.
-fn display_loop(lcd: &mut GpioDriver<Pin, Pin, Pin, Delay>) -> Result<()> {
-    for _ in 0..3 {
+fn display_loop(
+    ctx: &LessonContext,
+    lcd: &mut Lcd1602,
+    delay: Duration,
+    passes: u64,
+) -> Result<()> {
+    for _ in 0..passes {
         for message in MESSAGES.iter() {
-            // First clear the display.
-            lcd.clear_display().context("Failed to clear the display")?;
-            let lines: Vec<&str> = message.split('\n').collect();
-            println!("{}", lines[0]);
-            lcd.write(lines[0].as_bytes())
-                .context("Failed to write string")?;
-            if lines.len() == 2 {
-                // Write the second line.
-                lcd.set_dd_ram_addr(0x40)
-                    .context("Failed to move to second line")?;
-                println!("{}", lines[1]);
-                lcd.write(lines[1].as_bytes())
-                    .context("Failed to write string")?;
+            if !ctx.is_running() {
+                return Ok(());
             }
+            // First clear the display.
+            lcd.clear()?;
+            say!("{}", message);
+            write!(lcd, "{}", message).context("Failed to write message")?;
             // Wait a couple seconds so message can be seen.
-            sleep(Duration::from_secs(DELAY));
+            ctx.sleep(delay);
         }
-        println!();
+        say!();
     }
     Ok(())
 }
-
-/// Gets the GPIO pins from OS and setup LCD display.
-fn setup() -> Result<GpioDriver<Pin, Pin, Pin, Delay>> {
-    let rs = Pin::new(PIN_RS);
-    let e = Pin::new(PIN_E);
-    rs.export()
-        .context("Failed to export register select pin")?;
-    e.export().context("Failed to export enable pin")?;
-    rs.set_direction(Direction::High)
-        .context("Failed to set direction and level on register select pin")?;
-    e.set_direction(Direction::Low)
-        .context("Failed to set direction and level on enable pin")?;
-    let mut data = Vec::<Pin>::new();
-    let pin_numbers = [PIN_D4, PIN_D5, PIN_D6, PIN_D7];
-    for num in pin_numbers.iter() {
-        let pin = Pin::new(*num);
-        pin.export()
-            .context(format!("Failed to export data pin: {}", num))?;
-        pin.set_direction(Direction::Out)
-            .context(format!("Failed to set direction on data pin: {}", num))?;
-        data.push(pin);
-    }
-    let mut lcd = GpioDriver::new(rs, e, data, Delay);
-    let dc = Some(DisplayMode::DISPLAY_ON);
-    let ems = Some(EntryMode::ENTRY_LEFT | EntryMode::ENTRY_SHIFT_CURSOR);
-    let fm = Some(FunctionMode::LINES_2);
-    lcd.init(fm, dc, ems)
-        .context("Failed to initialize display instance")?;
-    Ok(lcd)
-}
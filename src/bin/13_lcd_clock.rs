@@ -0,0 +1,138 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Clock on the LCD1602 with the date on the first row and the time on the
+// second.
+// Unlike 13_lcd1602 which clears the display for each message only the
+// characters that have changed since the last update are written so the
+// display never flickers.
+// Run with `--rtc=<chip>` to read the time from a ds3231 or ds1302 real time
+// clock so it is right even without a network connection, and add `--set-rtc`
+// to first copy the system time into the clock.
+
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDateTime, Timelike};
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{cli::Common, rtc, run_lesson, say, Lcd1602, Rtc};
+
+// Number of characters in each row.
+const COLUMNS: usize = 16;
+
+/// Shows the date and time on the LCD1602.
+#[derive(StructOpt)]
+#[structopt(name = "13_lcd_clock")]
+struct Opt {
+    /// Keep time with a real time clock chip which is ds3231 or ds1302.
+    #[structopt(long)]
+    rtc: Option<String>,
+    /// Set the real time clock from the system time.
+    #[structopt(long)]
+    set_rtc: bool,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    opt.common.start()?;
+    run_lesson("13_LCD_Clock", |ctx| {
+        let mut rtc = opt.rtc.as_deref().map(rtc::open).transpose()?;
+        if let Some(rtc) = rtc.as_mut() {
+            if opt.set_rtc {
+                rtc.set(&Local::now().naive_local())?;
+                say!("clock set from the system time");
+            } else if rtc.lost_time()? {
+                say!("clock has stopped at some point, run with --set-rtc to fix it");
+            }
+        }
+        let mut lcd = Lcd1602::new()?;
+        let mut iterations = opt.common.iterations();
+        // What is currently on the display which starts out blank after new().
+        let mut shown = [[b' '; COLUMNS]; 2];
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            let now = now(&mut rtc)?;
+            let rows = [
+                centre(&now.format("%a %d %b %Y").to_string()),
+                centre(&now.format("%H:%M:%S").to_string()),
+            ];
+            for (row, (new, old)) in rows.iter().zip(shown.iter_mut()).enumerate() {
+                update_row(&mut lcd, row as u8, new, old)?;
+            }
+            // Wake up just after the next second starts. The clock chips only give
+            // whole seconds so check those more often instead.
+            let millis = match rtc {
+                Some(_) => 800,
+                None => now.nanosecond() / 1_000_000,
+            };
+            ctx.sleep(Duration::from_millis(1000 - u64::from(millis.min(999))));
+        }
+        lcd.clear()?;
+        Ok(())
+    })
+}
+
+/// Gets the time from the real time clock if there is one or the system if
+/// not.
+fn now(rtc: &mut Option<Box<dyn Rtc>>) -> Result<NaiveDateTime> {
+    match rtc {
+        Some(rtc) => Ok(rtc.now()?),
+        None => Ok(Local::now().naive_local()),
+    }
+}
+
+/// Pads text with spaces on both sides so it is centred on a row.
+fn centre(text: &str) -> [u8; COLUMNS] {
+    let mut row = [b' '; COLUMNS];
+    let text = format!("{:^width$.width$}", text, width = COLUMNS);
+    row.copy_from_slice(text.as_bytes());
+    row
+}
+
+/// Writes only the characters in the row which are different from what is
+/// already shown.
+///
+/// A run of changed characters is written together so the display only has
+/// to be told where to start once per run.
+fn update_row(
+    lcd: &mut Lcd1602,
+    row: u8,
+    new: &[u8; COLUMNS],
+    old: &mut [u8; COLUMNS],
+) -> Result<()> {
+    let mut col = 0;
+    while col < COLUMNS {
+        if new[col] == old[col] {
+            col += 1;
+            continue;
+        }
+        let start = col;
+        while col < COLUMNS && new[col] != old[col] {
+            col += 1;
+        }
+        let run = std::str::from_utf8(&new[start..col]).context("Row text is not ASCII")?;
+        lcd.print_at(row, start as u8, run)?;
+    }
+    *old = *new;
+    Ok(())
+}
@@ -0,0 +1,142 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Puts lessons 8 and 13 together with a menu on the LCD1602 driven by the
+// rotary encoder.
+// Turn the knob to move through the items, click to select, and long press to
+// go back or cancel editing a value.
+// The LCD uses two of the encoder's lesson pins so the encoder is moved to
+// CLK = 17, DT = 5, and SW = 6.
+
+use anyhow::{anyhow, Result};
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, run_lesson, say, Gesture, Item, Lcd1602, Menu, MenuEvent, RotaryEncoder,
+};
+
+// Encoder pins.
+const CLK_PIN: u8 = 17;
+const DT_PIN: u8 = 5;
+const SW_PIN: u8 = 6;
+// Time in milliseconds a press needs to be held to go back.
+const LONG_PRESS: u64 = 600;
+// Time in milliseconds between checks of the knob.
+const DELAY: u64 = 20;
+
+/// Menu on the LCD1602 driven by a rotary encoder.
+#[derive(StructOpt)]
+#[structopt(name = "13_lcd_menu")]
+struct Opt {
+    /// Gpio pin for the encoder's CLK (default 17).
+    #[structopt(long)]
+    clk: Option<u8>,
+    /// Gpio pin for the encoder's DT (default 5).
+    #[structopt(long)]
+    dt: Option<u8>,
+    /// Gpio pin for the switch (default 6).
+    #[structopt(long)]
+    sw: Option<u8>,
+    /// Time in milliseconds between checks of the knob (default 20).
+    #[structopt(long)]
+    delay: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("13_lcd_menu")?
+        .with("clk", opt.clk)?
+        .with("dt", opt.dt)?
+        .with("sw", opt.sw)?
+        .with("delay", opt.delay)?;
+    run_lesson("13_LCD_Menu", |ctx| {
+        let clk_pin = config.pin("clk", CLK_PIN)?;
+        let dt_pin = config.pin("dt", DT_PIN)?;
+        let sw_pin = config.pin("sw", SW_PIN)?;
+        let delay = config.get("delay", DELAY)?;
+        let mut lcd = Lcd1602::new()?;
+        let mut encoder = RotaryEncoder::with_switch(clk_pin, dt_pin, sw_pin)?;
+        encoder.set_divider(4)?;
+        // No double clicks so a click doesn't have to wait to see if another
+        // follows.
+        encoder
+            .switch()
+            .ok_or_else(|| anyhow!("Rotary encoder has no push switch"))?
+            .set_gesture_times(Duration::from_secs(0), Duration::from_millis(LONG_PRESS));
+        let gestures = encoder.switch_gestures()?;
+        let mut menu = Menu::new(vec![
+            Item::value("Brightness", 50, 0, 100),
+            Item::submenu(
+                "Motor",
+                vec![
+                    Item::value_with_step("Speed", 50, 0, 100, 5),
+                    Item::value("Ramp time", 10, 1, 50),
+                    Item::action("Start"),
+                    Item::action("Stop"),
+                ],
+            ),
+            Item::submenu(
+                "Clock",
+                vec![
+                    Item::value("Hour", 12, 0, 23),
+                    Item::value("Minute", 0, 0, 59),
+                ],
+            ),
+            Item::action("Reset"),
+            Item::action("About"),
+        ]);
+        let mut iterations = opt.common.iterations();
+        menu.render(&mut lcd)?;
+        let mut last = encoder.value();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            let gesture = gestures.recv_timeout(Duration::from_millis(delay)).ok();
+            let event = match gesture {
+                Some(Gesture::Click) => menu.select(),
+                Some(Gesture::LongPress) => {
+                    menu.back();
+                    None
+                }
+                _ => None,
+            };
+            match event {
+                Some(MenuEvent::Action(label)) => say!("action = {}", label),
+                Some(MenuEvent::Changed { label, value }) => say!("{} = {}", label, value),
+                None => {}
+            }
+            let value = encoder.value();
+            if value != last {
+                menu.turn(value - last);
+                last = value;
+            } else if gesture.is_none() {
+                continue;
+            }
+            menu.render(&mut lcd)?;
+        }
+        lcd.clear()?;
+        Ok(())
+    })
+}
@@ -0,0 +1,117 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Turns the LCD1602 into a simple light meter by showing the reading from a
+// photoresistor connected to channel 0 of an ADC0832 along with a bar graph.
+// A thermistor or potentiometer can be used instead without any changes.
+// The LCD uses two of the ADC's lesson pins so the ADC is moved to CS = 17,
+// CLK = 5, and DIO = 6.
+
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{cli::Common, run_lesson, Adc, Adc0832, Lcd1602};
+
+// ADC pins.
+const ADC_CS: u8 = 17;
+const ADC_CLK: u8 = 5;
+const ADC_DIO: u8 = 6;
+// ADC channel the sensor is connected to.
+const CHANNEL: u8 = 0;
+// Number of characters in each row.
+const COLUMNS: usize = 16;
+// Each character of the bar graph is split into this many columns.
+const STEPS: usize = 5;
+// Time in milliseconds between readings.
+const DELAY: u64 = 100;
+
+/// Shows a sensor reading as a bar graph on the LCD1602.
+#[derive(StructOpt)]
+#[structopt(name = "13_lcd_sensor")]
+struct Opt {
+    /// Gpio pin for the ADC's chip select (default 17).
+    #[structopt(long)]
+    adc_cs: Option<u8>,
+    /// Gpio pin for the ADC's clock (default 5).
+    #[structopt(long)]
+    adc_clk: Option<u8>,
+    /// Gpio pin for the ADC's data (default 6).
+    #[structopt(long)]
+    adc_dio: Option<u8>,
+    /// Time in milliseconds between readings (default 100).
+    #[structopt(long)]
+    delay: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("13_lcd_sensor")?
+        .with("adc_cs", opt.adc_cs)?
+        .with("adc_clk", opt.adc_clk)?
+        .with("adc_dio", opt.adc_dio)?
+        .with("delay", opt.delay)?;
+    run_lesson("13_LCD_Sensor", |ctx| {
+        let adc_cs = config.pin("adc_cs", ADC_CS)?;
+        let adc_clk = config.pin("adc_clk", ADC_CLK)?;
+        let adc_dio = config.pin("adc_dio", ADC_DIO)?;
+        let delay = config.get("delay", DELAY)?;
+        let mut adc = Adc0832::with_pins(adc_cs, adc_clk, adc_dio)?;
+        let mut lcd = Lcd1602::new()?;
+        // Custom characters 0-4 have 1-5 columns lit from the left.
+        for slot in 0..STEPS as u8 {
+            let row = (0x1f << (4 - slot)) & 0x1f;
+            lcd.create_char(slot, [row; 8])?;
+        }
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            let value = adc.read(CHANNEL)?;
+            let ratio = f64::from(value) / f64::from(adc.max_value());
+            // Rows are always completely rewritten instead of clearing the display
+            // first which would make it flicker.
+            let text = format!("Light {:>3} {:>3.0}%", value, ratio * 100.0);
+            lcd.print_at(0, 0, &format!("{:<width$}", text, width = COLUMNS))?;
+            lcd.print_at(1, 0, &bar(ratio))?;
+            ctx.sleep(Duration::from_millis(delay));
+        }
+        lcd.clear()?;
+        Ok(())
+    })
+}
+
+/// Builds a full row of bar graph from a fraction of full scale.
+fn bar(ratio: f64) -> String {
+    let lit = (ratio.clamp(0.0, 1.0) * (COLUMNS * STEPS) as f64).round() as usize;
+    let mut row = String::new();
+    for _ in 0..lit / STEPS {
+        row.push(char::from(STEPS as u8 - 1));
+    }
+    let partial = lit % STEPS;
+    if partial > 0 {
+        row.push(char::from(partial as u8 - 1));
+    }
+    format!("{:<width$}", row, width = COLUMNS)
+}
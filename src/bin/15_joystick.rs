@@ -0,0 +1,128 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Moves a dot around the 8x8 dot matrix from lesson 12 with a joystick read
+// through both channels of the ADC0832.
+// The matrix uses the ADC's lesson pins so the ADC is moved to CS = 5,
+// CLK = 6, and DIO = 13 with the joystick's switch on GPIO 19.
+// Pushing the stick further moves the dot faster and pressing the stick puts
+// it back in the middle.
+
+use anyhow::Result;
+use std::time::{Duration, Instant};
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    button::ButtonEvent, cli::Common, joystick::JoystickEvent, run_lesson, say, Adc0832,
+    DotMatrix8x8, Joystick, MatrixDisplay,
+};
+
+// ADC pins.
+const ADC_CS: u8 = 5;
+const ADC_CLK: u8 = 6;
+const ADC_DIO: u8 = 13;
+const SW_PIN: u8 = 19;
+// Time in milliseconds between reads of the stick.
+const DELAY: u64 = 20;
+// Time in milliseconds between moves of the dot with the stick pushed all the
+// way.
+const FASTEST: u64 = 60;
+
+/// Moves a dot around the dot matrix with a joystick.
+#[derive(StructOpt)]
+#[structopt(name = "15_joystick")]
+struct Opt {
+    /// Gpio pin for the ADC's chip select (default 5).
+    #[structopt(long)]
+    adc_cs: Option<u8>,
+    /// Gpio pin for the ADC's clock (default 6).
+    #[structopt(long)]
+    adc_clk: Option<u8>,
+    /// Gpio pin for the ADC's data (default 13).
+    #[structopt(long)]
+    adc_dio: Option<u8>,
+    /// Gpio pin for the switch (default 19).
+    #[structopt(long)]
+    sw: Option<u8>,
+    /// Time in milliseconds between reads of the stick (default 20).
+    #[structopt(long)]
+    delay: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("15_joystick")?
+        .with("adc_cs", opt.adc_cs)?
+        .with("adc_clk", opt.adc_clk)?
+        .with("adc_dio", opt.adc_dio)?
+        .with("sw", opt.sw)?
+        .with("delay", opt.delay)?;
+    run_lesson("15_Joystick", |ctx| {
+        let adc_cs = config.pin("adc_cs", ADC_CS)?;
+        let adc_clk = config.pin("adc_clk", ADC_CLK)?;
+        let adc_dio = config.pin("adc_dio", ADC_DIO)?;
+        let sw_pin = config.pin("sw", SW_PIN)?;
+        let delay = config.get("delay", DELAY)?;
+        let mut matrix = DotMatrix8x8::new()?;
+        let adc = Adc0832::with_pins(adc_cs, adc_clk, adc_dio)?;
+        let mut joystick = Joystick::new(adc, sw_pin)?;
+        say!("Leave the stick alone for a moment while it is calibrated");
+        joystick.calibrate_center()?;
+        let mut iterations = opt.common.iterations();
+        let (mut x, mut y) = (3i32, 3i32);
+        let mut moved_at = Instant::now();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            match joystick.poll()? {
+                Some(JoystickEvent::Button(ButtonEvent::Pressed)) => {
+                    x = 3;
+                    y = 3;
+                }
+                Some(event) => say!("{:?}", event),
+                None => {}
+            }
+            // Poll has already read the stick so no need to read it again.
+            let (dx, dy) = joystick.position();
+            let push = dx.abs().max(dy.abs());
+            // Further pushed means less time between moves.
+            if push > 0.0 && moved_at.elapsed() >= Duration::from_millis(FASTEST).div_f64(push) {
+                // Rows go down the matrix so up is towards row 0.
+                if dx.abs() > dy.abs() {
+                    x += dx.signum() as i32;
+                } else {
+                    y -= dy.signum() as i32;
+                }
+                x = x.clamp(0, 7);
+                y = y.clamp(0, 7);
+                moved_at = Instant::now();
+            }
+            matrix.clear();
+            matrix.set_pixel(x as usize, y as usize, true);
+            matrix.refresh()?;
+            ctx.sleep(Duration::from_millis(delay));
+        }
+        Ok(())
+    })
+}
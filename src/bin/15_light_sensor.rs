@@ -0,0 +1,115 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Reads a photoresistor through channel 0 of the ADC0832 and shows the light
+// level on the LED bar from lesson 3.
+// The LED bar uses the ADC's lesson pins so the ADC is moved to CS = 5,
+// CLK = 6, and DIO = 13.
+// Run with `--calibrate` to be asked to cover and then light up the sensor
+// before starting so the whole bar gets used.
+
+use anyhow::{Context, Result};
+use std::{io::stdin, time::Duration};
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, run_lesson, say, Adc0832, LedBar, LightSensor,
+};
+
+// ADC pins.
+const ADC_CS: u8 = 5;
+const ADC_CLK: u8 = 6;
+const ADC_DIO: u8 = 13;
+// ADC channel the photoresistor is connected to.
+const CHANNEL: u8 = 0;
+// Time in milliseconds between readings.
+const DELAY: u64 = 100;
+
+/// Reads a photoresistor through the ADC0832.
+#[derive(StructOpt)]
+#[structopt(name = "15_light_sensor")]
+struct Opt {
+    /// Gpio pin for the ADC's chip select (default 5).
+    #[structopt(long)]
+    adc_cs: Option<u8>,
+    /// Gpio pin for the ADC's clock (default 6).
+    #[structopt(long)]
+    adc_clk: Option<u8>,
+    /// Gpio pin for the ADC's data (default 13).
+    #[structopt(long)]
+    adc_dio: Option<u8>,
+    /// Time in milliseconds between readings (default 100).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Calibrate the dark and bright readings first.
+    #[structopt(long)]
+    calibrate: bool,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("15_light_sensor")?
+        .with("adc_cs", opt.adc_cs)?
+        .with("adc_clk", opt.adc_clk)?
+        .with("adc_dio", opt.adc_dio)?
+        .with("delay", opt.delay)?;
+    run_lesson("15_Light_Sensor", |ctx| {
+        let adc_cs = config.pin("adc_cs", ADC_CS)?;
+        let adc_clk = config.pin("adc_clk", ADC_CLK)?;
+        let adc_dio = config.pin("adc_dio", ADC_DIO)?;
+        let delay = config.get("delay", DELAY)?;
+        let mut adc = Adc0832::with_pins(adc_cs, adc_clk, adc_dio)?;
+        let mut bar = LedBar::new()?;
+        let mut sensor = LightSensor::new(CHANNEL);
+        if opt.calibrate {
+            wait_for_enter("Cover the sensor and press Enter")?;
+            sensor.calibrate_dark(&mut adc)?;
+            wait_for_enter("Shine a light on the sensor and press Enter")?;
+            sensor.calibrate_bright(&mut adc)?;
+        }
+        let mut iterations = opt.common.iterations();
+        let mut last = None;
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            let level = sensor.read(&mut adc)?.round();
+            if last != Some(level) {
+                say!("light = {}%", level);
+                bar.set_percent(level as f32)?;
+                last = Some(level);
+            }
+            ctx.sleep(Duration::from_millis(delay));
+        }
+        bar.clear()?;
+        Ok(())
+    })
+}
+
+fn wait_for_enter(prompt: &str) -> Result<()> {
+    say!("{}", prompt);
+    stdin()
+        .read_line(&mut String::new())
+        .context("Failed to read from stdin")?;
+    Ok(())
+}
@@ -0,0 +1,183 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Watches a soil moisture probe on channel 0 of the ADC0832 and beeps the
+// active buzzer from lesson 6 every few seconds while the plant needs watering.
+// The buzzer uses the ADC's CS pin so the ADC is moved to CS = 5, CLK = 6, and
+// DIO = 13.
+// Run with `--calibrate` to be asked to hold the probe in the air and then put
+// it in water. The calibration is saved to config/moisture.toml and used from
+// then on.
+// Run with `--threshold=<percent>` to change when the alarm goes off.
+// Saving a new threshold or delay in the config file takes effect right away.
+
+use anyhow::{Context, Result};
+use std::{io::stdin, path::Path, time::Duration};
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    buzzer::AlarmHandle,
+    cli::Common,
+    config::Lesson,
+    moisture::{Calibration, MoistureEvent},
+    run_lesson, say, ActiveBuzzer, Adc0832, Alarm, MoistureSensor,
+};
+
+// Settings which are pins and need a restart to change.
+const PIN_KEYS: [&str; 4] = ["adc_cs", "adc_clk", "adc_dio", "buzzer"];
+// ADC pins.
+const ADC_CS: u8 = 5;
+const ADC_CLK: u8 = 6;
+const ADC_DIO: u8 = 13;
+const BUZZER_PIN: u8 = 17;
+// ADC channel the probe is connected to.
+const CHANNEL: u8 = 0;
+const CALIBRATION_FILE: &str = "config/moisture.toml";
+// Moisture in percent below which the plant needs watering.
+const THRESHOLD: f64 = 30.0;
+// Extra moisture in percent needed before the alarm stops so it doesn't
+// chatter right at the threshold.
+const HYSTERESIS: f64 = 5.0;
+// Time in milliseconds between readings.
+const DELAY: u64 = 1000;
+
+/// Sounds an alarm when a plant needs watering.
+#[derive(StructOpt)]
+#[structopt(name = "15_plant_alarm")]
+struct Opt {
+    /// Gpio pin for the ADC's chip select (default 5).
+    #[structopt(long)]
+    adc_cs: Option<u8>,
+    /// Gpio pin for the ADC's clock (default 6).
+    #[structopt(long)]
+    adc_clk: Option<u8>,
+    /// Gpio pin for the ADC's data (default 13).
+    #[structopt(long)]
+    adc_dio: Option<u8>,
+    /// Gpio pin for the buzzer (default 17).
+    #[structopt(long)]
+    buzzer: Option<u8>,
+    /// Time in milliseconds between readings (default 1000).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Moisture percentage below which the alarm goes off (default 30).
+    #[structopt(long)]
+    threshold: Option<f64>,
+    /// Calibrate the dry and wet readings first and save them.
+    #[structopt(long)]
+    calibrate: bool,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = with_options(&opt, opt.common.lesson("15_plant_alarm")?)?;
+    run_lesson("15_PlantAlarm", |ctx| {
+        ctx.watch_config(&opt.common);
+        let adc_cs = config.pin("adc_cs", ADC_CS)?;
+        let adc_clk = config.pin("adc_clk", ADC_CLK)?;
+        let adc_dio = config.pin("adc_dio", ADC_DIO)?;
+        let buzzer_pin = config.pin("buzzer", BUZZER_PIN)?;
+        let (mut delay, mut threshold) = timing(&config)?;
+        let mut adc = Adc0832::with_pins(adc_cs, adc_clk, adc_dio)?;
+        let mut sensor = MoistureSensor::new(CHANNEL);
+        if opt.calibrate {
+            wait_for_enter("Hold the probe in the air and press Enter")?;
+            sensor.calibrate_dry(&mut adc)?;
+            wait_for_enter("Put the probe in a glass of water and press Enter")?;
+            sensor.calibrate_wet(&mut adc)?;
+            sensor.calibration().save(CALIBRATION_FILE)?;
+            say!("calibration saved to {}", CALIBRATION_FILE);
+        } else if Path::new(CALIBRATION_FILE).exists() {
+            sensor.set_calibration(Calibration::load(CALIBRATION_FILE)?)?;
+        } else {
+            say!("not calibrated yet, run with --calibrate for better readings");
+        }
+        let buzzer = ActiveBuzzer::new(buzzer_pin)?;
+        sensor.set_alarm(threshold, HYSTERESIS, sound_alarm(buzzer.handle()));
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            if let Some((new_delay, new_threshold)) = ctx.reload(|| -> Result<_> {
+                let new = with_options(&opt, opt.common.reload("15_plant_alarm")?)?;
+                timing(&new.keep_from(&config, &PIN_KEYS))
+            }) {
+                delay = new_delay;
+                if new_threshold != threshold {
+                    threshold = new_threshold;
+                    // The new alarm starts out thinking things are fine so
+                    // the old one is quietened until the next reading.
+                    buzzer.stop();
+                    sensor.set_alarm(threshold, HYSTERESIS, sound_alarm(buzzer.handle()));
+                }
+                say!("threshold = {:.0}%, delay = {}ms", threshold, delay);
+            }
+            let level = sensor.read(&mut adc)?;
+            say!("moisture = {:.0}%", level);
+            ctx.sleep(Duration::from_millis(delay));
+        }
+        Ok(())
+    })
+}
+
+/// Puts any settings given on the command line over the ones from the config
+/// file.
+fn with_options(opt: &Opt, config: Lesson) -> Result<Lesson> {
+    Ok(config
+        .with("adc_cs", opt.adc_cs)?
+        .with("adc_clk", opt.adc_clk)?
+        .with("adc_dio", opt.adc_dio)?
+        .with("buzzer", opt.buzzer)?
+        .with("delay", opt.delay)?
+        .with("threshold", opt.threshold)?)
+}
+
+/// Gets the delay and alarm threshold from the settings.
+fn timing(config: &Lesson) -> Result<(u64, f64)> {
+    Ok((
+        config.get("delay", DELAY)?,
+        config.get("threshold", THRESHOLD)?,
+    ))
+}
+
+/// Gives the alarm callback beeping the buzzer while the plant is too dry.
+fn sound_alarm(alarm: AlarmHandle) -> impl FnMut(MoistureEvent) + Send + 'static {
+    move |event| {
+        say!("{:?}", event);
+        match event {
+            // A short beep every few seconds is enough to be noticed.
+            MoistureEvent::TooDry(_) => alarm.start(Alarm::Custom(vec![
+                Duration::from_millis(150),
+                Duration::from_secs(5),
+            ])),
+            MoistureEvent::Recovered(_) => alarm.stop(),
+        }
+    }
+}
+
+fn wait_for_enter(prompt: &str) -> Result<()> {
+    say!("{}", prompt);
+    stdin()
+        .read_line(&mut String::new())
+        .context("Failed to read from stdin")?;
+    Ok(())
+}
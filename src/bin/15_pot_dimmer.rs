@@ -0,0 +1,92 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Reads a potentiometer through channel 0 of the ADC0832 on its lesson pins and
+// uses it to set the brightness of a LED wired like the one in lesson 1 but on
+// GPIO 22.
+// The readings are smoothed so the last bit of the ADC flickering back and
+// forth doesn't make the LED shimmer.
+
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, run_lesson, say, Adc, Adc0832, Led, PwmOutput,
+};
+
+const LED_PIN: u8 = 22;
+// ADC channel the potentiometer wiper is connected to.
+const CHANNEL: u8 = 0;
+// How much each new reading counts towards the smoothed one from 0.0 to 1.0.
+const SMOOTHING: f64 = 0.2;
+// Smallest change in brightness that is passed on to the LED.
+const HYSTERESIS: f64 = 0.005;
+// Time in milliseconds between readings.
+const DELAY: u64 = 20;
+
+/// Dims an LED with a potentiometer.
+#[derive(StructOpt)]
+#[structopt(name = "15_pot_dimmer")]
+struct Opt {
+    /// Gpio pin for the LED (default 22).
+    #[structopt(long)]
+    led: Option<u8>,
+    /// Time in milliseconds between readings (default 20).
+    #[structopt(long)]
+    delay: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("15_pot_dimmer")?
+        .with("led", opt.led)?
+        .with("delay", opt.delay)?;
+    run_lesson("15_Pot_Dimmer", |ctx| {
+        let led_pin = config.pin("led", LED_PIN)?;
+        let delay = config.get("delay", DELAY)?;
+        let mut adc = Adc0832::new()?;
+        let mut led = Led::active_low(led_pin)?;
+        let mut iterations = opt.common.iterations();
+        let mut smoothed = adc.read_ratio(CHANNEL)?;
+        let mut shown = -1.0;
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            // Skip the odd glitched conversion instead of stopping.
+            if let Ok(ratio) = adc.read_ratio(CHANNEL) {
+                smoothed += (ratio - smoothed) * SMOOTHING;
+            }
+            if (smoothed - shown).abs() >= HYSTERESIS {
+                shown = smoothed;
+                // Squaring makes the brightness look more even as the knob turns.
+                led.set_duty_cycle(shown * shown)?;
+                say!("brightness = {:.0}%", shown * 100.0);
+            }
+            ctx.sleep(Duration::from_millis(delay));
+        }
+        led.off()?;
+        Ok(())
+    })
+}
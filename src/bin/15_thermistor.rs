@@ -0,0 +1,90 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Reads the NTC thermistor through channel 0 of the ADC0832 on its lesson pins
+// and prints the temperature once a second.
+// Run with `--beta=<β>` if your thermistor isn't the usual β = 3950 one.
+
+use anyhow::Result;
+use log::error;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, run_lesson, say, thermistor::Conversion, Adc0832, Thermistor,
+};
+
+// ADC channel the thermistor is connected to.
+const CHANNEL: u8 = 0;
+// Resistance in ohms of the thermistor at 25°C.
+const NOMINAL_RESISTANCE: f64 = 10_000.0;
+// Time in milliseconds between readings.
+const DELAY: u64 = 1000;
+
+/// Reads the temperature from a thermistor.
+#[derive(StructOpt)]
+#[structopt(name = "15_thermistor")]
+struct Opt {
+    /// Time in milliseconds between readings (default 1000).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Beta coefficient of the thermistor.
+    #[structopt(long)]
+    beta: Option<f64>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("15_thermistor")?
+        .with("delay", opt.delay)?;
+    run_lesson("15_Thermistor", |ctx| {
+        let delay = config.get("delay", DELAY)?;
+        let beta = opt.beta;
+        let mut adc = Adc0832::new()?;
+        let mut thermistor = Thermistor::new(CHANNEL);
+        if let Some(beta) = beta {
+            thermistor.set_conversion(Conversion::Beta {
+                beta,
+                nominal_resistance: NOMINAL_RESISTANCE,
+                nominal_temperature: 25.0,
+            });
+        }
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            // A bad reading now and then shouldn't stop the lesson.
+            match thermistor.read_celsius(&mut adc) {
+                Ok(celsius) => say!(
+                    "temperature = {:.1}°C ({:.1}°F)",
+                    celsius,
+                    celsius * 9.0 / 5.0 + 32.0
+                ),
+                Err(e) => error!("{:#}", e),
+            }
+            ctx.sleep(Duration::from_millis(delay));
+        }
+        Ok(())
+    })
+}
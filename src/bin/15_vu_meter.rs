@@ -0,0 +1,99 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Turns the LED bar from lesson 3 into a VU meter for the sound sensor module
+// read through channel 0 of the ADC0832.
+// The LED bar uses the ADC's lesson pins so the ADC is moved to CS = 5,
+// CLK = 6, and DIO = 13.
+// The bar shows the RMS level over the last 50ms on a 40dB scale with a single
+// LED above it marking the recent peak.
+
+use anyhow::Result;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, led_bar::LEDS, run_lesson, Adc0832, LedBar, SoundSensor,
+};
+
+// ADC pins.
+const ADC_CS: u8 = 5;
+const ADC_CLK: u8 = 6;
+const ADC_DIO: u8 = 13;
+// ADC channel the sound sensor is connected to.
+const CHANNEL: u8 = 0;
+// Range shown on the bar in dB below full scale.
+const RANGE_DB: f64 = 40.0;
+
+/// Shows the sound level on the LED bar.
+#[derive(StructOpt)]
+#[structopt(name = "15_vu_meter")]
+struct Opt {
+    /// Gpio pin for the ADC's chip select (default 5).
+    #[structopt(long)]
+    adc_cs: Option<u8>,
+    /// Gpio pin for the ADC's clock (default 6).
+    #[structopt(long)]
+    adc_clk: Option<u8>,
+    /// Gpio pin for the ADC's data (default 13).
+    #[structopt(long)]
+    adc_dio: Option<u8>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("15_vu_meter")?
+        .with("adc_cs", opt.adc_cs)?
+        .with("adc_clk", opt.adc_clk)?
+        .with("adc_dio", opt.adc_dio)?;
+    run_lesson("15_VU_Meter", |ctx| {
+        let adc_cs = config.pin("adc_cs", ADC_CS)?;
+        let adc_clk = config.pin("adc_clk", ADC_CLK)?;
+        let adc_dio = config.pin("adc_dio", ADC_DIO)?;
+        let mut adc = Adc0832::with_pins(adc_cs, adc_clk, adc_dio)?;
+        let mut bar = LedBar::new()?;
+        let mut sensor = SoundSensor::new(CHANNEL);
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            // Measuring takes the window time so no sleep is needed.
+            let envelope = sensor.measure(&mut adc)?;
+            let level = leds(envelope.db());
+            let peak = leds(envelope.peak_db());
+            let mut mask = (0xffu16 >> (LEDS - level)) as u8;
+            if peak > 0 {
+                mask |= 1 << (peak - 1);
+            }
+            bar.set_mask(mask)?;
+        }
+        bar.clear()?;
+        Ok(())
+    })
+}
+
+/// Turns a level in dB below full scale into a number of LEDs.
+fn leds(db: f64) -> u8 {
+    let fraction = ((db + RANGE_DB) / RANGE_DB).clamp(0.0, 1.0);
+    (fraction * f64::from(LEDS)).round() as u8
+}
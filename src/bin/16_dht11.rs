@@ -0,0 +1,100 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Reads a DHT11 temperature and humidity sensor with its data line on GPIO 17
+// every couple of seconds.
+// Run with `--lcd` to also show the readings on the LCD1602 from lesson 13.
+
+use anyhow::Result;
+use log::error;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{cli::Common, run_lesson, say, Dht11, Lcd1602};
+
+const DHT_PIN: u8 = 17;
+// Custom LCD character for a degree sign.
+const DEGREE: [u8; 8] = [0x0c, 0x12, 0x12, 0x0c, 0x00, 0x00, 0x00, 0x00];
+// Time in milliseconds between readings.
+const DELAY: u64 = 2000;
+
+/// Reads a DHT11 temperature and humidity sensor.
+#[derive(StructOpt)]
+#[structopt(name = "16_dht11")]
+struct Opt {
+    /// Gpio pin for the DHT11's data (default 17).
+    #[structopt(long)]
+    dht: Option<u8>,
+    /// Time in milliseconds between readings (default 2000).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Also show the readings on the LCD1602.
+    #[structopt(long)]
+    lcd: bool,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("16_dht11")?
+        .with("dht", opt.dht)?
+        .with("delay", opt.delay)?;
+    run_lesson("16_DHT11", |ctx| {
+        let dht_pin = config.pin("dht", DHT_PIN)?;
+        let delay = config.get("delay", DELAY)?;
+        let mut dht = Dht11::new(dht_pin)?;
+        let mut lcd = if opt.lcd {
+            let mut lcd = Lcd1602::new()?;
+            lcd.create_char(0, DEGREE)?;
+            lcd.print_at(0, 0, "Reading DHT11...")?;
+            Some(lcd)
+        } else {
+            None
+        };
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            // A sensor that gives up now and then shouldn't stop the lesson.
+            match dht.read() {
+                Ok(reading) => {
+                    say!(
+                        "temperature = {:.1}°C, humidity = {:.0}%",
+                        reading.temperature,
+                        reading.humidity
+                    );
+                    if let Some(lcd) = lcd.as_mut() {
+                        lcd.print_at(0, 0, &format!("Temp: {:>5.1}\x00C  ", reading.temperature))?;
+                        lcd.print_at(1, 0, &format!("Humidity: {:>3.0}%  ", reading.humidity))?;
+                    }
+                }
+                Err(e) => error!("{:#}", e),
+            }
+            ctx.sleep(Duration::from_millis(delay));
+        }
+        if let Some(lcd) = lcd.as_mut() {
+            lcd.clear()?;
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,89 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Logs the temperature from every DS18B20 on the 1-Wire bus every few seconds.
+// Needs `dtoverlay=w1-gpio` in /boot/config.txt with the sensors' data line on
+// GPIO 4 and a 4.7kΩ pull-up to 3.3V.
+// Run with `--fahrenheit` to log in °F instead.
+
+use anyhow::{bail, Result};
+use chrono::Local;
+use log::error;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{cli::Common, run_lesson, say, Ds18b20};
+
+// Time in milliseconds between readings.
+const DELAY: u64 = 5000;
+
+/// Logs the temperature from DS18B20 sensors.
+#[derive(StructOpt)]
+#[structopt(name = "16_ds18b20")]
+struct Opt {
+    /// Time in milliseconds between readings (default 5000).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Log in °F instead of °C.
+    #[structopt(long)]
+    fahrenheit: bool,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt.common.lesson("16_ds18b20")?.with("delay", opt.delay)?;
+    run_lesson("16_DS18B20", |ctx| {
+        let delay = config.get("delay", DELAY)?;
+        let fahrenheit = opt.fahrenheit;
+        let sensors = Ds18b20::all()?;
+        if sensors.is_empty() {
+            bail!("No DS18B20 found on the 1-Wire bus");
+        }
+        for sensor in sensors.iter() {
+            say!("found {}", sensor.id());
+        }
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        'outer: while ctx.is_running() && iterations.again() {
+            let time = Local::now().format("%Y-%m-%d %H:%M:%S");
+            for sensor in sensors.iter() {
+                // One bad sensor shouldn't stop the others being logged.
+                let reading = if fahrenheit {
+                    sensor.read_fahrenheit().map(|t| format!("{:.2}°F", t))
+                } else {
+                    sensor.read_celsius().map(|t| format!("{:.2}°C", t))
+                };
+                match reading {
+                    Ok(reading) => say!("{} {} {}", time, sensor.id(), reading),
+                    Err(e) => error!("{} {:#}", time, e),
+                }
+                // Improves Ctrl-C responsiveness.
+                if !ctx.is_running() {
+                    break 'outer;
+                }
+            }
+            ctx.sleep(Duration::from_millis(delay));
+        }
+        Ok(())
+    })
+}
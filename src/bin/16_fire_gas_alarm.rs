@@ -0,0 +1,165 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Fire and gas alarm using a flame sensor on channel 0 and an MQ-2 gas sensor
+// on channel 1 of the ADC0832 with their digital outputs on GPIO 19 and 26.
+// While either goes off the active buzzer from lesson 6 sounds, a red LED on
+// GPIO 4 lights, and the LCD1602 from lesson 13 says what was found.
+// The LCD uses the ADC's lesson pins so the ADC is moved to CS = 5, CLK = 6,
+// and DIO = 13.
+// Run with `--warm-up=<seconds>` to change how long the MQ-2 is given to warm
+// up.
+
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, run_lesson, say, ActiveBuzzer, Adc0832, Alarm, FlameSensor, GasSensor, Lcd1602,
+    Led,
+};
+
+// ADC pins.
+const ADC_CS: u8 = 5;
+const ADC_CLK: u8 = 6;
+const ADC_DIO: u8 = 13;
+// ADC channels.
+const FLAME_CHANNEL: u8 = 0;
+const GAS_CHANNEL: u8 = 1;
+// Digital output pins.
+const FLAME_PIN: u8 = 19;
+const GAS_PIN: u8 = 26;
+const BUZZER_PIN: u8 = 17;
+const LED_PIN: u8 = 4;
+// Time in milliseconds between readings.
+const DELAY: u64 = 200;
+
+/// Sounds an alarm on flame or gas.
+#[derive(StructOpt)]
+#[structopt(name = "16_fire_gas_alarm")]
+struct Opt {
+    /// Gpio pin for the ADC's chip select (default 5).
+    #[structopt(long)]
+    adc_cs: Option<u8>,
+    /// Gpio pin for the ADC's clock (default 6).
+    #[structopt(long)]
+    adc_clk: Option<u8>,
+    /// Gpio pin for the ADC's data (default 13).
+    #[structopt(long)]
+    adc_dio: Option<u8>,
+    /// Gpio pin for the flame sensor's DO (default 19).
+    #[structopt(long)]
+    flame: Option<u8>,
+    /// Gpio pin for the gas sensor's DO (default 26).
+    #[structopt(long)]
+    gas: Option<u8>,
+    /// Gpio pin for the buzzer (default 17).
+    #[structopt(long)]
+    buzzer: Option<u8>,
+    /// Gpio pin for the LED (default 4).
+    #[structopt(long)]
+    led: Option<u8>,
+    /// Time in milliseconds between readings (default 200).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Seconds the gas sensor needs to warm up (default 60).
+    #[structopt(long)]
+    warm_up: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("16_fire_gas_alarm")?
+        .with("adc_cs", opt.adc_cs)?
+        .with("adc_clk", opt.adc_clk)?
+        .with("adc_dio", opt.adc_dio)?
+        .with("flame", opt.flame)?
+        .with("gas", opt.gas)?
+        .with("buzzer", opt.buzzer)?
+        .with("led", opt.led)?
+        .with("delay", opt.delay)?;
+    run_lesson("16_FireGasAlarm", |ctx| {
+        let adc_cs = config.pin("adc_cs", ADC_CS)?;
+        let adc_clk = config.pin("adc_clk", ADC_CLK)?;
+        let adc_dio = config.pin("adc_dio", ADC_DIO)?;
+        let flame_pin = config.pin("flame", FLAME_PIN)?;
+        let gas_pin = config.pin("gas", GAS_PIN)?;
+        let buzzer_pin = config.pin("buzzer", BUZZER_PIN)?;
+        let led_pin = config.pin("led", LED_PIN)?;
+        let delay = config.get("delay", DELAY)?;
+        let warm_up = opt.warm_up;
+        let mut adc = Adc0832::with_pins(adc_cs, adc_clk, adc_dio)?;
+        let mut flame = FlameSensor::with_digital(FLAME_CHANNEL, flame_pin)?;
+        let mut gas = GasSensor::with_digital(GAS_CHANNEL, gas_pin)?;
+        if let Some(warm_up) = warm_up {
+            gas.set_warm_up(Duration::from_secs(warm_up));
+        }
+        let buzzer = ActiveBuzzer::new(buzzer_pin)?;
+        let mut led = Led::active_low(led_pin)?;
+        let mut lcd = Lcd1602::new()?;
+        let mut iterations = opt.common.iterations();
+        let mut alarmed = false;
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            let fire = flame.detected(&mut adc)?;
+            let smoke = gas.detected(&mut adc)?;
+            let flame_level = flame.level(&mut adc)?;
+            let gas_level = gas.level(&mut adc)?;
+            let flame_state = if fire { "FIRE!" } else { "ok" };
+            let gas_state = if smoke {
+                "GAS!".to_string()
+            } else if gas.is_warm() {
+                "ok".to_string()
+            } else {
+                format!("wait{:3}", gas.warm_up_remaining().as_secs())
+            };
+            lcd.print_at(
+                0,
+                0,
+                &format!("Flame {:3.0}% {:<5}", flame_level, flame_state),
+            )?;
+            lcd.print_at(1, 0, &format!("Gas {:3.0}% {:<7}", gas_level, gas_state))?;
+            let alarm = fire || smoke;
+            if alarm != alarmed {
+                say!(
+                    "{} flame = {:.0}%, gas = {:.0}%",
+                    if alarm { "ALARM" } else { "clear" },
+                    flame_level,
+                    gas_level
+                );
+                if alarm {
+                    buzzer.start(Alarm::Fast);
+                } else {
+                    buzzer.stop();
+                }
+                led.set(alarm)?;
+                alarmed = alarm;
+            }
+            ctx.sleep(Duration::from_millis(delay));
+        }
+        lcd.clear()?;
+        Ok(())
+    })
+}
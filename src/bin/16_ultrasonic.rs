@@ -0,0 +1,86 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Measures distance with a HC-SR04 ultrasonic sensor with its trigger on
+// GPIO 17 and echo, through a voltage divider, on GPIO 18.
+// Run with `--temperature=<°C>` to correct for the air temperature.
+
+use anyhow::Result;
+use log::error;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{cli::Common, run_lesson, say, Ultrasonic};
+
+const TRIGGER_PIN: u8 = 17;
+const ECHO_PIN: u8 = 18;
+// Time in milliseconds between readings.
+const DELAY: u64 = 200;
+
+/// Measures distance with an ultrasonic sensor.
+#[derive(StructOpt)]
+#[structopt(name = "16_ultrasonic")]
+struct Opt {
+    /// Gpio pin for the sensor's trigger (default 17).
+    #[structopt(long)]
+    trigger: Option<u8>,
+    /// Gpio pin for the sensor's echo (default 18).
+    #[structopt(long)]
+    echo: Option<u8>,
+    /// Time in milliseconds between readings (default 200).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Air temperature in °C used for the speed of sound.
+    #[structopt(long)]
+    temperature: Option<f64>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("16_ultrasonic")?
+        .with("trigger", opt.trigger)?
+        .with("echo", opt.echo)?
+        .with("delay", opt.delay)?;
+    run_lesson("16_Ultrasonic", |ctx| {
+        let trigger_pin = config.pin("trigger", TRIGGER_PIN)?;
+        let echo_pin = config.pin("echo", ECHO_PIN)?;
+        let delay = config.get("delay", DELAY)?;
+        let temperature = opt.temperature;
+        let mut sensor = Ultrasonic::new(trigger_pin, echo_pin)?;
+        if let Some(temperature) = temperature {
+            sensor.set_temperature(temperature);
+        }
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            match sensor.distance_cm() {
+                Ok(distance) => say!("distance = {:.1} cm", distance),
+                Err(e) => error!("{:#}", e),
+            }
+            ctx.sleep(Duration::from_millis(delay));
+        }
+        Ok(())
+    })
+}
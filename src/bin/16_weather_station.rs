@@ -0,0 +1,137 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Weather station on the LCD1602 from lesson 13 using a BMP280 or BME280 on the
+// I2C bus. The first row has the temperature and humidity (BME280 only) and
+// the second the pressure with a rising, falling, or steady mark from the
+// change over the last hour, and the altitude.
+// Run with `--altitude=<metres>` when you know your altitude to work out the
+// sea level pressure or `--sea-level=<hPa>` to give it from a weather service.
+
+use anyhow::Result;
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{bmp280, cli::Common, run_lesson, say, Bmp280, Lcd1602};
+
+// Custom LCD character for a degree sign.
+const DEGREE: [u8; 8] = [0x0c, 0x12, 0x12, 0x0c, 0x00, 0x00, 0x00, 0x00];
+// Time in milliseconds between readings.
+const DELAY: u64 = 5000;
+// Time over which the pressure trend is worked out.
+const TREND_TIME: Duration = Duration::from_secs(60 * 60);
+// Pressure change in hPa over the trend time that counts as rising or falling.
+const TREND_CHANGE: f64 = 0.5;
+
+/// Weather station using a BMP280 or BME280.
+#[derive(StructOpt)]
+#[structopt(name = "16_weather_station")]
+struct Opt {
+    /// Time in milliseconds between readings (default 5000).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Altitude in metres used to work out the sea level pressure.
+    #[structopt(long)]
+    altitude: Option<f64>,
+    /// Sea level pressure in hPa used to work out the altitude.
+    #[structopt(long)]
+    sea_level: Option<f64>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("16_weather_station")?
+        .with("delay", opt.delay)?;
+    run_lesson("16_WeatherStation", |ctx| {
+        let delay = config.get("delay", DELAY)?;
+        let mut bmp = Bmp280::new()?;
+        say!("found a {:?}", bmp.chip());
+        let altitude = opt.altitude;
+        let sea_level = opt.sea_level;
+        if let Some(altitude) = altitude {
+            bmp.calibrate_altitude(altitude)?;
+            say!("sea level pressure = {:.1}hPa", bmp.sea_level());
+        } else if let Some(sea_level) = sea_level {
+            bmp.set_sea_level(sea_level);
+        }
+        let mut lcd = Lcd1602::new()?;
+        lcd.create_char(0, DEGREE)?;
+        let mut iterations = opt.common.iterations();
+        let mut history: VecDeque<(Instant, f64)> = VecDeque::new();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            let reading = bmp.read()?;
+            let now = Instant::now();
+            history.push_back((now, reading.pressure));
+            while history.len() > 1 && now - history[0].0 > TREND_TIME {
+                history.pop_front();
+            }
+            let change = reading.pressure - history[0].1;
+            let trend = if change > TREND_CHANGE {
+                '^'
+            } else if change < -TREND_CHANGE {
+                'v'
+            } else {
+                '-'
+            };
+            let altitude = bmp280::altitude(reading.pressure, bmp.sea_level());
+            let humidity = reading
+                .humidity
+                .map(|h| format!("{:3.0}%", h))
+                .unwrap_or_default();
+            say!(
+                "temperature = {:.1}°C, humidity = {}, pressure = {:.1}hPa {}, altitude = {:.0}m",
+                reading.temperature,
+                reading
+                    .humidity
+                    .map(|h| format!("{:.0}%", h))
+                    .unwrap_or_else(|| "-".into()),
+                reading.pressure,
+                trend,
+                altitude
+            );
+            lcd.print_at(
+                0,
+                0,
+                &format!("{:5.1}\x00C     {:4}", reading.temperature, humidity),
+            )?;
+            lcd.print_at(
+                1,
+                0,
+                &format!("{:6.1}{} {:6.0}m ", reading.pressure, trend, altitude),
+            )?;
+            // Sleep in small steps to keep Ctrl-C responsive.
+            let until = now + Duration::from_millis(delay);
+            while ctx.is_running() && Instant::now() < until {
+                ctx.sleep(Duration::from_millis(100));
+            }
+        }
+        lcd.clear()?;
+        Ok(())
+    })
+}
@@ -0,0 +1,106 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Picks the LED bar pattern from lesson 3 with the buttons on a NEC IR remote
+// like the small ones that come with a lot of kits. The IR receiver's output
+// goes to GPIO 5.
+// Buttons 1 to 4 choose knight rider, ping pong, sparkle, or fill and drain,
+// 0 turns the bar off, and + and - change the speed.
+// Codes for any other button are printed so other remotes can be mapped.
+
+use anyhow::Result;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, run_lesson, say, IrReceiver, LedBar, Pattern,
+};
+
+const IR_PIN: u8 = 5;
+// Commands sent by the usual 21 button remote.
+const BUTTON_0: u8 = 0x16;
+const BUTTON_1: u8 = 0x0c;
+const BUTTON_2: u8 = 0x18;
+const BUTTON_3: u8 = 0x5e;
+const BUTTON_4: u8 = 0x08;
+const BUTTON_MINUS: u8 = 0x07;
+const BUTTON_PLUS: u8 = 0x15;
+// Time in milliseconds each frame is shown and the limits for it.
+const STEP: u64 = 60;
+const FASTEST: u64 = 20;
+const SLOWEST: u64 = 300;
+
+/// Decodes an IR remote.
+#[derive(StructOpt)]
+#[structopt(name = "17_ir_remote")]
+struct Opt {
+    /// Gpio pin for the IR receiver (default 5).
+    #[structopt(long)]
+    ir: Option<u8>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt.common.lesson("17_ir_remote")?.with("ir", opt.ir)?;
+    run_lesson("17_IR_Remote", |ctx| {
+        let ir_pin = config.pin("ir", IR_PIN)?;
+        let mut bar = LedBar::new()?;
+        let mut ir = IrReceiver::new(ir_pin)?;
+        let codes = ir.codes()?;
+        let mut iterations = opt.common.iterations();
+        let mut pattern: Option<Pattern> = None;
+        let mut step = STEP;
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            for code in codes.try_iter() {
+                match code.command {
+                    // Holding + or - keeps changing the speed.
+                    BUTTON_MINUS => step = (step + 10).min(SLOWEST),
+                    BUTTON_PLUS => step = step.saturating_sub(10).max(FASTEST),
+                    _ if code.repeat => continue,
+                    BUTTON_0 => pattern = None,
+                    BUTTON_1 => pattern = Some(Pattern::KnightRider),
+                    BUTTON_2 => pattern = Some(Pattern::PingPong),
+                    BUTTON_3 => pattern = Some(Pattern::Sparkle),
+                    BUTTON_4 => pattern = Some(Pattern::FillDrain),
+                    _ => {}
+                }
+                say!(
+                    "address = {:#06x}, command = {:#04x}{}",
+                    code.address,
+                    code.command,
+                    if code.repeat { " (repeat)" } else { "" }
+                );
+            }
+            match &pattern {
+                Some(pattern) => bar.play(pattern, Duration::from_millis(step))?,
+                None => {
+                    bar.clear()?;
+                    ctx.sleep(Duration::from_millis(STEP));
+                }
+            }
+        }
+        bar.clear()?;
+        Ok(())
+    })
+}
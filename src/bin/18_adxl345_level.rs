@@ -0,0 +1,113 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Turns the 8x8 dot matrix from lesson 12 into a bubble level using an ADXL345
+// on the I2C bus with its INT1 pin on GPIO 22.
+// The bubble floats to the high side and reaches the edge at about 30°.
+// Double tap the board to make the way it is sitting now count as level and
+// single tap it to go back to the real level.
+// Run with `--spi` to use the ADXL345 on SPI0 CE1 instead.
+
+use anyhow::Result;
+use std::{sync::mpsc::channel, time::Duration};
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::hal::spi::{Bus, SlaveSelect};
+use superkit_rust_code_for_raspberrypi::{
+    adxl345::{TapConfig, TapEvent},
+    cli::Common,
+    run_lesson, say, Adxl345, DotMatrix8x8, MatrixDisplay,
+};
+
+const INT_PIN: u8 = 22;
+// Tilt in g that puts the bubble at the edge, about sin(30°).
+const FULL_SCALE: f64 = 0.5;
+// Time in milliseconds between readings.
+const DELAY: u64 = 20;
+
+/// Bubble level on the dot matrix using an ADXL345.
+#[derive(StructOpt)]
+#[structopt(name = "18_adxl345_level")]
+struct Opt {
+    /// Gpio pin for the ADXL345's INT1 (default 22).
+    #[structopt(long)]
+    int: Option<u8>,
+    /// Time in milliseconds between readings (default 20).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Talk to the ADXL345 over SPI instead of I2C.
+    #[structopt(long)]
+    spi: bool,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("18_adxl345_level")?
+        .with("int", opt.int)?
+        .with("delay", opt.delay)?;
+    run_lesson("18_ADXL345Level", |ctx| {
+        let int_pin = config.pin("int", INT_PIN)?;
+        let delay = config.get("delay", DELAY)?;
+        let mut matrix = DotMatrix8x8::new()?;
+        let mut adxl = if opt.spi {
+            Adxl345::with_spi(Bus::Spi0, SlaveSelect::Ss1)?
+        } else {
+            Adxl345::new()?
+        };
+        adxl.enable_taps(TapConfig::default())?;
+        let (tx, taps) = channel();
+        adxl.on_tap(int_pin, move |event| {
+            let _ = tx.send(event);
+        })?;
+        let mut iterations = opt.common.iterations();
+        let mut zero = [0.0, 0.0];
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            let [x, y, _] = adxl.read()?;
+            for event in taps.try_iter() {
+                say!("{:?} tap", event);
+                zero = match event {
+                    TapEvent::Double => [x, y],
+                    TapEvent::Single => [0.0, 0.0],
+                };
+            }
+            // The end of an axis that is raised reads positive.
+            let column = position(x - zero[0]);
+            // Rows go down the matrix so a raised Y end is towards row 0.
+            let row = 7 - position(y - zero[1]);
+            matrix.clear();
+            matrix.set_pixel(column, row, true);
+            matrix.refresh()?;
+            ctx.sleep(Duration::from_millis(delay));
+        }
+        Ok(())
+    })
+}
+
+/// Turns a tilt in g into a pixel from 0 to 7.
+fn position(tilt: f64) -> usize {
+    let fraction = (tilt.clamp(-FULL_SCALE, FULL_SCALE) + FULL_SCALE) / (2.0 * FULL_SCALE);
+    (fraction * 7.0).round() as usize
+}
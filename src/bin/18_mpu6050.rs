@@ -0,0 +1,104 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Streams the pitch and roll of an MPU6050 on the I2C bus worked out with a
+// complementary filter. Keep it still for the first second while the gyro is
+// calibrated.
+// Run with `--lcd` to also show them on the LCD1602 from lesson 13.
+
+use anyhow::{Context, Result};
+use std::{
+    io::{stdout, Write},
+    time::Duration,
+};
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, run_lesson, say, ComplementaryFilter, Lcd1602, Mpu6050,
+};
+
+// Custom LCD character for a degree sign.
+const DEGREE: [u8; 8] = [0x0c, 0x12, 0x12, 0x0c, 0x00, 0x00, 0x00, 0x00];
+// Time in milliseconds between readings.
+const DELAY: u64 = 20;
+// Number of readings between display updates.
+const SHOW_EVERY: u32 = 10;
+// Number of gyro samples used for calibration.
+const SAMPLES: u16 = 200;
+
+/// Shows the pitch and roll from a MPU6050.
+#[derive(StructOpt)]
+#[structopt(name = "18_mpu6050")]
+struct Opt {
+    /// Time in milliseconds between readings (default 20).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Also show the readings on the LCD1602.
+    #[structopt(long)]
+    lcd: bool,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt.common.lesson("18_mpu6050")?.with("delay", opt.delay)?;
+    run_lesson("18_MPU6050", |ctx| {
+        let delay = config.get("delay", DELAY)?;
+        let mut mpu = Mpu6050::new()?;
+        let mut lcd = if opt.lcd {
+            let mut lcd = Lcd1602::new()?;
+            lcd.create_char(0, DEGREE)?;
+            lcd.print_at(0, 0, "Calibrating...")?;
+            Some(lcd)
+        } else {
+            None
+        };
+        say!("calibrating gyro, keep still ...");
+        mpu.calibrate_gyro(SAMPLES)?;
+        let mut filter = ComplementaryFilter::default();
+        let mut iterations = opt.common.iterations();
+        let mut count = 0u32;
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            let reading = mpu.read()?;
+            let (pitch, roll) = filter.update(&reading);
+            count += 1;
+            if count == SHOW_EVERY {
+                count = 0;
+                print!(
+                    "\rpitch = {:>6.1}°, roll = {:>6.1}°, temperature = {:.1}°C  ",
+                    pitch, roll, reading.temperature
+                );
+                stdout().flush().context("Failed to flush stdout")?;
+                if let Some(lcd) = lcd.as_mut() {
+                    lcd.print_at(0, 0, &format!("Pitch: {:>6.1}\x00  ", pitch))?;
+                    lcd.print_at(1, 0, &format!("Roll:  {:>6.1}\x00  ", roll))?;
+                }
+            }
+            ctx.sleep(Duration::from_millis(delay));
+        }
+        if let Some(lcd) = lcd.as_mut() {
+            lcd.clear()?;
+        }
+        Ok(())
+    })
+}
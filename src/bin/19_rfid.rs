@@ -0,0 +1,124 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Reads cards with an RC522 RFID reader on SPI0 CE0 and checks them against a
+// whitelist. An allowed card lights the green LED on GPIO 17 for a second and
+// anything else flashes the red LED on GPIO 27.
+// The whitelist is loaded again for each card so new cards can be added while
+// this is running.
+// Usage: 19_rfid [file]
+
+use anyhow::Result;
+use log::error;
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{cli::Common, run_lesson, say, Led, Rc522, Whitelist};
+
+const DEFAULT_FILE: &str = "config/rfid.toml";
+const GREEN_PIN: u8 = 17;
+const RED_PIN: u8 = 27;
+// Time in milliseconds the green LED stays on.
+const GRANTED: u64 = 1000;
+// Time in milliseconds for each red LED flash.
+const DENIED: u64 = 150;
+// Time in milliseconds between looking for cards.
+const DELAY: u64 = 100;
+
+/// Checks RFID cards against a whitelist.
+#[derive(StructOpt)]
+#[structopt(name = "19_rfid")]
+struct Opt {
+    /// Gpio pin for the green LED (default 17).
+    #[structopt(long)]
+    green: Option<u8>,
+    /// Gpio pin for the red LED (default 27).
+    #[structopt(long)]
+    red: Option<u8>,
+    /// Time in milliseconds between looking for cards (default 100).
+    #[structopt(long)]
+    delay: Option<u64>,
+    /// Whitelist of cards that are let in.
+    #[structopt(default_value = DEFAULT_FILE)]
+    file: String,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("19_rfid")?
+        .with("green", opt.green)?
+        .with("red", opt.red)?
+        .with("delay", opt.delay)?;
+    run_lesson("19_RFID", |ctx| {
+        let green_pin = config.pin("green", GREEN_PIN)?;
+        let red_pin = config.pin("red", RED_PIN)?;
+        let delay = config.get("delay", DELAY)?;
+        let file = opt.file;
+        // Catch problems with the file before any card is shown.
+        Whitelist::load(&file)?;
+        let mut reader = Rc522::new()?;
+        say!("RC522 version {:#04x}", reader.version()?);
+        let mut green = Led::active_low(green_pin)?;
+        let mut red = Led::active_low(red_pin)?;
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            // A badly placed card shouldn't stop things.
+            let uid = match reader.read_uid() {
+                Ok(Some(uid)) => uid,
+                Ok(None) => {
+                    ctx.sleep(Duration::from_millis(delay));
+                    continue;
+                }
+                Err(e) => {
+                    error!("{:#}", e);
+                    continue;
+                }
+            };
+            let whitelist = Whitelist::load(&file).unwrap_or_else(|e| {
+                error!("{:#}", e);
+                Whitelist::default()
+            });
+            match whitelist.find(&uid) {
+                Some(card) => {
+                    say!("{} allowed: {}", uid, card.name);
+                    green.on()?;
+                    ctx.sleep(Duration::from_millis(GRANTED));
+                    green.off()?;
+                }
+                None => {
+                    say!("{} denied", uid);
+                    for _ in 0..3 {
+                        red.on()?;
+                        ctx.sleep(Duration::from_millis(DENIED));
+                        red.off()?;
+                        ctx.sleep(Duration::from_millis(DENIED));
+                    }
+                }
+            }
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,134 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+// Web dashboard for showing off the kit from a phone or laptop.
+// Pick the parts plugged in with options like
+// `dashboard --led=17 --rgb=18,27,22 --button=23` then browse to port 8080 of
+// the Raspberry Pi.
+// Each part gets a control on the page, a slider for the LED, a color picker
+// for the RGB LED, or a text box for the LCD where `|` starts the second row,
+// and the button shows when it is pressed.
+// Built with the mock backend the page also shows the parts the way the
+// simulator does.
+
+use anyhow::{bail, Result};
+use log::error;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, pwm::PwmOutput, rgb::Polarity, run_lesson, say, Button, Dashboard, EventBus,
+    Lcd1602, Led, RgbPwm,
+};
+
+const ADDRESS: &str = "0.0.0.0:8080";
+
+/// Controls and shows the kit from a web page.
+#[derive(StructOpt)]
+#[structopt(name = "dashboard")]
+struct Opt {
+    /// Address and port to serve the page on (default 0.0.0.0:8080).
+    #[structopt(long)]
+    address: Option<String>,
+    /// Gpio pin for an LED dimmed with a slider.
+    #[structopt(long)]
+    led: Option<u8>,
+    /// Gpio pins for the red, green, and blue of an RGB LED separated by
+    /// commas.
+    #[structopt(long, use_delimiter = true)]
+    rgb: Option<Vec<u8>>,
+    /// Show text from the page on the LCD1602 wired like lesson 13.
+    #[structopt(long)]
+    lcd: bool,
+    /// Gpio pin for a button shown on the page.
+    #[structopt(long)]
+    button: Option<u8>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("dashboard")?
+        .with("address", opt.address.clone())?
+        .with("led", opt.led)?
+        .with("rgb", opt.rgb.clone())?
+        .with("lcd", Some(opt.lcd).filter(|lcd| *lcd))?
+        .with("button", opt.button)?;
+    let address = config.get("address", ADDRESS.to_string())?;
+    let led_pin: Option<u8> = config.get("led", None)?;
+    let rgb_pins: Option<[u8; 3]> = match config.get::<Option<Vec<u8>>>("rgb", None)? {
+        Some(pins) if pins.len() == 3 => Some([pins[0], pins[1], pins[2]]),
+        Some(pins) => bail!("Need 3 pins for the RGB LED but was given {}", pins.len()),
+        None => None,
+    };
+    let lcd = config.get("lcd", false)?;
+    let button_pin: Option<u8> = config.get("button", None)?;
+    if led_pin.is_none() && rgb_pins.is_none() && !lcd && button_pin.is_none() {
+        bail!("Nothing to show, add at least one of --led, --rgb, --lcd, or --button");
+    }
+    run_lesson("Dashboard", |ctx| {
+        let bus = EventBus::new();
+        let dashboard = Dashboard::new(bus.clone());
+        if let Some(pin) = led_pin {
+            let mut led = Led::active_low(pin)?;
+            bus.on_level("led", move |level| {
+                if let Err(e) = led.set_duty_cycle(level) {
+                    error!("{:#}", e);
+                }
+            });
+            dashboard.add_level("led");
+        }
+        if let Some([red, green, blue]) = rgb_pins {
+            let mut rgb = RgbPwm::with_pins(red, green, blue, Polarity::CommonCathode)?;
+            bus.on_color("rgb", move |color| {
+                if let Err(e) = rgb.set_color(color) {
+                    error!("{:#}", e);
+                }
+            });
+            dashboard.add_color("rgb");
+        }
+        if lcd {
+            let mut lcd = Lcd1602::new()?;
+            bus.on_text("lcd", move |text| {
+                let result = lcd
+                    .clear()
+                    .and_then(|_| lcd.print(&text.replace('|', "\n")));
+                if let Err(e) = result {
+                    error!("{:#}", e);
+                }
+            });
+            dashboard.add_text("lcd");
+        }
+        // Kept until the dashboard stops so its events keep coming.
+        let _button = match button_pin {
+            Some(pin) => {
+                let mut button = Button::new(pin)?;
+                bus.attach_button("button", &mut button)?;
+                Some(button)
+            }
+            None => None,
+        };
+        say!("Browse to http://{}", address);
+        dashboard.serve(&address, &ctx.token())?;
+        Ok(())
+    })
+}
@@ -0,0 +1,218 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Logs sensor readings for experiments that run for hours or days.
+// Pick the sensors plugged in with options like
+// `datalog --ds18b20 --interval=60 --hours=168` to log the temperature once a
+// minute for a week.
+// Readings go in a CSV file for each day under `logs/`, or with the `sqlite`
+// and `influxdb` features into a SQLite database with `--sqlite=<file>` or an
+// InfluxDB server with `--influx=http://<server>:8086`.
+
+use anyhow::{bail, Result};
+use log::warn;
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common,
+    datalog::{CsvStore, DataLog},
+    run_lesson, say, Adc0832, Dht11, Ds18b20, LightSensor,
+};
+
+// Time in seconds between readings.
+const INTERVAL: u64 = 60;
+const DIR: &str = "logs";
+const PREFIX: &str = "superkit";
+// ADC channel for the photoresistor.
+const LIGHT_CHANNEL: u8 = 0;
+const SECONDS_PER_HOUR: u64 = 60 * 60;
+const SECONDS_PER_DAY: u64 = 24 * SECONDS_PER_HOUR;
+
+/// Logs sensor readings to CSV files, SQLite, or InfluxDB.
+#[derive(StructOpt)]
+#[structopt(name = "datalog")]
+struct Opt {
+    /// Time in seconds between readings (default 60).
+    #[structopt(long)]
+    interval: Option<u64>,
+    /// Stop after this many hours instead of running until Ctrl-C.
+    #[structopt(long)]
+    hours: Option<u64>,
+    /// Gpio pin for a DHT11's data.
+    #[structopt(long)]
+    dht11: Option<u8>,
+    /// Log the temperature from the first DS18B20.
+    #[structopt(long)]
+    ds18b20: bool,
+    /// Log the light level from a photoresistor on channel 0 of the ADC0832
+    /// wired like lesson 15.
+    #[structopt(long)]
+    light: bool,
+    /// Directory for the CSV files (default logs).
+    #[structopt(long, parse(from_os_str))]
+    dir: Option<PathBuf>,
+    /// Start of the CSV file names (default superkit).
+    #[structopt(long)]
+    prefix: Option<String>,
+    /// Delete CSV files and SQLite rows older than this many days.
+    #[structopt(long)]
+    keep_days: Option<u64>,
+    /// Log to this SQLite database instead of CSV files.
+    #[cfg(feature = "sqlite")]
+    #[structopt(long, parse(from_os_str))]
+    sqlite: Option<PathBuf>,
+    /// Log to the InfluxDB server at this URL instead of CSV files.
+    #[cfg(feature = "influxdb")]
+    #[structopt(long)]
+    influx: Option<String>,
+    /// InfluxDB database or bucket (default superkit).
+    #[cfg(feature = "influxdb")]
+    #[structopt(long)]
+    database: Option<String>,
+    /// InfluxDB token if the server needs one.
+    #[cfg(feature = "influxdb")]
+    #[structopt(long)]
+    token: Option<String>,
+    /// Also write CSV files when logging to SQLite or InfluxDB.
+    #[structopt(long)]
+    csv: bool,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("datalog")?
+        .with("interval", opt.interval)?
+        .with("dir", opt.dir.clone())?
+        .with("prefix", opt.prefix.clone())?
+        .with("keep_days", opt.keep_days)?;
+    let interval = Duration::from_secs(config.get("interval", INTERVAL)?);
+    let length = opt
+        .hours
+        .map(|hours| Duration::from_secs(hours * SECONDS_PER_HOUR));
+    let dir = config.get("dir", PathBuf::from(DIR))?;
+    let prefix = config.get("prefix", PREFIX.to_string())?;
+    let retention = config
+        .get::<Option<u64>>("keep_days", None)?
+        .map(|days| Duration::from_secs(days * SECONDS_PER_DAY));
+    if opt.dht11.is_none() && !opt.ds18b20 && !opt.light {
+        bail!("Nothing to log, add at least one of --dht11, --ds18b20, or --light");
+    }
+    run_lesson("DataLog", |ctx| {
+        let mut log = DataLog::new();
+        // CSV files are the default when nothing else is given.
+        #[allow(unused_mut)]
+        let mut csv = true;
+        #[cfg(feature = "sqlite")]
+        if let Some(path) = opt.sqlite.as_ref() {
+            let mut store = superkit_rust_code_for_raspberrypi::datalog::SqliteStore::open(path)?;
+            if let Some(retention) = retention {
+                store.set_retention(retention);
+            }
+            log.add(store);
+            say!("Logging to {}", path.display());
+            csv = false;
+        }
+        #[cfg(feature = "influxdb")]
+        if let Some(server) = opt.influx.as_ref() {
+            let database = opt.database.as_deref().unwrap_or(PREFIX);
+            let mut store =
+                superkit_rust_code_for_raspberrypi::datalog::InfluxStore::new(server, database);
+            if let Some(token) = opt.token.as_ref() {
+                store.set_token(token);
+            }
+            log.add(store);
+            say!("Logging to {} in {}", server, database);
+            csv = false;
+        }
+        if csv || opt.csv {
+            let mut store = CsvStore::new(&dir, &prefix)?;
+            if let Some(retention) = retention {
+                store.set_retention(retention);
+            }
+            log.add(store);
+            say!("Logging to {}", dir.display());
+        }
+        let mut dht11 = opt.dht11.map(Dht11::new).transpose()?;
+        let ds18b20 = if opt.ds18b20 {
+            Some(Ds18b20::first()?)
+        } else {
+            None
+        };
+        let mut light = if opt.light {
+            Some((Adc0832::new()?, LightSensor::new(LIGHT_CHANNEL)))
+        } else {
+            None
+        };
+        let start = Instant::now();
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received or the time is up.
+        while ctx.is_running() && iterations.again() {
+            if length.filter(|length| start.elapsed() >= *length).is_some() {
+                break;
+            }
+            let mut readings = Vec::new();
+            // A sensor that misses a reading is tried again next time
+            // instead of stopping the experiment.
+            if let Some(dht11) = dht11.as_mut() {
+                match dht11.read() {
+                    Ok(reading) => {
+                        readings.push(("temperature", reading.temperature));
+                        readings.push(("humidity", reading.humidity));
+                    }
+                    Err(e) => warn!("{:#}", e),
+                }
+            }
+            if let Some(ds18b20) = ds18b20.as_ref() {
+                match ds18b20.read_celsius() {
+                    Ok(celsius) => readings.push(("ds18b20", celsius)),
+                    Err(e) => warn!("{:#}", e),
+                }
+            }
+            if let Some((adc, sensor)) = light.as_mut() {
+                match sensor.read(adc) {
+                    Ok(level) => readings.push(("light", level)),
+                    Err(e) => warn!("{:#}", e),
+                }
+            }
+            say!(
+                "{}",
+                readings
+                    .iter()
+                    .map(|(name, value)| format!("{} = {:.1}", name, value))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            );
+            if let Err(e) = log.record(&readings) {
+                warn!("{:#}", e);
+            }
+            ctx.sleep(interval);
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,312 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Bridges the kit to an MQTT broker so it shows up in a home automation
+// system.
+// Pick the parts plugged in with options like
+// `mqtt --broker=homeassistant.local --dht11=4 --relay=17 --button=23`.
+// Readings are published to `superkit/<part>` every few seconds, the button
+// as on or off whenever it changes, and outputs take commands sent to
+// `superkit/<part>/set`, like `on`, `0.25`, `#ff8800`, or LCD text where `|`
+// starts the second row.
+// Saving a new broker, prefix, or interval in the config file takes effect
+// right away, changing which parts are used needs a restart.
+
+use anyhow::{bail, Result};
+use log::{error, warn};
+use std::time::Duration;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, config::Lesson, pwm::PwmOutput, rgb::Polarity, run_lesson, say, Adc0832, Button,
+    Dht11, Ds18b20, EventBus, FrequencyCounter, Lcd1602, Led, LightSensor, Mqtt, RgbPwm,
+    Ultrasonic,
+};
+
+const BROKER: &str = "localhost:1883";
+const PREFIX: &str = "superkit";
+// Time in seconds between readings.
+const INTERVAL: u64 = 10;
+// ADC channel for the photoresistor.
+const LIGHT_CHANNEL: u8 = 0;
+// Settings for the parts which need a restart to change.
+const PART_KEYS: [&str; 10] = [
+    "led",
+    "relay",
+    "rgb",
+    "lcd",
+    "button",
+    "dht11",
+    "ds18b20",
+    "ultrasonic",
+    "light",
+    "pulses",
+];
+
+/// Publishes sensor readings to and takes output commands from an MQTT broker.
+#[derive(StructOpt)]
+#[structopt(name = "mqtt")]
+struct Opt {
+    /// Broker as host or host:port (default localhost:1883).
+    #[structopt(long)]
+    broker: Option<String>,
+    /// Start of every topic (default superkit).
+    #[structopt(long)]
+    prefix: Option<String>,
+    /// Time in seconds between readings (default 10).
+    #[structopt(long)]
+    interval: Option<u64>,
+    /// Gpio pin for an LED dimmed from 0.0 to 1.0.
+    #[structopt(long)]
+    led: Option<u8>,
+    /// Gpio pin for a relay switched on or off.
+    #[structopt(long)]
+    relay: Option<u8>,
+    /// Gpio pins for the red, green, and blue of an RGB LED separated by
+    /// commas.
+    #[structopt(long, use_delimiter = true)]
+    rgb: Option<Vec<u8>>,
+    /// Show text on the LCD1602 wired like lesson 13.
+    #[structopt(long)]
+    lcd: bool,
+    /// Gpio pin for a button published as on or off.
+    #[structopt(long)]
+    button: Option<u8>,
+    /// Gpio pin for a DHT11's data.
+    #[structopt(long)]
+    dht11: Option<u8>,
+    /// Publish the temperature from the first DS18B20.
+    #[structopt(long)]
+    ds18b20: bool,
+    /// Gpio pins for an ultrasonic sensor's trigger and echo separated by a
+    /// comma.
+    #[structopt(long, use_delimiter = true)]
+    ultrasonic: Option<Vec<u8>>,
+    /// Publish the light level from a photoresistor on channel 0 of the
+    /// ADC0832 wired like lesson 15.
+    #[structopt(long)]
+    light: bool,
+    /// Gpio pin where pulses, like from a flow meter, are counted.
+    #[structopt(long)]
+    pulses: Option<u8>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = with_options(&opt, opt.common.lesson("mqtt")?)?;
+    let (mut broker, mut prefix, mut interval) = connection(&config)?;
+    let led_pin: Option<u8> = config.get("led", None)?;
+    let relay_pin: Option<u8> = config.get("relay", None)?;
+    let rgb_pins: Option<[u8; 3]> = match config.get::<Option<Vec<u8>>>("rgb", None)? {
+        Some(pins) if pins.len() == 3 => Some([pins[0], pins[1], pins[2]]),
+        Some(pins) => bail!("Need 3 pins for the RGB LED but was given {}", pins.len()),
+        None => None,
+    };
+    let lcd = config.get("lcd", false)?;
+    let button_pin: Option<u8> = config.get("button", None)?;
+    let dht11_pin: Option<u8> = config.get("dht11", None)?;
+    let ds18b20 = config.get("ds18b20", false)?;
+    let ultrasonic_pins: Option<[u8; 2]> =
+        match config.get::<Option<Vec<u8>>>("ultrasonic", None)? {
+            Some(pins) if pins.len() == 2 => Some([pins[0], pins[1]]),
+            Some(pins) => bail!(
+                "Need 2 pins for the ultrasonic sensor but was given {}",
+                pins.len()
+            ),
+            None => None,
+        };
+    let light = config.get("light", false)?;
+    let pulses_pin: Option<u8> = config.get("pulses", None)?;
+    run_lesson("MQTT", |ctx| {
+        ctx.watch_config(&opt.common);
+        let bus = EventBus::new();
+        // Connects and takes commands for the outputs being used.
+        let connect = |broker: &str, prefix: &str| -> Result<Mqtt> {
+            let mut mqtt = Mqtt::connect(broker, prefix, bus.clone())?;
+            if led_pin.is_some() {
+                mqtt.command_level("led")?;
+            }
+            if relay_pin.is_some() {
+                mqtt.command_level("relay")?;
+            }
+            if rgb_pins.is_some() {
+                mqtt.command_color("rgb")?;
+            }
+            if lcd {
+                mqtt.command_text("lcd")?;
+            }
+            Ok(mqtt)
+        };
+        let mut mqtt = connect(&broker, &prefix)?;
+        if let Some(pin) = led_pin {
+            let mut led = Led::active_low(pin)?;
+            bus.on_level("led", move |level| {
+                if let Err(e) = led.set_duty_cycle(level) {
+                    error!("{:#}", e);
+                }
+            });
+        }
+        if let Some(pin) = relay_pin {
+            let mut relay = Led::new(pin)?;
+            bus.on_level("relay", move |level| {
+                if let Err(e) = relay.set(level >= 0.5) {
+                    error!("{:#}", e);
+                }
+            });
+        }
+        if let Some([red, green, blue]) = rgb_pins {
+            let mut rgb = RgbPwm::with_pins(red, green, blue, Polarity::CommonCathode)?;
+            bus.on_color("rgb", move |color| {
+                if let Err(e) = rgb.set_color(color) {
+                    error!("{:#}", e);
+                }
+            });
+        }
+        if lcd {
+            let mut lcd = Lcd1602::new()?;
+            bus.on_text("lcd", move |text| {
+                let result = lcd
+                    .clear()
+                    .and_then(|_| lcd.print(&text.replace('|', "\n")));
+                if let Err(e) = result {
+                    error!("{:#}", e);
+                }
+            });
+        }
+        // Kept until the bridge stops so its events keep coming.
+        let _button = match button_pin {
+            Some(pin) => {
+                let mut button = Button::new(pin)?;
+                bus.attach_button("button", &mut button)?;
+                Some(button)
+            }
+            None => None,
+        };
+        let mut dht11 = dht11_pin.map(Dht11::new).transpose()?;
+        let ds18b20 = if ds18b20 {
+            Some(Ds18b20::first()?)
+        } else {
+            None
+        };
+        let mut ultrasonic = ultrasonic_pins
+            .map(|[trigger, echo]| Ultrasonic::new(trigger, echo))
+            .transpose()?;
+        let mut light = if light {
+            Some((Adc0832::new()?, LightSensor::new(LIGHT_CHANNEL)))
+        } else {
+            None
+        };
+        let pulses = pulses_pin.map(FrequencyCounter::with_pin).transpose()?;
+        say!("Bridging to {} under {}", broker, prefix);
+        let mut iterations = opt.common.iterations();
+        // Loop until Ctrl-C is received.
+        while ctx.is_running() && iterations.again() {
+            if let Some((new_broker, new_prefix, new_interval)) = ctx.reload(|| -> Result<_> {
+                let new = with_options(&opt, opt.common.reload("mqtt")?)?;
+                connection(&new.keep_from(&config, &PART_KEYS))
+            }) {
+                interval = new_interval;
+                if new_broker != broker || new_prefix != prefix {
+                    // Both connections would use the same client id so the
+                    // old one has to go first.
+                    drop(mqtt);
+                    mqtt = match connect(&new_broker, &new_prefix) {
+                        Ok(mqtt) => {
+                            broker = new_broker;
+                            prefix = new_prefix;
+                            say!("Bridging to {} under {}", broker, prefix);
+                            mqtt
+                        }
+                        Err(e) => {
+                            warn!("Keeping the old broker: {:#}", e);
+                            connect(&broker, &prefix)?
+                        }
+                    };
+                }
+            }
+            // A sensor that misses a reading is tried again next time
+            // instead of stopping the bridge.
+            if let Some(dht11) = dht11.as_mut() {
+                match dht11.read() {
+                    Ok(reading) => {
+                        mqtt.publish("temperature", reading.temperature)?;
+                        mqtt.publish("humidity", reading.humidity)?;
+                    }
+                    Err(e) => warn!("{:#}", e),
+                }
+            }
+            if let Some(ds18b20) = ds18b20.as_ref() {
+                match ds18b20.read_celsius() {
+                    Ok(celsius) => mqtt.publish("ds18b20", format!("{:.2}", celsius))?,
+                    Err(e) => warn!("{:#}", e),
+                }
+            }
+            if let Some(ultrasonic) = ultrasonic.as_mut() {
+                match ultrasonic.distance_cm() {
+                    Ok(distance) => mqtt.publish("distance", format!("{:.1}", distance))?,
+                    Err(e) => warn!("{:#}", e),
+                }
+            }
+            if let Some((adc, sensor)) = light.as_mut() {
+                match sensor.read(adc) {
+                    Ok(level) => mqtt.publish("light", format!("{:.1}", level))?,
+                    Err(e) => warn!("{:#}", e),
+                }
+            }
+            if let Some(pulses) = pulses.as_ref() {
+                mqtt.publish("pulses", pulses.count())?;
+            }
+            ctx.sleep(interval);
+        }
+        Ok(())
+    })
+}
+
+/// Puts any settings given on the command line over the ones from the config
+/// file.
+fn with_options(opt: &Opt, config: Lesson) -> Result<Lesson> {
+    Ok(config
+        .with("broker", opt.broker.clone())?
+        .with("prefix", opt.prefix.clone())?
+        .with("interval", opt.interval)?
+        .with("led", opt.led)?
+        .with("relay", opt.relay)?
+        .with("rgb", opt.rgb.clone())?
+        .with("lcd", Some(opt.lcd).filter(|lcd| *lcd))?
+        .with("button", opt.button)?
+        .with("dht11", opt.dht11)?
+        .with("ds18b20", Some(opt.ds18b20).filter(|ds18b20| *ds18b20))?
+        .with("ultrasonic", opt.ultrasonic.clone())?
+        .with("light", Some(opt.light).filter(|light| *light))?
+        .with("pulses", opt.pulses)?)
+}
+
+/// Gets the broker, topic prefix, and time between readings from the
+/// settings.
+fn connection(config: &Lesson) -> Result<(String, String, Duration)> {
+    Ok((
+        config.get("broker", BROKER.to_string())?,
+        config.get("prefix", PREFIX.to_string())?,
+        Duration::from_secs(config.get("interval", INTERVAL)?),
+    ))
+}
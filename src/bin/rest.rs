@@ -0,0 +1,126 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// REST service for driving outputs with curl, handy for scripting classes.
+// Pick the parts plugged in with options like
+// `rest --rgb=5,6,13 --relay=26 --lcd` then try
+// `curl -d '{"color": "#ff8800"}' http://raspberrypi:8080/rgb/color` or
+// `curl -X POST http://raspberrypi:8080/relay/on`.
+// `curl http://raspberrypi:8080/` lists what can be driven.
+
+use anyhow::{bail, Result};
+use structopt::StructOpt;
+#[cfg(feature = "lcd")]
+use superkit_rust_code_for_raspberrypi::Lcd1602;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, rgb::Polarity, run_lesson, say, Led, LedBar, RestApi, RgbPwm,
+};
+
+const ADDRESS: &str = "0.0.0.0:8080";
+
+/// Drives outputs from JSON sent to a REST service.
+#[derive(StructOpt)]
+#[structopt(name = "rest")]
+struct Opt {
+    /// Address and port to serve on (default 0.0.0.0:8080).
+    #[structopt(long)]
+    address: Option<String>,
+    /// Drive the LCD1602 wired like lesson 13 from POST /lcd/text.
+    #[cfg(feature = "lcd")]
+    #[structopt(long)]
+    lcd: bool,
+    /// Gpio pins for the red, green, and blue of an RGB LED separated by
+    /// commas driven from POST /rgb/color.
+    #[structopt(long, use_delimiter = true)]
+    rgb: Option<Vec<u8>>,
+    /// Gpio pins for the 8 LEDs of a bar graph separated by commas driven
+    /// from POST /ledbar/level.
+    #[structopt(long, use_delimiter = true)]
+    ledbar: Option<Vec<u8>>,
+    /// Gpio pin for a relay driven from POST /relay/on and /relay/off.
+    #[structopt(long)]
+    relay: Option<u8>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("rest")?
+        .with("address", opt.address.clone())?
+        .with("rgb", opt.rgb.clone())?
+        .with("ledbar", opt.ledbar.clone())?
+        .with("relay", opt.relay)?;
+    #[cfg(feature = "lcd")]
+    let config = config.with("lcd", Some(opt.lcd).filter(|lcd| *lcd))?;
+    let address = config.get("address", ADDRESS.to_string())?;
+    let lcd = config.get("lcd", false)?;
+    // The config file can still ask for it.
+    #[cfg(not(feature = "lcd"))]
+    if lcd {
+        bail!("Built without the lcd feature so the LCD can't be driven");
+    }
+    let rgb_pins: Option<[u8; 3]> = match config.get::<Option<Vec<u8>>>("rgb", None)? {
+        Some(pins) if pins.len() == 3 => Some([pins[0], pins[1], pins[2]]),
+        Some(pins) => bail!("Need 3 pins for the RGB LED but was given {}", pins.len()),
+        None => None,
+    };
+    let led_bar_pins: Option<[u8; 8]> = match config.get::<Option<Vec<u8>>>("ledbar", None)? {
+        Some(pins) if pins.len() == 8 => {
+            let mut array = [0; 8];
+            array.copy_from_slice(&pins);
+            Some(array)
+        }
+        Some(pins) => bail!("Need 8 pins for the LED bar but was given {}", pins.len()),
+        None => None,
+    };
+    let relay_pin: Option<u8> = config.get("relay", None)?;
+    if !lcd && rgb_pins.is_none() && led_bar_pins.is_none() && relay_pin.is_none() {
+        bail!("Nothing to drive, add at least one of --lcd, --rgb, --ledbar, or --relay");
+    }
+    run_lesson("REST", |ctx| {
+        let mut api = RestApi::new();
+        #[cfg(feature = "lcd")]
+        if lcd {
+            api.set_lcd(Lcd1602::new()?);
+        }
+        if let Some([red, green, blue]) = rgb_pins {
+            api.set_rgb(RgbPwm::with_pins(
+                red,
+                green,
+                blue,
+                Polarity::CommonCathode,
+            )?);
+        }
+        if let Some(pins) = led_bar_pins {
+            api.set_led_bar(LedBar::with_pins(pins)?);
+        }
+        if let Some(pin) = relay_pin {
+            api.set_relay(Led::new(pin)?);
+        }
+        say!("Serving on http://{}", address);
+        api.serve(&address, &ctx.token())?;
+        Ok(())
+    })
+}
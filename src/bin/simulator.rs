@@ -0,0 +1,199 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Runs a lesson built with the mock backend and shows what its LEDs, dot
+// matrix, seven segment digits, and LCD would be doing in the terminal.
+// Anything the lesson prints is shown below the parts and left on the screen
+// along with the final state of the parts once it stops.
+// Build everything first with
+// `cargo build --no-default-features --features mock,lessons` then run something like
+// `target/debug/simulator 12_dot_matrix --iterations=20`.
+
+use anyhow::{bail, Context, Result};
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    execute, queue,
+    style::Print,
+    terminal::{Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use signal_hook::{
+    consts::{SIGHUP, SIGINT, SIGTERM},
+    flag,
+};
+use std::{
+    collections::VecDeque,
+    env,
+    io::{stdout, BufRead, BufReader, Read, Stdout, Write},
+    net::TcpListener,
+    process::{exit, Child, Command, ExitStatus, Stdio},
+    sync::{atomic::AtomicBool, Arc, Mutex, PoisonError},
+    thread::{sleep, spawn, JoinHandle},
+    time::Duration,
+};
+use structopt::{clap::AppSettings, StructOpt};
+use superkit_rust_code_for_raspberrypi::simulator::{Panel, ADDRESS_VAR};
+
+// Time in milliseconds between redraws.
+const REFRESH_TIME: u64 = 50;
+// Number of lines of lesson output kept on screen.
+const MESSAGES: usize = 8;
+
+type Messages = Arc<Mutex<VecDeque<String>>>;
+
+/// Shows a lesson's output parts in the terminal without a Raspberry Pi.
+#[derive(StructOpt)]
+#[structopt(
+    name = "simulator",
+    setting = AppSettings::TrailingVarArg,
+    setting = AppSettings::AllowLeadingHyphen
+)]
+struct Opt {
+    /// Lesson to run like 01_led.
+    lesson: String,
+    /// Options passed on to the lesson.
+    #[structopt(allow_hyphen_values = true)]
+    args: Vec<String>,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let program = env::current_exe()
+        .context("Failed to find simulator program")?
+        .with_file_name(format!("{}{}", opt.lesson, env::consts::EXE_SUFFIX));
+    if !program.exists() {
+        bail!(
+            "Could not find lesson {}, build the lessons first with \
+             cargo build --no-default-features --features mock,lessons",
+            opt.lesson
+        );
+    }
+    let listener = TcpListener::bind("127.0.0.1:0").context("Failed to listen for lesson")?;
+    let address = listener
+        .local_addr()
+        .context("Failed to get listening address")?;
+    let mut child = Command::new(&program)
+        .args(&opt.args)
+        .env(ADDRESS_VAR, address.to_string())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context(format!("Failed to start {}", opt.lesson))?;
+    // Ctrl-C also goes to the lesson so leave it to stop on its own and
+    // restore the terminal afterwards.
+    let ignored = Arc::new(AtomicBool::new(false));
+    for signal in [SIGINT, SIGTERM, SIGHUP].iter() {
+        flag::register(*signal, ignored.clone()).context("Error setting signal handlers")?;
+    }
+    let panel = Arc::new(Mutex::new(Panel::default()));
+    let messages: Messages = Arc::new(Mutex::new(VecDeque::new()));
+    let mut readers = Vec::new();
+    if let Some(output) = child.stdout.take() {
+        readers.push(read_messages(output, messages.clone()));
+    }
+    if let Some(output) = child.stderr.take() {
+        readers.push(read_messages(output, messages.clone()));
+    }
+    let p = panel.clone();
+    spawn(move || -> Result<()> {
+        let (stream, _) = listener.accept().context("Failed to accept lesson")?;
+        let mut reader = BufReader::new(stream);
+        while let Some(update) = Panel::read_from(&mut reader)? {
+            *p.lock().unwrap_or_else(PoisonError::into_inner) = update;
+        }
+        Ok(())
+    });
+    let mut out = stdout();
+    execute!(out, EnterAlternateScreen, Hide).context("Failed to setup terminal")?;
+    let status = show(&mut out, &opt.lesson, &mut child, &panel, &messages);
+    execute!(out, Show, LeaveAlternateScreen).context("Failed to restore terminal")?;
+    let status = status?;
+    for reader in readers {
+        let _ = reader.join();
+    }
+    // Leave the final state and output of the lesson on the screen.
+    println!("{}", panel.lock().unwrap_or_else(PoisonError::into_inner));
+    for message in messages
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+    {
+        println!("{}", message);
+    }
+    exit(status.code().unwrap_or(1));
+}
+
+/// Redraws the screen until the lesson stops.
+fn show(
+    out: &mut Stdout,
+    lesson: &str,
+    child: &mut Child,
+    panel: &Mutex<Panel>,
+    messages: &Messages,
+) -> Result<ExitStatus> {
+    loop {
+        let text = format!(
+            "Simulating {}, Ctrl-C stops it\n\n{}\n{}",
+            lesson,
+            panel.lock().unwrap_or_else(PoisonError::into_inner),
+            messages
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .iter()
+                .cloned()
+                .collect::<Vec<String>>()
+                .join("\n")
+        );
+        // Lessons that read keys put the terminal in raw mode so position
+        // each line instead of relying on newlines.
+        for (row, line) in text.lines().enumerate() {
+            queue!(
+                out,
+                MoveTo(0, row as u16),
+                Print(line),
+                Clear(ClearType::UntilNewLine)
+            )?;
+        }
+        queue!(out, Clear(ClearType::FromCursorDown))?;
+        out.flush()?;
+        if let Some(status) = child.try_wait().context("Failed to check on lesson")? {
+            return Ok(status);
+        }
+        sleep(Duration::from_millis(REFRESH_TIME));
+    }
+}
+
+/// Keeps the last few lines the lesson printed.
+fn read_messages<R: Read + Send + 'static>(output: R, messages: Messages) -> JoinHandle<()> {
+    spawn(move || {
+        for line in BufReader::new(output).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+            let mut messages = messages.lock().unwrap_or_else(PoisonError::into_inner);
+            if messages.len() == MESSAGES {
+                messages.pop_front();
+            }
+            messages.push_back(line);
+        }
+    })
+}
@@ -0,0 +1,230 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Tool for working with the kit outside of the lessons.
+// `superkit run scripts/blink.toml` runs a script of GPIO steps, the scripts
+// in the scripts directory show what a script can do.
+// Scripts run once unless `--iterations=<n>` is given.
+// A lesson built with the mock backend and run with `--record=<file>` saves
+// what it did to the pins, `superkit replay <file>` plays that back onto the
+// pins and `superkit verify <expected> <actual>` checks two recordings wrote
+// the same things.
+// `superkit shell` takes commands like `pin 17 high`, `hc595 0xAA`,
+// `lcd "hello"`, `adc read 1`, or `pwm 18 50%` one at a time to check the
+// wiring before running a whole lesson, type `help` to see them all.
+// Tab completes the commands and the history is kept in ~/.superkit_history.
+// The shell is only there when built with `--features shell`.
+
+use anyhow::Result;
+#[cfg(feature = "shell")]
+use log::{error, warn};
+#[cfg(feature = "shell")]
+use rustyline::{
+    completion::Completer, error::ReadlineError, highlight::Highlighter, hint::Hinter,
+    validate::Validator, Context, Editor, Helper,
+};
+#[cfg(feature = "shell")]
+use std::env;
+use std::path::PathBuf;
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{cli::Common, run_lesson, say, Script, Session};
+#[cfg(feature = "shell")]
+use superkit_rust_code_for_raspberrypi::{
+    shell::{self, Reply},
+    Shell,
+};
+
+#[cfg(feature = "shell")]
+const HISTORY_FILE: &str = ".superkit_history";
+
+/// Works with the Super Kit without writing any Rust.
+#[derive(StructOpt)]
+#[structopt(name = "superkit")]
+enum Opt {
+    /// Runs the steps in a script file.
+    Run {
+        /// Script file like scripts/blink.toml.
+        #[structopt(parse(from_os_str))]
+        script: PathBuf,
+        #[structopt(flatten)]
+        common: Common,
+    },
+    /// Plays back the pin writes in a recorded session.
+    Replay {
+        /// Session file saved by a lesson's --record.
+        #[structopt(parse(from_os_str))]
+        session: PathBuf,
+        #[structopt(flatten)]
+        common: Common,
+    },
+    /// Checks a recorded session wrote the same pins in the same order as
+    /// another.
+    Verify {
+        /// Session file with what should have happened.
+        #[structopt(parse(from_os_str))]
+        expected: PathBuf,
+        /// Session file with what did happen.
+        #[structopt(parse(from_os_str))]
+        actual: PathBuf,
+        #[structopt(flatten)]
+        common: Common,
+    },
+    /// Takes commands to try out the parts one at a time.
+    #[cfg(feature = "shell")]
+    Shell {
+        #[structopt(flatten)]
+        common: Common,
+    },
+}
+
+fn main() -> Result<()> {
+    match Opt::from_args() {
+        Opt::Run { script, common } => run(script, common),
+        Opt::Replay { session, common } => replay(session, common),
+        Opt::Verify {
+            expected,
+            actual,
+            common,
+        } => verify(expected, actual, common),
+        #[cfg(feature = "shell")]
+        Opt::Shell { common } => run_shell(common),
+    }
+}
+
+/// Loads the script first so mistakes in it are shown before anything
+/// happens to the pins.
+fn run(path: PathBuf, common: Common) -> Result<()> {
+    common.start()?;
+    let script = Script::load(&path)?;
+    run_lesson(script.name(), |ctx| {
+        let token = ctx.token();
+        let mut count = 0;
+        while ctx.is_running() && count < common.iterations.unwrap_or(1) {
+            script.run(&token)?;
+            count += 1;
+        }
+        Ok(())
+    })
+}
+
+/// Plays back a session once unless `--iterations=<n>` is given.
+fn replay(path: PathBuf, common: Common) -> Result<()> {
+    common.start()?;
+    let session = Session::load(&path)?;
+    run_lesson("Replay", |ctx| {
+        let token = ctx.token();
+        let mut count = 0;
+        while ctx.is_running() && count < common.iterations.unwrap_or(1) {
+            session.replay(&token)?;
+            count += 1;
+        }
+        Ok(())
+    })
+}
+
+/// Compares two sessions without touching any pins.
+fn verify(expected: PathBuf, actual: PathBuf, common: Common) -> Result<()> {
+    common.start()?;
+    let expected_session = Session::load(&expected)?;
+    let actual_session = Session::load(&actual)?;
+    expected_session.verify(&actual_session)?;
+    say!(
+        "{} matches {}, {} writes",
+        actual.display(),
+        expected.display(),
+        expected_session.writes().count()
+    );
+    Ok(())
+}
+
+/// Reads commands until `quit`, Ctrl-D, or the shell is stopped.
+///
+/// Ctrl-C only throws away the line being typed like in other shells.
+#[cfg(feature = "shell")]
+fn run_shell(common: Common) -> Result<()> {
+    common.start()?;
+    run_lesson("Shell", |ctx| {
+        let mut editor = Editor::<Completion>::new();
+        editor.set_helper(Some(Completion));
+        let history = env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE));
+        if let Some(path) = history.as_ref() {
+            // There isn't one the first time.
+            let _ = editor.load_history(path);
+        }
+        let mut shell = Shell::new();
+        say!("Type help to see the commands");
+        while ctx.is_running() {
+            let line = match editor.readline("superkit> ") {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => break,
+                Err(e) => return Err(e.into()),
+            };
+            editor.add_history_entry(line.as_str());
+            // A mistake only needs to be pointed out, not end the shell.
+            match shell.execute(&line) {
+                Ok(Reply::Done) => {}
+                Ok(Reply::Show(text)) => say!("{}", text),
+                Ok(Reply::Quit) => break,
+                Err(e) => error!("{:#}", e),
+            }
+        }
+        if let Some(path) = history.as_ref() {
+            if let Err(e) = editor.save_history(path) {
+                warn!("Failed to save history to {}: {}", path.display(), e);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Tab completion of the shell commands for rustyline.
+#[cfg(feature = "shell")]
+struct Completion;
+
+#[cfg(feature = "shell")]
+impl Completer for Completion {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(shell::complete(&line[..pos]))
+    }
+}
+
+#[cfg(feature = "shell")]
+impl Hinter for Completion {
+    type Hint = String;
+}
+
+#[cfg(feature = "shell")]
+impl Highlighter for Completion {}
+
+#[cfg(feature = "shell")]
+impl Validator for Completion {}
+
+#[cfg(feature = "shell")]
+impl Helper for Completion {}
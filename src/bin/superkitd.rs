@@ -0,0 +1,106 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+// Runs a kit project as a service using the outputs, inputs, and schedule in
+// the superkitd table of the config file, see the daemon module for how they
+// are written.
+// Under systemd use Type=notify, like config/superkitd.service, so it knows
+// when everything is set up, `systemctl stop` sends SIGTERM which stops it
+// cleanly with every output let go of.
+// Saving the config file, SIGHUP, or `systemctl reload` reads the config file
+// again and starts over with the new settings, keeping the old ones if the new
+// ones don't work.
+
+use anyhow::Result;
+use log::{info, warn};
+use structopt::StructOpt;
+use superkit_rust_code_for_raspberrypi::{
+    cli::Common, config::Lesson, run_lesson, say, systemd, Daemon, EventBus,
+};
+
+/// Runs the parts and schedule from the config file as a service.
+#[derive(StructOpt)]
+#[structopt(name = "superkitd")]
+struct Opt {
+    /// Time in seconds between sensor readings (default 60).
+    #[structopt(long)]
+    interval: Option<u64>,
+    #[structopt(flatten)]
+    common: Common,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let config = opt
+        .common
+        .lesson("superkitd")?
+        .with("interval", opt.interval)?;
+    run_lesson("superkitd", |ctx| {
+        ctx.watch_config(&opt.common);
+        let token = ctx.token();
+        let mut config = config;
+        let mut daemon = Daemon::from_config(EventBus::new(), &config)?;
+        loop {
+            let summary = daemon.summary();
+            say!("Running with {}", summary);
+            // Not being able to tell systemd isn't a reason to stop.
+            if let Err(e) = systemd::status(&summary).and_then(|_| systemd::ready()) {
+                warn!("{:#}", e);
+            }
+            daemon.run(&token);
+            if !token.take_reload() || token.is_cancelled() {
+                break;
+            }
+            if let Err(e) = systemd::reloading() {
+                warn!("{:#}", e);
+            }
+            // The old parts have to let go of their pins before the new ones
+            // can have them.
+            drop(daemon);
+            daemon = match reload(&opt).and_then(|new| {
+                let daemon = Daemon::from_config(EventBus::new(), &new)?;
+                config = new;
+                Ok(daemon)
+            }) {
+                Ok(daemon) => {
+                    info!("Reloaded");
+                    daemon
+                }
+                Err(e) => {
+                    warn!("Keeping the old settings: {:#}", e);
+                    Daemon::from_config(EventBus::new(), &config)?
+                }
+            };
+        }
+        if let Err(e) = systemd::stopping() {
+            warn!("{:#}", e);
+        }
+        Ok(())
+    })
+}
+
+/// Reads the settings from the config file again.
+fn reload(opt: &Opt) -> Result<Lesson> {
+    Ok(opt
+        .common
+        .reload("superkitd")?
+        .with("interval", opt.interval)?)
+}
@@ -0,0 +1,315 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::i2c::I2c;
+use std::{thread::sleep, time::Duration};
+
+/// Default I2C address with SDO low, 0x77 if it is high.
+pub const ADDRESS: u16 = 0x76;
+/// Standard sea level pressure in hPa.
+pub const SEA_LEVEL: f64 = 1013.25;
+// Registers.
+const CALIBRATION: u8 = 0x88;
+const CALIBRATION_H1: u8 = 0xa1;
+const CHIP_ID: u8 = 0xd0;
+const RESET: u8 = 0xe0;
+const CALIBRATION_H2: u8 = 0xe1;
+const CTRL_HUM: u8 = 0xf2;
+const CTRL_MEAS: u8 = 0xf4;
+const CONFIG: u8 = 0xf5;
+const DATA: u8 = 0xf7;
+// What CHIP_ID reads back for each chip.
+const BMP280_ID: u8 = 0x58;
+const BME280_ID: u8 = 0x60;
+// Value written to RESET to reset the chip.
+const SOFT_RESET: u8 = 0xb6;
+// Normal mode where the chip keeps measuring by itself.
+const NORMAL_MODE: u8 = 0x03;
+// Standby of 500ms between measurements and an IIR filter coefficient of 4 to
+// smooth out gusts and doors closing.
+const STANDBY_FILTER: u8 = 0b100 << 5 | 0b010 << 2;
+
+/// How many samples the chip averages for each reading. More is smoother but
+/// slower.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Oversampling {
+    /// Don't measure this at all.
+    Skip,
+    X1,
+    X2,
+    X4,
+    X8,
+    X16,
+}
+
+/// Which chip was found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Chip {
+    /// Pressure and temperature.
+    Bmp280,
+    /// Pressure, temperature, and humidity.
+    Bme280,
+}
+
+/// One set of measurements.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Reading {
+    /// Temperature in °C.
+    pub temperature: f64,
+    /// Pressure in hPa.
+    pub pressure: f64,
+    /// Relative humidity in % for a BME280.
+    pub humidity: Option<f64>,
+}
+
+/// Factory calibration coefficients stored in each chip.
+#[derive(Clone, Copy, Debug, Default)]
+struct Calibration {
+    t1: f64,
+    t2: f64,
+    t3: f64,
+    p1: f64,
+    p2: f64,
+    p3: f64,
+    p4: f64,
+    p5: f64,
+    p6: f64,
+    p7: f64,
+    p8: f64,
+    p9: f64,
+    h1: f64,
+    h2: f64,
+    h3: f64,
+    h4: f64,
+    h5: f64,
+    h6: f64,
+}
+
+impl Calibration {
+    /// Internal method to turn a raw temperature into °C and the fine
+    /// temperature the other compensations need.
+    fn temperature(&self, raw: i32) -> (f64, f64) {
+        let raw = f64::from(raw);
+        let var1 = (raw / 16384.0 - self.t1 / 1024.0) * self.t2;
+        let var2 = (raw / 131072.0 - self.t1 / 8192.0).powi(2) * self.t3;
+        let fine = var1 + var2;
+        (fine / 5120.0, fine)
+    }
+    /// Internal method to turn a raw pressure into Pa.
+    fn pressure(&self, raw: i32, fine: f64) -> f64 {
+        let mut var1 = fine / 2.0 - 64000.0;
+        let mut var2 = var1 * var1 * self.p6 / 32768.0;
+        var2 += var1 * self.p5 * 2.0;
+        var2 = var2 / 4.0 + self.p4 * 65536.0;
+        var1 = (self.p3 * var1 * var1 / 524288.0 + self.p2 * var1) / 524288.0;
+        var1 = (1.0 + var1 / 32768.0) * self.p1;
+        if var1 == 0.0 {
+            // Avoids dividing by zero.
+            return 0.0;
+        }
+        let mut pressure = 1048576.0 - f64::from(raw);
+        pressure = (pressure - var2 / 4096.0) * 6250.0 / var1;
+        var1 = self.p9 * pressure * pressure / 2147483648.0;
+        var2 = pressure * self.p8 / 32768.0;
+        pressure + (var1 + var2 + self.p7) / 16.0
+    }
+    /// Internal method to turn a raw humidity into %.
+    fn humidity(&self, raw: i32, fine: f64) -> f64 {
+        let h = fine - 76800.0;
+        let h = (f64::from(raw) - (self.h4 * 64.0 + self.h5 / 16384.0 * h))
+            * (self.h2 / 65536.0
+                * (1.0 + self.h6 / 67108864.0 * h * (1.0 + self.h3 / 67108864.0 * h)));
+        let h = h * (1.0 - self.h1 * h / 524288.0);
+        h.clamp(0.0, 100.0)
+    }
+}
+
+/// Structure used to model a BMP280 pressure and temperature sensor or a
+/// BME280 which also does humidity on the I2C bus.
+pub struct Bmp280 {
+    i2c: I2c,
+    chip: Chip,
+    calibration: Calibration,
+    sea_level: f64,
+}
+
+impl Bmp280 {
+    /// Gets the chip at the default address on I2C bus 1.
+    pub fn new() -> Result<Self> {
+        Self::with_address(ADDRESS)
+    }
+    /// Gets the chip at the given address and starts it measuring.
+    pub fn with_address(address: u16) -> Result<Self> {
+        let mut i2c = I2c::new().context("Failed to get I2C bus, is I2C enabled")?;
+        i2c.set_slave_address(address)
+            .context(format!("Failed to set BMP280 address: {:#04x}", address))?;
+        let mut bmp = Bmp280 {
+            i2c,
+            chip: Chip::Bmp280,
+            calibration: Calibration::default(),
+            sea_level: SEA_LEVEL,
+        };
+        bmp.chip = match bmp.read_registers(CHIP_ID, 1)?[0] {
+            BMP280_ID => Chip::Bmp280,
+            BME280_ID => Chip::Bme280,
+            id => {
+                return Err(SuperKitError::Device(format!(
+                    "Unknown chip ID {:#04x}, is it a BMP280 or BME280",
+                    id
+                )))
+            }
+        };
+        bmp.write_register(RESET, SOFT_RESET)?;
+        // Give it time to copy the calibration out of its NVM.
+        sleep(Duration::from_millis(10));
+        bmp.read_calibration()?;
+        bmp.write_register(CONFIG, STANDBY_FILTER)?;
+        bmp.set_oversampling(Oversampling::X2, Oversampling::X16, Oversampling::X1)?;
+        Ok(bmp)
+    }
+    /// Which chip was found.
+    pub fn chip(&self) -> Chip {
+        self.chip
+    }
+    /// Changes the oversampling of temperature, pressure, and humidity.
+    ///
+    /// Humidity is ignored on a BMP280.
+    pub fn set_oversampling(
+        &mut self,
+        temperature: Oversampling,
+        pressure: Oversampling,
+        humidity: Oversampling,
+    ) -> Result<()> {
+        if self.chip == Chip::Bme280 {
+            // Only takes effect after CTRL_MEAS is written.
+            self.write_register(CTRL_HUM, humidity as u8)?;
+        }
+        self.write_register(
+            CTRL_MEAS,
+            (temperature as u8) << 5 | (pressure as u8) << 2 | NORMAL_MODE,
+        )?;
+        // Wait for the first measurement so readings straight after are good.
+        sleep(Duration::from_millis(50));
+        Ok(())
+    }
+    /// Gives the sea level pressure in hPa used to work out the altitude.
+    pub fn sea_level(&self) -> f64 {
+        self.sea_level
+    }
+    /// Changes the sea level pressure in hPa used to work out the altitude.
+    ///
+    /// Use today's value for your area from a weather service for the best
+    /// results.
+    pub fn set_sea_level(&mut self, pressure: f64) {
+        self.sea_level = pressure;
+    }
+    /// Works out the sea level pressure from the current pressure and a known
+    /// altitude in metres and uses it from now on.
+    pub fn calibrate_altitude(&mut self, altitude: f64) -> Result<()> {
+        let pressure = self.read()?.pressure;
+        self.sea_level = pressure / (1.0 - altitude / 44330.0).powf(5.255);
+        Ok(())
+    }
+    /// Reads all the measurements.
+    pub fn read(&mut self) -> Result<Reading> {
+        let len = if self.chip == Chip::Bme280 { 8 } else { 6 };
+        let data = self.read_registers(DATA, len)?;
+        // 20 bit values MSB first with the last 4 bits at the top of the XLSB.
+        let raw20 = |i: usize| {
+            i32::from(data[i]) << 12 | i32::from(data[i + 1]) << 4 | i32::from(data[i + 2]) >> 4
+        };
+        let (temperature, fine) = self.calibration.temperature(raw20(3));
+        let pressure = self.calibration.pressure(raw20(0), fine) / 100.0;
+        let humidity = if self.chip == Chip::Bme280 {
+            let raw = i32::from(data[6]) << 8 | i32::from(data[7]);
+            Some(self.calibration.humidity(raw, fine))
+        } else {
+            None
+        };
+        Ok(Reading {
+            temperature,
+            pressure,
+            humidity,
+        })
+    }
+    /// Estimates the altitude in metres from the pressure using the
+    /// international barometric formula.
+    pub fn altitude(&mut self) -> Result<f64> {
+        let pressure = self.read()?.pressure;
+        Ok(altitude(pressure, self.sea_level))
+    }
+    /// Internal method to read the calibration coefficients.
+    fn read_calibration(&mut self) -> Result<()> {
+        let c = self.read_registers(CALIBRATION, 24)?;
+        let unsigned = |i: usize| f64::from(u16::from_le_bytes([c[i], c[i + 1]]));
+        let signed = |i: usize| f64::from(i16::from_le_bytes([c[i], c[i + 1]]));
+        let mut calibration = Calibration {
+            t1: unsigned(0),
+            t2: signed(2),
+            t3: signed(4),
+            p1: unsigned(6),
+            p2: signed(8),
+            p3: signed(10),
+            p4: signed(12),
+            p5: signed(14),
+            p6: signed(16),
+            p7: signed(18),
+            p8: signed(20),
+            p9: signed(22),
+            ..Calibration::default()
+        };
+        if self.chip == Chip::Bme280 {
+            calibration.h1 = f64::from(self.read_registers(CALIBRATION_H1, 1)?[0]);
+            let h = self.read_registers(CALIBRATION_H2, 7)?;
+            calibration.h2 = f64::from(i16::from_le_bytes([h[0], h[1]]));
+            calibration.h3 = f64::from(h[2]);
+            // H4 and H5 are 12 bit values sharing the middle byte.
+            calibration.h4 = f64::from(i16::from(h[3] as i8) << 4 | i16::from(h[4] & 0x0f));
+            calibration.h5 = f64::from(i16::from(h[5] as i8) << 4 | i16::from(h[4] >> 4));
+            calibration.h6 = f64::from(h[6] as i8);
+        }
+        self.calibration = calibration;
+        Ok(())
+    }
+    /// Internal method to read registers starting at the given one.
+    fn read_registers(&mut self, register: u8, len: usize) -> Result<Vec<u8>> {
+        let mut buffer = vec![0u8; len];
+        self.i2c
+            .write_read(&[register], &mut buffer)
+            .context(format!("Failed to read BMP280 register: {:#04x}", register))?;
+        Ok(buffer)
+    }
+    /// Internal method to write a register.
+    fn write_register(&mut self, register: u8, value: u8) -> Result<()> {
+        self.i2c.smbus_write_byte(register, value).context(format!(
+            "Failed to write BMP280 register: {:#04x}",
+            register
+        ))
+    }
+}
+
+/// Estimates the altitude in metres for a pressure given the sea level
+/// pressure, both in hPa.
+pub fn altitude(pressure: f64, sea_level: f64) -> f64 {
+    44330.0 * (1.0 - (pressure / sea_level).powf(1.0 / 5.255))
+}
@@ -0,0 +1,137 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Result, SuperKitError};
+use crate::pwm::PwmOutput;
+use std::{
+    f64::consts::PI,
+    sync::atomic::{AtomicBool, Ordering},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    thread::{sleep, spawn, JoinHandle},
+    time::{Duration, Instant},
+};
+
+// Time in milliseconds between brightness changes. Fast enough that the steps
+// can't be seen.
+const STEP_TIME: u64 = 10;
+
+/// Settings shared with the worker thread.
+#[derive(Clone, Copy)]
+struct Settings {
+    period: Duration,
+    min: f64,
+    max: f64,
+}
+
+/// Slowly brightens and dims an LED following a sine wave like the sleep
+/// light on some laptops.
+///
+/// Started from the brighter/dimmer ramp of lesson 4 but works with anything
+/// that implements [PwmOutput].
+/// A background thread does the work so the caller is free to do other
+/// things.
+pub struct Breather {
+    settings: Arc<Mutex<Settings>>,
+    running: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Breather {
+    /// Starts breathing from fully off to fully on and back once per period.
+    pub fn new<P>(mut output: P, period: Duration) -> Result<Self>
+    where
+        P: PwmOutput + Send + 'static,
+    {
+        Self::check_period(period)?;
+        let settings = Arc::new(Mutex::new(Settings {
+            period,
+            min: 0.0,
+            max: 1.0,
+        }));
+        let running = Arc::new(AtomicBool::new(true));
+        let s = settings.clone();
+        let r = running.clone();
+        let worker = spawn(move || {
+            let start = Instant::now();
+            while r.load(Ordering::SeqCst) {
+                let settings = *s.lock().unwrap_or_else(PoisonError::into_inner);
+                let phase = start.elapsed().as_secs_f64() / settings.period.as_secs_f64();
+                // Starts at the bottom of the wave.
+                let level = (1.0 - (2.0 * PI * phase).cos()) / 2.0;
+                let duty_cycle = settings.min + (settings.max - settings.min) * level;
+                // Nothing useful can be done about an error here so just keep
+                // going.
+                let _ = output.set_duty_cycle(duty_cycle);
+                sleep(Duration::from_millis(STEP_TIME));
+            }
+            let _ = output.set_duty_cycle(0.0);
+        });
+        Ok(Breather {
+            settings,
+            running,
+            worker: Some(worker),
+        })
+    }
+    /// Changes how long one full breath takes.
+    pub fn set_period(&mut self, period: Duration) -> Result<()> {
+        Self::check_period(period)?;
+        self.lock().period = period;
+        Ok(())
+    }
+    /// Changes the dimmest and brightest duty cycles from 0.0 to 1.0.
+    pub fn set_range(&mut self, min: f64, max: f64) -> Result<()> {
+        if !(0.0..=1.0).contains(&min) || !(0.0..=1.0).contains(&max) || min > max {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Brightness range {} to {} needs 0.0 <= min <= max <= 1.0",
+                min, max
+            )));
+        }
+        let mut settings = self.lock();
+        settings.min = min;
+        settings.max = max;
+        Ok(())
+    }
+    /// Internal associative method (function) to validate a period.
+    fn check_period(period: Duration) -> Result<()> {
+        if period < Duration::from_millis(2 * STEP_TIME) {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Breathing period {:?} is too short",
+                period
+            )));
+        }
+        Ok(())
+    }
+    /// Internal method used to get at the settings shared with worker thread.
+    fn lock(&self) -> MutexGuard<'_, Settings> {
+        self.settings.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Stop the worker thread and insure the output is off before exiting.
+impl Drop for Breather {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
@@ -0,0 +1,427 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Publish and subscribe between input and output drivers.
+//!
+//! Inputs are attached to an [EventBus] under a name and outputs follow the
+//! names they care about, so something like a PIR sensor turning on a relay
+//! and a buzzer doesn't need a closure for each pairing.
+//! Outputs can also be told what to do with the set events, like the controls
+//! of the web dashboard do.
+
+use crate::encoder::Direction;
+use crate::error::Result;
+use crate::{Button, ButtonEvent, Color, RotaryEncoder};
+use log::trace;
+use serde::Serialize;
+use std::sync::{
+    mpsc::{channel, Receiver},
+    Arc, Mutex, MutexGuard, PoisonError,
+};
+
+/// Something that happened to one of the inputs attached to an [EventBus].
+///
+/// Source is the name the input was attached with.
+/// As JSON the kind of event is given in snake case along with its fields,
+/// like `{"kind":"button_pressed","source":"button"}`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Event {
+    ButtonPressed {
+        source: String,
+    },
+    ButtonReleased {
+        source: String,
+    },
+    /// The knob moved one step to the new value.
+    EncoderTurned {
+        source: String,
+        direction: Direction,
+        value: i32,
+    },
+    /// A level went above a threshold or dropped back below it.
+    ThresholdCrossed {
+        source: String,
+        value: f64,
+        above: bool,
+    },
+    /// A reading from a sensor, like a temperature or distance.
+    Sample {
+        source: String,
+        value: f64,
+    },
+    MotionDetected {
+        source: String,
+    },
+    MotionStopped {
+        source: String,
+    },
+    /// An output like a dimmed LED was asked to go to a level from 0.0 to
+    /// 1.0.
+    LevelSet {
+        source: String,
+        value: f64,
+    },
+    /// An RGB LED was asked to show a color.
+    ColorSet {
+        source: String,
+        color: Color,
+    },
+    /// A display was asked to show some text.
+    TextSet {
+        source: String,
+        text: String,
+    },
+}
+
+impl Event {
+    /// Name of the input the event came from.
+    pub fn source(&self) -> &str {
+        match self {
+            Event::ButtonPressed { source }
+            | Event::ButtonReleased { source }
+            | Event::EncoderTurned { source, .. }
+            | Event::ThresholdCrossed { source, .. }
+            | Event::Sample { source, .. }
+            | Event::MotionDetected { source }
+            | Event::MotionStopped { source }
+            | Event::LevelSet { source, .. }
+            | Event::ColorSet { source, .. }
+            | Event::TextSet { source, .. } => source,
+        }
+    }
+    /// Says if the event turns something on or off, like a press, motion, or
+    /// going above a threshold for on. None for events without an on or off
+    /// like encoder turns, samples, and the set events.
+    pub fn is_active(&self) -> Option<bool> {
+        match self {
+            Event::ButtonPressed { .. } | Event::MotionDetected { .. } => Some(true),
+            Event::ButtonReleased { .. } | Event::MotionStopped { .. } => Some(false),
+            Event::ThresholdCrossed { above, .. } => Some(*above),
+            Event::EncoderTurned { .. }
+            | Event::Sample { .. }
+            | Event::LevelSet { .. }
+            | Event::ColorSet { .. }
+            | Event::TextSet { .. } => None,
+        }
+    }
+}
+
+/// Id of a handler given by [EventBus::on()] for use with
+/// [EventBus::unsubscribe()].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+type Filter = Box<dyn Fn(&Event) -> bool + Send>;
+type Handler = Arc<Mutex<dyn FnMut(&Event) -> bool + Send>>;
+
+/// A handler and which events it wants.
+struct Subscriber {
+    id: u64,
+    filter: Filter,
+    handler: Handler,
+}
+
+#[derive(Default)]
+struct Subscribers {
+    next: u64,
+    list: Vec<Subscriber>,
+}
+
+/// Lightweight pub/sub for [Event]s.
+///
+/// Cloning is cheap and all the clones share the same subscribers so one can
+/// be moved into each input's callback.
+/// Handlers run on whichever thread published the event, usually an interrupt
+/// thread, so they should be quick.
+#[derive(Clone, Default)]
+pub struct EventBus {
+    subscribers: Arc<Mutex<Subscribers>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Sends the event to every handler that wants it.
+    ///
+    /// Handlers are free to publish more events but one publishing to itself
+    /// will deadlock.
+    pub fn publish(&self, event: Event) {
+        trace!(target: "bus", "{:?}", event);
+        // Handlers are called without holding the list so they can subscribe
+        // or publish.
+        let handlers: Vec<(u64, Handler)> = self
+            .lock()
+            .list
+            .iter()
+            .filter(|subscriber| (subscriber.filter)(&event))
+            .map(|subscriber| (subscriber.id, subscriber.handler.clone()))
+            .collect();
+        for (id, handler) in handlers {
+            let mut handler = handler.lock().unwrap_or_else(PoisonError::into_inner);
+            if !(*handler)(&event) {
+                self.remove(id);
+            }
+        }
+    }
+    /// Calls the handler for every event the filter accepts.
+    pub fn on<F, C>(&self, filter: F, mut handler: C) -> SubscriptionId
+    where
+        F: Fn(&Event) -> bool + Send + 'static,
+        C: FnMut(&Event) + Send + 'static,
+    {
+        self.add(
+            Box::new(filter),
+            Arc::new(Mutex::new(move |event: &Event| {
+                handler(event);
+                true
+            })),
+        )
+    }
+    /// Calls the handler for every event from the named input.
+    pub fn on_source<C>(&self, source: &str, handler: C) -> SubscriptionId
+    where
+        C: FnMut(&Event) + Send + 'static,
+    {
+        let source = source.to_string();
+        self.on(move |event| event.source() == source, handler)
+    }
+    /// Calls the handler with true or false each time the named input turns
+    /// on or off, see [Event::is_active()].
+    ///
+    /// Handy for outputs that just follow an input like a relay or buzzer.
+    pub fn follow<C>(&self, source: &str, mut handler: C) -> SubscriptionId
+    where
+        C: FnMut(bool) + Send + 'static,
+    {
+        let source = source.to_string();
+        self.on(
+            move |event| event.source() == source && event.is_active().is_some(),
+            move |event| {
+                if let Some(active) = event.is_active() {
+                    handler(active);
+                }
+            },
+        )
+    }
+    /// Calls the handler with the value of each [Event::LevelSet] for the
+    /// named output.
+    pub fn on_level<C>(&self, source: &str, mut handler: C) -> SubscriptionId
+    where
+        C: FnMut(f64) + Send + 'static,
+    {
+        self.on_source(source, move |event| {
+            if let Event::LevelSet { value, .. } = event {
+                handler(*value);
+            }
+        })
+    }
+    /// Calls the handler with the color of each [Event::ColorSet] for the
+    /// named output.
+    pub fn on_color<C>(&self, source: &str, mut handler: C) -> SubscriptionId
+    where
+        C: FnMut(Color) + Send + 'static,
+    {
+        self.on_source(source, move |event| {
+            if let Event::ColorSet { color, .. } = event {
+                handler(*color);
+            }
+        })
+    }
+    /// Calls the handler with the text of each [Event::TextSet] for the named
+    /// output.
+    pub fn on_text<C>(&self, source: &str, mut handler: C) -> SubscriptionId
+    where
+        C: FnMut(&str) + Send + 'static,
+    {
+        self.on_source(source, move |event| {
+            if let Event::TextSet { text, .. } = event {
+                handler(text);
+            }
+        })
+    }
+    /// Gives a receiver for every event, for a main loop to read instead of
+    /// using callbacks.
+    ///
+    /// Stops sending once the receiver is dropped.
+    pub fn receiver(&self) -> Receiver<Event> {
+        let (sender, receiver) = channel();
+        self.add(
+            Box::new(|_| true),
+            Arc::new(Mutex::new(move |event: &Event| {
+                sender.send(event.clone()).is_ok()
+            })),
+        );
+        receiver
+    }
+    /// Stops calling a handler. Gives false if it was already gone.
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.remove(id.0)
+    }
+    /// Publishes [Event::ButtonPressed] and [Event::ButtonReleased] for the
+    /// button.
+    ///
+    /// Replaces any callback given to [Button::on_event()].
+    pub fn attach_button(&self, source: &str, button: &mut Button) -> Result<()> {
+        let (bus, source) = (self.clone(), source.to_string());
+        button.on_event(move |event| {
+            let source = source.clone();
+            bus.publish(match event {
+                ButtonEvent::Pressed => Event::ButtonPressed { source },
+                ButtonEvent::Released => Event::ButtonReleased { source },
+            });
+        })
+    }
+    /// Publishes [Event::MotionDetected] and [Event::MotionStopped] for a
+    /// motion sensor like a PIR module.
+    ///
+    /// The sensor's output is read with a [Button] that is pressed while
+    /// motion is seen, so an active high one for a PIR module.
+    pub fn attach_motion(&self, source: &str, sensor: &mut Button) -> Result<()> {
+        let (bus, source) = (self.clone(), source.to_string());
+        sensor.on_event(move |event| {
+            let source = source.clone();
+            bus.publish(match event {
+                ButtonEvent::Pressed => Event::MotionDetected { source },
+                ButtonEvent::Released => Event::MotionStopped { source },
+            });
+        })
+    }
+    /// Publishes [Event::EncoderTurned] for each step of the knob.
+    ///
+    /// Replaces any callback given to [RotaryEncoder::on_turn()].
+    pub fn attach_encoder(&self, source: &str, encoder: &mut RotaryEncoder) {
+        let (bus, source) = (self.clone(), source.to_string());
+        encoder.on_turn(move |direction, value| {
+            bus.publish(Event::EncoderTurned {
+                source: source.clone(),
+                direction,
+                value,
+            })
+        });
+    }
+    /// Makes a [ThresholdWatcher] to feed readings from something like an ADC
+    /// to so it can publish [Event::ThresholdCrossed].
+    pub fn threshold(&self, source: &str, threshold: f64, hysteresis: f64) -> ThresholdWatcher {
+        ThresholdWatcher {
+            bus: self.clone(),
+            source: source.to_string(),
+            threshold: Hysteresis::new(threshold, hysteresis),
+        }
+    }
+    /// Internal method to add a subscriber.
+    fn add(&self, filter: Filter, handler: Handler) -> SubscriptionId {
+        let mut subscribers = self.lock();
+        let id = subscribers.next;
+        subscribers.next += 1;
+        subscribers.list.push(Subscriber {
+            id,
+            filter,
+            handler,
+        });
+        SubscriptionId(id)
+    }
+    /// Internal method to drop a subscriber.
+    fn remove(&self, id: u64) -> bool {
+        let mut subscribers = self.lock();
+        let before = subscribers.list.len();
+        subscribers.list.retain(|subscriber| subscriber.id != id);
+        subscribers.list.len() != before
+    }
+    /// Internal method to get at the subscribers.
+    fn lock(&self) -> MutexGuard<'_, Subscribers> {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Threshold on a level that only clears once the level has dropped back a
+/// bit so it doesn't chatter when the level sits right at the threshold.
+#[derive(Clone, Copy, Debug)]
+pub struct Hysteresis {
+    threshold: f64,
+    hysteresis: f64,
+    active: bool,
+}
+
+impl Hysteresis {
+    /// Makes a threshold that clears again at threshold - hysteresis.
+    pub fn new(threshold: f64, hysteresis: f64) -> Self {
+        Hysteresis {
+            threshold,
+            hysteresis: hysteresis.abs(),
+            active: false,
+        }
+    }
+    /// Checks if the level is above the threshold.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+    /// Feeds in a new level giving true or false if that set off or cleared
+    /// the threshold.
+    pub fn update(&mut self, level: f64) -> Option<bool> {
+        if !self.active && level >= self.threshold {
+            self.active = true;
+            Some(true)
+        } else if self.active && level < self.threshold - self.hysteresis {
+            self.active = false;
+            Some(false)
+        } else {
+            None
+        }
+    }
+    /// Forgets about any active alarm.
+    pub fn reset(&mut self) {
+        self.active = false;
+    }
+}
+
+/// Publishes [Event::ThresholdCrossed] as the readings it is given go above a
+/// threshold or drop back below it, made by [EventBus::threshold()].
+///
+/// Readings that can't interrupt, like from an ADC, have to be polled so this
+/// is fed from the main loop.
+pub struct ThresholdWatcher {
+    bus: EventBus,
+    source: String,
+    threshold: Hysteresis,
+}
+
+impl ThresholdWatcher {
+    /// Feeds in a new reading, publishing an event if it crossed the
+    /// threshold.
+    pub fn update(&mut self, value: f64) {
+        if let Some(above) = self.threshold.update(value) {
+            self.bus.publish(Event::ThresholdCrossed {
+                source: self.source.clone(),
+                value,
+                above,
+            });
+        }
+    }
+    /// Checks if the last reading left it above the threshold.
+    pub fn is_above(&self) -> bool {
+        self.threshold.is_active()
+    }
+}
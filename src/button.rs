@@ -0,0 +1,436 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::{
+    clock,
+    gpio::{Gpio, InputPin, Level, Trigger},
+};
+use crate::registry::{self, PinClaim};
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    sync::mpsc::{channel, Receiver, RecvTimeoutError},
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    thread::{spawn, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Default time the button has to stay still before a change is believed.
+const DEBOUNCE: Duration = Duration::from_millis(20);
+/// Default longest time between a release and the next press for them to
+/// count as a double click.
+const DOUBLE_CLICK: Duration = Duration::from_millis(300);
+/// Default time the button has to be held down for a long press.
+const LONG_PRESS: Duration = Duration::from_millis(800);
+
+/// Things a [Button] reports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ButtonEvent {
+    Pressed,
+    Released,
+}
+
+/// Higher level actions made up of one or more [ButtonEvent]s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gesture {
+    Click,
+    DoubleClick,
+    LongPress,
+}
+
+/// Where a [GestureDetector] is up to.
+#[derive(Clone, Copy, Debug)]
+enum GestureState {
+    Idle,
+    /// Held down since the given time, for the second time if part of a
+    /// possible double click.
+    Down {
+        since: Instant,
+        second: bool,
+    },
+    /// Released after a short press and waiting to see if another follows.
+    WaitSecond {
+        released_at: Instant,
+    },
+    /// A long press has been reported and the button is still down.
+    LongHeld,
+}
+
+/// Turns debounced presses and releases into clicks, double clicks, and long
+/// presses.
+///
+/// Works with anything that gives [ButtonEvent]s like the push button on the
+/// rotary encoder as well as a [Button].
+/// A single click is only reported once the double click time has passed
+/// without another press.
+/// A long press is reported as soon as the button has been held long enough
+/// without waiting for the release.
+#[derive(Clone, Copy, Debug)]
+pub struct GestureDetector {
+    double_click: Duration,
+    long_press: Duration,
+    state: GestureState,
+}
+
+impl Default for GestureDetector {
+    fn default() -> Self {
+        Self::new(DOUBLE_CLICK, LONG_PRESS)
+    }
+}
+
+impl GestureDetector {
+    /// Makes a detector with the given double click and long press times.
+    pub fn new(double_click: Duration, long_press: Duration) -> Self {
+        GestureDetector {
+            double_click,
+            long_press,
+            state: GestureState::Idle,
+        }
+    }
+    /// Feeds in an event that happened at the given time.
+    pub fn event(&mut self, event: ButtonEvent, now: Instant) -> Option<Gesture> {
+        // Catch up on any time outs first.
+        let gesture = self.tick(now);
+        let (state, from_event) = match (self.state, event) {
+            (GestureState::Idle, ButtonEvent::Pressed) => (
+                GestureState::Down {
+                    since: now,
+                    second: false,
+                },
+                None,
+            ),
+            (GestureState::WaitSecond { .. }, ButtonEvent::Pressed) => (
+                GestureState::Down {
+                    since: now,
+                    second: true,
+                },
+                None,
+            ),
+            (GestureState::Down { second: false, .. }, ButtonEvent::Released) => {
+                (GestureState::WaitSecond { released_at: now }, None)
+            }
+            (GestureState::Down { second: true, .. }, ButtonEvent::Released) => {
+                (GestureState::Idle, Some(Gesture::DoubleClick))
+            }
+            (GestureState::LongHeld, ButtonEvent::Released) => (GestureState::Idle, None),
+            (state, _) => (state, None),
+        };
+        self.state = state;
+        gesture.or(from_event)
+    }
+    /// Checks for things that happen because time has passed.
+    ///
+    /// Needs calling regularly or at [GestureDetector::deadline()].
+    pub fn tick(&mut self, now: Instant) -> Option<Gesture> {
+        match self.state {
+            GestureState::Down {
+                since,
+                second: false,
+            } if now - since >= self.long_press => {
+                self.state = GestureState::LongHeld;
+                Some(Gesture::LongPress)
+            }
+            GestureState::WaitSecond { released_at } if now - released_at > self.double_click => {
+                self.state = GestureState::Idle;
+                Some(Gesture::Click)
+            }
+            _ => None,
+        }
+    }
+    /// The next time [GestureDetector::tick()] could report something if
+    /// nothing else happens first.
+    pub fn deadline(&self) -> Option<Instant> {
+        match self.state {
+            GestureState::Down {
+                since,
+                second: false,
+            } => Some(since + self.long_press),
+            GestureState::WaitSecond { released_at } => {
+                Some(released_at + self.double_click + Duration::from_millis(1))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Debounce state shared with the interrupt callback and worker thread.
+struct Debouncer {
+    /// Last state actually read from the pin.
+    raw: bool,
+    /// When the raw state last changed.
+    changed_at: Instant,
+    /// The debounced state.
+    stable: bool,
+    /// Set by the interrupt callback when the raw state may have changed.
+    edge: bool,
+    window: Duration,
+}
+
+impl Debouncer {
+    /// Internal method to record a new raw reading.
+    fn update(&mut self, pressed: bool, now: Instant) {
+        if pressed != self.raw {
+            self.raw = pressed;
+            self.changed_at = now;
+        }
+    }
+    /// Internal method that accepts the raw state once it has held steady for
+    /// the whole window, giving the event if that changed anything.
+    fn settle(&mut self, now: Instant) -> Option<ButtonEvent> {
+        if self.raw == self.stable || now - self.changed_at < self.window {
+            return None;
+        }
+        self.stable = self.raw;
+        Some(if self.stable {
+            ButtonEvent::Pressed
+        } else {
+            ButtonEvent::Released
+        })
+    }
+}
+
+type Shared = Arc<(Mutex<Debouncer>, Condvar)>;
+
+/// Structure used to model a push button with real debouncing.
+///
+/// The buttons in the kit connect the pin to ground when pressed so the pin's
+/// pull-up is used and low means pressed.
+/// A change only counts once the pin has stayed at the new level for the
+/// whole debounce window so switch bounce never gets through as extra presses.
+///
+/// Can be used by calling [Button::poll()] often from a loop or by giving a
+/// callback to [Button::on_event()] which is driven by interrupts.
+pub struct Button {
+    pin: InputPin,
+    active_low: bool,
+    shared: Shared,
+    running: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+    gestures: GestureDetector,
+    gesture_worker: Option<JoinHandle<()>>,
+    _claim: PinClaim,
+}
+
+impl Button {
+    /// Gets the GPIO pin with its pull-up turned on.
+    pub fn new(pin: u8) -> Result<Self> {
+        Self::with_polarity(pin, true)
+    }
+    /// Gets the GPIO pin for an input that reads low when pressed if
+    /// active_low is set, like the kit's buttons, or high if not, like
+    /// sensors that drive the pin themselves.
+    ///
+    /// The pull-up is turned on for active low inputs and the pull-down for
+    /// active high ones so an unplugged input reads as released.
+    pub fn with_polarity(pin: u8, active_low: bool) -> Result<Self> {
+        let claim = registry::claim("Button", &[pin])?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        let pin = gpio
+            .get(pin)
+            .context(format!("Failed to get button pin: {}", pin))?;
+        let pin = if active_low {
+            pin.into_input_pullup()
+        } else {
+            pin.into_input_pulldown()
+        };
+        let pressed = pin.is_low() == active_low;
+        let debouncer = Debouncer {
+            raw: pressed,
+            changed_at: clock::now(),
+            stable: pressed,
+            edge: false,
+            window: DEBOUNCE,
+        };
+        Ok(Button {
+            pin,
+            active_low,
+            shared: Arc::new((Mutex::new(debouncer), Condvar::new())),
+            running: Arc::new(AtomicBool::new(false)),
+            worker: None,
+            gestures: GestureDetector::default(),
+            gesture_worker: None,
+            _claim: claim,
+        })
+    }
+    /// Changes how long the button has to stay still before a change is
+    /// believed. The default is 20ms which suits most tactile switches.
+    pub fn set_debounce(&mut self, window: Duration) {
+        self.lock().window = window;
+    }
+    /// Changes the longest time between a release and the next press for a
+    /// double click and how long the button has to be held for a long press.
+    ///
+    /// The defaults are 300ms and 800ms.
+    /// Takes effect for the next call to [Button::on_gesture()].
+    pub fn set_gesture_times(&mut self, double_click: Duration, long_press: Duration) {
+        self.gestures = GestureDetector::new(double_click, long_press);
+    }
+    /// Checks the debounced state of the button.
+    pub fn is_pressed(&self) -> bool {
+        self.lock().stable
+    }
+    /// Reads the button and gives any event now that it has settled.
+    ///
+    /// Needs to be called more often than the debounce window, every few
+    /// milliseconds is good.
+    /// Don't mix this with [Button::on_event()].
+    pub fn poll(&mut self) -> Option<ButtonEvent> {
+        let pressed = self.pin.is_low() == self.active_low;
+        let now = clock::now();
+        let mut debouncer = self.lock();
+        debouncer.update(pressed, now);
+        debouncer.settle(now)
+    }
+    /// Same as [Button::poll()] but gives gestures instead.
+    pub fn poll_gesture(&mut self) -> Option<Gesture> {
+        let event = self.poll();
+        let now = clock::now();
+        match event {
+            Some(event) => self.gestures.event(event, now),
+            None => self.gestures.tick(now),
+        }
+    }
+    /// Calls the callback from a background thread for each gesture.
+    ///
+    /// Replaces any callback given before to this or [Button::on_event()].
+    pub fn on_gesture<C>(&mut self, mut callback: C) -> Result<()>
+    where
+        C: FnMut(Gesture) + Send + 'static,
+    {
+        let (tx, rx) = channel();
+        self.on_event(move |event| {
+            let _ = tx.send((event, clock::now()));
+        })?;
+        let mut gestures = self.gestures;
+        self.gesture_worker = Some(spawn(move || loop {
+            let received = match gestures.deadline() {
+                Some(deadline) => rx.recv_timeout(deadline.saturating_duration_since(clock::now())),
+                None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            let gesture = match received {
+                Ok((event, at)) => gestures.event(event, at),
+                Err(RecvTimeoutError::Timeout) => gestures.tick(clock::now()),
+                // The event callback has been cleared.
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            if let Some(gesture) = gesture {
+                callback(gesture);
+            }
+        }));
+        Ok(())
+    }
+    /// Gives the gestures as they happen through a channel so they can be
+    /// used as an iterator with `for gesture in button.gestures()?.iter()`.
+    pub fn gestures(&mut self) -> Result<Receiver<Gesture>> {
+        let (tx, rx) = channel();
+        self.on_gesture(move |gesture| {
+            let _ = tx.send(gesture);
+        })?;
+        Ok(rx)
+    }
+    /// Calls the callback from a background thread for each debounced press
+    /// and release.
+    ///
+    /// Uses an interrupt so nothing runs while the button isn't being
+    /// touched.
+    /// Replaces any callback given before.
+    pub fn on_event<C>(&mut self, mut callback: C) -> Result<()>
+    where
+        C: FnMut(ButtonEvent) + Send + 'static,
+    {
+        self.clear_event()?;
+        let shared = self.shared.clone();
+        let active_low = self.active_low;
+        self.pin
+            .set_async_interrupt(Trigger::Both, move |level: Level| {
+                let (debouncer, edge) = &*shared;
+                let mut debouncer = debouncer.lock().unwrap_or_else(PoisonError::into_inner);
+                debouncer.update((level == Level::Low) == active_low, clock::now());
+                debouncer.edge = true;
+                edge.notify_one();
+            })
+            .context("Failed to set button interrupt")?;
+        self.running.store(true, Ordering::SeqCst);
+        let shared = self.shared.clone();
+        let r = self.running.clone();
+        self.worker = Some(spawn(move || {
+            let (debouncer, edge) = &*shared;
+            let mut guard = debouncer.lock().unwrap_or_else(PoisonError::into_inner);
+            while r.load(Ordering::SeqCst) {
+                if !guard.edge {
+                    guard = edge.wait(guard).unwrap_or_else(PoisonError::into_inner);
+                    continue;
+                }
+                // Wait for things to go quiet for a whole window.
+                let quiet = clock::now() - guard.changed_at;
+                if quiet < guard.window {
+                    let wait = guard.window - quiet;
+                    guard = edge
+                        .wait_timeout(guard, wait)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0;
+                    continue;
+                }
+                guard.edge = false;
+                if let Some(event) = guard.settle(clock::now()) {
+                    // Don't hold the lock while the callback runs.
+                    drop(guard);
+                    callback(event);
+                    guard = debouncer.lock().unwrap_or_else(PoisonError::into_inner);
+                }
+            }
+        }));
+        Ok(())
+    }
+    /// Stops calling the callback given to [Button::on_event()] or
+    /// [Button::on_gesture()].
+    pub fn clear_event(&mut self) -> Result<()> {
+        if let Some(worker) = self.worker.take() {
+            self.pin
+                .clear_async_interrupt()
+                .context("Failed to clear button interrupt")?;
+            // Holding the lock makes sure the worker is either waiting or
+            // yet to check the flag so the wake up can't be missed.
+            let guard = self.lock();
+            self.running.store(false, Ordering::SeqCst);
+            drop(guard);
+            self.shared.1.notify_one();
+            let _ = worker.join();
+        }
+        // Ends by itself once the event callback above is gone.
+        if let Some(worker) = self.gesture_worker.take() {
+            let _ = worker.join();
+        }
+        Ok(())
+    }
+    /// Internal method used to get at the state shared with the interrupt.
+    fn lock(&self) -> MutexGuard<'_, Debouncer> {
+        self.shared.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Stop the worker thread before exiting.
+impl Drop for Button {
+    fn drop(&mut self) {
+        let _ = self.clear_event();
+    }
+}
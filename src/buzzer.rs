@@ -0,0 +1,472 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::{
+    gpio::{Gpio, OutputPin},
+    pwm::{Channel, Polarity, Pwm},
+};
+use crate::registry::{self, PinClaim};
+use std::{
+    str::FromStr,
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    thread::{sleep, spawn, JoinHandle},
+    time::{Duration, Instant},
+};
+
+// Part of each note left silent so repeated notes don't run together.
+const GAP: f64 = 0.1;
+// MIDI note number of A4 which is 440Hz.
+const A4: i32 = 69;
+// Morse code timing unit in milliseconds.
+const DOT: u64 = 100;
+
+/// A note in a melody.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Note {
+    /// Silence.
+    Rest,
+    /// A tone at the given frequency in Hz.
+    Hz(f64),
+}
+
+impl Note {
+    /// Equal tempered note from its MIDI number where 60 is middle C and 69 is
+    /// A4 at 440Hz.
+    pub fn midi(number: u8) -> Self {
+        Note::Hz(440.0 * 2f64.powf(f64::from(i32::from(number) - A4) / 12.0))
+    }
+    /// The frequency in Hz or None for a rest.
+    pub fn frequency(&self) -> Option<f64> {
+        match self {
+            Note::Rest => None,
+            Note::Hz(hz) => Some(*hz),
+        }
+    }
+}
+
+/// Parses note names like `C4`, `F#5`, or `Bb3`, with `R` or `-` for a rest.
+impl FromStr for Note {
+    type Err = SuperKitError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("r") || s == "-" {
+            return Ok(Note::Rest);
+        }
+        let mut chars = s.chars();
+        let semitone = match chars.next().map(|c| c.to_ascii_uppercase()) {
+            Some('C') => 0,
+            Some('D') => 2,
+            Some('E') => 4,
+            Some('F') => 5,
+            Some('G') => 7,
+            Some('A') => 9,
+            Some('B') => 11,
+            _ => {
+                return Err(SuperKitError::InvalidArgument(format!(
+                    "Unknown note: {}",
+                    s
+                )))
+            }
+        };
+        let rest = chars.as_str();
+        let (semitone, octave) = if let Some(octave) = rest.strip_prefix('#') {
+            (semitone + 1, octave)
+        } else if let Some(octave) = rest.strip_prefix('b') {
+            (semitone - 1, octave)
+        } else {
+            (semitone, rest)
+        };
+        let octave = octave
+            .parse::<i32>()
+            .context(format!("Unknown octave in note: {}", s))?;
+        let number = (octave + 1) * 12 + semitone;
+        if !(0..=127).contains(&number) {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Note is out of range: {}",
+                s
+            )));
+        }
+        Ok(Note::midi(number as u8))
+    }
+}
+
+/// Where the square wave comes from.
+enum Output {
+    Hardware(Pwm),
+    /// Pin and if the buzzer sounds when the pin is low.
+    Software(OutputPin, bool),
+}
+
+/// Structure used to model a passive buzzer which needs a square wave to make
+/// a sound, at whatever pitch the wave is.
+///
+/// Hardware PWM gives the cleanest tone while software PWM works on any pin but
+/// sounds a little rough.
+pub struct Buzzer {
+    output: Output,
+    _claim: Option<PinClaim>,
+}
+
+impl Buzzer {
+    /// Gets the given pin for a buzzer driven through a PNP transistor like in
+    /// lesson 6 so it is quiet while the pin is high.
+    pub fn new(pin: u8) -> Result<Self> {
+        Self::software(pin, true)
+    }
+    /// Gets the given pin for software PWM with the buzzer on when the pin is
+    /// low if active_low is set or high if not.
+    pub fn software(pin: u8, active_low: bool) -> Result<Self> {
+        let claim = registry::claim("Buzzer", &[pin])?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        let pin = gpio
+            .get(pin)
+            .context(format!("Failed to get buzzer pin: {}", pin))?
+            .into_output();
+        let mut buzzer = Buzzer {
+            output: Output::Software(pin, active_low),
+            _claim: Some(claim),
+        };
+        buzzer.stop()?;
+        Ok(buzzer)
+    }
+    /// Uses one of the hardware PWM channels which need to be routed to a pin
+    /// with a pwm overlay in `/boot/config.txt` first.
+    ///
+    /// The pin idles low so this suits a buzzer driven through a NPN
+    /// transistor.
+    pub fn hardware(channel: Channel) -> Result<Self> {
+        let pwm = Pwm::with_frequency(channel, 440.0, 0.5, Polarity::Normal, false)
+            .context("Failed to get hardware PWM for buzzer, is the pwm overlay enabled")?;
+        Ok(Buzzer {
+            output: Output::Hardware(pwm),
+            _claim: None,
+        })
+    }
+    /// Starts a tone at the given frequency in Hz which keeps going until
+    /// [Buzzer::stop()] or another tone.
+    pub fn start(&mut self, frequency: f64) -> Result<()> {
+        if !frequency.is_finite() || frequency <= 0.0 {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Tone frequency {} has to be above zero",
+                frequency
+            )));
+        }
+        match &mut self.output {
+            Output::Hardware(pwm) => {
+                pwm.set_frequency(frequency, 0.5)
+                    .context("Failed to change buzzer frequency")?;
+                pwm.enable().context("Failed to enable buzzer PWM")?;
+            }
+            Output::Software(pin, _) => pin
+                .set_pwm_frequency(frequency, 0.5)
+                .context("Failed to change buzzer frequency")?,
+        }
+        Ok(())
+    }
+    /// Silences the buzzer.
+    pub fn stop(&mut self) -> Result<()> {
+        match &mut self.output {
+            Output::Hardware(pwm) => pwm.disable().context("Failed to disable buzzer PWM")?,
+            Output::Software(pin, active_low) => {
+                pin.clear_pwm().context("Failed to stop buzzer PWM")?;
+                if *active_low {
+                    pin.set_high();
+                } else {
+                    pin.set_low();
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Plays a tone at the given frequency in Hz for the given time.
+    pub fn tone(&mut self, frequency: f64, duration: Duration) -> Result<()> {
+        self.start(frequency)?;
+        sleep(duration);
+        self.stop()
+    }
+    /// Plays each note for its time with rests being silent.
+    ///
+    /// The end of each note is left silent so the same note played twice is
+    /// heard as two notes.
+    pub fn play_melody(&mut self, melody: &[(Note, Duration)]) -> Result<()> {
+        for (note, duration) in melody.iter() {
+            match note.frequency() {
+                Some(frequency) => {
+                    self.tone(frequency, duration.mul_f64(1.0 - GAP))?;
+                    sleep(duration.mul_f64(GAP));
+                }
+                None => sleep(*duration),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Insure the buzzer is quiet before exiting.
+impl Drop for Buzzer {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+/// Beep patterns for an [ActiveBuzzer].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Alarm {
+    /// One short beep.
+    Single,
+    /// ... --- ... in Morse code over and over.
+    Sos,
+    /// Quick beeps for something urgent.
+    Fast,
+    /// Slow beeps for something that needs looking at.
+    Slow,
+    /// Alternating on and off times starting with on, repeated.
+    Custom(Vec<Duration>),
+}
+
+impl Alarm {
+    /// Gives the alternating on and off times making up one cycle.
+    pub fn steps(&self) -> Vec<Duration> {
+        let ms = Duration::from_millis;
+        match self {
+            Alarm::Single => vec![ms(150)],
+            Alarm::Sos => {
+                let mut steps = Vec::new();
+                for (length, last_gap) in
+                    [(DOT, 3 * DOT), (3 * DOT, 3 * DOT), (DOT, 7 * DOT)].iter()
+                {
+                    for i in 0..3 {
+                        steps.push(ms(*length));
+                        steps.push(ms(if i == 2 { *last_gap } else { DOT }));
+                    }
+                }
+                steps
+            }
+            Alarm::Fast => vec![ms(100), ms(100)],
+            Alarm::Slow => vec![ms(500), ms(1000)],
+            Alarm::Custom(steps) => steps.clone(),
+        }
+    }
+    /// Checks if the pattern keeps going until stopped.
+    ///
+    /// A custom pattern with no time in it, like an empty one, only plays once
+    /// as repeating it would never give the buzzer a rest.
+    pub fn repeats(&self) -> bool {
+        match self {
+            Alarm::Single => false,
+            Alarm::Custom(steps) => steps.iter().any(|step| *step > Duration::ZERO),
+            _ => true,
+        }
+    }
+}
+
+/// Parses one of `single`, `sos`, `fast`, or `slow`.
+impl FromStr for Alarm {
+    type Err = SuperKitError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "single" => Ok(Alarm::Single),
+            "sos" => Ok(Alarm::Sos),
+            "fast" => Ok(Alarm::Fast),
+            "slow" => Ok(Alarm::Slow),
+            _ => Err(SuperKitError::InvalidArgument(format!(
+                "Unknown alarm: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// What the alarm worker is told to do.
+#[derive(Debug, Default)]
+struct AlarmState {
+    alarm: Option<Alarm>,
+    /// Bumped on every change so a playing pattern knows to stop.
+    generation: u64,
+    shutdown: bool,
+}
+
+type AlarmShared = Arc<(Mutex<AlarmState>, Condvar)>;
+
+/// Cheap to clone handle that can start and stop the alarm of an
+/// [ActiveBuzzer] from anywhere, like another driver's callback.
+#[derive(Clone)]
+pub struct AlarmHandle {
+    shared: AlarmShared,
+}
+
+impl AlarmHandle {
+    /// Starts a pattern replacing any that is playing.
+    pub fn start(&self, alarm: Alarm) {
+        self.change(Some(alarm));
+    }
+    /// Stops any pattern straight away.
+    pub fn stop(&self) {
+        self.change(None);
+    }
+    /// Checks if a pattern is playing.
+    pub fn is_sounding(&self) -> bool {
+        self.lock().alarm.is_some()
+    }
+    /// Internal method to change what is playing and wake the worker.
+    fn change(&self, alarm: Option<Alarm>) {
+        let mut state = self.lock();
+        state.alarm = alarm;
+        state.generation = state.generation.wrapping_add(1);
+        self.shared.1.notify_all();
+    }
+    /// Internal method to get the state lock even if the worker panicked.
+    fn lock(&self) -> MutexGuard<'_, AlarmState> {
+        self.shared.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Structure used to model an active buzzer like the one in lesson 6 which
+/// beeps by itself when switched on.
+///
+/// Patterns play on a background thread and can be started or stopped from
+/// anywhere through an [AlarmHandle].
+pub struct ActiveBuzzer {
+    handle: AlarmHandle,
+    worker: Option<JoinHandle<()>>,
+    _claim: PinClaim,
+}
+
+impl ActiveBuzzer {
+    /// Gets the given pin for a buzzer driven through a PNP transistor like in
+    /// lesson 6 so it is on while the pin is low.
+    pub fn new(pin: u8) -> Result<Self> {
+        Self::with_polarity(pin, true)
+    }
+    /// Gets the given pin with the buzzer on when the pin is low if active_low
+    /// is set or high if not.
+    pub fn with_polarity(pin: u8, active_low: bool) -> Result<Self> {
+        let claim = registry::claim("ActiveBuzzer", &[pin])?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        let mut pin = gpio
+            .get(pin)
+            .context(format!("Failed to get buzzer pin: {}", pin))?
+            .into_output();
+        let mut set = move |on: bool| {
+            if on != active_low {
+                pin.set_high();
+            } else {
+                pin.set_low();
+            }
+        };
+        set(false);
+        let shared: AlarmShared = Arc::new((Mutex::new(AlarmState::default()), Condvar::new()));
+        let handle = AlarmHandle {
+            shared: shared.clone(),
+        };
+        let worker = spawn(move || {
+            let (lock, condvar) = &*shared;
+            loop {
+                let mut state = lock.lock().unwrap_or_else(PoisonError::into_inner);
+                while state.alarm.is_none() && !state.shutdown {
+                    state = condvar.wait(state).unwrap_or_else(PoisonError::into_inner);
+                }
+                if state.shutdown {
+                    break;
+                }
+                let alarm = state.alarm.clone().unwrap_or(Alarm::Single);
+                let generation = state.generation;
+                drop(state);
+                'play: loop {
+                    // Checked here too as a pattern without any steps never
+                    // gets to the check after each one.
+                    {
+                        let state = lock.lock().unwrap_or_else(PoisonError::into_inner);
+                        if state.generation != generation || state.shutdown {
+                            break 'play;
+                        }
+                    }
+                    for (i, step) in alarm.steps().iter().enumerate() {
+                        set(i % 2 == 0);
+                        // Waiting on the condvar instead of sleeping lets a
+                        // change stop the pattern straight away.
+                        let deadline = Instant::now() + *step;
+                        let mut state = lock.lock().unwrap_or_else(PoisonError::into_inner);
+                        while state.generation == generation && !state.shutdown {
+                            let now = Instant::now();
+                            if now >= deadline {
+                                break;
+                            }
+                            state = condvar
+                                .wait_timeout(state, deadline - now)
+                                .unwrap_or_else(PoisonError::into_inner)
+                                .0;
+                        }
+                        if state.generation != generation || state.shutdown {
+                            break 'play;
+                        }
+                    }
+                    if !alarm.repeats() {
+                        let mut state = lock.lock().unwrap_or_else(PoisonError::into_inner);
+                        if state.generation == generation {
+                            state.alarm = None;
+                        }
+                        break;
+                    }
+                }
+                set(false);
+            }
+            set(false);
+        });
+        Ok(ActiveBuzzer {
+            handle,
+            worker: Some(worker),
+            _claim: claim,
+        })
+    }
+    /// Gives a handle that can start and stop the alarm from other threads or
+    /// callbacks.
+    pub fn handle(&self) -> AlarmHandle {
+        self.handle.clone()
+    }
+    /// Starts a pattern replacing any that is playing.
+    pub fn start(&self, alarm: Alarm) {
+        self.handle.start(alarm);
+    }
+    /// Stops any pattern straight away.
+    pub fn stop(&self) {
+        self.handle.stop();
+    }
+    /// Checks if a pattern is playing.
+    pub fn is_sounding(&self) -> bool {
+        self.handle.is_sounding()
+    }
+}
+
+/// Stop the worker thread and insure the buzzer is quiet before exiting.
+impl Drop for ActiveBuzzer {
+    fn drop(&mut self) {
+        self.handle.lock().shutdown = true;
+        self.handle.shared.1.notify_all();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
@@ -0,0 +1,120 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::led_bar::LEDS;
+use std::str::FromStr;
+
+// Number of frames in one cycle of the sparkle pattern.
+const SPARKLE_FRAMES: usize = 16;
+
+/// Patterns for running lights on a [crate::LedBar].
+///
+/// Each frame is a mask with bit 0 being LED 0.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Pattern {
+    /// A three LED wide streak sweeping back and forth with its tail behind
+    /// it like the car from the TV show.
+    KnightRider,
+    /// A single LED bouncing from end to end.
+    PingPong,
+    /// A couple of LEDs at random lit each frame.
+    Sparkle,
+    /// LEDs light up one at a time until the bar is full then go off again in
+    /// reverse.
+    FillDrain,
+    /// User defined frames.
+    Custom(Vec<u8>),
+}
+
+impl Pattern {
+    /// Gives the frames making up one cycle of the pattern.
+    ///
+    /// Cycles are made to join up smoothly so they can be repeated forever.
+    /// Sparkle gives different random frames every time.
+    pub fn frames(&self) -> Vec<u8> {
+        let last = LEDS - 1;
+        match self {
+            Pattern::KnightRider => {
+                let forward = (0..LEDS).map(|i| (0b111u16 << i >> 2) as u8);
+                let back = (1..last).rev().map(|i| (0b111u16 << i) as u8);
+                forward.chain(back).collect()
+            }
+            Pattern::PingPong => {
+                let forward = 0..LEDS;
+                let back = (1..last).rev();
+                forward.chain(back).map(|i| 1 << i).collect()
+            }
+            Pattern::Sparkle => (0..SPARKLE_FRAMES)
+                .map(|_| rand::random::<u8>() & rand::random::<u8>())
+                .collect(),
+            Pattern::FillDrain => {
+                let fill = (0..=LEDS).map(|i| (0xffu16 >> (LEDS - i)) as u8);
+                let drain = (1..LEDS).rev().map(|i| (0xffu16 >> (LEDS - i)) as u8);
+                fill.chain(drain).collect()
+            }
+            Pattern::Custom(frames) => frames.clone(),
+        }
+    }
+}
+
+/// Parses one of `knight_rider`, `ping_pong`, `sparkle`, or `fill_drain` or a
+/// comma separated list of frame masks in decimal, `0x` hex, or `0b` binary.
+impl FromStr for Pattern {
+    type Err = SuperKitError;
+    fn from_str(text: &str) -> Result<Self> {
+        let pattern = match text.trim().to_ascii_lowercase().as_str() {
+            "knight_rider" => Pattern::KnightRider,
+            "ping_pong" => Pattern::PingPong,
+            "sparkle" => Pattern::Sparkle,
+            "fill_drain" => Pattern::FillDrain,
+            _ => {
+                let frames = text
+                    .split(',')
+                    .map(|frame| parse_mask(frame.trim()))
+                    .collect::<Result<Vec<u8>>>()?;
+                Pattern::Custom(frames)
+            }
+        };
+        Ok(pattern)
+    }
+}
+
+/// Internal function to parse a single frame mask.
+fn parse_mask(text: &str) -> Result<u8> {
+    if text.is_empty() {
+        return Err(SuperKitError::InvalidArgument(
+            "Pattern has an empty frame".to_string(),
+        ));
+    }
+    let mask = if let Some(hex) = text.strip_prefix("0x") {
+        u8::from_str_radix(hex, 16)
+    } else if let Some(binary) = text.strip_prefix("0b") {
+        u8::from_str_radix(binary, 2)
+    } else {
+        text.parse()
+    };
+    mask.context(format!(
+        "Pattern frame \"{}\" is not a known pattern name or 8 bit mask",
+        text
+    ))
+}
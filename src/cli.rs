@@ -0,0 +1,199 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::config::{Config, Lesson};
+use crate::error::Result;
+use log::LevelFilter;
+use std::{io::Write, path::PathBuf};
+use structopt::StructOpt;
+
+/// Log target used by [say!()] for what a lesson has to say.
+///
+/// Lesson output is shown as is while everything else gets its level and
+/// target added in front so it stands out.
+pub const LESSON_TARGET: &str = "lesson";
+
+/// Logs a lesson message at info level so `--quiet` or `RUST_LOG` can turn it
+/// off.
+///
+/// Takes the same arguments as `println!()`.
+#[macro_export]
+macro_rules! say {
+    () => {
+        $crate::log::info!(target: $crate::cli::LESSON_TARGET, "")
+    };
+    ($($arg:tt)*) => {
+        $crate::log::info!(target: $crate::cli::LESSON_TARGET, $($arg)*)
+    };
+}
+
+// Options every lesson has which get added to its own options with
+// `#[structopt(flatten)]`.
+// Normal comments are used here as a doc comment would replace the about text
+// of every lesson in its --help.
+#[derive(Clone, Debug, Default, StructOpt)]
+pub struct Common {
+    /// Stop after this many times around the main loop instead of waiting for
+    /// Ctrl-C.
+    #[structopt(long)]
+    pub iterations: Option<u64>,
+    /// Only show errors.
+    #[structopt(short, long)]
+    pub quiet: bool,
+    /// Show debug messages from the drivers, use twice to also see trace
+    /// messages. `RUST_LOG` can be used instead to pick which drivers, like
+    /// `RUST_LOG=hc595=trace,lcd=debug`.
+    #[structopt(short, long, parse(from_occurrences))]
+    pub verbose: u8,
+    /// Config file to use instead of looking for superkit.toml.
+    #[structopt(long, parse(from_os_str))]
+    pub config: Option<PathBuf>,
+    /// Saves every pin write and read to this file when the lesson stops so
+    /// it can be replayed or checked later with `superkit`.
+    #[cfg(feature = "mock")]
+    #[structopt(long, parse(from_os_str))]
+    pub record: Option<PathBuf>,
+    /// GPIO backend, rppal (default), cdev, or cdev:<path> for the character
+    /// device of another chip.
+    #[cfg(all(feature = "cdev", not(feature = "mock")))]
+    #[structopt(long)]
+    pub gpio: Option<crate::hal::gpio::Backend>,
+}
+
+impl Common {
+    /// Loads the config file and gets the settings for the given lesson.
+    ///
+    /// Also does the same setup as [start()](Common::start), so call this
+    /// before anything is printed.
+    pub fn lesson(&self, name: &str) -> Result<Lesson> {
+        self.start()?;
+        self.reload(name)
+    }
+    /// Loads the config file again and gets the settings for the given
+    /// lesson without redoing the setup, for picking up changes while
+    /// running.
+    pub fn reload(&self, name: &str) -> Result<Lesson> {
+        let config = match self.config.as_ref() {
+            Some(path) => Config::load_from(path)?,
+            None => Config::load()?,
+        };
+        Ok(config.lesson(name))
+    }
+    /// The config file given with `--config` or else the one found where
+    /// [Config::load()] looks, if there is one.
+    pub fn config_path(&self) -> Option<PathBuf> {
+        self.config.clone().or_else(Config::find)
+    }
+    /// Does the setup for lessons without any settings in the config file.
+    ///
+    /// Sets up logging from `--quiet`, `--verbose`, and `RUST_LOG` and, with
+    /// the mock backend, connects to the simulator if it started the lesson
+    /// and starts any `--record`.
+    pub fn start(&self) -> Result<()> {
+        init_logging(self.level());
+        #[cfg(all(feature = "cdev", not(feature = "mock")))]
+        {
+            if let Some(backend) = self.gpio.clone() {
+                crate::hal::gpio::set_backend(backend);
+            }
+        }
+        #[cfg(feature = "mock")]
+        {
+            crate::simulator::connect()?;
+            if let Some(path) = self.record.as_ref() {
+                crate::session::record_to(path.clone());
+            }
+        }
+        Ok(())
+    }
+    /// Log level picked by `--quiet` and `--verbose`.
+    pub fn level(&self) -> LevelFilter {
+        match (self.quiet, self.verbose) {
+            (true, _) => LevelFilter::Error,
+            (false, 0) => LevelFilter::Info,
+            (false, 1) => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        }
+    }
+    /// Counts times around the main loop against `--iterations`.
+    pub fn iterations(&self) -> Iterations {
+        Iterations {
+            limit: self.iterations,
+            count: 0,
+        }
+    }
+}
+
+/// Counter used to stop a lesson's main loop after `--iterations`.
+#[derive(Clone, Copy, Debug)]
+pub struct Iterations {
+    limit: Option<u64>,
+    count: u64,
+}
+
+impl Iterations {
+    /// Counts another time around the loop and says if it should run again.
+    ///
+    /// Always true when there is no limit.
+    pub fn again(&mut self) -> bool {
+        match self.limit {
+            Some(limit) if self.count >= limit => false,
+            _ => {
+                self.count += 1;
+                true
+            }
+        }
+    }
+    /// Times around the loop so far.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+/// Starts logging to stdout at the given level with any `RUST_LOG`
+/// directives added on top.
+///
+/// Only the first call does anything so it is safe to call again.
+pub fn init_logging(level: LevelFilter) {
+    let mut builder = env_logger::Builder::new();
+    builder.filter_level(level);
+    // ureq logs every request at info which would be every InfluxDB sample.
+    #[cfg(feature = "influxdb")]
+    builder.filter_module("ureq", level.min(LevelFilter::Warn));
+    let _ = builder
+        .parse_env("RUST_LOG")
+        .format(|buf, record| {
+            if record.target() == LESSON_TARGET {
+                writeln!(buf, "{}", record.args())
+            } else {
+                writeln!(
+                    buf,
+                    "[{} {}] {}",
+                    record.level(),
+                    record.target(),
+                    record.args()
+                )
+            }
+        })
+        .target(env_logger::Target::Stdout)
+        .try_init();
+}
@@ -0,0 +1,219 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use serde::Serialize;
+use std::str::FromStr;
+
+/// An RGB color with 8 bits per channel.
+///
+/// Shared by everything in the kit that lights up in color so a color worked
+/// out for one can be used with another.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::new(0, 0, 0);
+    pub const WHITE: Color = Color::new(0xff, 0xff, 0xff);
+    pub const RED: Color = Color::new(0xff, 0, 0);
+    pub const GREEN: Color = Color::new(0, 0xff, 0);
+    pub const BLUE: Color = Color::new(0, 0, 0xff);
+    pub const CYAN: Color = Color::new(0, 0xff, 0xff);
+    pub const MAGENTA: Color = Color::new(0xff, 0, 0xff);
+    pub const YELLOW: Color = Color::new(0xff, 0xff, 0);
+    pub const ORANGE: Color = Color::new(0xff, 0x88, 0);
+    pub const PURPLE: Color = Color::new(0x80, 0, 0xff);
+    pub const PINK: Color = Color::new(0xff, 0x40, 0x80);
+    pub const WARM_WHITE: Color = Color::new(0xff, 0xb0, 0x60);
+    /// All the named colors with the names [Color::parse()] accepts.
+    pub const NAMED: [(&'static str, Color); 13] = [
+        ("black", Color::BLACK),
+        ("off", Color::BLACK),
+        ("white", Color::WHITE),
+        ("red", Color::RED),
+        ("green", Color::GREEN),
+        ("blue", Color::BLUE),
+        ("cyan", Color::CYAN),
+        ("magenta", Color::MAGENTA),
+        ("yellow", Color::YELLOW),
+        ("orange", Color::ORANGE),
+        ("purple", Color::PURPLE),
+        ("pink", Color::PINK),
+        ("warm_white", Color::WARM_WHITE),
+    ];
+
+    /// Makes a color from its red, green, and blue parts.
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b }
+    }
+    /// Makes a color from the `0xRRGGBB` form used in the lessons.
+    ///
+    /// Anything above the lower 24 bits is ignored.
+    pub const fn from_hex(hex: u32) -> Self {
+        Color {
+            r: (hex >> 16) as u8,
+            g: (hex >> 8) as u8,
+            b: hex as u8,
+        }
+    }
+    /// Makes a color from text which can be one of the [Color::NAMED] colors
+    /// in any case or hex digits in `#RRGGBB`, `#RGB`, or `0xRRGGBB` form.
+    ///
+    /// The leading `#` is optional.
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        if let Some((_, color)) = Color::NAMED
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(text))
+        {
+            return Ok(*color);
+        }
+        let digits = text
+            .strip_prefix('#')
+            .or_else(|| text.strip_prefix("0x"))
+            .or_else(|| text.strip_prefix("0X"))
+            .unwrap_or(text);
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Color \"{}\" is not a known color name or hex value like #FF8800",
+                text
+            )));
+        }
+        let hex = u32::from_str_radix(digits, 16)
+            .context(format!("Color \"{}\" is not a valid hex value", text))?;
+        match digits.len() {
+            6 => Ok(Color::from_hex(hex)),
+            // Each digit is doubled up so #F80 is the same as #FF8800.
+            3 => Ok(Color::new(
+                (hex >> 8) as u8 * 0x11,
+                (hex >> 4 & 0xf) as u8 * 0x11,
+                (hex & 0xf) as u8 * 0x11,
+            )),
+            len => Err(SuperKitError::InvalidArgument(format!(
+                "Color \"{}\" has {} hex digits but needs either 3 or 6",
+                text, len
+            ))),
+        }
+    }
+    /// Gives the color in `0xRRGGBB` form.
+    pub const fn to_hex(self) -> u32 {
+        (self.r as u32) << 16 | (self.g as u32) << 8 | self.b as u32
+    }
+    /// Makes a color from hue, saturation, and value.
+    ///
+    /// Hue is in degrees and wraps around so 360.0 is red again.
+    /// Saturation and value are from 0.0 to 1.0 and are clamped to that range.
+    pub fn from_hsv(hue: f64, saturation: f64, value: f64) -> Self {
+        let hue = hue.rem_euclid(360.0) / 60.0;
+        let saturation = saturation.clamp(0.0, 1.0);
+        let value = value.clamp(0.0, 1.0);
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (r, g, b) = match hue as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = value - chroma;
+        Color::from_ratios([r + m, g + m, b + m])
+    }
+    /// Gives the color as hue in degrees from 0.0 up to 360.0 plus saturation
+    /// and value from 0.0 to 1.0.
+    pub fn to_hsv(self) -> (f64, f64, f64) {
+        let [r, g, b] = self.to_ratios();
+        let max = r.max(g).max(b);
+        let chroma = max - r.min(g).min(b);
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / chroma).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / chroma + 2.0)
+        } else {
+            60.0 * ((r - g) / chroma + 4.0)
+        };
+        let saturation = if max == 0.0 { 0.0 } else { chroma / max };
+        (hue, saturation, max)
+    }
+    /// Makes a color from red, green, and blue fractions of full brightness
+    /// from 0.0 to 1.0 which are clamped to that range.
+    pub fn from_ratios(ratios: [f64; 3]) -> Self {
+        let to_u8 = |ratio: f64| (ratio.clamp(0.0, 1.0) * 255.0).round() as u8;
+        Color::new(to_u8(ratios[0]), to_u8(ratios[1]), to_u8(ratios[2]))
+    }
+    /// Gives red, green, and blue as fractions of full brightness from 0.0 to
+    /// 1.0 which is what PWM duty cycles use.
+    pub fn to_ratios(self) -> [f64; 3] {
+        [self.r, self.g, self.b].map(|c| f64::from(c) / 255.0)
+    }
+    /// Makes the color dimmer or brighter by multiplying each part by the
+    /// factor.
+    pub fn scale(self, factor: f64) -> Self {
+        let [r, g, b] = self.to_ratios();
+        Color::from_ratios([r * factor, g * factor, b * factor])
+    }
+    /// Gives the color part way between this one and another.
+    ///
+    /// A fraction of 0.0 is this color and 1.0 is the other one.
+    pub fn lerp(self, other: Color, fraction: f64) -> Self {
+        let fraction = fraction.clamp(0.0, 1.0);
+        let from = self.to_ratios();
+        let to = other.to_ratios();
+        Color::from_ratios([
+            from[0] + (to[0] - from[0]) * fraction,
+            from[1] + (to[1] - from[1]) * fraction,
+            from[2] + (to[2] - from[2]) * fraction,
+        ])
+    }
+}
+
+impl From<u32> for Color {
+    fn from(hex: u32) -> Self {
+        Color::from_hex(hex)
+    }
+}
+
+impl From<Color> for u32 {
+    fn from(color: Color) -> Self {
+        color.to_hex()
+    }
+}
+
+impl From<(u8, u8, u8)> for Color {
+    fn from((r, g, b): (u8, u8, u8)) -> Self {
+        Color::new(r, g, b)
+    }
+}
+
+impl FromStr for Color {
+    type Err = SuperKitError;
+    fn from_str(text: &str) -> Result<Self> {
+        Color::parse(text)
+    }
+}
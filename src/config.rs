@@ -0,0 +1,285 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::runner::CancelToken;
+use log::{debug, info, warn};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::BTreeMap,
+    convert::TryInto,
+    env, fs,
+    path::{Path, PathBuf},
+    thread::{spawn, JoinHandle},
+    time::{Duration, SystemTime},
+};
+use toml::value::Table;
+
+/// Name of the configuration file looked for.
+pub const FILE_NAME: &str = "superkit.toml";
+// Highest GPIO number on the 40 pin header.
+const MAX_PIN: u8 = 27;
+// Time between looking at the config file for changes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Settings for the lessons loaded from an optional `superkit.toml` so pins
+/// and timings can be changed to match your wiring without recompiling.
+///
+/// The file has a table for each lesson named after its binary and anything
+/// left out keeps the value in the code:
+///
+/// ```toml
+/// [01_led]
+/// led = 27
+/// delay = 250
+///
+/// [03_8led]
+/// pins = [4, 25, 24, 23, 22, 27, 18, 17]
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    lessons: BTreeMap<String, Table>,
+    path: Option<PathBuf>,
+}
+
+impl Config {
+    /// Loads `superkit.toml` from the current directory or failing that from
+    /// `~/.config/superkit/`.
+    ///
+    /// Not having a file at all is fine and gives an empty configuration so
+    /// all the defaults get used.
+    pub fn load() -> Result<Self> {
+        match Self::find() {
+            Some(path) => Self::load_from(path),
+            None => {
+                debug!(target: "config", "no superkit.toml found, using defaults");
+                Ok(Self::default())
+            }
+        }
+    }
+    /// Loads the configuration from the given file.
+    pub fn load_from<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        debug!(target: "config", "loading {}", path.display());
+        let text = fs::read_to_string(path)
+            .context(format!("Failed to read config file: {}", path.display()))?;
+        let lessons = toml::from_str(&text)
+            .context(format!("Failed to parse config file: {}", path.display()))?;
+        Ok(Config {
+            lessons,
+            path: Some(path.to_path_buf()),
+        })
+    }
+    /// Parses a configuration from TOML text.
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let lessons: BTreeMap<String, Table> =
+            toml::from_str(text).context("Failed to parse config")?;
+        Ok(Config {
+            lessons,
+            path: None,
+        })
+    }
+    /// Looks for the file in the places [Config::load()] uses.
+    pub fn find() -> Option<PathBuf> {
+        let local = PathBuf::from(FILE_NAME);
+        if local.is_file() {
+            return Some(local);
+        }
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        let user = config_home.join("superkit").join(FILE_NAME);
+        if user.is_file() {
+            Some(user)
+        } else {
+            None
+        }
+    }
+    /// The file the configuration came from if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+    /// Gets the settings for one lesson, which are empty if it isn't in the
+    /// file.
+    pub fn lesson(&self, name: &str) -> Lesson {
+        Lesson {
+            name: name.to_string(),
+            table: self.lessons.get(name).cloned().unwrap_or_default(),
+        }
+    }
+}
+
+/// The settings for one lesson from a [Config].
+#[derive(Clone, Debug, Default)]
+pub struct Lesson {
+    name: String,
+    table: Table,
+}
+
+impl Lesson {
+    /// Gets a setting or the default if it isn't given.
+    pub fn get<T: DeserializeOwned>(&self, key: &str, default: T) -> Result<T> {
+        match self.table.get(key) {
+            Some(value) => value.clone().try_into().map_err(|e| {
+                SuperKitError::Config(format!(
+                    "Config setting {}.{} has the wrong type: {}",
+                    self.name, key, e
+                ))
+            }),
+            None => Ok(default),
+        }
+    }
+    /// Replaces a setting with one given some other way, like on the command
+    /// line, leaving it alone when there isn't one.
+    pub fn with<T: Serialize>(mut self, key: &str, value: Option<T>) -> Result<Self> {
+        if let Some(value) = value {
+            let value = toml::Value::try_from(value).context(format!(
+                "Failed to set config setting {}.{}",
+                self.name, key
+            ))?;
+            self.table.insert(key.to_string(), value);
+        }
+        Ok(self)
+    }
+    /// Puts back the settings for the given keys from the ones the lesson is
+    /// running with, warning about each one that changed.
+    ///
+    /// Used on reloaded settings for the pins, which are only claimed once
+    /// when the lesson starts, so it is clear the change needs a restart.
+    pub fn keep_from(mut self, running: &Lesson, keys: &[&str]) -> Self {
+        for key in keys {
+            let old = running.table.get(*key);
+            if self.table.get(*key) == old {
+                continue;
+            }
+            let show = |value: Option<&toml::Value>| match value {
+                Some(value) => value.to_string(),
+                None => "the default".to_string(),
+            };
+            warn!(
+                target: "config",
+                "{}.{} changed from {} to {} but needs a restart, still using {}",
+                self.name,
+                key,
+                show(old),
+                show(self.table.get(*key)),
+                show(old)
+            );
+            match old {
+                Some(value) => self.table.insert(key.to_string(), value.clone()),
+                None => self.table.remove(*key),
+            };
+        }
+        self
+    }
+    /// Gets a GPIO pin number or the default if it isn't given.
+    pub fn pin(&self, key: &str, default: u8) -> Result<u8> {
+        let pin = self.get(key, default)?;
+        self.check_pin(key, pin)?;
+        Ok(pin)
+    }
+    /// Gets a fixed number of GPIO pin numbers or the defaults if they aren't
+    /// given.
+    pub fn pins<const N: usize>(&self, key: &str, default: [u8; N]) -> Result<[u8; N]> {
+        let pins: Vec<u8> = self.get(key, default.to_vec())?;
+        for pin in pins.iter() {
+            self.check_pin(key, *pin)?;
+        }
+        let len = pins.len();
+        pins.try_into().map_err(|_| {
+            SuperKitError::Config(format!(
+                "Config setting {}.{} needs {} pins but has {}",
+                self.name, key, N, len
+            ))
+        })
+    }
+    /// Internal method to make sure a pin is on the header.
+    fn check_pin(&self, key: &str, pin: u8) -> Result<()> {
+        if pin > MAX_PIN {
+            return Err(SuperKitError::Config(format!(
+                "Config setting {}.{} of {} isn't a GPIO pin, use 0-{}",
+                self.name, key, pin, MAX_PIN
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Watches a config file and asks for a reload through a [CancelToken]
+/// whenever it is saved, so a running lesson can pick up the new settings.
+///
+/// The file is looked at twice a second from a background thread instead of
+/// using inotify so it works the same everywhere, including on network
+/// shares.
+/// A change is only passed on once the file stops changing, so an editor
+/// saving in more than one step only causes one reload, and a file that goes
+/// missing is waited on until it is back.
+/// Stops watching when dropped.
+#[derive(Debug)]
+pub struct Watcher {
+    stop: CancelToken,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Watcher {
+    pub fn new<P: Into<PathBuf>>(path: P, token: CancelToken) -> Self {
+        let path = path.into();
+        let stop = CancelToken::new();
+        let watching = stop.clone();
+        let thread = spawn(move || {
+            debug!(target: "config", "watching {}", path.display());
+            let mut last = stamp(&path);
+            let mut changed = false;
+            while watching.sleep(WATCH_INTERVAL) {
+                let now = stamp(&path);
+                if now != last {
+                    last = now;
+                    changed = true;
+                } else if changed && now.is_some() {
+                    changed = false;
+                    info!(target: "config", "{} changed", path.display());
+                    token.request_reload();
+                }
+            }
+        });
+        Watcher {
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+/// Insure the watching thread has stopped before exiting.
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.stop.cancel();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Internal function to get what is compared to tell when a file changed.
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
@@ -0,0 +1,345 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Wires up the parts named in the `superkitd` table of the config file so a
+//! kit project can run as a service, like the `superkitd` binary does.
+//!
+//! Outputs follow the set events on an [EventBus] under their name, inputs
+//! publish to it, sensors are read every `interval` seconds and published as
+//! [Event::Sample]s, and the `schedule` rules of the [Scheduler] act on it
+//! all.
+//!
+//! ```toml
+//! [superkitd]
+//! interval = 60
+//!
+//! [superkitd.outputs]
+//! porch = { kind = "led", pin = 18 }
+//! heater = { kind = "relay", pin = 17 }
+//! mood = { kind = "rgb", pins = [12, 16, 20] }
+//! lcd = { kind = "lcd" }
+//!
+//! [superkitd.inputs]
+//! doorbell = { kind = "button", pin = 19 }
+//! hall = { kind = "motion", pin = 26 }
+//! temperature = { kind = "ds18b20" }
+//! room = { kind = "dht11", pin = 4 }
+//! tank = { kind = "ultrasonic", pins = [23, 24] }
+//! light = { kind = "light", channel = 0 }
+//!
+//! [[superkitd.schedule]]
+//! every = 600
+//! text = { output = "lcd", text = "Temp {temperature}C" }
+//! ```
+//!
+//! A DHT11 publishes both `<name>.temperature` and `<name>.humidity`.
+//! The ADC0832 is on the lesson 15 pins unless moved with
+//! `adc = [cs, clk, dio]`, which it has to be to share with the LCD, like
+//! `adc = [5, 6, 13]` from lesson 16.
+//! The `lcd` and `light` kinds need the `lcd` and `analog` features.
+
+use crate::bus::{Event, EventBus, SubscriptionId};
+use crate::config::Lesson;
+use crate::error::Result;
+#[cfg(any(not(feature = "lcd"), not(feature = "analog")))]
+use crate::error::SuperKitError;
+use crate::pwm::PwmOutput;
+use crate::runner::CancelToken;
+#[cfg(feature = "lcd")]
+use crate::Lcd1602;
+use crate::{rgb::Polarity, Button, Dht11, Ds18b20, Led, RgbPwm, Scheduler, Ultrasonic};
+#[cfg(feature = "analog")]
+use crate::{Adc0832, LightSensor};
+use chrono::Local;
+use log::{error, info, warn};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+/// Default time in seconds between sensor readings.
+const INTERVAL: u64 = 60;
+/// Longest time to sleep before checking the schedule again.
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+/// Something the daemon drives, followed by its name on the bus.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Output {
+    /// Active low LED dimmed by [Event::LevelSet] from 0.0 to 1.0.
+    Led { pin: u8 },
+    /// Relay switched on by [Event::LevelSet] of 0.5 or more.
+    Relay { pin: u8 },
+    /// RGB LED set by [Event::ColorSet].
+    Rgb { pins: [u8; 3] },
+    /// LCD1602 wired like lesson 13 showing [Event::TextSet].
+    Lcd,
+}
+
+/// Something the daemon reads, published on the bus under its name.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Input {
+    /// Button publishing presses and releases.
+    Button { pin: u8 },
+    /// PIR sensor publishing motion starting and stopping.
+    Motion { pin: u8 },
+    /// DHT11 temperature and humidity.
+    Dht11 { pin: u8 },
+    /// First DS18B20 temperature in Celsius.
+    Ds18b20,
+    /// Distance in cm from the trigger and echo pins.
+    Ultrasonic { pins: [u8; 2] },
+    /// Light level from a photoresistor on a channel of the ADC0832 wired like
+    /// lesson 15.
+    Light {
+        #[serde(default)]
+        channel: u8,
+    },
+}
+
+/// Internal enum of the sensors read on each interval.
+enum Sensor {
+    Dht11(Dht11),
+    Ds18b20(Ds18b20),
+    Ultrasonic(Ultrasonic),
+    #[cfg(feature = "analog")]
+    Light(LightSensor),
+}
+
+/// Structure holding everything the daemon set up from the config file.
+///
+/// Dropping it lets go of all the parts so their pins can be used again.
+pub struct Daemon {
+    bus: EventBus,
+    interval: Duration,
+    scheduler: Scheduler,
+    sensors: Vec<(String, Sensor)>,
+    #[cfg(feature = "analog")]
+    adc: Option<Adc0832>,
+    #[cfg(feature = "analog")]
+    adc_pins: Option<[u8; 3]>,
+    /// Kept so their events keep coming.
+    buttons: Vec<Button>,
+    subscriptions: Vec<SubscriptionId>,
+}
+
+impl Daemon {
+    /// Sets up the outputs, inputs, and schedule from the config table.
+    pub fn from_config(bus: EventBus, config: &Lesson) -> Result<Self> {
+        let interval = Duration::from_secs(config.get("interval", INTERVAL)?);
+        let outputs: BTreeMap<String, Output> = config.get("outputs", BTreeMap::new())?;
+        let inputs: BTreeMap<String, Input> = config.get("inputs", BTreeMap::new())?;
+        let scheduler = Scheduler::from_config(bus.clone(), config)?;
+        let mut daemon = Daemon {
+            bus,
+            interval,
+            scheduler,
+            sensors: Vec::new(),
+            #[cfg(feature = "analog")]
+            adc: None,
+            #[cfg(feature = "analog")]
+            adc_pins: config.get("adc", None)?,
+            buttons: Vec::new(),
+            subscriptions: Vec::new(),
+        };
+        for (name, output) in outputs {
+            daemon.add_output(&name, output)?;
+        }
+        for (name, input) in inputs {
+            daemon.add_input(&name, input)?;
+        }
+        Ok(daemon)
+    }
+    /// Gives the bus everything is attached to.
+    pub fn bus(&self) -> &EventBus {
+        &self.bus
+    }
+    /// Gives how many outputs, inputs, and schedule rules were set up.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} outputs, {} inputs, {} rules",
+            self.subscriptions.len(),
+            self.buttons.len() + self.sensors.len(),
+            self.scheduler.rules().len()
+        )
+    }
+    /// Reads the sensors and runs the schedule until the token is cancelled
+    /// or a reload is asked for, which is left for the caller to take.
+    pub fn run(&mut self, token: &CancelToken) {
+        let mut next_sample = Instant::now();
+        while token.is_running() && !token.is_reload_requested() {
+            let now = Instant::now();
+            if now >= next_sample {
+                self.sample();
+                next_sample = now + self.interval;
+            }
+            let local = Local::now();
+            self.scheduler.run_due(local, token);
+            let mut wait = next_sample
+                .saturating_duration_since(Instant::now())
+                .min(MAX_SLEEP);
+            if let Some(next) = self.scheduler.rules().iter().map(|(_, next)| *next).min() {
+                if let Ok(until) = (next - local).to_std() {
+                    wait = wait.min(until);
+                }
+            }
+            token.sleep(wait);
+        }
+    }
+    /// Reads every sensor once, publishing what it read.
+    ///
+    /// A sensor that misses a reading is tried again next time instead of
+    /// stopping the daemon.
+    pub fn sample(&mut self) {
+        for (name, sensor) in self.sensors.iter_mut() {
+            let readings = match sensor {
+                Sensor::Dht11(dht11) => dht11.read().map(|reading| {
+                    vec![
+                        (format!("{}.temperature", name), reading.temperature),
+                        (format!("{}.humidity", name), reading.humidity),
+                    ]
+                }),
+                Sensor::Ds18b20(ds18b20) => ds18b20
+                    .read_celsius()
+                    .map(|celsius| vec![(name.clone(), celsius)]),
+                Sensor::Ultrasonic(ultrasonic) => ultrasonic
+                    .distance_cm()
+                    .map(|distance| vec![(name.clone(), distance)]),
+                #[cfg(feature = "analog")]
+                Sensor::Light(sensor) => match self.adc.as_mut() {
+                    Some(adc) => sensor.read(adc).map(|level| vec![(name.clone(), level)]),
+                    None => continue,
+                },
+            };
+            match readings {
+                Ok(readings) => {
+                    for (source, value) in readings {
+                        self.bus.publish(Event::Sample { source, value });
+                    }
+                }
+                Err(e) => warn!(target: "superkitd", "{}: {}", name, e),
+            }
+        }
+    }
+    /// Internal method to have an output follow its set events.
+    fn add_output(&mut self, name: &str, output: Output) -> Result<()> {
+        info!(target: "superkitd", "Output {} is {:?}", name, output);
+        let id = match output {
+            Output::Led { pin } => {
+                let mut led = Led::active_low(pin)?;
+                self.bus.on_level(name, move |level| {
+                    if let Err(e) = led.set_duty_cycle(level) {
+                        error!(target: "superkitd", "{}", e);
+                    }
+                })
+            }
+            Output::Relay { pin } => {
+                let mut relay = Led::new(pin)?;
+                self.bus.on_level(name, move |level| {
+                    if let Err(e) = relay.set(level >= 0.5) {
+                        error!(target: "superkitd", "{}", e);
+                    }
+                })
+            }
+            Output::Rgb {
+                pins: [red, green, blue],
+            } => {
+                let mut rgb = RgbPwm::with_pins(red, green, blue, Polarity::CommonCathode)?;
+                self.bus.on_color(name, move |color| {
+                    if let Err(e) = rgb.set_color(color) {
+                        error!(target: "superkitd", "{}", e);
+                    }
+                })
+            }
+            #[cfg(feature = "lcd")]
+            Output::Lcd => {
+                let mut lcd = Lcd1602::new()?;
+                self.bus.on_text(name, move |text| {
+                    if let Err(e) = lcd.clear().and_then(|_| lcd.print(text)) {
+                        error!(target: "superkitd", "{}", e);
+                    }
+                })
+            }
+            #[cfg(not(feature = "lcd"))]
+            Output::Lcd => return Err(missing(name, "lcd")),
+        };
+        self.subscriptions.push(id);
+        Ok(())
+    }
+    /// Internal method to attach an input to the bus or add it to the
+    /// sensors.
+    fn add_input(&mut self, name: &str, input: Input) -> Result<()> {
+        info!(target: "superkitd", "Input {} is {:?}", name, input);
+        let sensor = match input {
+            Input::Button { pin } => {
+                let mut button = Button::new(pin)?;
+                self.bus.attach_button(name, &mut button)?;
+                self.buttons.push(button);
+                return Ok(());
+            }
+            Input::Motion { pin } => {
+                let mut sensor = Button::with_polarity(pin, false)?;
+                self.bus.attach_motion(name, &mut sensor)?;
+                self.buttons.push(sensor);
+                return Ok(());
+            }
+            Input::Dht11 { pin } => Sensor::Dht11(Dht11::new(pin)?),
+            Input::Ds18b20 => Sensor::Ds18b20(Ds18b20::first()?),
+            Input::Ultrasonic {
+                pins: [trigger, echo],
+            } => Sensor::Ultrasonic(Ultrasonic::new(trigger, echo)?),
+            #[cfg(feature = "analog")]
+            Input::Light { channel } => {
+                if self.adc.is_none() {
+                    self.adc = Some(match self.adc_pins {
+                        Some([cs, clk, dio]) => Adc0832::with_pins(cs, clk, dio)?,
+                        None => Adc0832::new()?,
+                    });
+                }
+                Sensor::Light(LightSensor::new(channel))
+            }
+            #[cfg(not(feature = "analog"))]
+            Input::Light { .. } => return Err(missing(name, "analog")),
+        };
+        self.sensors.push((name.to_string(), sensor));
+        Ok(())
+    }
+}
+
+/// Insure the outputs are let go of before exiting.
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        for id in self.subscriptions.drain(..) {
+            self.bus.unsubscribe(id);
+        }
+    }
+}
+
+/// Internal function for a part that needs a feature that wasn't built.
+#[cfg(any(not(feature = "lcd"), not(feature = "analog")))]
+fn missing(name: &str, feature: &str) -> SuperKitError {
+    SuperKitError::Config(format!(
+        "superkitd part {} needs the {} feature which wasn't built",
+        name, feature
+    ))
+}
@@ -0,0 +1,351 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Web page for controlling and watching the kit from a browser, handy for
+//! showing a class what is going on from a phone.
+//!
+//! The page is served by a small web server built into the program and only
+//! talks to the drivers through an [EventBus].
+//! Its controls publish the set events, like [Event::LevelSet], which the
+//! outputs follow and every event on the bus is shown as it happens.
+//! With the mock backend the LEDs, dot matrix, digits, and LCD decoded by the
+//! [simulator](crate::simulator) are shown too.
+//!
+//! Other pages, like a student's own chart, can open a WebSocket on `/events`
+//! to be sent every event on the bus as JSON the moment it happens instead of
+//! polling `/state`.
+
+use crate::bus::{Event, EventBus};
+use crate::error::{Context, Result, SuperKitError};
+use crate::runner::CancelToken;
+use crate::Color;
+use log::debug;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    io::{Cursor, Read},
+    sync::{mpsc::RecvTimeoutError, Arc, Mutex, MutexGuard, PoisonError},
+    thread::spawn,
+    time::Duration,
+};
+use tiny_http::{Header, Method, Request, Response, Server};
+use tungstenite::{handshake::derive_accept_key, protocol::Role, Message, WebSocket};
+
+/// The page itself, which gets everything else from `/state`.
+const PAGE: &str = include_str!("dashboard.html");
+/// Number of recent events kept for the page.
+const EVENTS: usize = 20;
+/// Time in milliseconds to wait for a request before checking for Ctrl-C.
+const POLL_TIME: u64 = 100;
+/// Most bytes read from the body of a request.
+const MAX_BODY: u64 = 1024;
+
+/// A control on the page and what it was last set to.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", content = "value", rename_all = "lowercase")]
+pub enum Control {
+    /// Slider from 0.0 to 1.0 that publishes [Event::LevelSet].
+    Level(f64),
+    /// Color picker that publishes [Event::ColorSet], the color is kept in
+    /// `#rrggbb` form.
+    Color(String),
+    /// Text box that publishes [Event::TextSet].
+    Text(String),
+}
+
+/// Last thing heard from an input on the bus.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Input {
+    /// On or off for things like buttons and motion sensors.
+    pub active: Option<bool>,
+    /// Last value of things like encoders and thresholds.
+    pub value: Option<f64>,
+}
+
+/// Everything the page shows, which it reads as JSON from `/state`.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct State {
+    pub controls: BTreeMap<String, Control>,
+    pub inputs: BTreeMap<String, Input>,
+    /// The most recent events on the bus, oldest first.
+    pub events: VecDeque<String>,
+    /// Parts decoded from the fake pins.
+    #[cfg(feature = "mock")]
+    pub panel: crate::simulator::Panel,
+}
+
+impl State {
+    /// Internal method to keep up with an event on the bus.
+    fn update(&mut self, event: &Event) {
+        if self.events.len() == EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(format!("{:?}", event));
+        let source = event.source();
+        let value = match event {
+            Event::LevelSet { value, .. } => Control::Level(*value),
+            Event::ColorSet { color, .. } => Control::Color(format!("#{:06x}", color.to_hex())),
+            Event::TextSet { text, .. } => Control::Text(text.clone()),
+            Event::EncoderTurned { value, .. } => {
+                self.input(source).value = Some(*value as f64);
+                return;
+            }
+            Event::Sample { value, .. } => {
+                self.input(source).value = Some(*value);
+                return;
+            }
+            Event::ThresholdCrossed { value, above, .. } => {
+                let input = self.input(source);
+                input.active = Some(*above);
+                input.value = Some(*value);
+                return;
+            }
+            _ => {
+                self.input(source).active = event.is_active();
+                return;
+            }
+        };
+        if let Some(control) = self.controls.get_mut(source) {
+            *control = value;
+        }
+    }
+    /// Internal method to get an input adding it the first time it is heard
+    /// from.
+    fn input(&mut self, source: &str) -> &mut Input {
+        self.inputs.entry(source.to_string()).or_default()
+    }
+}
+
+/// Structure holding what the web page shows and the bus its controls
+/// publish to.
+///
+/// Cloning is cheap and every clone shares the same state.
+#[derive(Clone)]
+pub struct Dashboard {
+    bus: EventBus,
+    state: Arc<Mutex<State>>,
+}
+
+impl Dashboard {
+    /// Starts following every event on the bus.
+    pub fn new(bus: EventBus) -> Self {
+        let state = Arc::new(Mutex::new(State::default()));
+        let s = state.clone();
+        bus.on(
+            |_| true,
+            move |event| {
+                s.lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .update(event)
+            },
+        );
+        #[cfg(feature = "mock")]
+        crate::simulator::follow();
+        Dashboard { bus, state }
+    }
+    /// Adds a slider for an output that follows [EventBus::on_level()].
+    pub fn add_level(&self, name: &str) {
+        self.add(name, Control::Level(0.0));
+    }
+    /// Adds a color picker for an output that follows
+    /// [EventBus::on_color()].
+    pub fn add_color(&self, name: &str) {
+        self.add(name, Control::Color("#000000".to_string()));
+    }
+    /// Adds a text box for an output that follows [EventBus::on_text()].
+    pub fn add_text(&self, name: &str) {
+        self.add(name, Control::Text(String::new()));
+    }
+    /// Gets a copy of what the page would show now.
+    pub fn state(&self) -> State {
+        #[allow(unused_mut)]
+        let mut state = self.lock().clone();
+        #[cfg(feature = "mock")]
+        {
+            state.panel = crate::simulator::panel();
+        }
+        state
+    }
+    /// Sets a control from text the same way the page does, publishing the
+    /// set event for it.
+    ///
+    /// Levels are a number from 0.0 to 1.0 and colors anything
+    /// [Color::parse()] takes.
+    pub fn set(&self, name: &str, value: &str) -> Result<()> {
+        let control = self.lock().controls.get(name).cloned();
+        let source = name.to_string();
+        let event = match control {
+            Some(Control::Level(_)) => {
+                let value: f64 = value
+                    .parse()
+                    .context(format!("Level \"{}\" is not a number", value))?;
+                if !(0.0..=1.0).contains(&value) {
+                    return Err(SuperKitError::InvalidArgument(format!(
+                        "Level {} is outside of 0.0 to 1.0",
+                        value
+                    )));
+                }
+                Event::LevelSet { source, value }
+            }
+            Some(Control::Color(_)) => Event::ColorSet {
+                source,
+                color: Color::parse(value)?,
+            },
+            Some(Control::Text(_)) => Event::TextSet {
+                source,
+                text: value.to_string(),
+            },
+            None => {
+                return Err(SuperKitError::InvalidArgument(format!(
+                    "There is no control called {}",
+                    name
+                )))
+            }
+        };
+        // Published without the state locked as it is updated by the bus.
+        self.bus.publish(event);
+        Ok(())
+    }
+    /// Serves the page on the given address, like `0.0.0.0:8080`, until the
+    /// token is cancelled.
+    ///
+    /// Requests are answered one at a time on the calling thread which is
+    /// plenty for a classroom.
+    pub fn serve(&self, address: &str, token: &CancelToken) -> Result<()> {
+        let server = Server::http(address).map_err(|source| SuperKitError::Http {
+            context: format!("Failed to start web server on {}", address),
+            source,
+        })?;
+        while token.is_running() {
+            if let Some(request) = server
+                .recv_timeout(Duration::from_millis(POLL_TIME))
+                .context("Failed to get web request")?
+            {
+                self.handle(request, token);
+            }
+        }
+        Ok(())
+    }
+    /// Internal method to answer a request from the page.
+    fn handle(&self, mut request: Request, token: &CancelToken) {
+        debug!(target: "dashboard", "{} {}", request.method(), request.url());
+        let method = request.method().clone();
+        let url = request.url().to_string();
+        let response = match (&method, url.as_str()) {
+            (Method::Get, "/") => respond(200, "text/html; charset=utf-8", PAGE.to_string()),
+            (Method::Get, "/events") => return self.stream(request, token),
+            (Method::Get, "/state") => match serde_json::to_string(&self.state()) {
+                Ok(json) => respond(200, "application/json", json),
+                Err(e) => respond(500, "text/plain", e.to_string()),
+            },
+            (Method::Post, url) if url.starts_with("/control/") => {
+                let mut body = String::new();
+                let result = request
+                    .as_reader()
+                    .take(MAX_BODY)
+                    .read_to_string(&mut body)
+                    .context("Failed to read control value")
+                    .and_then(|_| self.set(&url["/control/".len()..], body.trim()));
+                match result {
+                    Ok(()) => respond(204, "text/plain", String::new()),
+                    Err(e) => respond(400, "text/plain", e.to_string()),
+                }
+            }
+            _ => respond(404, "text/plain", "Not found".to_string()),
+        };
+        // The browser going away isn't a problem for the dashboard.
+        if let Err(e) = request.respond(response) {
+            debug!(target: "dashboard", "Failed to answer {}: {}", url, e);
+        }
+    }
+    /// Internal method to turn a request into a WebSocket which is sent every
+    /// event on the bus until the browser goes away or the token is
+    /// cancelled.
+    ///
+    /// Each WebSocket gets its own thread so it doesn't hold up the page.
+    fn stream(&self, request: Request, token: &CancelToken) {
+        let key = request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv("Sec-WebSocket-Key"))
+            .map(|header| derive_accept_key(header.value.as_bytes()));
+        let key = match key {
+            Some(key) => key,
+            None => {
+                let response = respond(400, "text/plain", "Expected a WebSocket".to_string());
+                if let Err(e) = request.respond(response) {
+                    debug!(target: "dashboard", "Failed to answer /events: {}", e);
+                }
+                return;
+            }
+        };
+        let mut response = Response::empty(101);
+        for (field, value) in &[
+            ("Upgrade", "websocket"),
+            ("Connection", "Upgrade"),
+            ("Sec-WebSocket-Accept", key.as_str()),
+        ] {
+            if let Ok(header) = Header::from_bytes(*field, *value) {
+                response.add_header(header);
+            }
+        }
+        // Subscribed before answering so nothing is missed in between.
+        let events = self.bus.receiver();
+        let stream = request.upgrade("websocket", response);
+        let token = token.clone();
+        spawn(move || {
+            let mut socket = WebSocket::from_raw_socket(stream, Role::Server, None);
+            while token.is_running() {
+                let event = match events.recv_timeout(Duration::from_millis(POLL_TIME)) {
+                    Ok(event) => event,
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                };
+                let result = serde_json::to_string(&event)
+                    .map_err(|e| e.to_string())
+                    .and_then(|json| socket.send(Message::Text(json)).map_err(|e| e.to_string()));
+                if let Err(e) = result {
+                    debug!(target: "dashboard", "Stopped streaming events: {}", e);
+                    return;
+                }
+            }
+            let _ = socket.close(None);
+            let _ = socket.flush();
+        });
+    }
+    /// Internal method to add a control.
+    fn add(&self, name: &str, control: Control) {
+        self.lock().controls.insert(name.to_string(), control);
+    }
+    /// Internal method to get at the state.
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Internal function to make a response with a status and content type.
+pub(crate) fn respond(status: u16, content_type: &str, body: String) -> Response<Cursor<Vec<u8>>> {
+    let response = Response::from_string(body).with_status_code(status);
+    match Header::from_bytes("Content-Type", content_type) {
+        Ok(header) => response.with_header(header),
+        Err(()) => response,
+    }
+}
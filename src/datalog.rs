@@ -0,0 +1,448 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Records timestamped sensor samples for experiments that run for hours or
+//! days, like logging the room temperature for a week.
+//!
+//! A [DataLog] writes each sample to every [Store] added to it:
+//!
+//! * [CsvStore] which starts a new file each day and can delete old ones.
+//! * [SqliteStore] which keeps everything in one table, only built with the
+//!   `sqlite` feature.
+//! * [InfluxStore] which sends InfluxDB line protocol over HTTP, only built
+//!   with the `influxdb` feature.
+//!
+//! With a store added, logging the room temperature once a minute for a week
+//! is then one call to
+//! `log.every("temperature", minute, Some(week), &token, || sensor.read_celsius())`.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::runner::CancelToken;
+use chrono::{DateTime, Local, NaiveDate};
+use log::{debug, warn};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// One reading from a sensor and when it was taken.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    pub time: DateTime<Local>,
+    /// What was measured, like `temperature`.
+    pub name: String,
+    pub value: f64,
+}
+
+impl Sample {
+    /// Makes a sample taken now.
+    pub fn now(name: &str, value: f64) -> Self {
+        Sample {
+            time: Local::now(),
+            name: name.to_string(),
+            value,
+        }
+    }
+}
+
+/// Somewhere samples are kept.
+pub trait Store {
+    /// Saves samples, all of them taken at about the same time.
+    fn store(&mut self, samples: &[Sample]) -> Result<()>;
+}
+
+/// Structure that writes samples to every store added to it.
+#[derive(Default)]
+pub struct DataLog {
+    stores: Vec<Box<dyn Store>>,
+}
+
+impl DataLog {
+    /// Makes a log without any stores.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Adds somewhere to keep the samples.
+    pub fn add<S: Store + 'static>(&mut self, store: S) {
+        self.stores.push(Box::new(store));
+    }
+    /// Records readings taken now, like `&[("temperature", 21.5)]`.
+    ///
+    /// Every store is tried even if one fails so an InfluxDB server going
+    /// away doesn't stop the CSV files, the first error is given back.
+    pub fn record(&mut self, readings: &[(&str, f64)]) -> Result<()> {
+        let samples: Vec<Sample> = readings
+            .iter()
+            .map(|(name, value)| Sample::now(name, *value))
+            .collect();
+        let mut result = Ok(());
+        for store in self.stores.iter_mut() {
+            if let Err(e) = store.store(&samples) {
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
+        }
+        result
+    }
+    /// Takes a reading every interval and records it under the name for the
+    /// given length of time, or until the token is cancelled.
+    ///
+    /// Long experiments shouldn't stop over one bad reading or a full disk
+    /// for a moment so errors are logged as warnings and it carries on.
+    pub fn every<F>(
+        &mut self,
+        name: &str,
+        interval: Duration,
+        length: Option<Duration>,
+        token: &CancelToken,
+        mut read: F,
+    ) where
+        F: FnMut() -> Result<f64>,
+    {
+        let start = Instant::now();
+        while token.is_running() {
+            if let Some(length) = length {
+                if start.elapsed() >= length {
+                    break;
+                }
+            }
+            let result = read().and_then(|value| self.record(&[(name, value)]));
+            if let Err(e) = result {
+                warn!(target: "datalog", "{}: {}", name, e);
+            }
+            token.sleep(interval);
+        }
+    }
+}
+
+/// Structure for keeping samples in CSV files, one for each day, named like
+/// `room-2020-11-02.csv`.
+///
+/// Each row is the time in RFC 3339 form, the name, and the value.
+/// Rows are flushed as they are written so little is lost if the power goes.
+pub struct CsvStore {
+    dir: PathBuf,
+    prefix: String,
+    retention: Option<Duration>,
+    current: Option<(NaiveDate, BufWriter<File>)>,
+}
+
+impl CsvStore {
+    /// Keeps files in the directory, which is made if needed, starting their
+    /// names with the prefix.
+    pub fn new<P: AsRef<Path>>(dir: P, prefix: &str) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).context(format!("Failed to make {}", dir.display()))?;
+        Ok(CsvStore {
+            dir,
+            prefix: prefix.to_string(),
+            retention: None,
+            current: None,
+        })
+    }
+    /// Deletes files for days older than this each time a new file is
+    /// started, by default they are all kept.
+    pub fn set_retention(&mut self, retention: Duration) {
+        self.retention = Some(retention);
+    }
+    /// Gives the file samples from the given day go in.
+    pub fn path(&self, date: NaiveDate) -> PathBuf {
+        self.dir
+            .join(format!("{}-{}.csv", self.prefix, date.format("%Y-%m-%d")))
+    }
+    /// Internal method to get the file for a day, starting a new one and
+    /// cleaning out old ones when the day changes.
+    fn file(&mut self, date: NaiveDate) -> Result<&mut BufWriter<File>> {
+        if self.current.as_ref().map(|(day, _)| *day) != Some(date) {
+            let path = self.path(date);
+            let is_new = !path.exists();
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .context(format!("Failed to open {}", path.display()))?;
+            let mut writer = BufWriter::new(file);
+            if is_new {
+                writeln!(writer, "time,name,value")
+                    .context(format!("Failed to write {}", path.display()))?;
+            }
+            debug!(target: "datalog", "Logging to {}", path.display());
+            self.current = Some((date, writer));
+            self.remove_old(date)?;
+        }
+        match self.current.as_mut() {
+            Some((_, writer)) => Ok(writer),
+            None => Err(SuperKitError::Device("CSV file wasn't opened".to_string())),
+        }
+    }
+    /// Internal method to delete files older than the retention.
+    fn remove_old(&self, today: NaiveDate) -> Result<()> {
+        let retention = match self.retention {
+            Some(retention) => retention,
+            None => return Ok(()),
+        };
+        let days = (retention.as_secs() / (24 * 60 * 60)) as i64;
+        let oldest = today - chrono::Duration::days(days);
+        let entries =
+            fs::read_dir(&self.dir).context(format!("Failed to list {}", self.dir.display()))?;
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let date = name
+                .strip_prefix(&format!("{}-", self.prefix))
+                .and_then(|rest| rest.strip_suffix(".csv"))
+                .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok());
+            if let Some(date) = date.filter(|date| *date < oldest) {
+                debug!(target: "datalog", "Removing {} from {}", name, date);
+                fs::remove_file(entry.path())
+                    .context(format!("Failed to remove {}", entry.path().display()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Store for CsvStore {
+    fn store(&mut self, samples: &[Sample]) -> Result<()> {
+        for sample in samples {
+            let writer = self.file(sample.time.naive_local().date())?;
+            // Names are quoted in case one has a comma.
+            writeln!(
+                writer,
+                "{},\"{}\",{}",
+                sample.time.to_rfc3339(),
+                sample.name.replace('"', "\"\""),
+                sample.value
+            )
+            .and_then(|_| writer.flush())
+            .context("Failed to write CSV sample")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use super::{Sample, Store};
+    use crate::error::{Result, SuperKitError};
+    use chrono::Utc;
+    use rusqlite::{params, Connection};
+    use std::{
+        path::Path,
+        time::{Duration, Instant},
+    };
+
+    /// Time between removing old samples.
+    const PRUNE_TIME: Duration = Duration::from_secs(60 * 60);
+    /// Times are kept in UTC in the form SQLite's own date functions use so
+    /// they sort as text.
+    const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+    /// Structure for keeping samples in a SQLite database.
+    ///
+    /// Samples go in a `samples` table with `time`, `name`, and `value`
+    /// columns where time is in UTC, so a week of temperatures can be had with
+    /// `SELECT * FROM samples WHERE name = 'temperature'`.
+    pub struct SqliteStore {
+        connection: Connection,
+        retention: Option<Duration>,
+        pruned: Option<Instant>,
+    }
+
+    impl SqliteStore {
+        /// Opens the database file, making it and the table if needed.
+        pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+            let path = path.as_ref();
+            let connection = Connection::open(path)
+                .and_then(|connection| {
+                    connection.execute_batch(
+                        "CREATE TABLE IF NOT EXISTS samples (
+                             time TEXT NOT NULL,
+                             name TEXT NOT NULL,
+                             value REAL NOT NULL
+                         );
+                         CREATE INDEX IF NOT EXISTS samples_time ON samples (time);",
+                    )?;
+                    Ok(connection)
+                })
+                .map_err(|source| SuperKitError::Database {
+                    context: format!("Failed to open {}", path.display()),
+                    source: Box::new(source),
+                })?;
+            Ok(SqliteStore {
+                connection,
+                retention: None,
+                pruned: None,
+            })
+        }
+        /// Deletes samples older than this about once an hour, by default
+        /// they are all kept.
+        pub fn set_retention(&mut self, retention: Duration) {
+            self.retention = Some(retention);
+        }
+        /// Internal method to delete samples older than the retention.
+        fn prune(&mut self) -> Result<()> {
+            let retention = match self.retention {
+                Some(retention) => retention,
+                None => return Ok(()),
+            };
+            if let Some(pruned) = self.pruned {
+                if pruned.elapsed() < PRUNE_TIME {
+                    return Ok(());
+                }
+            }
+            self.pruned = Some(Instant::now());
+            let retention = chrono::Duration::from_std(retention).map_err(|source| {
+                SuperKitError::Database {
+                    context: "Retention is too long".to_string(),
+                    source: Box::new(source),
+                }
+            })?;
+            let oldest = (Utc::now() - retention).format(TIME_FORMAT).to_string();
+            self.connection
+                .execute("DELETE FROM samples WHERE time < ?1", params![oldest])
+                .map_err(|source| SuperKitError::Database {
+                    context: "Failed to remove old samples".to_string(),
+                    source: Box::new(source),
+                })?;
+            Ok(())
+        }
+    }
+
+    impl Store for SqliteStore {
+        fn store(&mut self, samples: &[Sample]) -> Result<()> {
+            let transaction =
+                self.connection
+                    .transaction()
+                    .map_err(|source| SuperKitError::Database {
+                        context: "Failed to start saving samples".to_string(),
+                        source: Box::new(source),
+                    })?;
+            for sample in samples {
+                let time = sample.time.naive_utc().format(TIME_FORMAT).to_string();
+                transaction
+                    .execute(
+                        "INSERT INTO samples (time, name, value) VALUES (?1, ?2, ?3)",
+                        params![time, sample.name, sample.value],
+                    )
+                    .map_err(|source| SuperKitError::Database {
+                        context: format!("Failed to save {} sample", sample.name),
+                        source: Box::new(source),
+                    })?;
+            }
+            transaction
+                .commit()
+                .map_err(|source| SuperKitError::Database {
+                    context: "Failed to save samples".to_string(),
+                    source: Box::new(source),
+                })?;
+            self.prune()
+        }
+    }
+}
+
+#[cfg(feature = "influxdb")]
+pub use influxdb::InfluxStore;
+
+#[cfg(feature = "influxdb")]
+mod influxdb {
+    use super::{Sample, Store};
+    use crate::error::{Result, SuperKitError};
+    use std::time::Duration;
+
+    /// Time to wait for the server before giving up on a write.
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Structure for sending samples to an InfluxDB server.
+    ///
+    /// Each sample is a point in a measurement with its name and a `value`
+    /// field.
+    /// Retention is left to the database's own retention policy.
+    pub struct InfluxStore {
+        url: String,
+        token: Option<String>,
+    }
+
+    impl InfluxStore {
+        /// Writes to the database through the server's `/write` endpoint,
+        /// like `InfluxStore::new("http://localhost:8086", "superkit")`.
+        ///
+        /// InfluxDB 2 takes the bucket name as the database.
+        pub fn new(server: &str, database: &str) -> Self {
+            InfluxStore {
+                url: format!(
+                    "{}/write?db={}&precision=ms",
+                    server.trim_end_matches('/'),
+                    database
+                ),
+                token: None,
+            }
+        }
+        /// Sends a token with each write for servers that need one.
+        pub fn set_token(&mut self, token: &str) {
+            self.token = Some(token.to_string());
+        }
+    }
+
+    impl Store for InfluxStore {
+        fn store(&mut self, samples: &[Sample]) -> Result<()> {
+            let body: Vec<String> = samples.iter().map(line).collect();
+            let mut request = ureq::post(&self.url);
+            request.timeout(TIMEOUT);
+            if let Some(token) = self.token.as_ref() {
+                request.set("Authorization", &format!("Token {}", token));
+            }
+            let response = request.send_string(&body.join("\n"));
+            if let Some(error) = response.synthetic_error() {
+                return Err(SuperKitError::Http {
+                    context: "Failed to send samples to InfluxDB".to_string(),
+                    source: error.to_string().into(),
+                });
+            }
+            if response.error() {
+                let status = response.status();
+                let text = response.into_string().unwrap_or_default();
+                return Err(SuperKitError::Http {
+                    context: format!("InfluxDB refused samples with status {}", status),
+                    source: text.trim().to_string().into(),
+                });
+            }
+            Ok(())
+        }
+    }
+
+    /// Internal function to turn a sample into line protocol.
+    fn line(sample: &Sample) -> String {
+        // Measurement names need their commas and spaces escaped.
+        let name = sample.name.replace(',', "\\,").replace(' ', "\\ ");
+        format!(
+            "{} value={} {}",
+            name,
+            sample.value,
+            sample.time.timestamp_millis()
+        )
+    }
+}
@@ -0,0 +1,175 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::gpio::{Gpio, IoPin, Mode, PullUpDown};
+use crate::registry::{self, PinClaim};
+use std::{
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+/// Default number of times a failed read is tried again.
+const RETRIES: u8 = 5;
+// Shortest time allowed between reads of the sensor.
+const MIN_INTERVAL: Duration = Duration::from_secs(1);
+// Time the line is held low to wake the sensor up. Needs at least 18ms.
+const START_TIME: Duration = Duration::from_millis(20);
+// Longest time the whole answer can take, it is normally about 4ms.
+const TIMEOUT: Duration = Duration::from_millis(10);
+// High pulses longer than this in µs are 1 bits, 0 bits are about 27µs and 1
+// bits about 70µs.
+const ONE_THRESHOLD: u128 = 48;
+// Pulses in the answer, one response pulse followed by the 40 data bits.
+const PULSES: usize = 41;
+// Our own release of the line is usually seen as a short high pulse as well.
+const MAX_PULSES: usize = PULSES + 1;
+
+/// One reading from a [Dht11].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Reading {
+    /// Temperature in °C.
+    pub temperature: f64,
+    /// Relative humidity in percent.
+    pub humidity: f64,
+}
+
+/// Structure used to model a DHT11 temperature and humidity sensor using its
+/// single wire protocol.
+///
+/// The bits are timed by watching the pin in a tight loop so the odd read
+/// gets messed up when Linux schedules something else at just the wrong time.
+/// Bad reads are caught by the checksum and tried again.
+pub struct Dht11 {
+    pin: IoPin,
+    retries: u8,
+    last_read: Option<Instant>,
+    _claim: PinClaim,
+}
+
+impl Dht11 {
+    /// Gets the GPIO pin the sensor's data line is connected to.
+    pub fn new(pin: u8) -> Result<Self> {
+        let claim = registry::claim("Dht11", &[pin])?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        let mut pin = gpio
+            .get(pin)
+            .context(format!("Failed to get DHT11 pin: {}", pin))?
+            .into_io(Mode::Input);
+        pin.set_pullupdown(PullUpDown::PullUp);
+        Ok(Dht11 {
+            pin,
+            retries: RETRIES,
+            last_read: None,
+            _claim: claim,
+        })
+    }
+    /// Changes how many times a failed read is tried again. The default is 5.
+    pub fn set_retries(&mut self, retries: u8) {
+        self.retries = retries;
+    }
+    /// Reads the temperature and humidity trying again if needed.
+    ///
+    /// The sensor can only be read once a second so this waits if called
+    /// sooner than that.
+    pub fn read(&mut self) -> Result<Reading> {
+        let mut error = SuperKitError::Device("DHT11 was never read".to_string());
+        for _ in 0..=self.retries {
+            match self.read_once() {
+                Ok(reading) => return Ok(reading),
+                Err(e) => error = e,
+            }
+        }
+        Err(SuperKitError::Device(format!(
+            "Failed to read DHT11 after {} tries: {}",
+            u16::from(self.retries) + 1,
+            error
+        )))
+    }
+    /// Reads the sensor once without trying again.
+    pub fn read_once(&mut self) -> Result<Reading> {
+        if let Some(last) = self.last_read {
+            if let Some(wait) = MIN_INTERVAL.checked_sub(last.elapsed()) {
+                sleep(wait);
+            }
+        }
+        self.last_read = Some(Instant::now());
+        let highs = self.capture();
+        if highs.len() < PULSES {
+            return Err(SuperKitError::Device(format!(
+                "DHT11 only sent {} of {} pulses",
+                highs.len(),
+                PULSES
+            )));
+        }
+        let mut bytes = [0u8; 5];
+        for (i, high) in highs[highs.len() - 40..].iter().enumerate() {
+            if high.as_micros() > ONE_THRESHOLD {
+                bytes[i / 8] |= 0x80 >> (i % 8);
+            }
+        }
+        let sum = bytes[..4]
+            .iter()
+            .fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        if sum != bytes[4] {
+            return Err(SuperKitError::Device(format!(
+                "DHT11 checksum {:#04x} doesn't match {:#04x}",
+                bytes[4], sum
+            )));
+        }
+        let humidity = f64::from(bytes[0]) + f64::from(bytes[1]) / 10.0;
+        let mut temperature = f64::from(bytes[2]) + f64::from(bytes[3] & 0x7f) / 10.0;
+        if bytes[3] & 0x80 != 0 {
+            temperature = -temperature;
+        }
+        Ok(Reading {
+            temperature,
+            humidity,
+        })
+    }
+    /// Internal method to wake the sensor and time the high pulses of its
+    /// answer.
+    fn capture(&mut self) -> Vec<Duration> {
+        self.pin.set_mode(Mode::Output);
+        self.pin.set_low();
+        sleep(START_TIME);
+        // Letting go of the line lets the pull-up bring it high and the sensor
+        // answers about 20-40µs later.
+        self.pin.set_mode(Mode::Input);
+        let mut highs = Vec::with_capacity(MAX_PULSES);
+        let start = Instant::now();
+        let mut high = self.pin.is_high();
+        let mut since = start;
+        while highs.len() < MAX_PULSES && start.elapsed() < TIMEOUT {
+            let now_high = self.pin.is_high();
+            if now_high != high {
+                let now = Instant::now();
+                if high {
+                    highs.push(now - since);
+                }
+                high = now_high;
+                since = now;
+            }
+        }
+        highs
+    }
+}
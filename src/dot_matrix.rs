@@ -0,0 +1,425 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Result, SuperKitError};
+use crate::{font, HC595};
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    sync::{Arc, Mutex, PoisonError},
+    thread::{sleep, spawn, JoinHandle},
+    time::Duration,
+};
+
+/// How long each row is lit during a scan in microseconds.
+///
+/// Together with the time needed to shift out the row and column bytes this
+/// gives a full refresh rate of over 400Hz which is flicker free.
+const ROW_TIME: u64 = 200;
+
+/// How drawn pixels are combined with what is already in the framebuffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Blend {
+    /// Turns on drawn pixels and leaves the rest alone.
+    Or,
+    /// Only keeps pixels that are already on and also drawn. Mostly useful with
+    /// sprites to mask out part of the image.
+    And,
+    /// Toggles drawn pixels so drawing the same thing twice erases it.
+    Xor,
+}
+
+/// Pixels for one or more 8x8 matrix modules side by side.
+///
+/// Pixel (0, 0) is the top left corner of the left most module.
+/// Each module is stored as one byte per row with bit x set if pixel x of that
+/// module is on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrameBuffer {
+    modules: usize,
+    /// Rows of all the modules, module number changes fastest.
+    data: Vec<u8>,
+}
+
+impl FrameBuffer {
+    /// Creates a blank framebuffer for the given number of modules.
+    pub fn new(modules: usize) -> Self {
+        FrameBuffer {
+            modules,
+            data: vec![0; 8 * modules],
+        }
+    }
+    /// Number of 8x8 modules across.
+    pub fn modules(&self) -> usize {
+        self.modules
+    }
+    /// Width in pixels.
+    pub fn width(&self) -> usize {
+        8 * self.modules
+    }
+    /// Height in pixels which is always 8.
+    pub fn height(&self) -> usize {
+        8
+    }
+    /// Turns a single pixel on or off.
+    ///
+    /// Pixels outside the framebuffer are ignored so shapes can be drawn partly
+    /// off the edges.
+    pub fn set_pixel(&mut self, x: usize, y: usize, on: bool) {
+        if x >= self.width() || y >= 8 {
+            return;
+        }
+        let byte = &mut self.data[y * self.modules + x / 8];
+        if on {
+            *byte |= 1 << (x % 8);
+        } else {
+            *byte &= !(1 << (x % 8));
+        }
+    }
+    /// Returns if a pixel is on. Pixels outside the framebuffer are always off.
+    pub fn pixel(&self, x: usize, y: usize) -> bool {
+        x < self.width() && y < 8 && self.data[y * self.modules + x / 8] & (1 << (x % 8)) != 0
+    }
+    /// Turns all the pixels off.
+    pub fn clear(&mut self) {
+        for byte in self.data.iter_mut() {
+            *byte = 0;
+        }
+    }
+    /// Returns the rows of a single module.
+    pub fn module_rows(&self, module: usize) -> [u8; 8] {
+        let mut rows = [0u8; 8];
+        for (y, row) in rows.iter_mut().enumerate() {
+            *row = self.data[y * self.modules + module];
+        }
+        rows
+    }
+    /// Replaces the rows of a single module.
+    pub fn set_module_rows(&mut self, module: usize, rows: &[u8; 8]) {
+        for (y, row) in rows.iter().enumerate() {
+            self.data[y * self.modules + module] = *row;
+        }
+    }
+    /// Rotates each module's image a quarter turn clockwise.
+    ///
+    /// Like the other transforms this works on each 8x8 module separately so
+    /// it can be used to correct for how the modules are mounted.
+    pub fn rotate90(&mut self) {
+        self.transform(|rows| {
+            let mut rotated = [0u8; 8];
+            for (y, row) in rotated.iter_mut().enumerate() {
+                for x in 0..8 {
+                    // New pixel (x, y) comes from old pixel (y, 7 - x).
+                    if rows[7 - x] & (1 << y) != 0 {
+                        *row |= 1 << x;
+                    }
+                }
+            }
+            rotated
+        });
+    }
+    /// Rotates each module's image a half turn.
+    pub fn rotate180(&mut self) {
+        self.flip_horizontal();
+        self.flip_vertical();
+    }
+    /// Rotates each module's image a quarter turn counter-clockwise.
+    pub fn rotate270(&mut self) {
+        self.rotate180();
+        self.rotate90();
+    }
+    /// Mirrors each module's image left to right.
+    pub fn flip_horizontal(&mut self) {
+        self.transform(|rows| {
+            let mut flipped = rows;
+            for row in flipped.iter_mut() {
+                *row = row.reverse_bits();
+            }
+            flipped
+        });
+    }
+    /// Mirrors each module's image top to bottom.
+    pub fn flip_vertical(&mut self) {
+        self.transform(|mut rows| {
+            rows.reverse();
+            rows
+        });
+    }
+    /// Turns on all the pixels that were off and turns off the ones that were
+    /// on.
+    pub fn invert(&mut self) {
+        for byte in self.data.iter_mut() {
+            *byte = !*byte;
+        }
+    }
+    /// Draws a straight line between two points, including both ends.
+    ///
+    /// Like the other drawing primitives the coordinates can be negative or
+    /// past the edges and only the part of the shape that fits is drawn.
+    pub fn line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, blend: Blend) {
+        // Bresenham's line algorithm.
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut error = dx + dy;
+        let (mut x, mut y) = (x0, y0);
+        loop {
+            self.blend_pixel(x, y, true, blend);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * error;
+            if e2 >= dy {
+                error += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                error += dx;
+                y += sy;
+            }
+        }
+    }
+    /// Draws a rectangle with its top left corner at (x, y), either just the
+    /// outline or filled in.
+    pub fn rect(&mut self, x: i32, y: i32, width: i32, height: i32, filled: bool, blend: Blend) {
+        for py in y..y + height {
+            for px in x..x + width {
+                let edge = px == x || px == x + width - 1 || py == y || py == y + height - 1;
+                if filled || edge {
+                    self.blend_pixel(px, py, true, blend);
+                }
+            }
+        }
+    }
+    /// Draws an 8 pixel wide sprite with its top left corner at (x, y).
+    ///
+    /// The sprite uses the same format as
+    /// [draw_rows()](MatrixDisplay::draw_rows) but can have any number of rows.
+    /// Unlike the other primitives the off pixels of the sprite are also
+    /// blended so [Blend::And] can be used to mask.
+    pub fn blit(&mut self, x: i32, y: i32, sprite: &[u8], blend: Blend) {
+        for (sy, row) in sprite.iter().enumerate() {
+            for sx in 0..8 {
+                self.blend_pixel(x + sx, y + sy as i32, row & (1 << sx) != 0, blend);
+            }
+        }
+    }
+    /// Internal method used by the drawing primitives to combine one pixel.
+    fn blend_pixel(&mut self, x: i32, y: i32, on: bool, blend: Blend) {
+        if x < 0 || y < 0 {
+            return;
+        }
+        let (x, y) = (x as usize, y as usize);
+        let current = self.pixel(x, y);
+        let new = match blend {
+            Blend::Or => current | on,
+            Blend::And => current & on,
+            Blend::Xor => current ^ on,
+        };
+        self.set_pixel(x, y, new);
+    }
+    /// Internal method to apply a transform to each module's rows.
+    fn transform<F: Fn([u8; 8]) -> [u8; 8]>(&mut self, f: F) {
+        for module in 0..self.modules {
+            let rows = f(self.module_rows(module));
+            self.set_module_rows(module, &rows);
+        }
+    }
+}
+
+/// Common interface for dot matrix displays built on a [FrameBuffer].
+///
+/// Drawing only changes the framebuffer, what is shown is updated by calling
+/// [refresh()](MatrixDisplay::refresh) so half drawn frames are never seen.
+pub trait MatrixDisplay {
+    /// Framebuffer used for drawing.
+    fn frame(&self) -> &FrameBuffer;
+    /// Mutable framebuffer used for drawing.
+    fn frame_mut(&mut self) -> &mut FrameBuffer;
+    /// Shows the current framebuffer on the display.
+    fn refresh(&mut self) -> Result<()>;
+    /// Turns a single pixel on or off.
+    fn set_pixel(&mut self, x: usize, y: usize, on: bool) {
+        self.frame_mut().set_pixel(x, y, on);
+    }
+    /// Returns if a pixel is on.
+    fn pixel(&self, x: usize, y: usize) -> bool {
+        self.frame().pixel(x, y)
+    }
+    /// Turns all the pixels off.
+    fn clear(&mut self) {
+        self.frame_mut().clear();
+    }
+    /// Replaces the first module's image with the given rows.
+    ///
+    /// Each byte is one row, top to bottom, with bit x set if pixel x is on.
+    fn draw_rows(&mut self, rows: &[u8; 8]) {
+        self.frame_mut().set_module_rows(0, rows);
+    }
+    /// Returns a copy of the first module's image in the same format used by
+    /// [draw_rows()](MatrixDisplay::draw_rows).
+    fn rows(&self) -> [u8; 8] {
+        self.frame().module_rows(0)
+    }
+    /// Shows the current framebuffer then waits for the given amount of time.
+    fn refresh_for(&mut self, duration: Duration) -> Result<()> {
+        self.refresh()?;
+        sleep(duration);
+        Ok(())
+    }
+    /// Scrolls text from right to left across the display using the built-in
+    /// 5x7 font.
+    ///
+    /// The speed is how long the text stays in each position before moving one
+    /// column to the left.
+    /// Returns once the text has completely scrolled off the left side.
+    fn scroll_text(&mut self, text: &str, speed: Duration) -> Result<()> {
+        let width = self.frame().width();
+        // Start and end with a blank display so the text slides in and out.
+        let mut columns = vec![0x00; width];
+        columns.extend(font::render(text));
+        columns.extend(vec![0x00; width]);
+        for window in columns.windows(width) {
+            let frame = self.frame_mut();
+            frame.clear();
+            for (x, column) in window.iter().enumerate() {
+                for y in 0..8 {
+                    frame.set_pixel(x, y, column & (1 << y) != 0);
+                }
+            }
+            self.refresh_for(speed)?;
+        }
+        Ok(())
+    }
+}
+
+/// Structure used to model an 8x8 LED dot matrix driven by two chained 74HC595
+/// chips, one for the rows (anodes) and one for the columns (cathodes).
+///
+/// Used in Lesson 12.
+/// More matrices can be added side by side by chaining another 74HC595 for
+/// the columns of each one and wiring their rows in parallel with the first.
+/// The chain goes from the Raspberry Pi to the row chip then the column chip
+/// of each matrix from left to right.
+/// A background thread continuously scans the rows one at a time fast enough
+/// that a whole image looks steady.
+/// Pixel (0, 0) is the row and column driven by bit 0 of each chip.
+pub struct DotMatrix8x8 {
+    frame: FrameBuffer,
+    /// Frame the scanning thread is currently showing.
+    shown: Arc<Mutex<FrameBuffer>>,
+    running: Arc<AtomicBool>,
+    scanner: Option<JoinHandle<()>>,
+}
+
+impl DotMatrix8x8 {
+    /// Takes place of setup() from Python code and starts the scanning thread.
+    pub fn new() -> Result<Self> {
+        Self::with_modules(1)
+    }
+    /// Same as [new()](DotMatrix8x8::new) but for several cascaded matrices.
+    pub fn with_modules(modules: usize) -> Result<Self> {
+        Self::with_hc595(HC595::new()?, modules)
+    }
+    /// Same as [with_modules()](DotMatrix8x8::with_modules) but driven through
+    /// a shift register that has already been setup, like one on other pins.
+    pub fn with_hc595(mut hc595: HC595, modules: usize) -> Result<Self> {
+        if modules == 0 {
+            return Err(SuperKitError::InvalidArgument(
+                "Need at least one matrix module".to_string(),
+            ));
+        }
+        #[cfg(feature = "mock")]
+        crate::simulator::attach(crate::simulator::Part::Matrix {
+            hc595: hc595.pins(),
+            modules,
+        });
+        Self::blank(&mut hc595, modules);
+        let shown = Arc::new(Mutex::new(FrameBuffer::new(modules)));
+        let running = Arc::new(AtomicBool::new(true));
+        let sh = shown.clone();
+        let r = running.clone();
+        let scanner = spawn(move || {
+            while r.load(Ordering::SeqCst) {
+                let frame = sh.lock().unwrap_or_else(PoisonError::into_inner).clone();
+                Self::scan(&mut hc595, &frame);
+            }
+            Self::blank(&mut hc595, modules);
+        });
+        Ok(DotMatrix8x8 {
+            frame: FrameBuffer::new(modules),
+            shown,
+            running,
+            scanner: Some(scanner),
+        })
+    }
+    /// Internal associative method (function) used by the scanning thread for
+    /// one pass over all the rows.
+    fn scan(hc595: &mut HC595, frame: &FrameBuffer) {
+        let images: Vec<[u8; 8]> = (0..frame.modules()).map(|m| frame.module_rows(m)).collect();
+        for row in 0..8 {
+            // Right most matrix is the end of the chain so it goes first.
+            // Columns are active low so invert the row bits for them.
+            for rows in images.iter().rev() {
+                hc595.serial_in(!rows[row]);
+            }
+            hc595.serial_in(1 << row);
+            hc595.parallel_out();
+            sleep(Duration::from_micros(ROW_TIME));
+        }
+    }
+    /// Internal associative method (function) to turn off all the LEDs.
+    fn blank(hc595: &mut HC595, modules: usize) {
+        for _ in 0..modules {
+            hc595.serial_in(0xff);
+        }
+        hc595.serial_in(0x00);
+        hc595.parallel_out();
+    }
+}
+
+impl MatrixDisplay for DotMatrix8x8 {
+    fn frame(&self) -> &FrameBuffer {
+        &self.frame
+    }
+    fn frame_mut(&mut self) -> &mut FrameBuffer {
+        &mut self.frame
+    }
+    /// Returns right away as the scanning thread does the actual work.
+    fn refresh(&mut self) -> Result<()> {
+        self.shown
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone_from(&self.frame);
+        Ok(())
+    }
+}
+
+/// Stop the scanning thread and insure all the LEDs are off before exiting.
+impl Drop for DotMatrix8x8 {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(scanner) = self.scanner.take() {
+            let _ = scanner.join();
+        }
+    }
+}
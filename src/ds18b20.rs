@@ -0,0 +1,160 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+// Where the kernel's w1-gpio and w1-therm drivers put 1-Wire devices.
+const W1_DEVICES: &str = "/sys/bus/w1/devices";
+// Family code of the DS18B20 at the start of each device id.
+const FAMILY: &str = "28-";
+// Bytes of scratchpad memory the sensor sends.
+const SCRATCHPAD: usize = 9;
+
+/// Structure used to model a DS18B20 1-Wire temperature sensor like the
+/// waterproof probes that come with the bigger kits.
+///
+/// Uses the kernel's 1-Wire drivers which need `dtoverlay=w1-gpio` in
+/// /boot/config.txt, with the data line on GPIO 4 by default.
+/// Any number of sensors can share the line and each one is found by its
+/// unique id.
+#[derive(Clone, Debug)]
+pub struct Ds18b20 {
+    id: String,
+    path: PathBuf,
+}
+
+impl Ds18b20 {
+    /// Finds every DS18B20 on the 1-Wire bus.
+    pub fn all() -> Result<Vec<Self>> {
+        let entries = fs::read_dir(W1_DEVICES).context(format!(
+            "Failed to read {}, is the w1-gpio overlay enabled",
+            W1_DEVICES
+        ))?;
+        let mut sensors = Vec::new();
+        for entry in entries {
+            let entry = entry.context("Failed to read 1-Wire device entry")?;
+            let id = entry.file_name().to_string_lossy().into_owned();
+            if id.starts_with(FAMILY) {
+                sensors.push(Self::with_id(&id)?);
+            }
+        }
+        sensors.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(sensors)
+    }
+    /// Gets the first DS18B20 found which is handy when there is only one.
+    pub fn first() -> Result<Self> {
+        match Self::all()?.into_iter().next() {
+            Some(sensor) => Ok(sensor),
+            None => Err(SuperKitError::Device(
+                "No DS18B20 found on the 1-Wire bus".to_string(),
+            )),
+        }
+    }
+    /// Gets the sensor with the given id like `28-0316a279b5ff`.
+    pub fn with_id(id: &str) -> Result<Self> {
+        let path = Path::new(W1_DEVICES).join(id).join("w1_slave");
+        if !path.exists() {
+            return Err(SuperKitError::Device(format!(
+                "No 1-Wire device with id {}",
+                id
+            )));
+        }
+        Ok(Ds18b20 {
+            id: id.to_string(),
+            path,
+        })
+    }
+    /// The sensor's unique id.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+    /// Reads the temperature in °C.
+    ///
+    /// Takes up to 750ms as the sensor does a fresh conversion each time.
+    pub fn read_celsius(&self) -> Result<f64> {
+        let text = fs::read_to_string(&self.path)
+            .context(format!("Failed to read DS18B20 {}", self.id))?;
+        Self::parse(&text).map_err(|e| {
+            SuperKitError::Device(format!("Bad reading from DS18B20 {}: {}", self.id, e))
+        })
+    }
+    /// Reads the temperature in °F.
+    pub fn read_fahrenheit(&self) -> Result<f64> {
+        Ok(self.read_celsius()? * 9.0 / 5.0 + 32.0)
+    }
+    /// Internal associative method (function) to check and decode the
+    /// scratchpad bytes the driver gives.
+    ///
+    /// The first line is the bytes followed by the driver's CRC result, and
+    /// the second line the same bytes followed by the temperature in m°C.
+    fn parse(text: &str) -> Result<f64> {
+        let line = text.lines().next().unwrap_or_default();
+        let bytes = line
+            .split_whitespace()
+            .take(SCRATCHPAD)
+            .map(|hex| u8::from_str_radix(hex, 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .context("Failed to parse scratchpad bytes")?;
+        if bytes.len() != SCRATCHPAD {
+            return Err(SuperKitError::Device(format!(
+                "Only got {} of {} scratchpad bytes",
+                bytes.len(),
+                SCRATCHPAD
+            )));
+        }
+        // The driver checks the CRC too but do it here so glitches on the line
+        // can't get through even if it didn't.
+        if crc8(&bytes[..SCRATCHPAD - 1]) != bytes[SCRATCHPAD - 1] || !line.ends_with("YES") {
+            return Err(SuperKitError::Device("CRC check failed".to_string()));
+        }
+        // All ones with a good CRC is what a sensor that lost power part way
+        // through sends, and 85°C is the power on value before any conversion.
+        let raw = i16::from_le_bytes([bytes[0], bytes[1]]);
+        if bytes.iter().all(|byte| *byte == 0xff) || raw == 0x0550 {
+            return Err(SuperKitError::Device(
+                "Sensor didn't do a conversion, check its power".to_string(),
+            ));
+        }
+        Ok(f64::from(raw) / 16.0)
+    }
+}
+
+/// Dallas/Maxim 1-Wire CRC8 with polynomial x⁸ + x⁵ + x⁴ + 1.
+fn crc8(bytes: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for byte in bytes {
+        let mut byte = *byte;
+        for _ in 0..8 {
+            let mix = (crc ^ byte) & 0x01;
+            crc >>= 1;
+            if mix != 0 {
+                crc ^= 0x8c;
+            }
+            byte >>= 1;
+        }
+    }
+    crc
+}
@@ -0,0 +1,457 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::button::{Button, Gesture};
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::{
+    clock,
+    gpio::{Gpio, InputPin, Level, Trigger},
+};
+use crate::registry::{self, PinClaim};
+use log::{debug, trace};
+use serde::Serialize;
+use std::{
+    sync::atomic::{AtomicI32, Ordering},
+    sync::mpsc::Receiver,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+// Pins used in lesson 8.
+const CLK_PIN: u8 = 18;
+const DT_PIN: u8 = 17;
+const SW_PIN: u8 = 27;
+// Time without a step after which the knob is taken to have stopped.
+const IDLE: Duration = Duration::from_millis(250);
+// How much each new step interval counts towards the smoothed velocity.
+const SMOOTHING: f64 = 0.5;
+// Count change for each move between states indexed by the previous state
+// times four plus the new state where a state is `clk << 1 | dt`.
+// Clockwise goes 00 -> 10 -> 11 -> 01 -> 00. Both pins changing at once is
+// invalid and counts as 0 here with [Decoder::update()] keeping track of them.
+#[rustfmt::skip]
+const TRANSITIONS: [i8; 16] = [
+    0, -1, 1, 0,
+    1, 0, 0, -1,
+    -1, 0, 0, 1,
+    0, 1, -1, 0,
+];
+
+/// Which way the knob was turned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// What happens when the count goes past the end of a range set with
+/// [RotaryEncoder::set_range()].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bounds {
+    /// Stops at the ends like a volume control.
+    Clamp,
+    /// Goes round to the other end like a menu.
+    Wrap,
+}
+
+/// Range the count is kept in.
+#[derive(Clone, Copy, Debug)]
+struct Range {
+    min: i32,
+    max: i32,
+    bounds: Bounds,
+}
+
+impl Range {
+    /// Internal method to bring a value into the range.
+    fn apply(&self, value: i64) -> i32 {
+        match self.bounds {
+            Bounds::Clamp => value.clamp(self.min as i64, self.max as i64) as i32,
+            Bounds::Wrap => {
+                let span = self.max as i64 - self.min as i64 + 1;
+                (self.min as i64 + (value - self.min as i64).rem_euclid(span)) as i32
+            }
+        }
+    }
+}
+
+type TurnCallback = Box<dyn FnMut(Direction, i32) + Send>;
+
+/// Makes steps count for more when the knob is spun fast so a long menu or a
+/// wide range can be crossed quickly while slow turns stay precise.
+#[derive(Clone, Copy, Debug)]
+pub struct Acceleration {
+    /// Speed in steps per second below which each step counts as one.
+    pub threshold: f64,
+    /// Most a single step can count for.
+    pub max_multiplier: i32,
+}
+
+impl Default for Acceleration {
+    fn default() -> Self {
+        Acceleration {
+            threshold: 10.0,
+            max_multiplier: 10,
+        }
+    }
+}
+
+impl Acceleration {
+    /// How much a step counts for at the given speed in steps per second.
+    pub fn multiplier(&self, speed: f64) -> i32 {
+        if speed <= self.threshold {
+            return 1;
+        }
+        ((speed / self.threshold).round() as i32).clamp(1, self.max_multiplier.max(1))
+    }
+}
+
+/// Table based quadrature decoder that uses every edge on both channels.
+///
+/// Gives 4 counts per detent on the usual encoders with the divider used to
+/// bring that back down to one step per detent when wanted.
+/// Both pins changing at the same time means a state was missed so the
+/// direction can't be known and the change is ignored instead of being
+/// miscounted.
+#[derive(Clone, Copy, Debug)]
+pub struct Decoder {
+    state: u8,
+    divider: u8,
+    partial: i16,
+    invalid: u32,
+}
+
+impl Decoder {
+    /// Makes a decoder starting from the current pin levels with one step for
+    /// every divider counts.
+    pub fn new(clk: bool, dt: bool, divider: u8) -> Self {
+        Decoder {
+            state: Self::state(clk, dt),
+            divider: divider.max(1),
+            partial: 0,
+            invalid: 0,
+        }
+    }
+    /// Changes how many counts make one step.
+    pub fn set_divider(&mut self, divider: u8) {
+        self.divider = divider.max(1);
+        self.partial = 0;
+    }
+    /// Number of invalid transitions seen so far.
+    pub fn invalid(&self) -> u32 {
+        self.invalid
+    }
+    /// Feeds in the current pin levels returning a direction when enough
+    /// counts have built up for a step.
+    pub fn update(&mut self, clk: bool, dt: bool) -> Option<Direction> {
+        let state = Self::state(clk, dt);
+        let previous = self.state;
+        self.state = state;
+        if state ^ previous == 0b11 {
+            self.invalid = self.invalid.wrapping_add(1);
+            return None;
+        }
+        // Kept wider than the table so dividers above 127 still work.
+        self.partial += i16::from(TRANSITIONS[(previous << 2 | state) as usize]);
+        let divider = i16::from(self.divider);
+        if self.partial >= divider {
+            self.partial -= divider;
+            Some(Direction::Clockwise)
+        } else if self.partial <= -divider {
+            self.partial += divider;
+            Some(Direction::CounterClockwise)
+        } else {
+            None
+        }
+    }
+    /// Internal associative method (function) to pack the pin levels.
+    fn state(clk: bool, dt: bool) -> u8 {
+        (clk as u8) << 1 | dt as u8
+    }
+}
+
+/// Things shared with the pin interrupts.
+struct Shared {
+    clk: bool,
+    dt: bool,
+    decoder: Decoder,
+    callback: Option<TurnCallback>,
+    acceleration: Option<Acceleration>,
+    range: Option<Range>,
+    last_step: Option<Instant>,
+    velocity: f64,
+}
+
+impl Shared {
+    /// Internal method to run the decoder after either pin changes.
+    fn update(&mut self, count: &AtomicI32) {
+        if let Some(direction) = self.decoder.update(self.clk, self.dt) {
+            let sign = match direction {
+                Direction::Clockwise => 1,
+                Direction::CounterClockwise => -1,
+            };
+            self.track(sign as f64, clock::now());
+            let step = match self.acceleration {
+                Some(acceleration) => sign * acceleration.multiplier(self.velocity.abs()),
+                None => sign,
+            };
+            // Only changed under the lock so there's no race with the load.
+            let old = count.load(Ordering::SeqCst);
+            let new = self.bound(old as i64 + step as i64);
+            if new == old {
+                return;
+            }
+            count.store(new, Ordering::SeqCst);
+            trace!(target: "encoder", "{:?} to {}", direction, new);
+            if let Some(callback) = self.callback.as_mut() {
+                callback(direction, new);
+            }
+        }
+    }
+    /// Internal method to keep a value inside any range set.
+    fn bound(&self, value: i64) -> i32 {
+        match self.range {
+            Some(range) => range.apply(value),
+            None => value as i32,
+        }
+    }
+    /// Internal method to update the smoothed velocity with a step in the
+    /// given direction.
+    fn track(&mut self, sign: f64, now: Instant) {
+        let instant = match self.last_step {
+            Some(last) if now - last < IDLE => sign / (now - last).as_secs_f64().max(1e-6),
+            // Starting from still counts as the slowest speed still moving.
+            _ => sign / IDLE.as_secs_f64(),
+        };
+        // Changing direction starts over instead of averaging through zero.
+        self.velocity = if self.velocity * sign > 0.0 && self.velocity(now) != 0.0 {
+            self.velocity + (instant - self.velocity) * SMOOTHING
+        } else {
+            instant
+        };
+        self.last_step = Some(now);
+    }
+    /// Internal method to get the velocity dropping to zero once the knob has
+    /// been still for a while.
+    fn velocity(&self, now: Instant) -> f64 {
+        match self.last_step {
+            Some(last) if now - last < IDLE => self.velocity,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Structure used to model the rotary encoder from lesson 8.
+///
+/// Interrupts on both pins do the decoding so no steps are missed when the
+/// knob is spun quickly and nothing is burning CPU while it sits idle.
+/// By default every edge is counted which gives 4 counts per detent, use
+/// [RotaryEncoder::set_divider()] to change that.
+pub struct RotaryEncoder {
+    clk: InputPin,
+    dt: InputPin,
+    count: Arc<AtomicI32>,
+    shared: Arc<Mutex<Shared>>,
+    switch: Option<Button>,
+    _claim: PinClaim,
+}
+
+impl RotaryEncoder {
+    /// Gets the lesson's pins including the push switch and starts watching
+    /// them.
+    pub fn new() -> Result<Self> {
+        Self::with_switch(CLK_PIN, DT_PIN, SW_PIN)
+    }
+    /// Same as [RotaryEncoder::with_pins()] plus the push switch on the sw
+    /// pin which is debounced and turned into clicks and long presses.
+    pub fn with_switch(clk: u8, dt: u8, sw: u8) -> Result<Self> {
+        let mut encoder = Self::with_pins(clk, dt)?;
+        encoder.switch = Some(Button::new(sw)?);
+        Ok(encoder)
+    }
+    /// Gets the given clk (A) and dt (B) pins without a push switch and
+    /// starts watching them.
+    pub fn with_pins(clk: u8, dt: u8) -> Result<Self> {
+        debug!(target: "encoder", "clk = {}, dt = {}", clk, dt);
+        let claim = registry::claim("RotaryEncoder", &[clk, dt])?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        let mut clk = gpio.get(clk).context("Failed to get clk pin")?.into_input();
+        let mut dt = gpio.get(dt).context("Failed to get dt pin")?.into_input();
+        let count = Arc::new(AtomicI32::new(0));
+        let shared = Arc::new(Mutex::new(Shared {
+            clk: clk.is_high(),
+            dt: dt.is_high(),
+            decoder: Decoder::new(clk.is_high(), dt.is_high(), 1),
+            callback: None,
+            acceleration: None,
+            range: None,
+            last_step: None,
+            velocity: 0.0,
+        }));
+        // Each interrupt only knows the level of its own pin so the last level
+        // of the other one is kept in the shared state.
+        let (c, s) = (count.clone(), shared.clone());
+        clk.set_async_interrupt(Trigger::Both, move |level: Level| {
+            let mut shared = s.lock().unwrap_or_else(PoisonError::into_inner);
+            shared.clk = level == Level::High;
+            shared.update(&c);
+        })
+        .context("Failed to set clk interrupt")?;
+        let (c, s) = (count.clone(), shared.clone());
+        dt.set_async_interrupt(Trigger::Both, move |level: Level| {
+            let mut shared = s.lock().unwrap_or_else(PoisonError::into_inner);
+            shared.dt = level == Level::High;
+            shared.update(&c);
+        })
+        .context("Failed to set dt interrupt")?;
+        Ok(RotaryEncoder {
+            clk,
+            dt,
+            count,
+            shared,
+            switch: None,
+            _claim: claim,
+        })
+    }
+    /// The count so far where clockwise is up.
+    pub fn value(&self) -> i32 {
+        self.count.load(Ordering::SeqCst)
+    }
+    /// Changes the count like when zeroing it.
+    ///
+    /// A value outside of any range set is brought into it.
+    pub fn set_value(&self, value: i32) {
+        let shared = self.lock();
+        self.count
+            .store(shared.bound(value as i64), Ordering::SeqCst);
+    }
+    /// Keeps the count from min to max inclusive either stopping at the ends
+    /// or wrapping round to the other end.
+    ///
+    /// The current count is brought into the range straight away.
+    pub fn set_range(&mut self, min: i32, max: i32, bounds: Bounds) -> Result<()> {
+        if min > max {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Rotary encoder range min {} is above max {}",
+                min, max
+            )));
+        }
+        let mut shared = self.lock();
+        shared.range = Some(Range { min, max, bounds });
+        let value = shared.bound(self.count.load(Ordering::SeqCst) as i64);
+        self.count.store(value, Ordering::SeqCst);
+        Ok(())
+    }
+    /// Removes any range so the count can go anywhere again.
+    pub fn clear_range(&mut self) {
+        self.lock().range = None;
+    }
+    /// Zeroes the count which is then brought into any range set.
+    pub fn reset(&self) {
+        self.set_value(0);
+    }
+    /// Changes how many counts make one step, 4 gives one step per detent on
+    /// most encoders.
+    pub fn set_divider(&mut self, divider: u8) -> Result<()> {
+        if divider == 0 {
+            return Err(SuperKitError::InvalidArgument(
+                "Rotary encoder divider has to be at least 1".to_string(),
+            ));
+        }
+        self.lock().decoder.set_divider(divider);
+        Ok(())
+    }
+    /// Number of invalid transitions seen which is a sign of a noisy encoder
+    /// or one being turned faster than it can be read.
+    pub fn invalid_transitions(&self) -> u32 {
+        self.lock().decoder.invalid()
+    }
+    /// How fast the knob is turning in steps per second where clockwise is
+    /// positive, or 0.0 when it has stopped.
+    pub fn velocity(&self) -> f64 {
+        self.lock().velocity(clock::now())
+    }
+    /// Turns on making fast turns count for more, or back off with None.
+    ///
+    /// The callback and value see the bigger changes while
+    /// [RotaryEncoder::velocity()] still counts actual steps.
+    pub fn set_acceleration(&mut self, acceleration: Option<Acceleration>) {
+        self.lock().acceleration = acceleration;
+    }
+    /// Calls the given callback with the direction and new count each time the
+    /// knob is turned.
+    ///
+    /// The callback is run on the interrupt thread so should be quick.
+    /// Replaces any callback set before.
+    pub fn on_turn<C>(&mut self, callback: C)
+    where
+        C: FnMut(Direction, i32) + Send + 'static,
+    {
+        self.lock().callback = Some(Box::new(callback));
+    }
+    /// Removes any callback set by [RotaryEncoder::on_turn()].
+    pub fn clear_turn(&mut self) {
+        self.lock().callback = None;
+    }
+    /// The push switch if there is one for changing its debounce or gesture
+    /// times, or polling it directly.
+    pub fn switch(&mut self) -> Option<&mut Button> {
+        self.switch.as_mut()
+    }
+    /// Calls the callback from a background thread for each click, double
+    /// click, and long press of the push switch.
+    ///
+    /// Replaces any callback given before.
+    pub fn on_switch<C>(&mut self, callback: C) -> Result<()>
+    where
+        C: FnMut(Gesture) + Send + 'static,
+    {
+        self.switch
+            .as_mut()
+            .ok_or_else(|| {
+                SuperKitError::InvalidArgument("Rotary encoder has no push switch".to_string())
+            })?
+            .on_gesture(callback)
+    }
+    /// Gives the push switch gestures as they happen through a channel.
+    pub fn switch_gestures(&mut self) -> Result<Receiver<Gesture>> {
+        self.switch
+            .as_mut()
+            .ok_or_else(|| {
+                SuperKitError::InvalidArgument("Rotary encoder has no push switch".to_string())
+            })?
+            .gestures()
+    }
+    /// Internal method to get the shared lock even if a callback panicked.
+    fn lock(&self) -> MutexGuard<'_, Shared> {
+        self.shared.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Insure the interrupts have stopped before exiting.
+impl Drop for RotaryEncoder {
+    fn drop(&mut self) {
+        let _ = self.clk.clear_async_interrupt();
+        let _ = self.dt.clear_async_interrupt();
+    }
+}
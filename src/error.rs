@@ -0,0 +1,272 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Error type used by the whole library.
+//!
+//! Every fallible function in the library returns a [SuperKitError] so
+//! programs can match on what went wrong instead of looking at the message.
+//! It works with `?` in a function returning `anyhow::Result` like the lessons
+//! do.
+
+use crate::hal::{gpio, i2c, pwm, spi};
+use std::{
+    io,
+    num::{ParseFloatError, ParseIntError},
+    string::FromUtf8Error,
+};
+use thiserror::Error;
+
+/// Same as [std::result::Result] with [SuperKitError] as the default error.
+pub type Result<T, E = SuperKitError> = std::result::Result<T, E>;
+
+/// Everything that can go wrong in the library.
+///
+/// The variants wrapping an error from the hardware or the OS say what was
+/// being done when it happened with the original error as the source.
+#[derive(Debug, Error)]
+pub enum SuperKitError {
+    /// Couldn't get at the GPIO at all, usually from not running on a
+    /// Raspberry Pi or not having permission.
+    #[error("Failed to get GPIO instance")]
+    GpioInit(#[source] gpio::Error),
+    /// The pin doesn't exist or something else is already using it.
+    #[error("{context}: pin {pin} is not available")]
+    PinUnavailable {
+        pin: u8,
+        context: String,
+        #[source]
+        source: gpio::Error,
+    },
+    /// Another driver in this program already has the pin.
+    #[error("pin {pin} already claimed by {owner}")]
+    PinClaimed { pin: u8, owner: &'static str },
+    /// Any other GPIO error like setting up an interrupt.
+    #[error("{context}")]
+    Gpio {
+        context: String,
+        #[source]
+        source: gpio::Error,
+    },
+    /// A pin was asked to do hardware PWM which it can't.
+    #[error("Hardware PWM isn't supported on GPIO {pin}, only 12, 13, 18, and 19 have it")]
+    PwmUnsupported { pin: u8 },
+    /// Error from one of the hardware PWM channels.
+    #[error("{context}")]
+    Pwm {
+        context: String,
+        #[source]
+        source: pwm::Error,
+    },
+    /// Error talking to something on the I2C bus.
+    #[error("{context}")]
+    I2c {
+        context: String,
+        #[source]
+        source: i2c::Error,
+    },
+    /// Error talking to something on the SPI bus.
+    #[error("{context}")]
+    Spi {
+        context: String,
+        #[source]
+        source: spi::Error,
+    },
+    /// Error reading or writing a file like the 1-Wire devices or a saved
+    /// calibration.
+    #[error("{context}")]
+    Io {
+        context: String,
+        #[source]
+        source: io::Error,
+    },
+    /// Text like a file, a setting, or a note name couldn't be understood or
+    /// written out in the expected format.
+    #[error("{context}")]
+    Parse {
+        context: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// The web server couldn't be started.
+    #[error("{context}")]
+    Http {
+        context: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// Reading or writing a database failed.
+    #[error("{context}")]
+    Database {
+        context: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// Talking to the MQTT broker failed.
+    #[error("{context}")]
+    Mqtt {
+        context: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// Problem with the config file.
+    #[error("{0}")]
+    Config(String),
+    /// A sensor or other device didn't answer in time.
+    #[error("{0}")]
+    Timeout(String),
+    /// A device answered with something that doesn't make sense, like a bad
+    /// checksum, or wasn't found at all.
+    #[error("{0}")]
+    Device(String),
+    /// Something given to the library is out of range or not understood.
+    #[error("{0}")]
+    InvalidArgument(String),
+    /// A recorded session didn't match what was done this time.
+    #[error("{0}")]
+    Mismatch(String),
+    /// Ctrl-C and the other signals couldn't be hooked up.
+    #[error("Error setting signal handlers")]
+    Signals(#[source] io::Error),
+    /// A background worker thread panicked.
+    #[error("{0} worker thread panicked")]
+    ThreadPanic(String),
+}
+
+/// Same idea as `anyhow::Context` for adding what was being done to the errors
+/// that come from rppal and the OS.
+pub(crate) trait Context<T> {
+    fn context<C: Into<String>>(self, context: C) -> Result<T>;
+    fn with_context<C: Into<String>, F: FnOnce() -> C>(self, f: F) -> Result<T>;
+}
+
+impl<T, E: Wrap> Context<T> for Result<T, E> {
+    fn context<C: Into<String>>(self, context: C) -> Result<T> {
+        self.map_err(|source| source.wrap(context.into()))
+    }
+    fn with_context<C: Into<String>, F: FnOnce() -> C>(self, f: F) -> Result<T> {
+        self.map_err(|source| source.wrap(f().into()))
+    }
+}
+
+/// Internal trait for the errors [Context] knows which variant to put in.
+pub(crate) trait Wrap {
+    fn wrap(self, context: String) -> SuperKitError;
+}
+
+impl Wrap for gpio::Error {
+    fn wrap(self, context: String) -> SuperKitError {
+        // The mock backend doesn't have any other GPIO errors.
+        #[allow(unreachable_patterns)]
+        match self {
+            gpio::Error::PinNotAvailable(pin) => SuperKitError::PinUnavailable {
+                pin,
+                context,
+                source: self,
+            },
+            _ => SuperKitError::Gpio {
+                context,
+                source: self,
+            },
+        }
+    }
+}
+
+impl Wrap for pwm::Error {
+    fn wrap(self, context: String) -> SuperKitError {
+        SuperKitError::Pwm {
+            context,
+            source: self,
+        }
+    }
+}
+
+impl Wrap for i2c::Error {
+    fn wrap(self, context: String) -> SuperKitError {
+        SuperKitError::I2c {
+            context,
+            source: self,
+        }
+    }
+}
+
+impl Wrap for spi::Error {
+    fn wrap(self, context: String) -> SuperKitError {
+        SuperKitError::Spi {
+            context,
+            source: self,
+        }
+    }
+}
+
+impl Wrap for io::Error {
+    fn wrap(self, context: String) -> SuperKitError {
+        SuperKitError::Io {
+            context,
+            source: self,
+        }
+    }
+}
+
+impl Wrap for ParseIntError {
+    fn wrap(self, context: String) -> SuperKitError {
+        SuperKitError::Parse {
+            context,
+            source: Box::new(self),
+        }
+    }
+}
+
+impl Wrap for ParseFloatError {
+    fn wrap(self, context: String) -> SuperKitError {
+        SuperKitError::Parse {
+            context,
+            source: Box::new(self),
+        }
+    }
+}
+
+impl Wrap for FromUtf8Error {
+    fn wrap(self, context: String) -> SuperKitError {
+        SuperKitError::Parse {
+            context,
+            source: Box::new(self),
+        }
+    }
+}
+
+impl Wrap for toml::de::Error {
+    fn wrap(self, context: String) -> SuperKitError {
+        SuperKitError::Parse {
+            context,
+            source: Box::new(self),
+        }
+    }
+}
+
+impl Wrap for toml::ser::Error {
+    fn wrap(self, context: String) -> SuperKitError {
+        SuperKitError::Parse {
+            context,
+            source: Box::new(self),
+        }
+    }
+}
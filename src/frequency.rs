@@ -0,0 +1,297 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::gpio::{Gpio, InputPin, Level, Trigger};
+use crate::registry::{self, PinClaim};
+use std::{
+    collections::VecDeque,
+    sync::atomic::{AtomicU64, Ordering},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+// Pin used in lesson 9.
+const SIG_PIN: u8 = 17;
+/// Default time edges are counted over for each measurement.
+const GATE_TIME: Duration = Duration::from_secs(1);
+
+/// Result of counting edges over a gate time.
+#[derive(Clone, Copy, Debug)]
+pub struct Frequency {
+    /// Rising edges seen during the gate.
+    pub edges: u64,
+    /// How long the gate was actually open.
+    pub gate: Duration,
+    /// Average time the signal was high during the gate.
+    pub high: Option<Duration>,
+    /// Average time the signal was low during the gate.
+    pub low: Option<Duration>,
+}
+
+impl Frequency {
+    /// The frequency in Hz.
+    pub fn hz(&self) -> f64 {
+        let seconds = self.gate.as_secs_f64();
+        if seconds > 0.0 {
+            self.edges as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+    /// Time for one cycle or None if there were no edges.
+    pub fn period(&self) -> Option<Duration> {
+        if self.edges == 0 {
+            None
+        } else {
+            Some(self.gate / self.edges.min(u32::MAX as u64) as u32)
+        }
+    }
+    /// Fraction of each cycle the signal is high from 0.0 to 1.0 or None if a
+    /// full cycle wasn't seen.
+    pub fn duty_cycle(&self) -> Option<f64> {
+        let high = self.high?.as_secs_f64();
+        let low = self.low?.as_secs_f64();
+        if high + low > 0.0 {
+            Some(high / (high + low))
+        } else {
+            None
+        }
+    }
+}
+
+/// Min, max, mean, and standard deviation of the samples in a [Stats] window.
+#[derive(Clone, Copy, Debug)]
+pub struct Summary {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+/// Rolling statistics over the last so many samples like frequency readings
+/// so drift over time or temperature can be seen.
+#[derive(Clone, Debug)]
+pub struct Stats {
+    window: usize,
+    samples: VecDeque<f64>,
+}
+
+impl Stats {
+    /// Makes an empty set of stats that keeps the given number of samples.
+    pub fn new(window: usize) -> Self {
+        let window = window.max(1);
+        Stats {
+            window,
+            samples: VecDeque::with_capacity(window),
+        }
+    }
+    /// Adds a sample dropping the oldest one if the window is full.
+    pub fn push(&mut self, sample: f64) {
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+    /// Number of samples currently in the window.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+    /// Checks if there are no samples yet.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+    /// Drops all the samples.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+    /// Works out the stats for the samples in the window or None if there
+    /// aren't any.
+    pub fn summary(&self) -> Option<Summary> {
+        let count = self.samples.len();
+        if count == 0 {
+            return None;
+        }
+        let mut min = f64::INFINITY;
+        let mut max = f64::NEG_INFINITY;
+        let mut sum = 0.0;
+        for sample in self.samples.iter() {
+            min = min.min(*sample);
+            max = max.max(*sample);
+            sum += sample;
+        }
+        let mean = sum / count as f64;
+        // Population standard deviation worked out around the mean which
+        // avoids the rounding trouble of the sum of squares shortcut.
+        let variance = self
+            .samples
+            .iter()
+            .map(|sample| (sample - mean).powi(2))
+            .sum::<f64>()
+            / count as f64;
+        Some(Summary {
+            count,
+            min,
+            max,
+            mean,
+            std_dev: variance.sqrt(),
+        })
+    }
+}
+
+/// Pulse timing shared with the interrupt.
+#[derive(Debug, Default)]
+struct Pulses {
+    /// Level and time of the last edge.
+    last: Option<(bool, Instant)>,
+    high_total: Duration,
+    high_count: u32,
+    low_total: Duration,
+    low_count: u32,
+}
+
+impl Pulses {
+    /// Internal method to time the pulse that just ended.
+    fn edge(&mut self, high: bool, now: Instant) {
+        if let Some((was_high, at)) = self.last {
+            // Two edges the same way means one was missed so the time can't be
+            // trusted.
+            if was_high != high {
+                if was_high {
+                    self.high_total += now - at;
+                    self.high_count += 1;
+                } else {
+                    self.low_total += now - at;
+                    self.low_count += 1;
+                }
+            }
+        }
+        self.last = Some((high, now));
+    }
+    /// Internal method to start timing over for a new gate.
+    fn restart(&mut self) {
+        let last = self.last;
+        *self = Pulses::default();
+        self.last = last;
+    }
+    /// Internal method to get an average pulse width.
+    fn average(total: Duration, count: u32) -> Option<Duration> {
+        if count == 0 {
+            None
+        } else {
+            Some(total / count)
+        }
+    }
+}
+
+/// Measures the frequency of a square wave like the output of the 555 timer in
+/// lesson 9 by counting rising edges over a gate time.
+///
+/// Both edges are timed as well so the high and low times, and from them the
+/// duty cycle, can be checked against what the RC values should give.
+/// Edges are counted by an interrupt so nothing is missed while the program is
+/// busy elsewhere. Good up to a few kHz, above that the interrupts can't keep
+/// up and the reading will be low.
+pub struct FrequencyCounter {
+    pin: InputPin,
+    edges: Arc<AtomicU64>,
+    pulses: Arc<Mutex<Pulses>>,
+    gate: Duration,
+    _claim: PinClaim,
+}
+
+impl FrequencyCounter {
+    /// Gets the lesson's pin and starts counting.
+    pub fn new() -> Result<Self> {
+        Self::with_pin(SIG_PIN)
+    }
+    /// Gets the given pin with its pull-up turned on and starts counting.
+    pub fn with_pin(pin: u8) -> Result<Self> {
+        let claim = registry::claim("FrequencyCounter", &[pin])?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        let mut pin = gpio
+            .get(pin)
+            .context(format!("Failed to get signal pin: {}", pin))?
+            .into_input_pullup();
+        let edges = Arc::new(AtomicU64::new(0));
+        let pulses = Arc::new(Mutex::new(Pulses::default()));
+        let (e, p) = (edges.clone(), pulses.clone());
+        pin.set_async_interrupt(Trigger::Both, move |level: Level| {
+            let now = Instant::now();
+            let high = level == Level::High;
+            if high {
+                e.fetch_add(1, Ordering::SeqCst);
+            }
+            p.lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .edge(high, now);
+        })
+        .context("Failed to set signal interrupt")?;
+        Ok(FrequencyCounter {
+            pin,
+            edges,
+            pulses,
+            gate: GATE_TIME,
+            _claim: claim,
+        })
+    }
+    /// Total rising edges seen so far which wraps back to zero on overflow.
+    pub fn count(&self) -> u64 {
+        self.edges.load(Ordering::SeqCst)
+    }
+    /// Changes how long edges are counted for each measurement.
+    ///
+    /// Longer gives more resolution with 1 second, the default, giving
+    /// readings to the nearest Hz.
+    pub fn set_gate_time(&mut self, gate: Duration) {
+        self.gate = gate;
+    }
+    /// Counts edges for the gate time and gives the result.
+    pub fn measure(&self) -> Frequency {
+        let start = Instant::now();
+        let before = self.count();
+        self.lock().restart();
+        sleep(self.gate);
+        let after = self.count();
+        let pulses = self.lock();
+        Frequency {
+            // Wrapping keeps this right even if the count overflowed.
+            edges: after.wrapping_sub(before),
+            gate: start.elapsed(),
+            high: Pulses::average(pulses.high_total, pulses.high_count),
+            low: Pulses::average(pulses.low_total, pulses.low_count),
+        }
+    }
+    /// Internal method to get the pulse lock even if the interrupt panicked.
+    fn lock(&self) -> MutexGuard<'_, Pulses> {
+        self.pulses.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Insure the interrupt has stopped before exiting.
+impl Drop for FrequencyCounter {
+    fn drop(&mut self) {
+        let _ = self.pin.clear_async_interrupt();
+    }
+}
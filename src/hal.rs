@@ -0,0 +1,60 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Hardware abstraction layer the rest of the library talks to instead of
+//! using rppal directly.
+//!
+//! Normally this is just rppal but building with the `mock` feature swaps in
+//! in-memory fakes from [mock] that remember everything done to them, so the
+//! lessons and tests can be run on any computer without a Raspberry Pi.
+//! Building with the `cdev` feature puts [gpio] in front of rppal so the
+//! Linux GPIO character device can be picked instead when the program starts.
+//! Drivers that time things read [clock::now()] so the mock backend can stop
+//! and move time for tests.
+
+#[cfg(feature = "mock")]
+pub mod mock;
+
+#[cfg(feature = "mock")]
+pub use mock::{clock, gpio, i2c, pwm, spi, system};
+#[cfg(all(not(feature = "mock"), feature = "cdev"))]
+pub mod gpio;
+
+#[cfg(all(not(feature = "mock"), not(feature = "cdev")))]
+pub use rppal::{gpio, i2c, pwm, spi, system};
+#[cfg(all(not(feature = "mock"), feature = "cdev"))]
+pub use rppal::{i2c, pwm, spi, system};
+
+#[cfg(not(feature = "mock"))]
+pub mod clock {
+    //! Real time as the drivers see it.
+
+    use std::time::Instant;
+
+    /// Gets the time now.
+    pub fn now() -> Instant {
+        Instant::now()
+    }
+}
+
+#[cfg(not(any(feature = "hardware", feature = "mock")))]
+compile_error!("Either the hardware (default) or the mock feature is needed");
@@ -0,0 +1,820 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! GPIO through either rppal or the Linux GPIO character device, picked when
+//! the program starts.
+//!
+//! rppal talks to `/dev/gpiomem` which is the default as it is the fastest
+//! and can do everything the lessons need.
+//! The character device, `/dev/gpiochip0`, is for systems where
+//! `/dev/gpiomem` is locked down or where each pin should be given out with
+//! its own udev rules so programs can run without root.
+//! It can't set the pull-up or pull-down resistors, those need to be set in
+//! `/boot/config.txt` like `gpio=17=ip,pu`, and its software PWM runs on a
+//! thread for each pin.
+//!
+//! Pick it with `--gpio=cdev` on any lesson, `--gpio=cdev:/dev/gpiochip4` for
+//! another chip, or the `SUPERKIT_GPIO` environment variable.
+//! Only built with the `cdev` feature.
+
+use gpio_cdev::{
+    Chip, EventRequestFlags, EventType, Line, LineEventHandle, LineHandle, LineRequestFlags,
+};
+use lazy_static::lazy_static;
+use log::{debug, error, warn};
+use nix::poll::{poll, PollFd, PollFlags};
+use std::{
+    collections::BTreeSet,
+    env, fmt,
+    os::unix::io::AsRawFd,
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    thread::{sleep, spawn, JoinHandle},
+    time::Duration,
+};
+use thiserror::Error;
+
+pub use rppal::gpio::{Level, Mode, PullUpDown, Trigger};
+
+/// Environment variable the backend is read from when it isn't set with
+/// [set_backend()].
+pub const BACKEND_VAR: &str = "SUPERKIT_GPIO";
+/// Chip used by `cdev` without a path.
+const CHIP: &str = "/dev/gpiochip0";
+/// Name the character device shows as using the pins.
+const CONSUMER: &str = "superkit";
+/// Time in milliseconds between checks for being stopped while waiting for
+/// an interrupt.
+const POLL_TIME: i32 = 100;
+
+lazy_static! {
+    static ref BACKEND: Mutex<Option<Backend>> = Mutex::new(None);
+    /// Pins handed out through the character device.
+    static ref CLAIMED: Mutex<BTreeSet<u8>> = Mutex::new(BTreeSet::new());
+}
+
+/// Which way GPIO is done.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// rppal through `/dev/gpiomem`.
+    Rppal,
+    /// The Linux GPIO character device at the path.
+    Cdev(PathBuf),
+}
+
+/// Takes `rppal`, `cdev`, or `cdev:<path>`.
+impl FromStr for Backend {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        match text.trim() {
+            "rppal" => Ok(Backend::Rppal),
+            "cdev" => Ok(Backend::Cdev(PathBuf::from(CHIP))),
+            text => match text.strip_prefix("cdev:") {
+                Some(path) if !path.is_empty() => Ok(Backend::Cdev(PathBuf::from(path))),
+                _ => Err(Error::UnknownBackend(text.to_string())),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backend::Rppal => write!(f, "rppal"),
+            Backend::Cdev(path) => write!(f, "cdev:{}", path.display()),
+        }
+    }
+}
+
+/// Picks the backend used by every [Gpio::new()] after this.
+pub fn set_backend(backend: Backend) {
+    debug!(target: "gpio", "Using {}", backend);
+    *BACKEND.lock().unwrap_or_else(PoisonError::into_inner) = Some(backend);
+}
+
+/// Gives the backend that was set, or the one from the environment, or rppal.
+pub fn backend() -> Result<Backend> {
+    if let Some(backend) = BACKEND
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+    {
+        return Ok(backend);
+    }
+    match env::var(BACKEND_VAR) {
+        Ok(text) => text.parse(),
+        Err(_) => Ok(Backend::Rppal),
+    }
+}
+
+/// Error from either backend.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The pin doesn't exist or is already in use.
+    #[error("Pin {0} is not available")]
+    PinNotAvailable(u8),
+    /// The backend named isn't one of `rppal`, `cdev`, or `cdev:<path>`.
+    #[error("GPIO backend \"{0}\" isn't rppal, cdev, or cdev:<path>")]
+    UnknownBackend(String),
+    #[error(transparent)]
+    Rppal(rppal::gpio::Error),
+    #[error(transparent)]
+    Cdev(#[from] gpio_cdev::Error),
+}
+
+impl From<rppal::gpio::Error> for Error {
+    fn from(error: rppal::gpio::Error) -> Self {
+        match error {
+            rppal::gpio::Error::PinNotAvailable(pin) => Error::PinNotAvailable(pin),
+            error => Error::Rppal(error),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Same as rppal's `Gpio` but for whichever backend was picked.
+pub struct Gpio {
+    inner: GpioInner,
+}
+
+enum GpioInner {
+    Rppal(rppal::gpio::Gpio),
+    Cdev(Arc<Mutex<Chip>>),
+}
+
+impl Gpio {
+    pub fn new() -> Result<Gpio> {
+        let inner = match backend()? {
+            Backend::Rppal => GpioInner::Rppal(rppal::gpio::Gpio::new()?),
+            Backend::Cdev(path) => GpioInner::Cdev(Arc::new(Mutex::new(Chip::new(path)?))),
+        };
+        Ok(Gpio { inner })
+    }
+    pub fn get(&self, pin: u8) -> Result<Pin> {
+        match &self.inner {
+            GpioInner::Rppal(gpio) => Ok(Pin::Rppal(gpio.get(pin)?)),
+            GpioInner::Cdev(chip) => {
+                let line = chip
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .get_line(u32::from(pin))
+                    .map_err(|_| Error::PinNotAvailable(pin))?;
+                // Something else, like the kernel or another program, has it.
+                if line.info()?.is_used() {
+                    return Err(Error::PinNotAvailable(pin));
+                }
+                let claim = Claim::new(pin)?;
+                Ok(Pin::Cdev(CdevPin { claim, line }))
+            }
+        }
+    }
+}
+
+/// Internal structure holding a character device pin until it is dropped.
+struct Claim(u8);
+
+impl Claim {
+    fn new(pin: u8) -> Result<Self> {
+        if !claimed().insert(pin) {
+            return Err(Error::PinNotAvailable(pin));
+        }
+        Ok(Claim(pin))
+    }
+}
+
+/// Insure the pin can be had again once it is dropped.
+impl Drop for Claim {
+    fn drop(&mut self) {
+        claimed().remove(&self.0);
+    }
+}
+
+/// Internal function to get at the claimed pins.
+fn claimed() -> MutexGuard<'static, BTreeSet<u8>> {
+    CLAIMED.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Internal function to request a line, logging any failure as the rppal
+/// methods this stands in for can't fail.
+fn request(line: &Line, flags: LineRequestFlags, level: Level) -> Option<LineHandle> {
+    match line.request(flags, value(level), CONSUMER) {
+        Ok(handle) => Some(handle),
+        Err(e) => {
+            error!(target: "gpio", "Failed to request GPIO {}: {}", line.offset(), e);
+            None
+        }
+    }
+}
+
+/// Internal function to turn a level into a line value.
+fn value(level: Level) -> u8 {
+    match level {
+        Level::Low => 0,
+        Level::High => 1,
+    }
+}
+
+/// Internal function to turn a line value into a level.
+fn level(value: std::result::Result<u8, gpio_cdev::Error>) -> Level {
+    match value {
+        Ok(0) => Level::Low,
+        Ok(_) => Level::High,
+        Err(e) => {
+            debug!(target: "gpio", "Failed to read line: {}", e);
+            Level::Low
+        }
+    }
+}
+
+/// Internal function to warn that a pull resistor can't be set.
+fn warn_pull(pin: u8, pull: &str) {
+    warn!(
+        target: "gpio",
+        "The character device can't pull GPIO {} {}, add gpio={}=ip,p{} to /boot/config.txt",
+        pin,
+        pull,
+        pin,
+        &pull[..1]
+    );
+}
+
+/// Internal structure for a pin from the character device that hasn't been
+/// requested as anything yet.
+pub struct CdevPin {
+    claim: Claim,
+    line: Line,
+}
+
+pub enum Pin {
+    Rppal(rppal::gpio::Pin),
+    Cdev(CdevPin),
+}
+
+impl Pin {
+    pub fn pin(&self) -> u8 {
+        match self {
+            Pin::Rppal(pin) => pin.pin(),
+            Pin::Cdev(CdevPin { claim, .. }) => claim.0,
+        }
+    }
+    pub fn read(&self) -> Level {
+        match self {
+            Pin::Rppal(pin) => pin.read(),
+            Pin::Cdev(CdevPin { line, .. }) => {
+                match line.request(LineRequestFlags::INPUT, 0, CONSUMER) {
+                    Ok(handle) => level(handle.get_value()),
+                    Err(e) => level(Err(e)),
+                }
+            }
+        }
+    }
+    pub fn is_low(&self) -> bool {
+        self.read() == Level::Low
+    }
+    pub fn is_high(&self) -> bool {
+        self.read() == Level::High
+    }
+    pub fn into_input(self) -> InputPin {
+        match self {
+            Pin::Rppal(pin) => InputPin::Rppal(pin.into_input()),
+            Pin::Cdev(CdevPin { claim, line }) => InputPin::Cdev(CdevInput::new(claim, line)),
+        }
+    }
+    pub fn into_input_pullup(self) -> InputPin {
+        match self {
+            Pin::Rppal(pin) => InputPin::Rppal(pin.into_input_pullup()),
+            Pin::Cdev(CdevPin { claim, line }) => {
+                warn_pull(claim.0, "up");
+                InputPin::Cdev(CdevInput::new(claim, line))
+            }
+        }
+    }
+    pub fn into_input_pulldown(self) -> InputPin {
+        match self {
+            Pin::Rppal(pin) => InputPin::Rppal(pin.into_input_pulldown()),
+            Pin::Cdev(CdevPin { claim, line }) => {
+                warn_pull(claim.0, "down");
+                InputPin::Cdev(CdevInput::new(claim, line))
+            }
+        }
+    }
+    pub fn into_output(self) -> OutputPin {
+        match self {
+            Pin::Rppal(pin) => OutputPin::Rppal(pin.into_output()),
+            Pin::Cdev(CdevPin { claim, line }) => {
+                let handle = request(&line, LineRequestFlags::OUTPUT, Level::Low).map(Arc::new);
+                OutputPin::Cdev(CdevOutput {
+                    claim,
+                    handle,
+                    level: Level::Low,
+                    pwm: None,
+                })
+            }
+        }
+    }
+    pub fn into_io(self, mode: Mode) -> IoPin {
+        match self {
+            Pin::Rppal(pin) => IoPin::Rppal(pin.into_io(mode)),
+            Pin::Cdev(CdevPin { claim, line }) => {
+                let mut pin = CdevIo {
+                    claim,
+                    line,
+                    handle: None,
+                    output: false,
+                    level: Level::Low,
+                };
+                pin.set_mode(mode);
+                IoPin::Cdev(pin)
+            }
+        }
+    }
+}
+
+/// What an input line was requested as.
+enum Request {
+    None,
+    Handle(LineHandle),
+    Events(LineEventHandle),
+}
+
+/// Internal structure for an input through the character device.
+pub struct CdevInput {
+    claim: Claim,
+    line: Line,
+    request: Arc<Mutex<Request>>,
+    callback: Option<(Arc<AtomicBool>, JoinHandle<()>)>,
+}
+
+impl CdevInput {
+    fn new(claim: Claim, line: Line) -> Self {
+        let request = match request(&line, LineRequestFlags::INPUT, Level::Low) {
+            Some(handle) => Request::Handle(handle),
+            None => Request::None,
+        };
+        CdevInput {
+            claim,
+            line,
+            request: Arc::new(Mutex::new(request)),
+            callback: None,
+        }
+    }
+    fn read(&self) -> Level {
+        match &*self.lock() {
+            Request::Handle(handle) => level(handle.get_value()),
+            Request::Events(events) => level(events.get_value()),
+            Request::None => Level::Low,
+        }
+    }
+    fn set_interrupt(&mut self, trigger: Trigger) -> Result<()> {
+        self.clear_callback();
+        let flags = match trigger {
+            Trigger::Disabled => return self.clear_interrupt(),
+            Trigger::RisingEdge => EventRequestFlags::RISING_EDGE,
+            Trigger::FallingEdge => EventRequestFlags::FALLING_EDGE,
+            Trigger::Both => EventRequestFlags::BOTH_EDGES,
+        };
+        let mut request = self.lock();
+        // The line has to be given back before it can be asked for again.
+        *request = Request::None;
+        *request = Request::Events(self.line.events(LineRequestFlags::INPUT, flags, CONSUMER)?);
+        Ok(())
+    }
+    fn clear_interrupt(&mut self) -> Result<()> {
+        self.clear_callback();
+        let mut request = self.lock();
+        *request = Request::None;
+        *request = Request::Handle(self.line.request(LineRequestFlags::INPUT, 0, CONSUMER)?);
+        Ok(())
+    }
+    fn poll_interrupt(&mut self, reset: bool, timeout: Option<Duration>) -> Result<Option<Level>> {
+        if reset {
+            while next_event(&self.request, 0)?.is_some() {}
+        }
+        let timeout = match timeout {
+            Some(timeout) => timeout.as_millis().min(i32::MAX as u128) as i32,
+            None => -1,
+        };
+        next_event(&self.request, timeout)
+    }
+    fn set_async_interrupt<C>(&mut self, trigger: Trigger, mut callback: C) -> Result<()>
+    where
+        C: FnMut(Level) + Send + 'static,
+    {
+        self.set_interrupt(trigger)?;
+        let running = Arc::new(AtomicBool::new(true));
+        let worker = spawn({
+            let (request, running) = (self.request.clone(), running.clone());
+            move || {
+                while running.load(Ordering::SeqCst) {
+                    match next_event(&request, POLL_TIME) {
+                        Ok(Some(level)) => callback(level),
+                        Ok(None) => {}
+                        Err(e) => {
+                            error!(target: "gpio", "Stopped watching for interrupts: {}", e);
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+        self.callback = Some((running, worker));
+        Ok(())
+    }
+    fn clear_async_interrupt(&mut self) -> Result<()> {
+        self.clear_interrupt()
+    }
+    /// Internal method to stop the thread running an interrupt callback.
+    fn clear_callback(&mut self) {
+        if let Some((running, worker)) = self.callback.take() {
+            running.store(false, Ordering::SeqCst);
+            let _ = worker.join();
+        }
+    }
+    /// Internal method to get at the request.
+    fn lock(&self) -> MutexGuard<'_, Request> {
+        lock(&self.request)
+    }
+}
+
+/// Insure the interrupt thread is stopped before exiting.
+impl Drop for CdevInput {
+    fn drop(&mut self) {
+        self.clear_callback();
+    }
+}
+
+/// Internal function to get at an input's request.
+fn lock(request: &Mutex<Request>) -> MutexGuard<'_, Request> {
+    request.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Internal function to wait up to the timeout in milliseconds, or forever
+/// for -1, for the next edge.
+///
+/// The request isn't locked while waiting so the pin can still be read.
+fn next_event(request: &Mutex<Request>, timeout: i32) -> Result<Option<Level>> {
+    let fd = match &*lock(request) {
+        Request::Events(events) => events.as_raw_fd(),
+        _ => return Ok(None),
+    };
+    let mut fds = [PollFd::new(fd, PollFlags::POLLIN)];
+    match poll(&mut fds, timeout) {
+        Ok(0) => return Ok(None),
+        Ok(_) => {}
+        // A signal, like Ctrl-C, just ends the wait early.
+        Err(nix::errno::Errno::EINTR) => return Ok(None),
+        Err(e) => return Err(Error::Cdev(gpio_cdev::Error::from(std::io::Error::from(e)))),
+    }
+    match &mut *lock(request) {
+        Request::Events(events) => Ok(Some(match events.get_event()?.event_type() {
+            EventType::RisingEdge => Level::High,
+            EventType::FallingEdge => Level::Low,
+        })),
+        _ => Ok(None),
+    }
+}
+
+pub enum InputPin {
+    Rppal(rppal::gpio::InputPin),
+    Cdev(CdevInput),
+}
+
+impl InputPin {
+    pub fn pin(&self) -> u8 {
+        match self {
+            InputPin::Rppal(pin) => pin.pin(),
+            InputPin::Cdev(pin) => pin.claim.0,
+        }
+    }
+    pub fn read(&self) -> Level {
+        match self {
+            InputPin::Rppal(pin) => pin.read(),
+            InputPin::Cdev(pin) => pin.read(),
+        }
+    }
+    pub fn is_low(&self) -> bool {
+        self.read() == Level::Low
+    }
+    pub fn is_high(&self) -> bool {
+        self.read() == Level::High
+    }
+    pub fn set_interrupt(&mut self, trigger: Trigger) -> Result<()> {
+        match self {
+            InputPin::Rppal(pin) => Ok(pin.set_interrupt(trigger)?),
+            InputPin::Cdev(pin) => pin.set_interrupt(trigger),
+        }
+    }
+    pub fn clear_interrupt(&mut self) -> Result<()> {
+        match self {
+            InputPin::Rppal(pin) => Ok(pin.clear_interrupt()?),
+            InputPin::Cdev(pin) => pin.clear_interrupt(),
+        }
+    }
+    pub fn poll_interrupt(
+        &mut self,
+        reset: bool,
+        timeout: Option<Duration>,
+    ) -> Result<Option<Level>> {
+        match self {
+            InputPin::Rppal(pin) => Ok(pin.poll_interrupt(reset, timeout)?),
+            InputPin::Cdev(pin) => pin.poll_interrupt(reset, timeout),
+        }
+    }
+    pub fn set_async_interrupt<C>(&mut self, trigger: Trigger, callback: C) -> Result<()>
+    where
+        C: FnMut(Level) + Send + 'static,
+    {
+        match self {
+            InputPin::Rppal(pin) => Ok(pin.set_async_interrupt(trigger, callback)?),
+            InputPin::Cdev(pin) => pin.set_async_interrupt(trigger, callback),
+        }
+    }
+    pub fn clear_async_interrupt(&mut self) -> Result<()> {
+        match self {
+            InputPin::Rppal(pin) => Ok(pin.clear_async_interrupt()?),
+            InputPin::Cdev(pin) => pin.clear_async_interrupt(),
+        }
+    }
+    pub fn set_reset_on_drop(&mut self, reset_on_drop: bool) {
+        if let InputPin::Rppal(pin) = self {
+            pin.set_reset_on_drop(reset_on_drop);
+        }
+    }
+}
+
+/// Internal structure for an output through the character device.
+pub struct CdevOutput {
+    claim: Claim,
+    handle: Option<Arc<LineHandle>>,
+    level: Level,
+    pwm: Option<(Arc<AtomicBool>, JoinHandle<()>)>,
+}
+
+impl CdevOutput {
+    fn write(&mut self, level: Level) {
+        self.level = level;
+        if let Some(handle) = self.handle.as_ref() {
+            if let Err(e) = handle.set_value(value(level)) {
+                error!(target: "gpio", "Failed to write GPIO {}: {}", self.claim.0, e);
+            }
+        }
+    }
+    fn set_pwm_frequency(&mut self, frequency: f64, duty_cycle: f64) -> Result<()> {
+        self.clear_pwm();
+        let handle = match self.handle.clone() {
+            Some(handle) => handle,
+            None => return Ok(()),
+        };
+        let period = if frequency > 0.0 {
+            Duration::from_secs_f64(1.0 / frequency)
+        } else {
+            Duration::from_secs(0)
+        };
+        let high = period.mul_f64(duty_cycle.clamp(0.0, 1.0));
+        let low = period - high;
+        let running = Arc::new(AtomicBool::new(true));
+        let r = running.clone();
+        let worker = spawn(move || {
+            while r.load(Ordering::SeqCst) {
+                // Errors were already shown when the pin was taken.
+                if !high.is_zero() {
+                    let _ = handle.set_value(1);
+                    sleep(high);
+                }
+                if !low.is_zero() {
+                    let _ = handle.set_value(0);
+                    sleep(low);
+                }
+                if period.is_zero() {
+                    sleep(Duration::from_millis(POLL_TIME as u64));
+                }
+            }
+        });
+        self.pwm = Some((running, worker));
+        Ok(())
+    }
+    fn clear_pwm(&mut self) {
+        if let Some((running, worker)) = self.pwm.take() {
+            running.store(false, Ordering::SeqCst);
+            let _ = worker.join();
+            self.write(self.level);
+        }
+    }
+}
+
+/// Insure the PWM thread is stopped before exiting.
+impl Drop for CdevOutput {
+    fn drop(&mut self) {
+        self.clear_pwm();
+    }
+}
+
+pub enum OutputPin {
+    Rppal(rppal::gpio::OutputPin),
+    Cdev(CdevOutput),
+}
+
+impl OutputPin {
+    pub fn pin(&self) -> u8 {
+        match self {
+            OutputPin::Rppal(pin) => pin.pin(),
+            OutputPin::Cdev(pin) => pin.claim.0,
+        }
+    }
+    pub fn is_set_low(&self) -> bool {
+        !self.is_set_high()
+    }
+    pub fn is_set_high(&self) -> bool {
+        match self {
+            OutputPin::Rppal(pin) => pin.is_set_high(),
+            OutputPin::Cdev(pin) => pin.level == Level::High,
+        }
+    }
+    pub fn write(&mut self, level: Level) {
+        match self {
+            OutputPin::Rppal(pin) => pin.write(level),
+            OutputPin::Cdev(pin) => pin.write(level),
+        }
+    }
+    pub fn set_low(&mut self) {
+        self.write(Level::Low);
+    }
+    pub fn set_high(&mut self) {
+        self.write(Level::High);
+    }
+    pub fn toggle(&mut self) {
+        if self.is_set_high() {
+            self.set_low();
+        } else {
+            self.set_high();
+        }
+    }
+    pub fn set_pwm(&mut self, period: Duration, pulse_width: Duration) -> Result<()> {
+        match self {
+            OutputPin::Rppal(pin) => Ok(pin.set_pwm(period, pulse_width)?),
+            OutputPin::Cdev(pin) => {
+                let period = period.as_secs_f64();
+                if period > 0.0 {
+                    pin.set_pwm_frequency(1.0 / period, pulse_width.as_secs_f64() / period)
+                } else {
+                    pin.set_pwm_frequency(0.0, 0.0)
+                }
+            }
+        }
+    }
+    pub fn set_pwm_frequency(&mut self, frequency: f64, duty_cycle: f64) -> Result<()> {
+        match self {
+            OutputPin::Rppal(pin) => Ok(pin.set_pwm_frequency(frequency, duty_cycle)?),
+            OutputPin::Cdev(pin) => pin.set_pwm_frequency(frequency, duty_cycle),
+        }
+    }
+    pub fn clear_pwm(&mut self) -> Result<()> {
+        match self {
+            OutputPin::Rppal(pin) => Ok(pin.clear_pwm()?),
+            OutputPin::Cdev(pin) => {
+                pin.clear_pwm();
+                Ok(())
+            }
+        }
+    }
+    pub fn set_reset_on_drop(&mut self, reset_on_drop: bool) {
+        if let OutputPin::Rppal(pin) = self {
+            pin.set_reset_on_drop(reset_on_drop);
+        }
+    }
+}
+
+/// Internal structure for a pin that switches between input and output
+/// through the character device, which is done by asking for the line again.
+pub struct CdevIo {
+    claim: Claim,
+    line: Line,
+    handle: Option<LineHandle>,
+    output: bool,
+    level: Level,
+}
+
+impl CdevIo {
+    fn set_mode(&mut self, mode: Mode) {
+        let (flags, output) = match mode {
+            Mode::Output => (LineRequestFlags::OUTPUT, true),
+            Mode::Input => (LineRequestFlags::INPUT, false),
+            mode => {
+                warn!(
+                    target: "gpio",
+                    "The character device can't put GPIO {} in {:?} mode",
+                    self.claim.0,
+                    mode
+                );
+                (LineRequestFlags::INPUT, false)
+            }
+        };
+        self.handle = None;
+        self.handle = request(&self.line, flags, self.level);
+        self.output = output;
+    }
+    fn read(&self) -> Level {
+        match self.handle.as_ref() {
+            Some(handle) => level(handle.get_value()),
+            None => Level::Low,
+        }
+    }
+    fn write(&mut self, level: Level) {
+        // Like rppal the level is kept for when the pin becomes an output.
+        self.level = level;
+        if let (true, Some(handle)) = (self.output, self.handle.as_ref()) {
+            if let Err(e) = handle.set_value(value(level)) {
+                error!(target: "gpio", "Failed to write GPIO {}: {}", self.claim.0, e);
+            }
+        }
+    }
+}
+
+pub enum IoPin {
+    Rppal(rppal::gpio::IoPin),
+    Cdev(CdevIo),
+}
+
+impl IoPin {
+    pub fn pin(&self) -> u8 {
+        match self {
+            IoPin::Rppal(pin) => pin.pin(),
+            IoPin::Cdev(pin) => pin.claim.0,
+        }
+    }
+    pub fn set_mode(&mut self, mode: Mode) {
+        match self {
+            IoPin::Rppal(pin) => pin.set_mode(mode),
+            IoPin::Cdev(pin) => pin.set_mode(mode),
+        }
+    }
+    pub fn set_pullupdown(&mut self, pud: PullUpDown) {
+        match self {
+            IoPin::Rppal(pin) => pin.set_pullupdown(pud),
+            IoPin::Cdev(pin) => match pud {
+                PullUpDown::PullUp => warn_pull(pin.claim.0, "up"),
+                PullUpDown::PullDown => warn_pull(pin.claim.0, "down"),
+                PullUpDown::Off => {}
+            },
+        }
+    }
+    pub fn read(&self) -> Level {
+        match self {
+            IoPin::Rppal(pin) => pin.read(),
+            IoPin::Cdev(pin) => pin.read(),
+        }
+    }
+    pub fn is_low(&self) -> bool {
+        self.read() == Level::Low
+    }
+    pub fn is_high(&self) -> bool {
+        self.read() == Level::High
+    }
+    pub fn write(&mut self, level: Level) {
+        match self {
+            IoPin::Rppal(pin) => pin.write(level),
+            IoPin::Cdev(pin) => pin.write(level),
+        }
+    }
+    pub fn set_low(&mut self) {
+        self.write(Level::Low);
+    }
+    pub fn set_high(&mut self) {
+        self.write(Level::High);
+    }
+    pub fn toggle(&mut self) {
+        match self {
+            IoPin::Rppal(pin) => pin.toggle(),
+            IoPin::Cdev(pin) => pin.write(!pin.level),
+        }
+    }
+    pub fn set_reset_on_drop(&mut self, reset_on_drop: bool) {
+        if let IoPin::Rppal(pin) = self {
+            pin.set_reset_on_drop(reset_on_drop);
+        }
+    }
+}
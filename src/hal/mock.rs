@@ -0,0 +1,1161 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! In-memory fakes of the parts of rppal used by the library.
+//!
+//! Each fake keeps the state a real pin or bus would have and adds a
+//! [Record] of every change to a shared log so tests can check what a driver
+//! did. Inputs and devices on the buses are controlled with the extra
+//! functions in each module like [gpio::set_level()] and
+//! [i2c::set_registers()], and time as the drivers see it with [clock].
+
+use lazy_static::lazy_static;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+/// Something done to one of the fakes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operation {
+    /// A pin was taken and set to a mode.
+    Claim { pin: u8, mode: gpio::Mode },
+    /// A pin was given back.
+    Release { pin: u8 },
+    /// An output or IO pin changed mode.
+    SetMode { pin: u8, mode: gpio::Mode },
+    /// An output or IO pin was driven.
+    Write { pin: u8, level: gpio::Level },
+    /// A pin was read.
+    Read { pin: u8, level: gpio::Level },
+    /// Software PWM was started or changed on a pin.
+    SoftPwm {
+        pin: u8,
+        frequency: f64,
+        duty_cycle: f64,
+    },
+    /// Software PWM was stopped on a pin.
+    ClearSoftPwm { pin: u8 },
+    /// A hardware PWM channel was changed.
+    Pwm {
+        channel: pwm::Channel,
+        frequency: f64,
+        duty_cycle: f64,
+        enabled: bool,
+    },
+    /// Bytes were written to a device on an I2C bus.
+    I2cWrite {
+        bus: u8,
+        address: u16,
+        bytes: Vec<u8>,
+    },
+    /// Bytes were read from a device on an I2C bus.
+    I2cRead {
+        bus: u8,
+        address: u16,
+        bytes: Vec<u8>,
+    },
+    /// Bytes were sent, and maybe received at the same time, on a SPI bus.
+    SpiTransfer {
+        bus: spi::Bus,
+        slave_select: spi::SlaveSelect,
+        written: Vec<u8>,
+        read: Vec<u8>,
+    },
+}
+
+/// An [Operation] and when it happened.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    /// Time since the fakes were started or last [reset()].
+    pub elapsed: Duration,
+    pub operation: Operation,
+}
+
+type Callback = Box<dyn FnMut(gpio::Level) + Send>;
+type Watcher = Box<dyn FnMut(&Record) + Send>;
+
+struct State {
+    start: Instant,
+    /// Real time when the clock was last frozen or let run again.
+    anchor: Instant,
+    /// What the clock said at the anchor.
+    fake: Instant,
+    frozen: bool,
+    records: Vec<Record>,
+    recording: bool,
+    watchers: Vec<Watcher>,
+    claimed: HashSet<u8>,
+    levels: HashMap<u8, gpio::Level>,
+    interrupts: HashMap<u8, (gpio::Trigger, Option<Callback>)>,
+    pending: HashMap<u8, gpio::Level>,
+    pwm: HashMap<pwm::Channel, pwm::Settings>,
+    i2c: HashMap<(u8, u16), [u8; 256]>,
+    pointers: HashMap<(u8, u16), u8>,
+    spi: HashMap<(spi::Bus, spi::SlaveSelect), VecDeque<Vec<u8>>>,
+}
+
+impl State {
+    fn new() -> Self {
+        let now = Instant::now();
+        State {
+            start: now,
+            anchor: now,
+            fake: now,
+            frozen: false,
+            records: Vec::new(),
+            recording: true,
+            watchers: Vec::new(),
+            claimed: HashSet::new(),
+            levels: HashMap::new(),
+            interrupts: HashMap::new(),
+            pending: HashMap::new(),
+            pwm: HashMap::new(),
+            i2c: HashMap::new(),
+            pointers: HashMap::new(),
+            spi: HashMap::new(),
+        }
+    }
+    fn now(&self) -> Instant {
+        if self.frozen {
+            self.fake
+        } else {
+            self.fake + self.anchor.elapsed()
+        }
+    }
+    fn record(&mut self, operation: Operation) {
+        let record = Record {
+            elapsed: self.now().saturating_duration_since(self.start),
+            operation,
+        };
+        for watcher in self.watchers.iter_mut() {
+            watcher(&record);
+        }
+        if self.recording {
+            self.records.push(record);
+        }
+    }
+}
+
+lazy_static! {
+    static ref STATE: Mutex<State> = Mutex::new(State::new());
+}
+
+/// Internal associative method (function) to get at the shared state of the
+/// fakes.
+fn state() -> MutexGuard<'static, State> {
+    STATE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Gets a copy of everything done to the fakes so far.
+pub fn records() -> Vec<Record> {
+    state().records.clone()
+}
+
+/// Same as [records()] without the times.
+pub fn operations() -> Vec<Operation> {
+    state()
+        .records
+        .iter()
+        .map(|record| record.operation.clone())
+        .collect()
+}
+
+/// Time since the fakes were started or last [reset()], the same clock used
+/// for [Record::elapsed].
+pub fn elapsed() -> Duration {
+    let state = state();
+    state.now().saturating_duration_since(state.start)
+}
+
+/// Empties the log without touching the state of the fakes.
+pub fn clear_records() {
+    state().records.clear();
+}
+
+/// Turns the log on or off.
+///
+/// Something like a scanning display adds thousands of records a second so
+/// long running programs that only use [watch()] should turn it off.
+pub fn set_recording(recording: bool) {
+    state().recording = recording;
+}
+
+/// Calls the watcher with every new [Record] as it happens, even when the log
+/// is off.
+///
+/// The watcher is run while the fakes are locked so it must not use them.
+pub fn watch<W>(watcher: W)
+where
+    W: FnMut(&Record) + Send + 'static,
+{
+    state().watchers.push(Box::new(watcher));
+}
+
+/// Puts all the fakes back the way they started, forgetting pins, interrupts,
+/// devices, watchers and the log.
+pub fn reset() {
+    *state() = State::new();
+}
+
+pub mod clock {
+    //! Fake clock the drivers that time things, like the debouncing in
+    //! [Button](crate::Button), read instead of [Instant::now()].
+    //!
+    //! It follows real time until [freeze()] is called after which it only
+    //! moves when [advance()] is, so timing can be tested without waiting or
+    //! being thrown off by a slow computer.
+
+    use super::state;
+    use std::time::{Duration, Instant};
+
+    /// Gets the time on the clock.
+    pub fn now() -> Instant {
+        state().now()
+    }
+    /// Stops the clock where it is.
+    pub fn freeze() {
+        let mut state = state();
+        state.fake = state.now();
+        state.anchor = Instant::now();
+        state.frozen = true;
+    }
+    /// Lets the clock follow real time again from where it was stopped.
+    pub fn resume() {
+        let mut state = state();
+        state.fake = state.now();
+        state.anchor = Instant::now();
+        state.frozen = false;
+    }
+    /// Moves the clock forward whether it is frozen or not.
+    pub fn advance(duration: Duration) {
+        state().fake += duration;
+    }
+    /// Checks if [freeze()] has stopped the clock.
+    pub fn is_frozen() -> bool {
+        state().frozen
+    }
+}
+
+pub mod gpio {
+    //! Fake GPIO pins.
+
+    use super::{state, Operation};
+    use std::{fmt, ops::Not, thread::sleep, time::Duration};
+
+    /// Error from the fake GPIO.
+    #[derive(Debug)]
+    pub enum Error {
+        /// The pin doesn't exist or is already in use.
+        PinNotAvailable(u8),
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Error::PinNotAvailable(pin) => write!(f, "Pin {} is not available", pin),
+            }
+        }
+    }
+
+    impl std::error::Error for Error {}
+
+    pub type Result<T> = std::result::Result<T, Error>;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub enum Level {
+        Low,
+        High,
+    }
+
+    impl Not for Level {
+        type Output = Level;
+        fn not(self) -> Level {
+            match self {
+                Level::Low => Level::High,
+                Level::High => Level::Low,
+            }
+        }
+    }
+
+    impl fmt::Display for Level {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Level::Low => write!(f, "Low"),
+                Level::High => write!(f, "High"),
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Trigger {
+        Disabled,
+        RisingEdge,
+        FallingEdge,
+        Both,
+    }
+
+    impl Trigger {
+        /// Internal method to check if going to the level fires the trigger.
+        fn fires(self, from: Level, to: Level) -> bool {
+            from != to
+                && match self {
+                    Trigger::Disabled => false,
+                    Trigger::RisingEdge => to == Level::High,
+                    Trigger::FallingEdge => to == Level::Low,
+                    Trigger::Both => true,
+                }
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Mode {
+        Input,
+        Output,
+        Alt0,
+        Alt1,
+        Alt2,
+        Alt3,
+        Alt4,
+        Alt5,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum PullUpDown {
+        Off,
+        PullDown,
+        PullUp,
+    }
+
+    // Highest GPIO number on the 40 pin header.
+    const MAX_PIN: u8 = 27;
+
+    /// Sets what an input pin reads, like a button being pressed, and runs
+    /// any interrupt it fires.
+    ///
+    /// The callback is run on the calling thread instead of a thread of its
+    /// own like rppal does.
+    pub fn set_level(pin: u8, level: Level) {
+        let callback = {
+            let mut state = state();
+            let from = state.levels.insert(pin, level).unwrap_or(Level::Low);
+            let fired = match state.interrupts.get(&pin) {
+                Some((trigger, _)) => trigger.fires(from, level),
+                None => false,
+            };
+            if !fired {
+                return;
+            }
+            state.pending.insert(pin, level);
+            state
+                .interrupts
+                .get_mut(&pin)
+                .and_then(|(_, callback)| callback.take())
+        };
+        // Run without the state locked so the callback can use other pins.
+        if let Some(mut callback) = callback {
+            callback(level);
+            let mut state = state();
+            if let Some((_, slot)) = state.interrupts.get_mut(&pin) {
+                if slot.is_none() {
+                    *slot = Some(callback);
+                }
+            }
+        }
+    }
+
+    /// Gets the level a pin is at which for an output is what it was last set
+    /// to.
+    pub fn level(pin: u8) -> Level {
+        state().levels.get(&pin).copied().unwrap_or(Level::Low)
+    }
+
+    /// Internal function to read a pin and log it.
+    fn read_level(pin: u8) -> Level {
+        let mut state = state();
+        let level = state.levels.get(&pin).copied().unwrap_or(Level::Low);
+        state.record(Operation::Read { pin, level });
+        level
+    }
+
+    /// Internal function to drive a pin and log it.
+    fn write_level(pin: u8, level: Level) {
+        let mut state = state();
+        state.levels.insert(pin, level);
+        state.record(Operation::Write { pin, level });
+    }
+
+    /// Internal function to take a pin in the given mode.
+    fn claim(pin: u8, mode: Mode, default: Option<Level>) {
+        let mut state = state();
+        if let Some(level) = default {
+            state.levels.entry(pin).or_insert(level);
+        }
+        state.record(Operation::Claim { pin, mode });
+    }
+
+    /// Internal function to give back a pin.
+    fn release(pin: u8) {
+        let mut state = state();
+        state.claimed.remove(&pin);
+        state.interrupts.remove(&pin);
+        state.pending.remove(&pin);
+        state.record(Operation::Release { pin });
+    }
+
+    pub struct Gpio;
+
+    impl Gpio {
+        pub fn new() -> Result<Gpio> {
+            Ok(Gpio)
+        }
+        pub fn get(&self, pin: u8) -> Result<Pin> {
+            if pin > MAX_PIN || !state().claimed.insert(pin) {
+                return Err(Error::PinNotAvailable(pin));
+            }
+            Ok(Pin { pin })
+        }
+    }
+
+    pub struct Pin {
+        pin: u8,
+    }
+
+    impl Pin {
+        pub fn pin(&self) -> u8 {
+            self.pin
+        }
+        pub fn read(&self) -> Level {
+            read_level(self.pin)
+        }
+        pub fn is_low(&self) -> bool {
+            self.read() == Level::Low
+        }
+        pub fn is_high(&self) -> bool {
+            self.read() == Level::High
+        }
+        pub fn into_input(self) -> InputPin {
+            self.into_input_with(None)
+        }
+        pub fn into_input_pullup(self) -> InputPin {
+            self.into_input_with(Some(Level::High))
+        }
+        pub fn into_input_pulldown(self) -> InputPin {
+            self.into_input_with(Some(Level::Low))
+        }
+        pub fn into_output(self) -> OutputPin {
+            let pin = self.take();
+            claim(pin, Mode::Output, None);
+            OutputPin { pin }
+        }
+        pub fn into_io(self, mode: Mode) -> IoPin {
+            let pin = self.take();
+            claim(pin, mode, None);
+            IoPin { pin }
+        }
+        /// Internal method to turn into an input with an optional pull.
+        fn into_input_with(self, pull: Option<Level>) -> InputPin {
+            let pin = self.take();
+            claim(pin, Mode::Input, pull);
+            InputPin { pin }
+        }
+        /// Internal method to hand the pin on without releasing it.
+        fn take(self) -> u8 {
+            let pin = self.pin;
+            std::mem::forget(self);
+            pin
+        }
+    }
+
+    /// Gives back the pin if it was never turned into anything.
+    impl Drop for Pin {
+        fn drop(&mut self) {
+            state().claimed.remove(&self.pin);
+        }
+    }
+
+    pub struct InputPin {
+        pin: u8,
+    }
+
+    impl InputPin {
+        pub fn pin(&self) -> u8 {
+            self.pin
+        }
+        pub fn read(&self) -> Level {
+            read_level(self.pin)
+        }
+        pub fn is_low(&self) -> bool {
+            self.read() == Level::Low
+        }
+        pub fn is_high(&self) -> bool {
+            self.read() == Level::High
+        }
+        pub fn set_interrupt(&mut self, trigger: Trigger) -> Result<()> {
+            let mut state = state();
+            state.pending.remove(&self.pin);
+            state.interrupts.insert(self.pin, (trigger, None));
+            Ok(())
+        }
+        pub fn clear_interrupt(&mut self) -> Result<()> {
+            let mut state = state();
+            state.interrupts.remove(&self.pin);
+            state.pending.remove(&self.pin);
+            Ok(())
+        }
+        pub fn poll_interrupt(
+            &mut self,
+            reset: bool,
+            timeout: Option<Duration>,
+        ) -> Result<Option<Level>> {
+            if reset {
+                state().pending.remove(&self.pin);
+            }
+            // Checks every millisecond so another thread can set the level.
+            let mut waited = Duration::from_millis(0);
+            loop {
+                if let Some(level) = state().pending.remove(&self.pin) {
+                    return Ok(Some(level));
+                }
+                if matches!(timeout, Some(timeout) if waited >= timeout) {
+                    return Ok(None);
+                }
+                sleep(Duration::from_millis(1));
+                waited += Duration::from_millis(1);
+            }
+        }
+        pub fn set_async_interrupt<C>(&mut self, trigger: Trigger, callback: C) -> Result<()>
+        where
+            C: FnMut(Level) + Send + 'static,
+        {
+            state()
+                .interrupts
+                .insert(self.pin, (trigger, Some(Box::new(callback))));
+            Ok(())
+        }
+        pub fn clear_async_interrupt(&mut self) -> Result<()> {
+            state().interrupts.remove(&self.pin);
+            Ok(())
+        }
+        pub fn set_reset_on_drop(&mut self, _reset_on_drop: bool) {}
+    }
+
+    impl Drop for InputPin {
+        fn drop(&mut self) {
+            release(self.pin);
+        }
+    }
+
+    pub struct OutputPin {
+        pin: u8,
+    }
+
+    impl OutputPin {
+        pub fn pin(&self) -> u8 {
+            self.pin
+        }
+        pub fn is_set_low(&self) -> bool {
+            level(self.pin) == Level::Low
+        }
+        pub fn is_set_high(&self) -> bool {
+            level(self.pin) == Level::High
+        }
+        pub fn write(&mut self, level: Level) {
+            write_level(self.pin, level);
+        }
+        pub fn set_low(&mut self) {
+            self.write(Level::Low);
+        }
+        pub fn set_high(&mut self) {
+            self.write(Level::High);
+        }
+        pub fn toggle(&mut self) {
+            self.write(!level(self.pin));
+        }
+        pub fn set_pwm(&mut self, period: Duration, pulse_width: Duration) -> Result<()> {
+            let period = period.as_secs_f64();
+            let frequency = if period > 0.0 { 1.0 / period } else { 0.0 };
+            let duty_cycle = if period > 0.0 {
+                pulse_width.as_secs_f64() / period
+            } else {
+                0.0
+            };
+            self.set_pwm_frequency(frequency, duty_cycle)
+        }
+        pub fn set_pwm_frequency(&mut self, frequency: f64, duty_cycle: f64) -> Result<()> {
+            state().record(Operation::SoftPwm {
+                pin: self.pin,
+                frequency,
+                duty_cycle,
+            });
+            Ok(())
+        }
+        pub fn clear_pwm(&mut self) -> Result<()> {
+            state().record(Operation::ClearSoftPwm { pin: self.pin });
+            Ok(())
+        }
+        pub fn set_reset_on_drop(&mut self, _reset_on_drop: bool) {}
+    }
+
+    impl Drop for OutputPin {
+        fn drop(&mut self) {
+            release(self.pin);
+        }
+    }
+
+    pub struct IoPin {
+        pin: u8,
+    }
+
+    impl IoPin {
+        pub fn pin(&self) -> u8 {
+            self.pin
+        }
+        pub fn set_mode(&mut self, mode: Mode) {
+            state().record(Operation::SetMode {
+                pin: self.pin,
+                mode,
+            });
+        }
+        pub fn set_pullupdown(&mut self, _pud: PullUpDown) {}
+        pub fn read(&self) -> Level {
+            read_level(self.pin)
+        }
+        pub fn is_low(&self) -> bool {
+            self.read() == Level::Low
+        }
+        pub fn is_high(&self) -> bool {
+            self.read() == Level::High
+        }
+        pub fn write(&mut self, level: Level) {
+            write_level(self.pin, level);
+        }
+        pub fn set_low(&mut self) {
+            self.write(Level::Low);
+        }
+        pub fn set_high(&mut self) {
+            self.write(Level::High);
+        }
+        pub fn toggle(&mut self) {
+            self.write(!level(self.pin));
+        }
+        pub fn set_reset_on_drop(&mut self, _reset_on_drop: bool) {}
+    }
+
+    impl Drop for IoPin {
+        fn drop(&mut self) {
+            release(self.pin);
+        }
+    }
+}
+
+pub mod pwm {
+    //! Fake hardware PWM channels.
+
+    use super::{state, Operation};
+    use std::{fmt, time::Duration};
+
+    /// Error from the fake PWM.
+    #[derive(Debug)]
+    pub enum Error {
+        /// A zero frequency or period was asked for.
+        InvalidPeriod,
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Error::InvalidPeriod => write!(f, "Invalid PWM period"),
+            }
+        }
+    }
+
+    impl std::error::Error for Error {}
+
+    pub type Result<T> = std::result::Result<T, Error>;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub enum Channel {
+        Pwm0,
+        Pwm1,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Polarity {
+        Normal,
+        Inverse,
+    }
+
+    /// Internal structure with the current settings of a channel.
+    #[derive(Clone, Copy, Debug)]
+    pub(super) struct Settings {
+        frequency: f64,
+        duty_cycle: f64,
+        polarity: Polarity,
+        enabled: bool,
+    }
+
+    /// Gets the frequency, duty cycle and if a channel is enabled.
+    pub fn settings(channel: Channel) -> Option<(f64, f64, bool)> {
+        state()
+            .pwm
+            .get(&channel)
+            .map(|settings| (settings.frequency, settings.duty_cycle, settings.enabled))
+    }
+
+    pub struct Pwm {
+        channel: Channel,
+    }
+
+    impl Pwm {
+        pub fn new(channel: Channel) -> Result<Pwm> {
+            Self::with_frequency(channel, 0.0, 0.0, Polarity::Normal, false)
+        }
+        pub fn with_period(
+            channel: Channel,
+            period: Duration,
+            pulse_width: Duration,
+            polarity: Polarity,
+            enabled: bool,
+        ) -> Result<Pwm> {
+            let (frequency, duty_cycle) = Self::from_period(period, pulse_width)?;
+            Self::with_frequency(channel, frequency, duty_cycle, polarity, enabled)
+        }
+        pub fn with_frequency(
+            channel: Channel,
+            frequency: f64,
+            duty_cycle: f64,
+            polarity: Polarity,
+            enabled: bool,
+        ) -> Result<Pwm> {
+            let pwm = Pwm { channel };
+            pwm.update(|settings| {
+                *settings = Settings {
+                    frequency,
+                    duty_cycle,
+                    polarity,
+                    enabled,
+                }
+            });
+            Ok(pwm)
+        }
+        pub fn period(&self) -> Result<Duration> {
+            let frequency = self.settings().frequency;
+            if frequency <= 0.0 {
+                return Err(Error::InvalidPeriod);
+            }
+            Ok(Duration::from_secs_f64(1.0 / frequency))
+        }
+        pub fn set_period(&self, period: Duration) -> Result<()> {
+            let (frequency, _) = Self::from_period(period, Duration::from_secs(0))?;
+            self.update(|settings| settings.frequency = frequency);
+            Ok(())
+        }
+        pub fn pulse_width(&self) -> Result<Duration> {
+            Ok(self.period()?.mul_f64(self.settings().duty_cycle))
+        }
+        pub fn set_pulse_width(&self, pulse_width: Duration) -> Result<()> {
+            let (_, duty_cycle) = Self::from_period(self.period()?, pulse_width)?;
+            self.update(|settings| settings.duty_cycle = duty_cycle);
+            Ok(())
+        }
+        pub fn frequency(&self) -> Result<f64> {
+            Ok(self.settings().frequency)
+        }
+        pub fn duty_cycle(&self) -> Result<f64> {
+            Ok(self.settings().duty_cycle)
+        }
+        pub fn set_frequency(&self, frequency: f64, duty_cycle: f64) -> Result<()> {
+            self.update(|settings| {
+                settings.frequency = frequency;
+                settings.duty_cycle = duty_cycle;
+            });
+            Ok(())
+        }
+        pub fn set_duty_cycle(&self, duty_cycle: f64) -> Result<()> {
+            self.update(|settings| settings.duty_cycle = duty_cycle);
+            Ok(())
+        }
+        pub fn polarity(&self) -> Result<Polarity> {
+            Ok(self.settings().polarity)
+        }
+        pub fn set_polarity(&self, polarity: Polarity) -> Result<()> {
+            self.update(|settings| settings.polarity = polarity);
+            Ok(())
+        }
+        pub fn is_enabled(&self) -> Result<bool> {
+            Ok(self.settings().enabled)
+        }
+        pub fn enable(&self) -> Result<()> {
+            self.update(|settings| settings.enabled = true);
+            Ok(())
+        }
+        pub fn disable(&self) -> Result<()> {
+            self.update(|settings| settings.enabled = false);
+            Ok(())
+        }
+        pub fn set_reset_on_drop(&mut self, _reset_on_drop: bool) {}
+        /// Internal associative method (function) to turn a period and pulse
+        /// width into a frequency and duty cycle.
+        fn from_period(period: Duration, pulse_width: Duration) -> Result<(f64, f64)> {
+            let period = period.as_secs_f64();
+            if period <= 0.0 {
+                return Err(Error::InvalidPeriod);
+            }
+            Ok((1.0 / period, pulse_width.as_secs_f64() / period))
+        }
+        /// Internal method to get the current settings.
+        fn settings(&self) -> Settings {
+            state().pwm.get(&self.channel).copied().unwrap_or(Settings {
+                frequency: 0.0,
+                duty_cycle: 0.0,
+                polarity: Polarity::Normal,
+                enabled: false,
+            })
+        }
+        /// Internal method to change the settings and log them.
+        fn update<F: FnOnce(&mut Settings)>(&self, change: F) {
+            let mut settings = self.settings();
+            change(&mut settings);
+            let mut state = state();
+            state.pwm.insert(self.channel, settings);
+            state.record(Operation::Pwm {
+                channel: self.channel,
+                frequency: settings.frequency,
+                duty_cycle: settings.duty_cycle,
+                enabled: settings.enabled,
+            });
+        }
+    }
+
+    /// Turns the channel off like rppal does.
+    impl Drop for Pwm {
+        fn drop(&mut self) {
+            if self.settings().enabled {
+                self.update(|settings| settings.enabled = false);
+            }
+        }
+    }
+}
+
+pub mod i2c {
+    //! Fake I2C bus where every address has 256 registers that can be
+    //! written and read like most sensors.
+
+    use super::{state, Operation};
+    use std::fmt;
+
+    /// Error from the fake I2C bus.
+    #[derive(Debug)]
+    pub enum Error {
+        /// Nothing has been put at the address with [set_registers()].
+        NoDevice(u16),
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Error::NoDevice(address) => write!(f, "No I2C device at {:#04x}", address),
+            }
+        }
+    }
+
+    impl std::error::Error for Error {}
+
+    pub type Result<T> = std::result::Result<T, Error>;
+
+    // Bus I2c::new() uses.
+    const DEFAULT_BUS: u8 = 1;
+
+    /// Adds a device at the address on bus 1 if needed and sets some of its
+    /// registers starting at the given one.
+    pub fn set_registers(address: u16, start: u8, values: &[u8]) {
+        let mut state = state();
+        let registers = state.i2c.entry((DEFAULT_BUS, address)).or_insert([0; 256]);
+        for (offset, value) in values.iter().enumerate() {
+            registers[(start as usize + offset) % 256] = *value;
+        }
+    }
+
+    /// Gets all the registers of the device at the address on bus 1.
+    pub fn registers(address: u16) -> Option<Vec<u8>> {
+        state()
+            .i2c
+            .get(&(DEFAULT_BUS, address))
+            .map(|registers| registers.to_vec())
+    }
+
+    pub struct I2c {
+        bus: u8,
+        address: u16,
+    }
+
+    impl I2c {
+        pub fn new() -> Result<I2c> {
+            Self::with_bus(DEFAULT_BUS)
+        }
+        pub fn with_bus(bus: u8) -> Result<I2c> {
+            Ok(I2c { bus, address: 0 })
+        }
+        pub fn bus(&self) -> u8 {
+            self.bus
+        }
+        pub fn clock_speed(&self) -> Result<u32> {
+            Ok(100_000)
+        }
+        pub fn set_slave_address(&mut self, slave_address: u16) -> Result<()> {
+            self.address = slave_address;
+            Ok(())
+        }
+        pub fn set_timeout(&self, _timeout: u32) -> Result<()> {
+            Ok(())
+        }
+        /// Reads from the register pointer on, moving it along.
+        pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+            self.read_from(None, buffer)?;
+            Ok(buffer.len())
+        }
+        /// The first byte sets the register pointer and the rest get written
+        /// from there.
+        pub fn write(&mut self, buffer: &[u8]) -> Result<usize> {
+            self.write_to(buffer)?;
+            Ok(buffer.len())
+        }
+        pub fn write_read(&self, write_buffer: &[u8], read_buffer: &mut [u8]) -> Result<()> {
+            self.write_to(write_buffer)?;
+            self.read_from(None, read_buffer)
+        }
+        pub fn block_read(&self, command: u8, buffer: &mut [u8]) -> Result<()> {
+            self.read_from(Some(command), buffer)
+        }
+        pub fn block_write(&self, command: u8, buffer: &[u8]) -> Result<()> {
+            let mut bytes = vec![command];
+            bytes.extend_from_slice(buffer);
+            self.write_to(&bytes)
+        }
+        pub fn smbus_read_byte(&self, command: u8) -> Result<u8> {
+            let mut buffer = [0];
+            self.read_from(Some(command), &mut buffer)?;
+            Ok(buffer[0])
+        }
+        pub fn smbus_write_byte(&self, command: u8, value: u8) -> Result<()> {
+            self.write_to(&[command, value])
+        }
+        pub fn smbus_read_word(&self, command: u8) -> Result<u16> {
+            let mut buffer = [0; 2];
+            self.read_from(Some(command), &mut buffer)?;
+            Ok(u16::from_le_bytes(buffer))
+        }
+        pub fn smbus_write_word(&self, command: u8, value: u16) -> Result<()> {
+            let [low, high] = value.to_le_bytes();
+            self.write_to(&[command, low, high])
+        }
+        /// Internal method to write to the device and log it.
+        fn write_to(&self, bytes: &[u8]) -> Result<()> {
+            let key = (self.bus, self.address);
+            let mut state = state();
+            let registers = state
+                .i2c
+                .get_mut(&key)
+                .ok_or(Error::NoDevice(self.address))?;
+            if let Some((pointer, values)) = bytes.split_first() {
+                for (offset, value) in values.iter().enumerate() {
+                    registers[(*pointer as usize + offset) % 256] = *value;
+                }
+                let next = pointer.wrapping_add(values.len() as u8);
+                state.pointers.insert(key, next);
+            }
+            state.record(Operation::I2cWrite {
+                bus: self.bus,
+                address: self.address,
+                bytes: bytes.to_vec(),
+            });
+            Ok(())
+        }
+        /// Internal method to read from the device and log it.
+        fn read_from(&self, start: Option<u8>, buffer: &mut [u8]) -> Result<()> {
+            let key = (self.bus, self.address);
+            let mut state = state();
+            let registers = *state.i2c.get(&key).ok_or(Error::NoDevice(self.address))?;
+            let pointer = start.unwrap_or_else(|| state.pointers.get(&key).copied().unwrap_or(0));
+            for (offset, byte) in buffer.iter_mut().enumerate() {
+                *byte = registers[(pointer as usize + offset) % 256];
+            }
+            state
+                .pointers
+                .insert(key, pointer.wrapping_add(buffer.len() as u8));
+            state.record(Operation::I2cRead {
+                bus: self.bus,
+                address: self.address,
+                bytes: buffer.to_vec(),
+            });
+            Ok(())
+        }
+    }
+}
+
+pub mod spi {
+    //! Fake SPI bus that answers with bytes queued up ahead of time.
+
+    use super::{state, Operation};
+    use std::fmt;
+
+    /// Error from the fake SPI bus, which never actually happens.
+    #[derive(Debug)]
+    pub enum Error {}
+
+    impl fmt::Display for Error {
+        fn fmt(&self, _f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match *self {}
+        }
+    }
+
+    impl std::error::Error for Error {}
+
+    pub type Result<T> = std::result::Result<T, Error>;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub enum Bus {
+        Spi0,
+        Spi1,
+        Spi2,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub enum SlaveSelect {
+        Ss0,
+        Ss1,
+        Ss2,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Mode {
+        Mode0,
+        Mode1,
+        Mode2,
+        Mode3,
+    }
+
+    /// Queues the bytes the device sends back during a later transfer or
+    /// read. Without anything queued the device sends back zeros.
+    pub fn queue_response(bus: Bus, slave_select: SlaveSelect, bytes: &[u8]) {
+        state()
+            .spi
+            .entry((bus, slave_select))
+            .or_default()
+            .push_back(bytes.to_vec());
+    }
+
+    pub struct Spi {
+        bus: Bus,
+        slave_select: SlaveSelect,
+        clock_speed: u32,
+    }
+
+    impl Spi {
+        pub fn new(
+            bus: Bus,
+            slave_select: SlaveSelect,
+            clock_speed: u32,
+            _mode: Mode,
+        ) -> Result<Spi> {
+            Ok(Spi {
+                bus,
+                slave_select,
+                clock_speed,
+            })
+        }
+        pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+            self.transfer(buffer, &[])
+        }
+        pub fn write(&mut self, buffer: &[u8]) -> Result<usize> {
+            self.transfer(&mut [], buffer)
+        }
+        pub fn transfer(&self, read_buffer: &mut [u8], write_buffer: &[u8]) -> Result<usize> {
+            let mut state = state();
+            let response = state
+                .spi
+                .get_mut(&(self.bus, self.slave_select))
+                .and_then(|queue| queue.pop_front())
+                .unwrap_or_default();
+            for (i, byte) in read_buffer.iter_mut().enumerate() {
+                *byte = response.get(i).copied().unwrap_or(0);
+            }
+            state.record(Operation::SpiTransfer {
+                bus: self.bus,
+                slave_select: self.slave_select,
+                written: write_buffer.to_vec(),
+                read: read_buffer.to_vec(),
+            });
+            Ok(read_buffer.len().max(write_buffer.len()))
+        }
+        pub fn clock_speed(&self) -> Result<u32> {
+            Ok(self.clock_speed)
+        }
+        pub fn set_clock_speed(&self, _clock_speed: u32) -> Result<()> {
+            Ok(())
+        }
+    }
+}
+
+pub mod system {
+    //! Fake device information.
+
+    use std::fmt;
+
+    /// Error from the fake device information, which never actually happens.
+    #[derive(Debug)]
+    pub enum Error {}
+
+    impl fmt::Display for Error {
+        fn fmt(&self, _f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match *self {}
+        }
+    }
+
+    impl std::error::Error for Error {}
+
+    pub type Result<T> = std::result::Result<T, Error>;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Model {
+        Mock,
+    }
+
+    impl fmt::Display for Model {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "Mock Raspberry Pi")
+        }
+    }
+
+    pub struct DeviceInfo;
+
+    impl DeviceInfo {
+        pub fn new() -> Result<DeviceInfo> {
+            Ok(DeviceInfo)
+        }
+        pub fn model(&self) -> Model {
+            Model::Mock
+        }
+    }
+}
@@ -0,0 +1,180 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::gpio::{Gpio, InputPin};
+use crate::registry::{self, PinClaim};
+use crate::Adc;
+use std::time::{Duration, Instant};
+
+pub use crate::bus::Hysteresis;
+
+/// Default level in percent that sets off a [FlameSensor].
+const FLAME_THRESHOLD: f64 = 50.0;
+/// Default level in percent that sets off a [GasSensor].
+const GAS_THRESHOLD: f64 = 40.0;
+/// Default drop in percent below the threshold needed to clear an alarm.
+const HYSTERESIS: f64 = 5.0;
+/// Default time the MQ-2's heater needs before readings mean anything.
+const WARM_UP: Duration = Duration::from_secs(60);
+
+/// Internal function to get a module's digital output pin.
+///
+/// The modules pull their output low when set off by the level chosen with
+/// their trim pot.
+fn digital_pin(pin: u8, owner: &'static str) -> Result<(InputPin, PinClaim)> {
+    let claim = registry::claim(owner, &[pin])?;
+    let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+    let pin = gpio
+        .get(pin)
+        .context(format!("Failed to get digital output pin: {}", pin))?
+        .into_input_pullup();
+    Ok((pin, claim))
+}
+
+/// Structure used to model an IR flame sensor module with its analog output
+/// read by one channel of an [Adc] and optionally its digital output on a
+/// GPIO pin.
+///
+/// The ADC is passed in for each reading so other sensors can share its other
+/// channels.
+pub struct FlameSensor {
+    channel: u8,
+    pin: Option<(InputPin, PinClaim)>,
+    threshold: Hysteresis,
+}
+
+impl FlameSensor {
+    /// Makes a sensor on the given ADC channel.
+    pub fn new(channel: u8) -> Self {
+        FlameSensor {
+            channel,
+            pin: None,
+            threshold: Hysteresis::new(FLAME_THRESHOLD, HYSTERESIS),
+        }
+    }
+    /// Makes a sensor on the given ADC channel that also uses the module's
+    /// digital output on the given GPIO pin.
+    pub fn with_digital(channel: u8, pin: u8) -> Result<Self> {
+        let mut sensor = Self::new(channel);
+        sensor.pin = Some(digital_pin(pin, "FlameSensor")?);
+        Ok(sensor)
+    }
+    /// Changes the level in percent that counts as a flame and how far it has
+    /// to drop below that to clear.
+    pub fn set_threshold(&mut self, threshold: f64, hysteresis: f64) {
+        self.threshold = Hysteresis::new(threshold, hysteresis);
+    }
+    /// Reads how much flame the sensor sees from 0.0 to 100.0 percent.
+    pub fn level<A: Adc>(&self, adc: &mut A) -> Result<f64> {
+        // The module's output drops as it sees more IR.
+        Ok((1.0 - adc.read_ratio(self.channel)?) * 100.0)
+    }
+    /// Checks if the module's digital output says there is a flame. Always
+    /// false without one.
+    pub fn is_triggered(&self) -> bool {
+        matches!(&self.pin, Some((pin, _)) if pin.is_low())
+    }
+    /// Reads the level and checks for a flame using either the threshold or
+    /// the digital output.
+    pub fn detected<A: Adc>(&mut self, adc: &mut A) -> Result<bool> {
+        let level = self.level(adc)?;
+        self.threshold.update(level);
+        Ok(self.threshold.is_active() || self.is_triggered())
+    }
+}
+
+/// Structure used to model an MQ-2 smoke and flammable gas sensor module with
+/// its analog output read by one channel of an [Adc] and optionally its
+/// digital output on a GPIO pin.
+///
+/// The sensor has a heater that needs to warm up before its readings can be
+/// trusted so nothing is detected until then.
+/// The module needs 5V for the heater but its analog output must go through
+/// a divider to keep it under 3.3V for the ADC.
+pub struct GasSensor {
+    channel: u8,
+    pin: Option<(InputPin, PinClaim)>,
+    threshold: Hysteresis,
+    started: Instant,
+    warm_up: Duration,
+}
+
+impl GasSensor {
+    /// Makes a sensor on the given ADC channel starting the warm up time now.
+    pub fn new(channel: u8) -> Self {
+        GasSensor {
+            channel,
+            pin: None,
+            threshold: Hysteresis::new(GAS_THRESHOLD, HYSTERESIS),
+            started: Instant::now(),
+            warm_up: WARM_UP,
+        }
+    }
+    /// Makes a sensor on the given ADC channel that also uses the module's
+    /// digital output on the given GPIO pin.
+    pub fn with_digital(channel: u8, pin: u8) -> Result<Self> {
+        let mut sensor = Self::new(channel);
+        sensor.pin = Some(digital_pin(pin, "GasSensor")?);
+        Ok(sensor)
+    }
+    /// Changes the level in percent that counts as gas and how far it has to
+    /// drop below that to clear.
+    pub fn set_threshold(&mut self, threshold: f64, hysteresis: f64) {
+        self.threshold = Hysteresis::new(threshold, hysteresis);
+    }
+    /// Changes how long the heater needs after power on. The default is a
+    /// minute but a new sensor really wants a day or so the first time.
+    pub fn set_warm_up(&mut self, warm_up: Duration) {
+        self.warm_up = warm_up;
+    }
+    /// Checks if the heater has had time to warm up.
+    pub fn is_warm(&self) -> bool {
+        self.started.elapsed() >= self.warm_up
+    }
+    /// How much longer the heater needs, zero once warm.
+    pub fn warm_up_remaining(&self) -> Duration {
+        self.warm_up
+            .checked_sub(self.started.elapsed())
+            .unwrap_or_default()
+    }
+    /// Reads the gas level from 0.0 to 100.0 percent of the ADC's range.
+    pub fn level<A: Adc>(&self, adc: &mut A) -> Result<f64> {
+        Ok(adc.read_ratio(self.channel)? * 100.0)
+    }
+    /// Checks if the module's digital output says there is gas. Always false
+    /// without one or while warming up.
+    pub fn is_triggered(&self) -> bool {
+        self.is_warm() && matches!(&self.pin, Some((pin, _)) if pin.is_low())
+    }
+    /// Reads the level and checks for gas using either the threshold or the
+    /// digital output. Always false while warming up.
+    pub fn detected<A: Adc>(&mut self, adc: &mut A) -> Result<bool> {
+        let level = self.level(adc)?;
+        if !self.is_warm() {
+            self.threshold.reset();
+            return Ok(false);
+        }
+        self.threshold.update(level);
+        Ok(self.threshold.is_active() || self.is_triggered())
+    }
+}
@@ -0,0 +1,187 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::gpio::{Gpio, OutputPin};
+use crate::registry::{self, PinClaim};
+use log::{debug, trace};
+use std::hint::spin_loop;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+const SDI: u8 = 17;
+const RCLK: u8 = 18;
+const SRCLK: u8 = 27;
+
+/// Busy waits long enough for a clock strobe to be seen by the chip.
+///
+/// A sleep can't be shorter than about 50µs on Linux which made shifting out a
+/// byte take close to half a millisecond and limited how fast things like the
+/// dot matrix could be scanned.
+fn strobe_delay() {
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_micros(1) {
+        spin_loop();
+    }
+}
+
+/// Structure used to model a 74HC595 8-Bit Shift Register chip.
+///
+/// Used in Lessons 10, 11, and 12.
+pub struct HC595 {
+    sdi: OutputPin,
+    rclk: OutputPin,
+    srclk: OutputPin,
+    _claim: PinClaim,
+}
+
+impl HC595 {
+    /// Takes place of setup() from Python code.
+    pub fn new() -> Result<Self> {
+        Self::with_pins(SDI, RCLK, SRCLK)
+    }
+    /// Same as [new()](HC595::new) but with the chip wired to other pins.
+    pub fn with_pins(sdi: u8, rclk: u8, srclk: u8) -> Result<Self> {
+        debug!(target: "hc595", "sdi = {}, rclk = {}, srclk = {}", sdi, rclk, srclk);
+        let claim = registry::claim("HC595", &[sdi, rclk, srclk])?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        let mut sdi = gpio
+            .get(sdi)
+            .context("Failed to get sdi pin")?
+            .into_output();
+        sdi.set_low();
+        let mut rclk = gpio
+            .get(rclk)
+            .context("Failed to get rclk pin")?
+            .into_output();
+        rclk.set_low();
+        let mut srclk = gpio
+            .get(srclk)
+            .context("Failed to get srclk pin")?
+            .into_output();
+        srclk.set_low();
+        let hc595 = HC595 {
+            sdi,
+            rclk,
+            srclk,
+            _claim: claim,
+        };
+        #[cfg(feature = "mock")]
+        crate::simulator::attach(crate::simulator::Part::Hc595(hc595.pins()));
+        Ok(hc595)
+    }
+    /// Internal method used by the simulator to get the sdi, rclk, and srclk
+    /// pin numbers.
+    #[cfg(feature = "mock")]
+    pub(crate) fn pins(&self) -> [u8; 3] {
+        [self.sdi.pin(), self.rclk.pin(), self.srclk.pin()]
+    }
+    /// Some function as hc595_in() from Python code.
+    pub fn serial_in(&mut self, data: u8) {
+        trace!(target: "hc595", "shifting in {:#010b}", data);
+        // Switch from bit shifting data around to iterating pre-calculated mask
+        // values.
+        for mask in ([0x80, 0x40, 0x20, 0x10, 0x08, 0x04, 0x02, 0x01]).iter() {
+            if data & mask > 0 {
+                self.sdi.set_high();
+            } else {
+                self.sdi.set_low();
+            }
+            // Strobe shift register clock.
+            self.srclk.set_high();
+            strobe_delay();
+            self.srclk.set_low();
+        }
+    }
+    /// Same as hc595_out() function from Python code.
+    pub fn parallel_out(&mut self) {
+        trace!(target: "hc595", "latching outputs");
+        // Strobe output latch clock.
+        self.rclk.set_high();
+        strobe_delay();
+        self.rclk.set_low();
+    }
+}
+
+/// Insure output on 75HC595 is all zero (off) before exiting.
+impl Drop for HC595 {
+    fn drop(&mut self) {
+        self.serial_in(0);
+        self.parallel_out();
+        self.sdi.set_low();
+        self.rclk.set_low();
+        self.srclk.set_low();
+    }
+}
+
+/// Thread-safe handle to a shared [HC595].
+///
+/// Cloning the handle is cheap and every clone drives the same chip, so an
+/// interrupt callback and the main loop can both update the outputs without
+/// fighting over `&mut`.
+#[derive(Clone)]
+pub struct SharedHC595 {
+    inner: Arc<Mutex<HC595>>,
+}
+
+impl SharedHC595 {
+    /// Same as [HC595::new()] but wrapped for sharing between threads.
+    pub fn new() -> Result<Self> {
+        Ok(Self::from(HC595::new()?))
+    }
+    /// Same as [HC595::with_pins()] but wrapped for sharing between threads.
+    pub fn with_pins(sdi: u8, rclk: u8, srclk: u8) -> Result<Self> {
+        Ok(Self::from(HC595::with_pins(sdi, rclk, srclk)?))
+    }
+    /// Shifts in a byte and latches it to the outputs as a single operation.
+    pub fn write(&self, data: u8) {
+        let mut hc595 = self.lock();
+        hc595.serial_in(data);
+        hc595.parallel_out();
+    }
+    /// Shifts in several bytes, for chained chips, then latches them all.
+    ///
+    /// The last byte ends up on the chip nearest the Raspberry Pi.
+    pub fn write_all(&self, data: &[u8]) {
+        let mut hc595 = self.lock();
+        for byte in data.iter() {
+            hc595.serial_in(*byte);
+        }
+        hc595.parallel_out();
+    }
+    /// Gives exclusive access to the underlying chip for longer sequences.
+    ///
+    /// Other handles block until the returned guard is dropped.
+    pub fn lock(&self) -> MutexGuard<'_, HC595> {
+        // A panic in another thread doesn't leave the chip in an unusable state
+        // so just keep going with it.
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl From<HC595> for SharedHC595 {
+    fn from(hc595: HC595) -> Self {
+        SharedHC595 {
+            inner: Arc::new(Mutex::new(hc595)),
+        }
+    }
+}
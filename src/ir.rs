@@ -0,0 +1,219 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::gpio::{Gpio, InputPin, Level, Trigger};
+use crate::registry::{self, PinClaim};
+use std::{
+    sync::mpsc::{channel, Receiver},
+    time::{Duration, Instant},
+};
+
+// NEC timings in µs.
+const LEADER_MARK: u64 = 9000;
+const LEADER_SPACE: u64 = 4500;
+const REPEAT_SPACE: u64 = 2250;
+const BIT_MARK: u64 = 562;
+const ZERO_SPACE: u64 = 562;
+const ONE_SPACE: u64 = 1687;
+// Bits in a NEC frame.
+const BITS: u8 = 32;
+
+/// A decoded NEC remote control button press.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NecCode {
+    /// Which remote it came from, 8 bits for classic NEC or 16 for extended.
+    pub address: u16,
+    /// Which button was pressed.
+    pub command: u8,
+    /// Set for the short repeat frames sent about every 110ms while a button
+    /// is held down.
+    pub repeat: bool,
+}
+
+/// Where a [NecDecoder] is up to.
+#[derive(Clone, Copy, Debug)]
+enum NecState {
+    Idle,
+    /// Seen the 9ms leader mark.
+    Leader,
+    /// Seen a repeat space and waiting for its closing mark.
+    Repeat,
+    /// Reading data bits.
+    Data {
+        bits: u32,
+        count: u8,
+    },
+}
+
+/// Turns mark and space timings into [NecCode]s.
+///
+/// Works with any source of timings so can be tested or fed from a recording
+/// as well as a [IrReceiver].
+#[derive(Clone, Copy, Debug)]
+pub struct NecDecoder {
+    state: NecState,
+    last: Option<NecCode>,
+}
+
+impl Default for NecDecoder {
+    fn default() -> Self {
+        NecDecoder {
+            state: NecState::Idle,
+            last: None,
+        }
+    }
+}
+
+impl NecDecoder {
+    /// Feeds in a mark (carrier on) or space (carrier off) that lasted the
+    /// given time.
+    pub fn pulse(&mut self, mark: bool, duration: Duration) -> Option<NecCode> {
+        let us = duration.as_micros() as u64;
+        let (state, code) = match (self.state, mark) {
+            (_, true) if close(us, LEADER_MARK) => (NecState::Leader, None),
+            (NecState::Leader, false) if close(us, LEADER_SPACE) => {
+                (NecState::Data { bits: 0, count: 0 }, None)
+            }
+            (NecState::Leader, false) if close(us, REPEAT_SPACE) => (NecState::Repeat, None),
+            (NecState::Repeat, true) if close(us, BIT_MARK) => (
+                NecState::Idle,
+                self.last.map(|last| NecCode {
+                    repeat: true,
+                    ..last
+                }),
+            ),
+            (NecState::Data { bits, count }, true) if close(us, BIT_MARK) => {
+                (NecState::Data { bits, count }, None)
+            }
+            (NecState::Data { bits, count }, false) => {
+                // Bits are sent LSB first.
+                let bits = if close(us, ONE_SPACE) {
+                    bits | 1 << count
+                } else if close(us, ZERO_SPACE) {
+                    bits
+                } else {
+                    self.state = NecState::Idle;
+                    return None;
+                };
+                if count + 1 == BITS {
+                    let code = Self::decode(bits);
+                    self.last = code;
+                    (NecState::Idle, code)
+                } else {
+                    (
+                        NecState::Data {
+                            bits,
+                            count: count + 1,
+                        },
+                        None,
+                    )
+                }
+            }
+            _ => (NecState::Idle, None),
+        };
+        self.state = state;
+        code
+    }
+    /// Internal associative method (function) to check and split up a frame.
+    fn decode(bits: u32) -> Option<NecCode> {
+        let [address, address_check, command, command_check] = bits.to_le_bytes();
+        if command != !command_check {
+            return None;
+        }
+        // Extended NEC uses the address check byte as more address bits.
+        let address = if address == !address_check {
+            u16::from(address)
+        } else {
+            u16::from_le_bytes([address, address_check])
+        };
+        Some(NecCode {
+            address,
+            command,
+            repeat: false,
+        })
+    }
+}
+
+/// Checks a time is within 25% of what was expected.
+fn close(actual: u64, expected: u64) -> bool {
+    actual * 4 >= expected * 3 && actual * 4 <= expected * 5
+}
+
+/// Structure used to model an IR receiver module like the VS1838B decoding
+/// remote controls using the NEC protocol.
+///
+/// The receiver's output is low while it sees the 38kHz carrier (a mark) and
+/// high otherwise (a space).
+/// An interrupt timestamps each edge so nothing runs between button presses.
+pub struct IrReceiver {
+    pin: InputPin,
+    _claim: PinClaim,
+}
+
+impl IrReceiver {
+    /// Gets the GPIO pin connected to the receiver's output.
+    pub fn new(pin: u8) -> Result<Self> {
+        let claim = registry::claim("IrReceiver", &[pin])?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        let pin = gpio
+            .get(pin)
+            .context(format!("Failed to get IR receiver pin: {}", pin))?
+            .into_input_pullup();
+        Ok(IrReceiver { pin, _claim: claim })
+    }
+    /// Calls the callback from the interrupt thread for each code received.
+    ///
+    /// Replaces any callback given before.
+    pub fn on_code<C>(&mut self, mut callback: C) -> Result<()>
+    where
+        C: FnMut(NecCode) + Send + 'static,
+    {
+        let mut decoder = NecDecoder::default();
+        let mut last = Instant::now();
+        self.pin
+            .set_async_interrupt(Trigger::Both, move |level: Level| {
+                let now = Instant::now();
+                // Going high ends a mark and going low ends a space.
+                if let Some(code) = decoder.pulse(level == Level::High, now - last) {
+                    callback(code);
+                }
+                last = now;
+            })
+            .context("Failed to set IR receiver interrupt")
+    }
+    /// Gives the codes as they are received through a channel.
+    pub fn codes(&mut self) -> Result<Receiver<NecCode>> {
+        let (tx, rx) = channel();
+        self.on_code(move |code| {
+            let _ = tx.send(code);
+        })?;
+        Ok(rx)
+    }
+}
+
+/// Insure the interrupt has stopped before exiting.
+impl Drop for IrReceiver {
+    fn drop(&mut self) {
+        let _ = self.pin.clear_async_interrupt();
+    }
+}
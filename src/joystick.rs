@@ -0,0 +1,238 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Result, SuperKitError};
+use crate::{button::ButtonEvent, Adc, Button};
+
+// ADC channels used for each axis.
+const X_CHANNEL: u8 = 0;
+const Y_CHANNEL: u8 = 1;
+/// Default distance from the center, as a fraction of full travel, that is
+/// ignored.
+const DEAD_ZONE: f64 = 0.1;
+/// Default distance from the center, as a fraction of full travel, the stick
+/// has to be pushed to count as pointing in a direction.
+const THRESHOLD: f64 = 0.5;
+
+/// Which way the stick is pushed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Things a [Joystick] reports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JoystickEvent {
+    /// The stick was pushed in a new direction.
+    Moved(Direction),
+    /// The stick went back to the middle.
+    Centered,
+    /// The stick was pressed down or let go.
+    Button(ButtonEvent),
+}
+
+/// Calibration of one axis as raw ADC ratios.
+#[derive(Clone, Copy, Debug)]
+struct Axis {
+    min: f64,
+    center: f64,
+    max: f64,
+    invert: bool,
+}
+
+impl Axis {
+    /// Internal method to turn a raw ratio into -1.0 to 1.0.
+    fn position(&self, raw: f64) -> f64 {
+        let position = if raw >= self.center {
+            (raw - self.center) / (self.max - self.center).max(f64::EPSILON)
+        } else {
+            (raw - self.center) / (self.center - self.min).max(f64::EPSILON)
+        };
+        let position = position.clamp(-1.0, 1.0);
+        if self.invert {
+            -position
+        } else {
+            position
+        }
+    }
+}
+
+/// Structure used to model a two axis analog joystick with a push switch like
+/// the PS2 style one used with the kit.
+///
+/// X and Y are read from channels 0 and 1 of an [Adc] and the switch from a
+/// GPIO pin using a debounced [Button].
+/// Positions are from -1.0 to 1.0 with right and up being positive and a dead
+/// zone around the middle so a stick at rest reads as exactly 0.0.
+pub struct Joystick<A: Adc> {
+    adc: A,
+    button: Button,
+    x: Axis,
+    y: Axis,
+    dead_zone: f64,
+    threshold: f64,
+    position: (f64, f64),
+    direction: Option<Direction>,
+}
+
+impl<A: Adc> Joystick<A> {
+    /// Uses both channels of the ADC for the axes and the given pin for the
+    /// switch.
+    pub fn new(adc: A, button_pin: u8) -> Result<Self> {
+        let axis = Axis {
+            min: 0.0,
+            center: 0.5,
+            max: 1.0,
+            invert: false,
+        };
+        Ok(Joystick {
+            adc,
+            button: Button::new(button_pin)?,
+            x: axis,
+            y: axis,
+            dead_zone: DEAD_ZONE,
+            threshold: THRESHOLD,
+            position: (0.0, 0.0),
+            direction: None,
+        })
+    }
+    /// Uses the current reading as the middle, call it while the stick is
+    /// left alone.
+    pub fn calibrate_center(&mut self) -> Result<()> {
+        let (x, y) = self.read_raw()?;
+        self.x.center = x;
+        self.y.center = y;
+        Ok(())
+    }
+    /// Sets the raw ratios from 0.0 to 1.0 read at the ends of each axis for
+    /// sticks that don't reach all the way.
+    pub fn set_limits(&mut self, x: (f64, f64), y: (f64, f64)) -> Result<()> {
+        for (min, max) in [x, y].iter() {
+            if !(0.0..=1.0).contains(min) || !(0.0..=1.0).contains(max) || min >= max {
+                return Err(SuperKitError::InvalidArgument(format!(
+                    "Joystick limits {} to {} are not usable",
+                    min, max
+                )));
+            }
+        }
+        self.x.min = x.0;
+        self.x.max = x.1;
+        self.y.min = y.0;
+        self.y.max = y.1;
+        Ok(())
+    }
+    /// Flips the direction of either axis for sticks that are mounted
+    /// differently.
+    pub fn set_invert(&mut self, x: bool, y: bool) {
+        self.x.invert = x;
+        self.y.invert = y;
+    }
+    /// Changes how far from the middle, from 0.0 to 1.0, is ignored. The
+    /// default is 0.1.
+    pub fn set_dead_zone(&mut self, dead_zone: f64) {
+        self.dead_zone = dead_zone.clamp(0.0, 0.99);
+    }
+    /// Changes how far from the middle, from 0.0 to 1.0, the stick needs to be
+    /// pushed to count as a direction. The default is 0.5.
+    pub fn set_threshold(&mut self, threshold: f64) {
+        self.threshold = threshold.clamp(0.0, 1.0);
+    }
+    /// Reads the raw X and Y ratios from 0.0 to 1.0.
+    pub fn read_raw(&mut self) -> Result<(f64, f64)> {
+        Ok((
+            self.adc.read_ratio(X_CHANNEL)?,
+            self.adc.read_ratio(Y_CHANNEL)?,
+        ))
+    }
+    /// Reads the X and Y positions from -1.0 to 1.0.
+    ///
+    /// The dead zone is taken out of the middle and the rest stretched so the
+    /// stick still reaches 1.0 at the ends.
+    pub fn read(&mut self) -> Result<(f64, f64)> {
+        let (x, y) = self.read_raw()?;
+        // The modules read lower when pushed up so Y is flipped to have up be
+        // positive.
+        let (x, y) = (self.x.position(x), -self.y.position(y));
+        let distance = x.hypot(y);
+        self.position = if distance <= self.dead_zone {
+            (0.0, 0.0)
+        } else {
+            let scale = ((distance - self.dead_zone) / (1.0 - self.dead_zone)).min(1.0) / distance;
+            (x * scale, y * scale)
+        };
+        Ok(self.position)
+    }
+    /// The position from the last time the stick was read by any method
+    /// without reading it again.
+    pub fn position(&self) -> (f64, f64) {
+        self.position
+    }
+    /// Reads which way the stick is pushed, if it is pushed far enough.
+    pub fn direction(&mut self) -> Result<Option<Direction>> {
+        self.read()?;
+        Ok(self.pointing())
+    }
+    /// Checks the stick and switch and gives any change since last time.
+    ///
+    /// Needs to be called often from a loop, every 10 to 20ms is good.
+    pub fn poll(&mut self) -> Result<Option<JoystickEvent>> {
+        if let Some(event) = self.button.poll() {
+            return Ok(Some(JoystickEvent::Button(event)));
+        }
+        // Events are about the stick pointing somewhere new, the position
+        // itself is still kept up to date for position().
+        let direction = self.direction()?;
+        if direction == self.direction {
+            return Ok(None);
+        }
+        self.direction = direction;
+        Ok(Some(match direction {
+            Some(direction) => JoystickEvent::Moved(direction),
+            None => JoystickEvent::Centered,
+        }))
+    }
+    /// Checks the debounced state of the switch.
+    pub fn is_pressed(&self) -> bool {
+        self.button.is_pressed()
+    }
+    /// Internal method to work out the direction of the last position read.
+    fn pointing(&self) -> Option<Direction> {
+        let (x, y) = self.position;
+        if x.hypot(y) < self.threshold {
+            return None;
+        }
+        Some(if x.abs() > y.abs() {
+            if x > 0.0 {
+                Direction::Right
+            } else {
+                Direction::Left
+            }
+        } else if y > 0.0 {
+            Direction::Up
+        } else {
+            Direction::Down
+        })
+    }
+}
@@ -0,0 +1,446 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::gpio::{Gpio, OutputPin};
+use crate::registry::{self, PinClaim};
+use log::{debug, trace};
+use std::{fmt, thread::sleep, time::Duration};
+
+// The 4 bit data bus pins.
+const PIN_D4: u8 = 25;
+const PIN_D5: u8 = 24;
+const PIN_D6: u8 = 23;
+const PIN_D7: u8 = 18;
+// The control pins.
+const PIN_E: u8 = 22;
+const PIN_RS: u8 = 27;
+/// Number of custom characters the controller can hold.
+pub const CUSTOM_CHARS: u8 = 8;
+// HD44780 instructions.
+const CLEAR_DISPLAY: u8 = 0x01;
+const RETURN_HOME: u8 = 0x02;
+const ENTRY_MODE_SET: u8 = 0x04;
+const DISPLAY_CONTROL: u8 = 0x08;
+const CURSOR_SHIFT: u8 = 0x10;
+const FUNCTION_SET: u8 = 0x20;
+const SET_CG_RAM_ADDR: u8 = 0x40;
+const SET_DD_RAM_ADDR: u8 = 0x80;
+// Instruction flags.
+const ENTRY_INCREMENT: u8 = 0x02;
+const DISPLAY_ON: u8 = 0x04;
+const CURSOR_ON: u8 = 0x02;
+const BLINK_ON: u8 = 0x01;
+const DISPLAY_MOVE: u8 = 0x08;
+const MOVE_RIGHT: u8 = 0x04;
+const LINES_2: u8 = 0x08;
+// Most instructions take less than 40µs but some like clear display take
+// over 1.5ms.
+const SHORT_DELAY: u64 = 50;
+const LONG_DELAY: u64 = 2000;
+// Degree sign in the controller's character ROM.
+const DEGREE: u8 = 0xDF;
+
+/// The supported display sizes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Geometry {
+    /// 16 characters by 2 rows like the one in the kit.
+    Lcd16x2,
+    /// 20 characters by 4 rows.
+    Lcd20x4,
+}
+
+impl Geometry {
+    /// Number of rows on the display.
+    pub fn rows(self) -> u8 {
+        self.row_addresses().len() as u8
+    }
+    /// Number of characters in each row.
+    pub fn columns(self) -> u8 {
+        match self {
+            Geometry::Lcd16x2 => 16,
+            Geometry::Lcd20x4 => 20,
+        }
+    }
+    /// Internal method to get the display data RAM address of the start of
+    /// each row.
+    ///
+    /// The controller only has two 40 byte lines of memory so on a 20x4
+    /// display the third and fourth rows are the second halves of the first
+    /// and second lines.
+    pub(crate) fn row_addresses(self) -> &'static [u8] {
+        match self {
+            Geometry::Lcd16x2 => &[0x00, 0x40],
+            Geometry::Lcd20x4 => &[0x00, 0x40, 0x14, 0x54],
+        }
+    }
+}
+
+/// What happens to text that runs past the end of a row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Wrap {
+    /// Leave it to the controller which keeps going into memory past the edge
+    /// of the display on a 16x2 or into the third and fourth rows on a 20x4.
+    Off,
+    /// Drop anything past the end of the row.
+    Truncate,
+    /// Carry on from the start of the next row, going back to the first row
+    /// after the last one.
+    NextRow,
+}
+
+/// Structure used to model a 16x2 LCD using a HD44780 compatible controller
+/// connected with a 4 bit data bus.
+///
+/// Used in Lesson 13.
+/// A 20x4 display on the same controller also works by giving its [Geometry]
+/// when constructed.
+/// Also implements [fmt::Write] so things like
+/// `write!(lcd, "T={:.1}C", t)` work with a '\n' moving to the start of the
+/// next row.
+/// The pins are driven directly through rppal so it works on current kernels
+/// which have dropped the old sysfs GPIO interface.
+/// Like the old destroy() the pins are reset back to inputs when dropped.
+pub struct Lcd1602 {
+    rs: OutputPin,
+    e: OutputPin,
+    data: Vec<OutputPin>,
+    geometry: Geometry,
+    wrap: Wrap,
+    /// Row and column of the display the next character will be written to.
+    row: u8,
+    col: u8,
+    /// Display data RAM address the next character will be written to.
+    address: u8,
+    /// Current display, cursor, and blink flags.
+    display_control: u8,
+    _claim: PinClaim,
+}
+
+impl Lcd1602 {
+    /// Gets the GPIO pins and setup the kit's 16x2 LCD display.
+    pub fn new() -> Result<Self> {
+        Self::with_geometry(Geometry::Lcd16x2, Wrap::Off)
+    }
+    /// Gets the GPIO pins and setup a LCD display of the given size which
+    /// handles text running past the end of a row in the given way.
+    pub fn with_geometry(geometry: Geometry, wrap: Wrap) -> Result<Self> {
+        Self::with_pins(
+            PIN_RS,
+            PIN_E,
+            [PIN_D4, PIN_D5, PIN_D6, PIN_D7],
+            geometry,
+            wrap,
+        )
+    }
+    /// Same as [with_geometry()](Lcd1602::with_geometry) but with the display
+    /// wired to other pins where data is D4 to D7.
+    pub fn with_pins(rs: u8, e: u8, data: [u8; 4], geometry: Geometry, wrap: Wrap) -> Result<Self> {
+        debug!(
+            target: "lcd",
+            "rs = {}, e = {}, data = {:?}, {:?}",
+            rs,
+            e,
+            data,
+            geometry
+        );
+        let mut pins = vec![rs, e];
+        pins.extend_from_slice(&data);
+        let claim = registry::claim("Lcd1602", &pins)?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        let mut rs = gpio
+            .get(rs)
+            .context("Failed to get register select pin")?
+            .into_output();
+        rs.set_low();
+        let mut e = gpio
+            .get(e)
+            .context("Failed to get enable pin")?
+            .into_output();
+        e.set_low();
+        let pin_numbers = data;
+        let mut data = Vec::new();
+        for num in pin_numbers.iter() {
+            let mut pin = gpio
+                .get(*num)
+                .context(format!("Failed to get data pin: {}", num))?
+                .into_output();
+            pin.set_low();
+            data.push(pin);
+        }
+        #[cfg(feature = "mock")]
+        crate::simulator::attach(crate::simulator::Part::Lcd {
+            rs: rs.pin(),
+            e: e.pin(),
+            data: pin_numbers,
+            columns: geometry.columns(),
+            rows: geometry.row_addresses().to_vec(),
+        });
+        let mut lcd = Lcd1602 {
+            rs,
+            e,
+            data,
+            geometry,
+            wrap,
+            row: 0,
+            col: 0,
+            address: 0,
+            display_control: DISPLAY_ON,
+            _claim: claim,
+        };
+        lcd.init();
+        Ok(lcd)
+    }
+    /// Clears the display and moves back to the start of the first row.
+    pub fn clear(&mut self) -> Result<()> {
+        self.command(CLEAR_DISPLAY);
+        sleep(Duration::from_micros(LONG_DELAY));
+        self.row = 0;
+        self.col = 0;
+        self.address = 0;
+        Ok(())
+    }
+    /// Moves the cursor back to the start of the first row and undoes any
+    /// display shifting.
+    pub fn home(&mut self) -> Result<()> {
+        self.command(RETURN_HOME);
+        sleep(Duration::from_micros(LONG_DELAY));
+        self.row = 0;
+        self.col = 0;
+        self.address = 0;
+        Ok(())
+    }
+    /// Size of the display.
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+    /// Moves the cursor to the given row and column, both starting from 0.
+    ///
+    /// This is where the next character printed will go.
+    pub fn set_cursor(&mut self, row: u8, col: u8) -> Result<()> {
+        if row >= self.geometry.rows() || col >= self.geometry.columns() {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Position ({}, {}) is outside of the display",
+                row, col
+            )));
+        }
+        self.move_to(row, col);
+        Ok(())
+    }
+    /// Shows or hides the underline cursor.
+    pub fn show_cursor(&mut self, on: bool) -> Result<()> {
+        self.set_display_flag(CURSOR_ON, on);
+        Ok(())
+    }
+    /// Turns blinking of the character at the cursor on or off.
+    pub fn blink_cursor(&mut self, on: bool) -> Result<()> {
+        self.set_display_flag(BLINK_ON, on);
+        Ok(())
+    }
+    /// Turns the whole display on or off without losing what is on it.
+    pub fn set_display(&mut self, on: bool) -> Result<()> {
+        self.set_display_flag(DISPLAY_ON, on);
+        Ok(())
+    }
+    /// Scrolls everything on the display one position to the left.
+    ///
+    /// Each row scrolls through all 40 characters of its memory so text
+    /// written past the edge can be brought into view.
+    pub fn shift_display_left(&mut self) -> Result<()> {
+        self.command(CURSOR_SHIFT | DISPLAY_MOVE);
+        Ok(())
+    }
+    /// Scrolls everything on the display one position to the right.
+    pub fn shift_display_right(&mut self) -> Result<()> {
+        self.command(CURSOR_SHIFT | DISPLAY_MOVE | MOVE_RIGHT);
+        Ok(())
+    }
+    /// Moves to the given row and column, both starting from 0, then prints the
+    /// text from there.
+    pub fn print_at(&mut self, row: u8, col: u8, text: &str) -> Result<()> {
+        self.set_cursor(row, col)?;
+        self.print(text)
+    }
+    /// Defines one of the custom characters.
+    ///
+    /// The bitmap is one byte per row, top to bottom, using the lower 5 bits
+    /// with bit 4 being the left most pixel.
+    /// The last row is also where the cursor is shown so is usually left blank.
+    pub fn create_char(&mut self, slot: u8, bitmap: [u8; 8]) -> Result<()> {
+        if slot >= CUSTOM_CHARS {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Custom character slot {} is above the maximum of {}",
+                slot,
+                CUSTOM_CHARS - 1
+            )));
+        }
+        self.command(SET_CG_RAM_ADDR | (slot << 3));
+        for row in bitmap.iter() {
+            self.send(row & 0x1f, true);
+        }
+        // Go back to writing on the display where things were left off.
+        self.command(SET_DD_RAM_ADDR | self.address);
+        Ok(())
+    }
+    /// Prints one of the custom characters at the current position.
+    ///
+    /// They can also be included in normal text as '\x00' to '\x07'.
+    pub fn print_custom(&mut self, slot: u8) -> Result<()> {
+        if slot >= CUSTOM_CHARS {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Custom character slot {} is above the maximum of {}",
+                slot,
+                CUSTOM_CHARS - 1
+            )));
+        }
+        self.write_byte(slot);
+        Ok(())
+    }
+    /// Prints text at the current position.
+    ///
+    /// A '\n' moves to the start of the next row.
+    pub fn print(&mut self, text: &str) -> Result<()> {
+        let mut lines = text.split('\n');
+        if let Some(first) = lines.next() {
+            self.write_bytes(first);
+        }
+        for line in lines {
+            self.move_to((self.row + 1) % self.geometry.rows(), 0);
+            self.write_bytes(line);
+        }
+        Ok(())
+    }
+    /// Internal method to change one of the display control flags.
+    fn set_display_flag(&mut self, flag: u8, on: bool) {
+        if on {
+            self.display_control |= flag;
+        } else {
+            self.display_control &= !flag;
+        }
+        self.command(DISPLAY_CONTROL | self.display_control);
+    }
+    /// Internal method to move to a position already known to be on the
+    /// display.
+    fn move_to(&mut self, row: u8, col: u8) {
+        self.row = row;
+        self.col = col;
+        self.address = self.geometry.row_addresses()[row as usize] + col;
+        self.command(SET_DD_RAM_ADDR | self.address);
+    }
+    /// Internal method to write text without any special handling.
+    ///
+    /// Each character takes one cell, the controller's ROM only has ASCII and
+    /// a degree sign so anything else shows as '?'.
+    fn write_bytes(&mut self, text: &str) {
+        for c in text.chars() {
+            let byte = match c {
+                '°' => DEGREE,
+                c if c.is_ascii() => c as u8,
+                _ => b'?',
+            };
+            self.write_byte(byte);
+        }
+    }
+    /// Internal method to write a single character and keep track of where the
+    /// controller will put the next one.
+    fn write_byte(&mut self, byte: u8) {
+        if self.col >= self.geometry.columns() {
+            match self.wrap {
+                Wrap::Off => {}
+                Wrap::Truncate => return,
+                Wrap::NextRow => self.move_to((self.row + 1) % self.geometry.rows(), 0),
+            }
+        }
+        self.send(byte, true);
+        self.col = self.col.saturating_add(1);
+        // Each row has 40 bytes of memory and the end of one wraps around to
+        // the start of the other.
+        self.address = match self.address + 1 {
+            0x28 => 0x40,
+            0x68 => 0x00,
+            next => next,
+        };
+    }
+    /// Internal method that does the power on sequence to get the controller
+    /// into 4 bit mode then sets up the display.
+    fn init(&mut self) {
+        // Give the display time to power up.
+        sleep(Duration::from_millis(50));
+        // The controller could be in 8 bit mode or half way through a 4 bit
+        // transfer so first force it into 8 bit mode.
+        self.write_nibble(0x03);
+        sleep(Duration::from_micros(4500));
+        self.write_nibble(0x03);
+        sleep(Duration::from_micros(SHORT_DELAY * 3));
+        self.write_nibble(0x03);
+        sleep(Duration::from_micros(SHORT_DELAY * 3));
+        // Now switch to 4 bit mode.
+        self.write_nibble(0x02);
+        sleep(Duration::from_micros(SHORT_DELAY));
+        self.command(FUNCTION_SET | LINES_2);
+        self.command(DISPLAY_CONTROL | self.display_control);
+        self.command(CLEAR_DISPLAY);
+        sleep(Duration::from_micros(LONG_DELAY));
+        self.command(ENTRY_MODE_SET | ENTRY_INCREMENT);
+    }
+    /// Internal method to send an instruction.
+    fn command(&mut self, instruction: u8) {
+        self.send(instruction, false);
+    }
+    /// Internal method to send a byte as two nibbles, high one first.
+    ///
+    /// Register select is high for data and low for instructions.
+    fn send(&mut self, byte: u8, data: bool) {
+        if data {
+            trace!(target: "lcd", "data {:#04x}", byte);
+        } else {
+            trace!(target: "lcd", "instruction {:#010b}", byte);
+        }
+        if data {
+            self.rs.set_high();
+        } else {
+            self.rs.set_low();
+        }
+        self.write_nibble(byte >> 4);
+        self.write_nibble(byte & 0x0f);
+        sleep(Duration::from_micros(SHORT_DELAY));
+    }
+    /// Internal method to put a nibble on the data bus and strobe it in.
+    fn write_nibble(&mut self, nibble: u8) {
+        for (bit, pin) in self.data.iter_mut().enumerate() {
+            if nibble & (1 << bit) != 0 {
+                pin.set_high();
+            } else {
+                pin.set_low();
+            }
+        }
+        self.e.set_high();
+        sleep(Duration::from_micros(1));
+        self.e.set_low();
+    }
+}
+
+impl fmt::Write for Lcd1602 {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.print(s).map_err(|_| fmt::Error)
+    }
+}
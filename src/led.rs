@@ -0,0 +1,144 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::gpio::{Gpio, OutputPin};
+use crate::pwm::{PwmOutput, SOFT_FREQUENCY};
+use crate::registry::{self, PinClaim};
+use std::time::Duration;
+
+/// Structure used to model a single LED on a GPIO pin.
+///
+/// Hides which level turns the LED on so code can just say `on()` or `off()`.
+/// The LEDs in lessons 1 through 3 are wired from 3.3V through the LED to the
+/// pin so they are active low and light up when the pin is low.
+pub struct Led {
+    pin: OutputPin,
+    active_low: bool,
+    lit: bool,
+    _claim: PinClaim,
+}
+
+impl Led {
+    /// Gets the GPIO pin for a LED that lights up when the pin is high and
+    /// starts with it off.
+    pub fn new(pin: u8) -> Result<Self> {
+        Self::with_polarity(pin, false)
+    }
+    /// Gets the GPIO pin for a LED that lights up when the pin is low, like
+    /// the ones in the early lessons, and starts with it off.
+    pub fn active_low(pin: u8) -> Result<Self> {
+        Self::with_polarity(pin, true)
+    }
+    /// Turns the LED on and stops any blinking.
+    pub fn on(&mut self) -> Result<()> {
+        self.set(true)
+    }
+    /// Turns the LED off and stops any blinking.
+    pub fn off(&mut self) -> Result<()> {
+        self.set(false)
+    }
+    /// Turns the LED on if it is off and off if it is on.
+    pub fn toggle(&mut self) -> Result<()> {
+        self.set(!self.lit)
+    }
+    /// Turns the LED on or off and stops any blinking.
+    pub fn set(&mut self, on: bool) -> Result<()> {
+        self.pin
+            .clear_pwm()
+            .context("Failed to stop LED blinking")?;
+        if on != self.active_low {
+            self.pin.set_high();
+        } else {
+            self.pin.set_low();
+        }
+        self.lit = on;
+        Ok(())
+    }
+    /// Checks if the LED was last turned on.
+    ///
+    /// A blinking LED counts as on.
+    pub fn is_on(&self) -> bool {
+        self.lit
+    }
+    /// Starts the LED blinking in the background, once per period, lit for the
+    /// given fraction of each period from 0.0 to 1.0.
+    ///
+    /// Keeps going until the LED is turned on or off.
+    pub fn blink(&mut self, period: Duration, duty: f64) -> Result<()> {
+        if !(0.0..=1.0).contains(&duty) {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Blink duty {} is outside of 0.0 to 1.0",
+                duty
+            )));
+        }
+        // The pulse is the time the pin is high.
+        let high = if self.active_low { 1.0 - duty } else { duty };
+        self.pin
+            .set_pwm(period, period.mul_f64(high))
+            .context("Failed to start LED blinking")?;
+        self.lit = true;
+        Ok(())
+    }
+    /// Internal associative method (function) that does the real work of
+    /// getting the pin.
+    fn with_polarity(pin: u8, active_low: bool) -> Result<Self> {
+        let claim = registry::claim("Led", &[pin])?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        let pin = gpio
+            .get(pin)
+            .context(format!("Failed to get led pin: {}", pin))?
+            .into_output();
+        let mut led = Led {
+            pin,
+            active_low,
+            lit: false,
+            _claim: claim,
+        };
+        led.off()?;
+        Ok(led)
+    }
+}
+
+/// Dims the LED using software PWM taking active low into account.
+impl PwmOutput for Led {
+    fn set_duty_cycle(&mut self, duty_cycle: f64) -> Result<()> {
+        let duty_cycle = duty_cycle.clamp(0.0, 1.0);
+        let high = if self.active_low {
+            1.0 - duty_cycle
+        } else {
+            duty_cycle
+        };
+        self.pin
+            .set_pwm_frequency(SOFT_FREQUENCY, high)
+            .context("Failed to change LED duty cycle")?;
+        self.lit = duty_cycle > 0.0;
+        Ok(())
+    }
+}
+
+/// Insure the LED is off before exiting.
+impl Drop for Led {
+    fn drop(&mut self) {
+        let _ = self.off();
+    }
+}
@@ -0,0 +1,104 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Result, SuperKitError};
+use crate::{Led, Pattern};
+use std::{thread::sleep, time::Duration};
+
+// Gpio pin numbers used in lesson 3.
+const PINS: [u8; 8] = [17, 18, 27, 22, 23, 24, 25, 4];
+/// Number of LEDs in the bar.
+pub const LEDS: u8 = 8;
+
+/// Structure used to model the row of 8 LEDs from lesson 3 as a bar graph.
+///
+/// LED 0 is the first pin and bit 0 of a mask.
+/// Handy as a VU meter or progress indicator for other lessons.
+pub struct LedBar {
+    leds: Vec<Led>,
+    mask: u8,
+}
+
+impl LedBar {
+    /// Gets the lesson's pins with all the LEDs off.
+    pub fn new() -> Result<Self> {
+        Self::with_pins(PINS)
+    }
+    /// Gets the given pins, first LED first, with all the LEDs off.
+    ///
+    /// The LEDs are taken to be active low like in the lesson.
+    pub fn with_pins(pins: [u8; LEDS as usize]) -> Result<Self> {
+        let leds = pins
+            .iter()
+            .map(|pin| Led::active_low(*pin))
+            .collect::<Result<Vec<Led>>>()?;
+        #[cfg(feature = "mock")]
+        crate::simulator::attach(crate::simulator::Part::LedBar(pins));
+        Ok(LedBar { leds, mask: 0 })
+    }
+    /// Which LEDs are currently on.
+    pub fn mask(&self) -> u8 {
+        self.mask
+    }
+    /// Turns on exactly the LEDs whose bits are set.
+    pub fn set_mask(&mut self, mask: u8) -> Result<()> {
+        for (bit, led) in self.leds.iter_mut().enumerate() {
+            let on = mask & (1 << bit) != 0;
+            // Only touch the LEDs that change.
+            if on != (self.mask & (1 << bit) != 0) {
+                led.set(on)?;
+            }
+        }
+        self.mask = mask;
+        Ok(())
+    }
+    /// Lights the given number of LEDs starting from LED 0.
+    pub fn set_level(&mut self, level: u8) -> Result<()> {
+        if level > LEDS {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Level {} is above the maximum of {}",
+                level, LEDS
+            )));
+        }
+        self.set_mask((0xffu16 >> (LEDS - level)) as u8)
+    }
+    /// Lights the part of the bar matching a percentage from 0.0 to 100.0,
+    /// rounded to the nearest LED and clamped to that range.
+    pub fn set_percent(&mut self, percent: f32) -> Result<()> {
+        let level = (percent.clamp(0.0, 100.0) / 100.0 * f32::from(LEDS)).round();
+        self.set_level(level as u8)
+    }
+    /// Turns all the LEDs off.
+    pub fn clear(&mut self) -> Result<()> {
+        self.set_mask(0)
+    }
+    /// Shows one cycle of a pattern with each frame lasting the given time.
+    ///
+    /// Call it in a loop to keep the pattern going.
+    pub fn play(&mut self, pattern: &Pattern, step: Duration) -> Result<()> {
+        for frame in pattern.frames() {
+            self.set_mask(frame)?;
+            sleep(step);
+        }
+        Ok(())
+    }
+}
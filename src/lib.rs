@@ -20,77 +20,171 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use anyhow::{Context, Result};
-use rppal::gpio::{Gpio, OutputPin};
-use std::thread::sleep;
-use std::time::Duration;
+#[cfg(feature = "analog")]
+pub mod adc;
+#[cfg(feature = "i2c-sensors")]
+pub mod adxl345;
+#[cfg(feature = "shift-register")]
+pub mod animation;
+#[cfg(feature = "i2c-sensors")]
+pub mod bmp280;
+pub mod breather;
+pub mod bus;
+pub mod button;
+pub mod buzzer;
+pub mod chaser;
+pub mod cli;
+pub mod color;
+pub mod config;
+pub mod daemon;
+#[cfg(feature = "network")]
+pub mod dashboard;
+pub mod datalog;
+pub mod dht11;
+#[cfg(feature = "shift-register")]
+pub mod dot_matrix;
+pub mod ds18b20;
+pub mod easing;
+pub mod encoder;
+pub mod error;
+#[cfg(feature = "shift-register")]
+pub mod font;
+pub mod frequency;
+pub mod hal;
+#[cfg(feature = "analog")]
+pub mod hazard;
+#[cfg(feature = "shift-register")]
+pub mod hc595;
+pub mod ir;
+#[cfg(feature = "analog")]
+pub mod joystick;
+#[cfg(feature = "lcd")]
+pub mod lcd1602;
+pub mod led;
+pub mod led_bar;
+#[cfg(feature = "analog")]
+pub mod light;
+#[cfg(feature = "shift-register")]
+pub mod max7219;
+#[cfg(feature = "lcd")]
+pub mod menu;
+#[cfg(feature = "analog")]
+pub mod moisture;
+pub mod motor;
+#[cfg(feature = "i2c-sensors")]
+pub mod mpu6050;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+pub mod neopixel;
+pub mod pwm;
+pub mod rc522;
+pub mod reed;
+pub mod registry;
+#[cfg(feature = "network")]
+pub mod rest;
+pub mod rgb;
+pub mod rtc;
+pub mod runner;
+pub mod schedule;
+pub mod script;
+#[cfg(feature = "shift-register")]
+pub mod segment;
+pub mod servo;
+pub mod session;
+#[cfg(feature = "shell")]
+pub mod shell;
+#[cfg(feature = "mock")]
+pub mod simulator;
+#[cfg(feature = "analog")]
+pub mod sound;
+pub mod stepper;
+pub mod systemd;
+#[cfg(feature = "analog")]
+pub mod thermistor;
+pub mod tilt;
+pub mod touch;
+pub mod ultrasonic;
 
-const SDI: u8 = 17;
-const RCLK: u8 = 18;
-const SRCLK: u8 = 27;
+// Used by say!() so lessons don't need their own log dependency.
+#[doc(hidden)]
+pub use log;
 
-/// Structure used to model a 74HC595 8-Bit Shift Register chip.
-///
-/// Used in Lessons 10, 11, and 12.
-pub struct HC595 {
-    sdi: OutputPin,
-    rclk: OutputPin,
-    srclk: OutputPin,
-}
-
-impl HC595 {
-    /// Takes place of setup() from Python code.
-    pub fn new() -> Result<Self> {
-        let gpio = Gpio::new().context("Failed to get GPIO instance")?;
-        let mut sdi = gpio
-            .get(SDI)
-            .context("Failed to get sdi pin")?
-            .into_output();
-        sdi.set_low();
-        let mut rclk = gpio
-            .get(RCLK)
-            .context("Failed to get rclk pin")?
-            .into_output();
-        rclk.set_low();
-        let mut srclk = gpio
-            .get(SRCLK)
-            .context("Failed to get srclk pin")?
-            .into_output();
-        srclk.set_low();
-        Ok(HC595 { sdi, rclk, srclk })
-    }
-    /// Some function as hc595_in() from Python code.
-    pub fn serial_in(&mut self, data: u8) {
-        // Switch from bit shifting data around to iterating pre-calculated mask
-        // values.
-        for mask in ([0x80, 0x40, 0x20, 0x10, 0x08, 0x04, 0x02, 0x01]).iter() {
-            if data & mask > 0 {
-                self.sdi.set_high();
-            } else {
-                self.sdi.set_low();
-            }
-            // Strobe shift register clock.
-            self.srclk.set_high();
-            sleep(Duration::from_micros(1));
-            self.srclk.set_low();
-        }
-    }
-    /// Same as hc595_out() function from Python code.
-    pub fn parallel_out(&mut self) {
-        // Strobe output latch clock.
-        self.rclk.set_high();
-        sleep(Duration::from_micros(1));
-        self.rclk.set_low();
-    }
-}
-
-/// Insure output on 75HC595 is all zero (off) before exiting.
-impl Drop for HC595 {
-    fn drop(&mut self) {
-        self.serial_in(0);
-        self.parallel_out();
-        self.sdi.set_low();
-        self.rclk.set_low();
-        self.srclk.set_low();
-    }
-}
+#[cfg(feature = "analog")]
+pub use adc::{Adc, Adc0832};
+#[cfg(feature = "i2c-sensors")]
+pub use adxl345::Adxl345;
+#[cfg(feature = "shift-register")]
+pub use animation::Animation;
+#[cfg(feature = "i2c-sensors")]
+pub use bmp280::Bmp280;
+pub use breather::Breather;
+pub use bus::{Event, EventBus, ThresholdWatcher};
+pub use button::{Button, ButtonEvent, Gesture, GestureDetector};
+pub use buzzer::{ActiveBuzzer, Alarm, Buzzer, Note};
+pub use chaser::Pattern;
+pub use color::Color;
+pub use config::Config;
+pub use daemon::Daemon;
+#[cfg(feature = "network")]
+pub use dashboard::Dashboard;
+pub use datalog::DataLog;
+pub use dht11::Dht11;
+#[cfg(feature = "shift-register")]
+pub use dot_matrix::{Blend, DotMatrix8x8, FrameBuffer, MatrixDisplay};
+pub use ds18b20::Ds18b20;
+pub use easing::Easing;
+pub use encoder::RotaryEncoder;
+pub use error::{Result, SuperKitError};
+pub use frequency::FrequencyCounter;
+#[cfg(feature = "analog")]
+pub use hazard::{FlameSensor, GasSensor};
+#[cfg(feature = "shift-register")]
+pub use hc595::{SharedHC595, HC595};
+pub use ir::{IrReceiver, NecCode};
+#[cfg(feature = "analog")]
+pub use joystick::Joystick;
+#[cfg(feature = "lcd")]
+pub use lcd1602::Lcd1602;
+pub use led::Led;
+pub use led_bar::LedBar;
+#[cfg(feature = "analog")]
+pub use light::LightSensor;
+#[cfg(feature = "shift-register")]
+pub use max7219::Max7219;
+#[cfg(feature = "lcd")]
+pub use menu::{Item, Menu, MenuEvent};
+#[cfg(feature = "analog")]
+pub use moisture::MoistureSensor;
+pub use motor::{DcMotor, DifferentialDrive};
+#[cfg(feature = "i2c-sensors")]
+pub use mpu6050::{ComplementaryFilter, Mpu6050};
+#[cfg(feature = "mqtt")]
+pub use mqtt::Mqtt;
+pub use neopixel::NeoPixel;
+pub use pwm::{OutputBank, PwmOutput, SoftPwm};
+pub use rc522::{Rc522, Uid, Whitelist};
+pub use reed::{DoorEvent, ReedSwitch};
+pub use registry::{PinClaim, PinRegistry};
+#[cfg(feature = "network")]
+pub use rest::RestApi;
+pub use rgb::RgbPwm;
+pub use rtc::{Ds1302, Ds3231, Rtc};
+pub use runner::{run_lesson, CancelToken, LessonContext};
+pub use schedule::Scheduler;
+pub use script::Script;
+#[cfg(feature = "shift-register")]
+pub use segment::SegmentDisplay;
+pub use servo::Servo;
+#[cfg(feature = "mock")]
+pub use session::Recorder;
+pub use session::{PinEvent, Session};
+#[cfg(feature = "shell")]
+pub use shell::Shell;
+#[cfg(feature = "analog")]
+pub use sound::SoundSensor;
+pub use stepper::Stepper;
+#[cfg(feature = "analog")]
+pub use thermistor::Thermistor;
+pub use tilt::{Orientation, TiltSwitch};
+pub use touch::{TouchEvent, TouchSensor};
+pub use ultrasonic::Ultrasonic;
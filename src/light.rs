@@ -0,0 +1,92 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Result, SuperKitError};
+use crate::Adc;
+
+/// Structure used to model a photoresistor (LDR) in a voltage divider read by
+/// one channel of an [Adc].
+///
+/// The ADC is passed in for each reading so other sensors can share its other
+/// channels.
+/// Readings are calibrated between a dark and a bright raw ratio which by
+/// default are 0.0 and 1.0. If the light level goes down as the light goes up
+/// the divider is the other way round to what is expected, just calibrate it
+/// and the two get swapped.
+#[derive(Clone, Copy, Debug)]
+pub struct LightSensor {
+    channel: u8,
+    dark: f64,
+    bright: f64,
+}
+
+impl LightSensor {
+    /// Makes a sensor on the given ADC channel with the default calibration.
+    pub fn new(channel: u8) -> Self {
+        LightSensor {
+            channel,
+            dark: 0.0,
+            bright: 1.0,
+        }
+    }
+    /// The ADC channel being read.
+    pub fn channel(&self) -> u8 {
+        self.channel
+    }
+    /// Sets the raw ratios from 0.0 to 1.0 read in the dark and in bright
+    /// light.
+    pub fn set_calibration(&mut self, dark: f64, bright: f64) -> Result<()> {
+        if !(0.0..=1.0).contains(&dark) || !(0.0..=1.0).contains(&bright) {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Light calibration {} to {} is outside of 0.0 to 1.0",
+                dark, bright
+            )));
+        }
+        if (dark - bright).abs() < f64::EPSILON {
+            return Err(SuperKitError::InvalidArgument(
+                "Light calibration needs dark and bright to be different".to_string(),
+            ));
+        }
+        self.dark = dark;
+        self.bright = bright;
+        Ok(())
+    }
+    /// Uses the current reading as what dark looks like.
+    pub fn calibrate_dark<A: Adc>(&mut self, adc: &mut A) -> Result<()> {
+        let raw = self.read_raw(adc)?;
+        self.set_calibration(raw, self.bright)
+    }
+    /// Uses the current reading as what bright light looks like.
+    pub fn calibrate_bright<A: Adc>(&mut self, adc: &mut A) -> Result<()> {
+        let raw = self.read_raw(adc)?;
+        self.set_calibration(self.dark, raw)
+    }
+    /// Reads the raw ratio from the ADC from 0.0 to 1.0.
+    pub fn read_raw<A: Adc>(&self, adc: &mut A) -> Result<f64> {
+        adc.read_ratio(self.channel)
+    }
+    /// Reads the light level from 0.0 (dark) to 100.0 (bright) percent.
+    pub fn read<A: Adc>(&self, adc: &mut A) -> Result<f64> {
+        let raw = self.read_raw(adc)?;
+        Ok(((raw - self.dark) / (self.bright - self.dark) * 100.0).clamp(0.0, 100.0))
+    }
+}
@@ -0,0 +1,139 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::dot_matrix::{FrameBuffer, MatrixDisplay};
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::spi::{Bus, Mode, SlaveSelect, Spi};
+
+// SPI clock speed in Hz. The MAX7219 can go up to 10MHz.
+const CLOCK_SPEED: u32 = 8_000_000;
+// Register addresses.
+const REG_DIGIT0: u8 = 0x01;
+const REG_DECODE_MODE: u8 = 0x09;
+const REG_INTENSITY: u8 = 0x0a;
+const REG_SCAN_LIMIT: u8 = 0x0b;
+const REG_SHUTDOWN: u8 = 0x0c;
+const REG_DISPLAY_TEST: u8 = 0x0f;
+/// Highest brightness level accepted by [Max7219::set_intensity()].
+pub const MAX_INTENSITY: u8 = 15;
+
+/// Structure used to model one or more cascaded MAX7219 8x8 LED matrix
+/// modules connected to SPI0.
+///
+/// An alternative to the 74HC595 based matrix from lesson 12 that does its own
+/// scanning so it never flickers.
+/// Module 0 is the left most which is the last one in the chain from the
+/// Raspberry Pi.
+/// Bit 7 of each digit register is taken to be the left most column as it is
+/// on most of the common modules.
+pub struct Max7219 {
+    spi: Spi,
+    frame: FrameBuffer,
+}
+
+impl Max7219 {
+    /// Opens SPI0 using CE0 and sets up the given number of cascaded modules.
+    pub fn new(modules: usize) -> Result<Self> {
+        if modules == 0 {
+            return Err(SuperKitError::InvalidArgument(
+                "Need at least one MAX7219 module".to_string(),
+            ));
+        }
+        let spi = Spi::new(Bus::Spi0, SlaveSelect::Ss0, CLOCK_SPEED, Mode::Mode0)
+            .context("Failed to get SPI instance")?;
+        let mut max7219 = Max7219 {
+            spi,
+            frame: FrameBuffer::new(modules),
+        };
+        max7219.write_all(REG_DISPLAY_TEST, 0)?;
+        // Using raw matrix data instead of 7 segment digits.
+        max7219.write_all(REG_DECODE_MODE, 0)?;
+        max7219.write_all(REG_SCAN_LIMIT, 7)?;
+        max7219.write_all(REG_INTENSITY, MAX_INTENSITY / 2)?;
+        max7219.refresh()?;
+        max7219.shutdown(false)?;
+        Ok(max7219)
+    }
+    /// Sets the brightness of all the modules from 0 to [MAX_INTENSITY].
+    pub fn set_intensity(&mut self, intensity: u8) -> Result<()> {
+        if intensity > MAX_INTENSITY {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Intensity {} is above the maximum of {}",
+                intensity, MAX_INTENSITY
+            )));
+        }
+        self.write_all(REG_INTENSITY, intensity)
+    }
+    /// Turns the LEDs off (true) to save power or back on (false).
+    ///
+    /// The display data is kept while shut down.
+    pub fn shutdown(&mut self, shutdown: bool) -> Result<()> {
+        self.write_all(REG_SHUTDOWN, if shutdown { 0 } else { 1 })
+    }
+    /// Internal method to write the same value to a register in all modules.
+    fn write_all(&mut self, register: u8, data: u8) -> Result<()> {
+        let values = vec![data; self.frame.modules()];
+        self.write_each(register, &values)
+    }
+    /// Internal method to write a register in each module with its own value.
+    ///
+    /// Values are in module order with module 0 being sent first so it ends up
+    /// in the last chip of the chain.
+    fn write_each(&mut self, register: u8, values: &[u8]) -> Result<()> {
+        let mut buffer = Vec::with_capacity(2 * values.len());
+        for value in values.iter() {
+            buffer.push(register);
+            buffer.push(*value);
+        }
+        self.spi
+            .write(&buffer)
+            .context("Failed to write to MAX7219")?;
+        Ok(())
+    }
+}
+
+impl MatrixDisplay for Max7219 {
+    fn frame(&self) -> &FrameBuffer {
+        &self.frame
+    }
+    fn frame_mut(&mut self) -> &mut FrameBuffer {
+        &mut self.frame
+    }
+    fn refresh(&mut self) -> Result<()> {
+        let modules = self.frame.modules();
+        let images: Vec<[u8; 8]> = (0..modules).map(|m| self.frame.module_rows(m)).collect();
+        for row in 0..8 {
+            let values: Vec<u8> = images.iter().map(|rows| rows[row].reverse_bits()).collect();
+            self.write_each(REG_DIGIT0 + row as u8, &values)?;
+        }
+        Ok(())
+    }
+}
+
+/// Insure all the LEDs are off before exiting.
+impl Drop for Max7219 {
+    fn drop(&mut self) {
+        self.frame.clear();
+        let _ = self.refresh();
+        let _ = self.shutdown(true);
+    }
+}
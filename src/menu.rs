@@ -0,0 +1,302 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::Result;
+use crate::Lcd1602;
+
+// Custom character slots used for the scroll indicators.
+const UP_SLOT: u8 = 6;
+const DOWN_SLOT: u8 = 7;
+const UP_ARROW: [u8; 8] = [0x04, 0x0e, 0x1f, 0x00, 0x00, 0x00, 0x00, 0x00];
+const DOWN_ARROW: [u8; 8] = [0x00, 0x00, 0x00, 0x00, 0x1f, 0x0e, 0x04, 0x00];
+// Marks the selected item.
+const SELECTED: char = '>';
+// Shown after a submenu's label, a right arrow in the controller's character
+// set even though it is a '~' in ASCII.
+const SUBMENU: &str = "\x7e";
+
+/// What kind of thing an [Item] is.
+#[derive(Clone, Debug)]
+enum Kind {
+    Action,
+    Submenu(Vec<Item>),
+    Value {
+        value: i32,
+        min: i32,
+        max: i32,
+        step: i32,
+    },
+}
+
+/// One line in a [Menu].
+#[derive(Clone, Debug)]
+pub struct Item {
+    label: String,
+    kind: Kind,
+}
+
+impl Item {
+    /// An item that gives a [MenuEvent::Action] when selected.
+    pub fn action(label: &str) -> Self {
+        Item {
+            label: label.to_string(),
+            kind: Kind::Action,
+        }
+    }
+    /// An item that opens another list of items when selected.
+    pub fn submenu(label: &str, items: Vec<Item>) -> Self {
+        Item {
+            label: label.to_string(),
+            kind: Kind::Submenu(items),
+        }
+    }
+    /// A number from min to max that is edited by selecting it, turning the
+    /// knob, and selecting it again.
+    pub fn value(label: &str, value: i32, min: i32, max: i32) -> Self {
+        Self::value_with_step(label, value, min, max, 1)
+    }
+    /// Same as [Item::value()] but each step of the knob changes it by step.
+    pub fn value_with_step(label: &str, value: i32, min: i32, max: i32, step: i32) -> Self {
+        Item {
+            label: label.to_string(),
+            kind: Kind::Value {
+                value: value.clamp(min, max),
+                min,
+                max,
+                step: step.max(1),
+            },
+        }
+    }
+    /// The text shown for the item.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+    /// The current number if the item is a value.
+    pub fn get(&self) -> Option<i32> {
+        match self.kind {
+            Kind::Value { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+    /// Internal method to find a value anywhere below this item.
+    fn find(&self, label: &str) -> Option<i32> {
+        match &self.kind {
+            Kind::Value { value, .. } if self.label == label => Some(*value),
+            Kind::Submenu(items) => items.iter().find_map(|item| item.find(label)),
+            _ => None,
+        }
+    }
+}
+
+/// Things that happen in a [Menu] that the program needs to act on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MenuEvent {
+    /// An action item with the given label was selected.
+    Action(String),
+    /// A value item with the given label was edited and confirmed.
+    Changed { label: String, value: i32 },
+}
+
+/// A scrolling menu of actions, submenus, and values shown on a [Lcd1602].
+///
+/// Made to be driven by a [crate::RotaryEncoder] with turns going to
+/// [Menu::turn()], a click to [Menu::select()], and a long press to
+/// [Menu::back()] but any buttons will do.
+/// Uses the last column for scroll indicators drawn with custom characters 6
+/// and 7 so those can't be used for anything else while the menu is shown.
+pub struct Menu {
+    items: Vec<Item>,
+    /// Selected index and top row of each menu above the current one.
+    path: Vec<(usize, usize)>,
+    selected: usize,
+    top: usize,
+    /// Value before editing started so it can be put back.
+    editing: Option<i32>,
+    arrows: bool,
+}
+
+impl Menu {
+    /// Makes a menu starting on the first item.
+    pub fn new(items: Vec<Item>) -> Self {
+        Menu {
+            items,
+            path: Vec::new(),
+            selected: 0,
+            top: 0,
+            editing: None,
+            arrows: false,
+        }
+    }
+    /// Checks if a value is being edited.
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some()
+    }
+    /// The current number of the value item with the given label in any
+    /// submenu.
+    pub fn value(&self, label: &str) -> Option<i32> {
+        self.items.iter().find_map(|item| item.find(label))
+    }
+    /// Moves the selection, or changes the value being edited, by the given
+    /// number of steps with positive going down the list or up in value.
+    pub fn turn(&mut self, steps: i32) {
+        let selected = self.selected;
+        if self.editing.is_some() {
+            if let Some(Item {
+                kind:
+                    Kind::Value {
+                        value,
+                        min,
+                        max,
+                        step,
+                    },
+                ..
+            }) = self.items_mut().get_mut(selected)
+            {
+                let new = *value as i64 + steps as i64 * *step as i64;
+                *value = new.clamp(*min as i64, *max as i64) as i32;
+            }
+            return;
+        }
+        let last = self.items().len().saturating_sub(1) as i64;
+        self.selected = (selected as i64 + steps as i64).clamp(0, last) as usize;
+    }
+    /// Acts on the selected item.
+    ///
+    /// Actions give an event, submenus are opened, and values start or finish
+    /// being edited with an event once finished.
+    pub fn select(&mut self) -> Option<MenuEvent> {
+        let selected = self.selected;
+        let item = self.items().get(selected)?;
+        let label = item.label.clone();
+        match &item.kind {
+            Kind::Action => Some(MenuEvent::Action(label)),
+            Kind::Submenu(_) => {
+                self.path.push((selected, self.top));
+                self.selected = 0;
+                self.top = 0;
+                None
+            }
+            Kind::Value { value, .. } => {
+                let value = *value;
+                if self.editing.take().is_some() {
+                    Some(MenuEvent::Changed { label, value })
+                } else {
+                    self.editing = Some(value);
+                    None
+                }
+            }
+        }
+    }
+    /// Cancels any editing putting the old value back, or else goes back up
+    /// to the menu above.
+    pub fn back(&mut self) {
+        if let Some(old) = self.editing.take() {
+            let selected = self.selected;
+            if let Some(Item {
+                kind: Kind::Value { value, .. },
+                ..
+            }) = self.items_mut().get_mut(selected)
+            {
+                *value = old;
+            }
+        } else if let Some((selected, top)) = self.path.pop() {
+            self.selected = selected;
+            self.top = top;
+        }
+    }
+    /// Draws the menu filling the whole display.
+    pub fn render(&mut self, lcd: &mut Lcd1602) -> Result<()> {
+        if !self.arrows {
+            lcd.create_char(UP_SLOT, UP_ARROW)?;
+            lcd.create_char(DOWN_SLOT, DOWN_ARROW)?;
+            self.arrows = true;
+        }
+        let rows = lcd.geometry().rows() as usize;
+        let columns = lcd.geometry().columns() as usize;
+        // Scroll just enough to keep the selection in view.
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected >= self.top + rows {
+            self.top = self.selected + 1 - rows;
+        }
+        let len = self.items().len();
+        for row in 0..rows {
+            let index = self.top + row;
+            let mut line = match self.items().get(index) {
+                Some(item) => self.line(item, index == self.selected, columns - 1),
+                None => " ".repeat(columns - 1),
+            };
+            line.push(if row == 0 && self.top > 0 {
+                UP_SLOT as char
+            } else if row == rows - 1 && self.top + rows < len {
+                DOWN_SLOT as char
+            } else {
+                ' '
+            });
+            lcd.print_at(row as u8, 0, &line)?;
+        }
+        Ok(())
+    }
+    /// Internal method to lay out one item in the given width.
+    fn line(&self, item: &Item, selected: bool, width: usize) -> String {
+        let right = match item.kind {
+            Kind::Action => String::new(),
+            Kind::Submenu(_) => SUBMENU.to_string(),
+            Kind::Value { value, .. } if selected && self.editing.is_some() => {
+                format!("[{}]", value)
+            }
+            Kind::Value { value, .. } => value.to_string(),
+        };
+        let marker = if selected { SELECTED } else { ' ' };
+        let room = width.saturating_sub(right.chars().count() + 1);
+        let label: String = item.label.chars().take(room).collect();
+        // Cut by characters as labels aren't always ASCII.
+        format!("{}{:<room$}{}", marker, label, right, room = room)
+            .chars()
+            .take(width)
+            .collect()
+    }
+    /// Internal method to get the items of the menu currently shown.
+    fn items(&self) -> &[Item] {
+        let mut items = self.items.as_slice();
+        for (index, _) in self.path.iter() {
+            // Only submenus are ever added to the path.
+            items = match &items[*index].kind {
+                Kind::Submenu(sub) => sub,
+                _ => return &[],
+            };
+        }
+        items
+    }
+    /// Internal method to change the items of the menu currently shown.
+    fn items_mut(&mut self) -> &mut [Item] {
+        let mut items = self.items.as_mut_slice();
+        for (index, _) in self.path.iter() {
+            // Only submenus are ever added to the path.
+            items = match &mut items[*index].kind {
+                Kind::Submenu(sub) => sub,
+                _ => return &mut [],
+            };
+        }
+        items
+    }
+}
@@ -0,0 +1,203 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::Adc;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// Raw ADC ratios read with the probe dry and wet.
+///
+/// Saved as a small TOML file so calibrating only needs doing once:
+///
+/// ```toml
+/// dry = 0.82
+/// wet = 0.35
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Calibration {
+    pub dry: f64,
+    pub wet: f64,
+}
+
+/// Most resistive and capacitive probes read high when dry.
+impl Default for Calibration {
+    fn default() -> Self {
+        Calibration { dry: 1.0, wet: 0.0 }
+    }
+}
+
+impl Calibration {
+    /// Loads a calibration from a TOML file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).context(format!(
+            "Failed to read calibration file: {}",
+            path.display()
+        ))?;
+        let calibration: Calibration = toml::from_str(&text).context(format!(
+            "Failed to parse calibration file: {}",
+            path.display()
+        ))?;
+        calibration.check()?;
+        Ok(calibration)
+    }
+    /// Saves the calibration to a TOML file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let text = toml::to_string(self).context("Failed to serialize calibration")?;
+        fs::write(path, text).context(format!(
+            "Failed to write calibration file: {}",
+            path.display()
+        ))
+    }
+    /// Internal method to make sure the calibration can be used.
+    fn check(&self) -> Result<()> {
+        if !(0.0..=1.0).contains(&self.dry) || !(0.0..=1.0).contains(&self.wet) {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Moisture calibration {} to {} is outside of 0.0 to 1.0",
+                self.dry, self.wet
+            )));
+        }
+        if (self.dry - self.wet).abs() < f64::EPSILON {
+            return Err(SuperKitError::InvalidArgument(
+                "Moisture calibration needs dry and wet to be different".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Things the moisture alarm reports.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MoistureEvent {
+    /// The moisture dropped below the threshold, with the level in %.
+    TooDry(f64),
+    /// The moisture went back above the threshold plus the hysteresis, with
+    /// the level in %.
+    Recovered(f64),
+}
+
+/// Alarm settings and state.
+struct Alarm {
+    threshold: f64,
+    hysteresis: f64,
+    dry: bool,
+    callback: Box<dyn FnMut(MoistureEvent) + Send>,
+}
+
+/// Structure used to model a resistive or capacitive soil moisture probe, or
+/// a water level sensor, read by one channel of an [Adc].
+///
+/// The ADC is passed in for each reading so other sensors can share its other
+/// channels.
+pub struct MoistureSensor {
+    channel: u8,
+    calibration: Calibration,
+    alarm: Option<Alarm>,
+}
+
+impl MoistureSensor {
+    /// Makes a sensor on the given ADC channel with the default calibration.
+    pub fn new(channel: u8) -> Self {
+        MoistureSensor {
+            channel,
+            calibration: Calibration::default(),
+            alarm: None,
+        }
+    }
+    /// The ADC channel being read.
+    pub fn channel(&self) -> u8 {
+        self.channel
+    }
+    /// Gives the calibration.
+    pub fn calibration(&self) -> Calibration {
+        self.calibration
+    }
+    /// Replaces the calibration.
+    pub fn set_calibration(&mut self, calibration: Calibration) -> Result<()> {
+        calibration.check()?;
+        self.calibration = calibration;
+        Ok(())
+    }
+    /// Uses the current reading as what dry looks like.
+    pub fn calibrate_dry<A: Adc>(&mut self, adc: &mut A) -> Result<()> {
+        let dry = self.read_raw(adc)?;
+        self.set_calibration(Calibration {
+            dry,
+            ..self.calibration
+        })
+    }
+    /// Uses the current reading as what wet looks like.
+    pub fn calibrate_wet<A: Adc>(&mut self, adc: &mut A) -> Result<()> {
+        let wet = self.read_raw(adc)?;
+        self.set_calibration(Calibration {
+            wet,
+            ..self.calibration
+        })
+    }
+    /// Calls the callback when the moisture drops below the threshold percent
+    /// and again once it is back above the threshold plus the hysteresis.
+    ///
+    /// Checked each time [MoistureSensor::read()] is called.
+    /// Replaces any alarm set before.
+    pub fn set_alarm<C>(&mut self, threshold: f64, hysteresis: f64, callback: C)
+    where
+        C: FnMut(MoistureEvent) + Send + 'static,
+    {
+        self.alarm = Some(Alarm {
+            threshold,
+            hysteresis: hysteresis.abs(),
+            dry: false,
+            callback: Box::new(callback),
+        });
+    }
+    /// Removes the alarm.
+    pub fn clear_alarm(&mut self) {
+        self.alarm = None;
+    }
+    /// Checks if the alarm thinks things are too dry.
+    pub fn is_dry(&self) -> bool {
+        matches!(self.alarm, Some(Alarm { dry: true, .. }))
+    }
+    /// Reads the raw ratio from the ADC from 0.0 to 1.0.
+    pub fn read_raw<A: Adc>(&self, adc: &mut A) -> Result<f64> {
+        adc.read_ratio(self.channel)
+    }
+    /// Reads the moisture from 0.0 (dry) to 100.0 (wet) percent and checks the
+    /// alarm.
+    pub fn read<A: Adc>(&mut self, adc: &mut A) -> Result<f64> {
+        let raw = self.read_raw(adc)?;
+        let Calibration { dry, wet } = self.calibration;
+        let level = ((raw - dry) / (wet - dry) * 100.0).clamp(0.0, 100.0);
+        if let Some(alarm) = self.alarm.as_mut() {
+            if !alarm.dry && level < alarm.threshold {
+                alarm.dry = true;
+                (alarm.callback)(MoistureEvent::TooDry(level));
+            } else if alarm.dry && level >= alarm.threshold + alarm.hysteresis {
+                alarm.dry = false;
+                (alarm.callback)(MoistureEvent::Recovered(level));
+            }
+        }
+        Ok(level)
+    }
+}
@@ -0,0 +1,397 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::gpio::{Gpio, OutputPin};
+use crate::registry::{self, PinClaim};
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    thread::{sleep, spawn, JoinHandle},
+    time::{Duration, Instant},
+};
+
+// Pins used in lesson 7.
+const MOTOR_PIN1: u8 = 17;
+const MOTOR_PIN2: u8 = 18;
+const MOTOR_ENABLE: u8 = 27;
+/// Default time the motor is left coasting when it changes direction.
+const DEAD_TIME: Duration = Duration::from_millis(200);
+// PWM frequency in Hz used on the enable pin for speed control.
+const FREQUENCY: f64 = 100.0;
+// Time in milliseconds between speed changes while ramping.
+const STEP_TIME: u64 = 20;
+
+/// What the motor is being told to do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Forward,
+    Reverse,
+    /// Both motor leads are held at the same level which brakes the motor.
+    Stopped,
+    /// The driver is disabled and the motor is left to spin down on its own.
+    Coasting,
+}
+
+/// The pins and state shared with the ramp worker.
+struct Driver {
+    in1: OutputPin,
+    in2: OutputPin,
+    enable: OutputPin,
+    direction: Direction,
+    speed: f64,
+    dead_time: Duration,
+}
+
+impl Driver {
+    /// Internal method to disable the driver.
+    fn coast(&mut self) -> Result<()> {
+        self.enable
+            .clear_pwm()
+            .context("Failed to stop motor PWM")?;
+        self.enable.set_low();
+        self.direction = Direction::Coasting;
+        self.speed = 0.0;
+        Ok(())
+    }
+    /// Internal method to brake the motor.
+    fn stop(&mut self) -> Result<()> {
+        self.enable
+            .clear_pwm()
+            .context("Failed to stop motor PWM")?;
+        self.in1.set_low();
+        self.in2.set_low();
+        self.enable.set_high();
+        self.direction = Direction::Stopped;
+        self.speed = 0.0;
+        Ok(())
+    }
+    /// Internal method to run the motor at a speed from -1.0 (full reverse) to
+    /// 1.0 (full forward) with a dead time if it is reversing.
+    fn set_speed(&mut self, speed: f64) -> Result<()> {
+        let speed = speed.clamp(-1.0, 1.0);
+        let direction = if speed > 0.0 {
+            Direction::Forward
+        } else if speed < 0.0 {
+            Direction::Reverse
+        } else {
+            return self.coast();
+        };
+        if self.direction != direction {
+            if matches!(self.direction, Direction::Forward | Direction::Reverse) {
+                self.coast()?;
+                sleep(self.dead_time);
+            }
+            // Disabled while the inputs change so they are never both driven
+            // part way through.
+            self.enable
+                .clear_pwm()
+                .context("Failed to stop motor PWM")?;
+            self.enable.set_low();
+            if direction == Direction::Forward {
+                self.in2.set_low();
+                self.in1.set_high();
+            } else {
+                self.in1.set_low();
+                self.in2.set_high();
+            }
+            self.direction = direction;
+        }
+        self.enable
+            .set_pwm_frequency(FREQUENCY, speed.abs())
+            .context("Failed to change motor speed")?;
+        self.speed = speed;
+        Ok(())
+    }
+}
+
+/// A speed ramp running in the background.
+struct Ramp {
+    running: Arc<AtomicBool>,
+    worker: JoinHandle<Result<()>>,
+}
+
+/// Structure used to model a DC motor on one channel of a L293D H-bridge.
+///
+/// Used in Lesson 7.
+/// Going straight from one direction to the other lets the back EMF of the
+/// still spinning motor fight the driver so the motor is always left
+/// coasting for a short dead time in between.
+/// Speed is controlled with software PWM on the enable pin.
+pub struct DcMotor {
+    driver: Arc<Mutex<Driver>>,
+    ramp: Option<Ramp>,
+    max_acceleration: Option<f64>,
+    _claim: PinClaim,
+}
+
+impl DcMotor {
+    /// Gets the lesson's pins with the motor coasting.
+    pub fn new() -> Result<Self> {
+        Self::with_pins(MOTOR_PIN1, MOTOR_PIN2, MOTOR_ENABLE)
+    }
+    /// Gets the given input and enable pins with the motor coasting.
+    pub fn with_pins(in1: u8, in2: u8, enable: u8) -> Result<Self> {
+        let claim = registry::claim("DcMotor", &[in1, in2, enable])?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        // Enable first so the motor can't start while the inputs are set up.
+        let mut enable = gpio
+            .get(enable)
+            .context("Failed to get enable pin")?
+            .into_output();
+        enable.set_low();
+        let mut in1 = gpio
+            .get(in1)
+            .context("Failed to get motor1 pin")?
+            .into_output();
+        in1.set_low();
+        let mut in2 = gpio
+            .get(in2)
+            .context("Failed to get motor2 pin")?
+            .into_output();
+        in2.set_low();
+        let driver = Driver {
+            in1,
+            in2,
+            enable,
+            direction: Direction::Coasting,
+            speed: 0.0,
+            dead_time: DEAD_TIME,
+        };
+        Ok(DcMotor {
+            driver: Arc::new(Mutex::new(driver)),
+            ramp: None,
+            max_acceleration: None,
+            _claim: claim,
+        })
+    }
+    /// What the motor was last told to do.
+    pub fn direction(&self) -> Direction {
+        self.lock().direction
+    }
+    /// The current speed from -1.0 (full reverse) to 1.0 (full forward).
+    pub fn speed(&self) -> f64 {
+        self.lock().speed
+    }
+    /// Changes how long the motor coasts when it changes direction. The
+    /// default is 200ms.
+    pub fn set_dead_time(&mut self, dead_time: Duration) {
+        self.lock().dead_time = dead_time;
+    }
+    /// Limits how quickly ramps can change the speed in full speed per
+    /// second. Ramps that would be quicker are stretched out to fit.
+    ///
+    /// None, the default, means no limit.
+    pub fn set_max_acceleration(&mut self, max_acceleration: Option<f64>) -> Result<()> {
+        if let Some(limit) = max_acceleration {
+            if !limit.is_finite() || limit <= 0.0 {
+                return Err(SuperKitError::InvalidArgument(format!(
+                    "Maximum acceleration {} has to be above zero",
+                    limit
+                )));
+            }
+        }
+        self.max_acceleration = max_acceleration;
+        Ok(())
+    }
+    /// Runs the motor forward at full speed, or clockwise in the lesson.
+    pub fn forward(&mut self) -> Result<()> {
+        self.set_speed(1.0)
+    }
+    /// Runs the motor in reverse at full speed, or counter-clockwise in the
+    /// lesson.
+    pub fn reverse(&mut self) -> Result<()> {
+        self.set_speed(-1.0)
+    }
+    /// Runs the motor at a speed from -1.0 (full reverse) to 1.0 (full
+    /// forward) straight away with 0.0 leaving it coasting.
+    pub fn set_speed(&mut self, speed: f64) -> Result<()> {
+        self.stop_ramp()?;
+        self.lock().set_speed(speed)
+    }
+    /// Brakes the motor by shorting its leads together through the driver.
+    pub fn stop(&mut self) -> Result<()> {
+        // Stop the motor even if the ramp had failed.
+        let ramp = self.stop_ramp();
+        self.lock().stop()?;
+        ramp
+    }
+    /// Disables the driver so the motor spins down on its own like the
+    /// lesson's "stopped".
+    pub fn coast(&mut self) -> Result<()> {
+        // Stop the motor even if the ramp had failed.
+        let ramp = self.stop_ramp();
+        self.lock().coast()?;
+        ramp
+    }
+    /// Starts smoothly changing the speed to a new one over the given time.
+    ///
+    /// Small motors can draw enough current starting at full speed to brown
+    /// out the Pi so this is a kinder way to get going.
+    /// The ramp runs in the background so this returns straight away.
+    /// Use [DcMotor::wait_ramp()] to wait for it to finish.
+    pub fn ramp_to(&mut self, speed: f64, duration: Duration) -> Result<()> {
+        if !speed.is_finite() {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Motor speed {} has to be a number",
+                speed
+            )));
+        }
+        self.stop_ramp()?;
+        let to = speed.clamp(-1.0, 1.0);
+        let from = self.speed();
+        let mut duration = duration;
+        if let Some(limit) = self.max_acceleration {
+            let needed = (to - from).abs() / limit;
+            let needed = Duration::try_from_secs_f64(needed).map_err(|_| {
+                SuperKitError::InvalidArgument(format!(
+                    "Ramp would take too long at a maximum acceleration of {}",
+                    limit
+                ))
+            })?;
+            duration = duration.max(needed);
+        }
+        let running = Arc::new(AtomicBool::new(true));
+        let r = running.clone();
+        let driver = self.driver.clone();
+        let worker = spawn(move || {
+            let start = Instant::now();
+            while r.load(Ordering::SeqCst) {
+                let elapsed = start.elapsed();
+                if elapsed >= duration {
+                    break;
+                }
+                let t = elapsed.as_secs_f64() / duration.as_secs_f64();
+                driver
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .set_speed(from + (to - from) * t)?;
+                sleep(Duration::from_millis(STEP_TIME));
+            }
+            if r.load(Ordering::SeqCst) {
+                driver
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .set_speed(to)?;
+            }
+            Ok(())
+        });
+        self.ramp = Some(Ramp { running, worker });
+        Ok(())
+    }
+    /// Checks if a ramp is still running.
+    pub fn is_ramping(&self) -> bool {
+        matches!(&self.ramp, Some(ramp) if !ramp.worker.is_finished())
+    }
+    /// Waits for any running ramp to finish.
+    pub fn wait_ramp(&mut self) -> Result<()> {
+        match self.ramp.take() {
+            Some(ramp) => ramp
+                .worker
+                .join()
+                .map_err(|_| SuperKitError::ThreadPanic("Motor ramp".to_string()))?,
+            None => Ok(()),
+        }
+    }
+    /// Stops any running ramp leaving the motor at whatever speed it got to.
+    pub fn stop_ramp(&mut self) -> Result<()> {
+        if let Some(ramp) = &self.ramp {
+            ramp.running.store(false, Ordering::SeqCst);
+        }
+        self.wait_ramp()
+    }
+    /// Internal method used to get at the driver shared with the ramp worker.
+    fn lock(&self) -> MutexGuard<'_, Driver> {
+        self.driver.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Insure the motor is left coasting before exiting.
+impl Drop for DcMotor {
+    fn drop(&mut self) {
+        let _ = self.coast();
+    }
+}
+
+/// Two DC motors on the two channels of a L293D steering a small robot by
+/// running its left and right wheels at different speeds.
+pub struct DifferentialDrive {
+    left: DcMotor,
+    right: DcMotor,
+}
+
+impl DifferentialDrive {
+    /// Takes the motors for the left and right wheels.
+    pub fn new(left: DcMotor, right: DcMotor) -> Self {
+        DifferentialDrive { left, right }
+    }
+    /// Drives with a forward speed and a turning rate both from -1.0 to 1.0.
+    ///
+    /// A positive turning rate turns to the left (counter-clockwise).
+    /// With both at 1.0 the left wheel stops and the right one runs flat out
+    /// and a turning rate by itself spins the robot on the spot.
+    /// When the mix asks for more than full speed from a wheel both are scaled
+    /// back together so the robot still follows the same curve.
+    pub fn drive(&mut self, linear: f64, angular: f64) -> Result<()> {
+        let (left, right) = Self::mix(linear, angular);
+        self.left.set_speed(left)?;
+        self.right.set_speed(right)
+    }
+    /// Same as [DifferentialDrive::drive()] but ramps both wheels to their new
+    /// speeds over the given time and waits for them to get there.
+    pub fn ramp_drive(&mut self, linear: f64, angular: f64, duration: Duration) -> Result<()> {
+        let (left, right) = Self::mix(linear, angular);
+        self.left.ramp_to(left, duration)?;
+        self.right.ramp_to(right, duration)?;
+        self.left.wait_ramp()?;
+        self.right.wait_ramp()
+    }
+    /// Brakes both wheels.
+    pub fn stop(&mut self) -> Result<()> {
+        let left = self.left.stop();
+        self.right.stop()?;
+        left
+    }
+    /// Lets both wheels spin down on their own.
+    pub fn coast(&mut self) -> Result<()> {
+        let left = self.left.coast();
+        self.right.coast()?;
+        left
+    }
+    /// The left wheel's motor.
+    pub fn left(&mut self) -> &mut DcMotor {
+        &mut self.left
+    }
+    /// The right wheel's motor.
+    pub fn right(&mut self) -> &mut DcMotor {
+        &mut self.right
+    }
+    /// Internal associative method (function) to work out the wheel speeds.
+    fn mix(linear: f64, angular: f64) -> (f64, f64) {
+        let linear = linear.clamp(-1.0, 1.0);
+        let angular = angular.clamp(-1.0, 1.0);
+        let left = linear - angular;
+        let right = linear + angular;
+        let scale = left.abs().max(right.abs()).max(1.0);
+        (left / scale, right / scale)
+    }
+}
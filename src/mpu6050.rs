@@ -0,0 +1,310 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::i2c::I2c;
+use std::{
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+/// Default I2C address with AD0 low.
+pub const ADDRESS: u16 = 0x68;
+// Registers.
+const SMPLRT_DIV: u8 = 0x19;
+const CONFIG: u8 = 0x1a;
+const GYRO_CONFIG: u8 = 0x1b;
+const ACCEL_CONFIG: u8 = 0x1c;
+const ACCEL_XOUT_H: u8 = 0x3b;
+const PWR_MGMT_1: u8 = 0x6b;
+const WHO_AM_I: u8 = 0x75;
+// What WHO_AM_I reads back, it doesn't change with AD0.
+const IDENTITY: u8 = 0x68;
+// Wakes the chip using the X gyro as the clock which is more stable than the
+// internal oscillator.
+const CLOCK_PLL_XGYRO: u8 = 0x01;
+// Sets the digital low pass filter to 44Hz to take the edge off vibration.
+const DLPF_44HZ: u8 = 0x03;
+// Time between gyro samples used during calibration.
+const CALIBRATION_DELAY: Duration = Duration::from_millis(5);
+/// Default weight given to the gyro by [ComplementaryFilter].
+pub const ALPHA: f64 = 0.98;
+
+/// Full scale range of the accelerometer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccelRange {
+    G2,
+    G4,
+    G8,
+    G16,
+}
+
+impl AccelRange {
+    /// Internal method to get the ACCEL_CONFIG bits.
+    fn bits(self) -> u8 {
+        (self as u8) << 3
+    }
+    /// How many counts make 1g.
+    pub fn lsb_per_g(self) -> f64 {
+        16384.0 / f64::from(1u8 << self as u8)
+    }
+}
+
+/// Full scale range of the gyro in degrees per second.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GyroRange {
+    Dps250,
+    Dps500,
+    Dps1000,
+    Dps2000,
+}
+
+impl GyroRange {
+    /// Internal method to get the GYRO_CONFIG bits.
+    fn bits(self) -> u8 {
+        (self as u8) << 3
+    }
+    /// How many counts make 1°/s.
+    pub fn lsb_per_dps(self) -> f64 {
+        131.0 / f64::from(1u8 << self as u8)
+    }
+}
+
+/// Readings straight from the chip's registers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RawReading {
+    pub accel: [i16; 3],
+    pub temperature: i16,
+    pub gyro: [i16; 3],
+}
+
+/// Readings scaled to g for the accelerometer, °C for the temperature, and °/s
+/// for the gyro.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Reading {
+    pub accel: [f64; 3],
+    pub temperature: f64,
+    pub gyro: [f64; 3],
+}
+
+/// Structure used to model an MPU6050 accelerometer and gyro on the I2C bus.
+pub struct Mpu6050 {
+    i2c: I2c,
+    accel_range: AccelRange,
+    gyro_range: GyroRange,
+    gyro_offset: [f64; 3],
+}
+
+impl Mpu6050 {
+    /// Gets the chip at the default address on I2C bus 1.
+    pub fn new() -> Result<Self> {
+        Self::with_address(ADDRESS)
+    }
+    /// Gets the chip at the given address, 0x69 if AD0 is high.
+    ///
+    /// Checks it really is an MPU6050 then wakes it up with ±2g and ±250°/s
+    /// ranges.
+    pub fn with_address(address: u16) -> Result<Self> {
+        let mut i2c = I2c::new().context("Failed to get I2C bus, is I2C enabled")?;
+        i2c.set_slave_address(address)
+            .context(format!("Failed to set MPU6050 address: {:#04x}", address))?;
+        let identity = i2c
+            .smbus_read_byte(WHO_AM_I)
+            .context("Failed to read MPU6050 WHO_AM_I")?;
+        if identity != IDENTITY {
+            return Err(SuperKitError::Device(format!(
+                "Expected WHO_AM_I of {:#04x} but found {:#04x}, is it an MPU6050",
+                IDENTITY, identity
+            )));
+        }
+        let mut mpu = Mpu6050 {
+            i2c,
+            accel_range: AccelRange::G2,
+            gyro_range: GyroRange::Dps250,
+            gyro_offset: [0.0; 3],
+        };
+        mpu.write(PWR_MGMT_1, CLOCK_PLL_XGYRO)?;
+        mpu.write(CONFIG, DLPF_44HZ)?;
+        // 1kHz with the low pass filter on so this gives 200Hz.
+        mpu.write(SMPLRT_DIV, 4)?;
+        mpu.set_accel_range(AccelRange::G2)?;
+        mpu.set_gyro_range(GyroRange::Dps250)?;
+        Ok(mpu)
+    }
+    /// Gives the accelerometer range.
+    pub fn accel_range(&self) -> AccelRange {
+        self.accel_range
+    }
+    /// Changes the accelerometer range. Smaller ranges give finer readings.
+    pub fn set_accel_range(&mut self, range: AccelRange) -> Result<()> {
+        self.write(ACCEL_CONFIG, range.bits())?;
+        self.accel_range = range;
+        Ok(())
+    }
+    /// Gives the gyro range.
+    pub fn gyro_range(&self) -> GyroRange {
+        self.gyro_range
+    }
+    /// Changes the gyro range. Smaller ranges give finer readings.
+    pub fn set_gyro_range(&mut self, range: GyroRange) -> Result<()> {
+        self.write(GYRO_CONFIG, range.bits())?;
+        self.gyro_range = range;
+        Ok(())
+    }
+    /// Works out the gyro's zero offsets by averaging the given number of
+    /// readings which are then taken off all later scaled readings.
+    ///
+    /// The chip needs to be kept still while this runs.
+    pub fn calibrate_gyro(&mut self, samples: u16) -> Result<()> {
+        if samples == 0 {
+            return Err(SuperKitError::InvalidArgument(
+                "Need at least one sample to calibrate".to_string(),
+            ));
+        }
+        let mut sum = [0.0; 3];
+        for _ in 0..samples {
+            let raw = self.read_raw()?;
+            for (sum, gyro) in sum.iter_mut().zip(raw.gyro.iter()) {
+                *sum += f64::from(*gyro);
+            }
+            sleep(CALIBRATION_DELAY);
+        }
+        let scale = self.gyro_range.lsb_per_dps() * f64::from(samples);
+        for (offset, sum) in self.gyro_offset.iter_mut().zip(sum.iter()) {
+            *offset = sum / scale;
+        }
+        Ok(())
+    }
+    /// Reads all the sensors at once so they are from the same sample.
+    pub fn read_raw(&mut self) -> Result<RawReading> {
+        let mut buffer = [0u8; 14];
+        self.i2c
+            .write_read(&[ACCEL_XOUT_H], &mut buffer)
+            .context("Failed to read MPU6050 data")?;
+        let word = |i: usize| i16::from_be_bytes([buffer[i], buffer[i + 1]]);
+        Ok(RawReading {
+            accel: [word(0), word(2), word(4)],
+            temperature: word(6),
+            gyro: [word(8), word(10), word(12)],
+        })
+    }
+    /// Reads all the sensors and scales them using the current ranges.
+    pub fn read(&mut self) -> Result<Reading> {
+        let raw = self.read_raw()?;
+        let accel_scale = self.accel_range.lsb_per_g();
+        let gyro_scale = self.gyro_range.lsb_per_dps();
+        let mut reading = Reading {
+            // From the register map datasheet.
+            temperature: f64::from(raw.temperature) / 340.0 + 36.53,
+            ..Reading::default()
+        };
+        for i in 0..3 {
+            reading.accel[i] = f64::from(raw.accel[i]) / accel_scale;
+            reading.gyro[i] = f64::from(raw.gyro[i]) / gyro_scale - self.gyro_offset[i];
+        }
+        Ok(reading)
+    }
+    /// Internal method to write a register.
+    fn write(&mut self, register: u8, value: u8) -> Result<()> {
+        self.i2c.smbus_write_byte(register, value).context(format!(
+            "Failed to write MPU6050 register: {:#04x}",
+            register
+        ))
+    }
+}
+
+/// Estimates pitch and roll by mixing the gyro, which is smooth but drifts,
+/// with the accelerometer, which doesn't drift but is upset by every bump.
+///
+/// Angles are in degrees with the chip flat being 0 and follow the right hand
+/// rule the same as the gyro's axes.
+#[derive(Clone, Copy, Debug)]
+pub struct ComplementaryFilter {
+    alpha: f64,
+    pitch: f64,
+    roll: f64,
+    last: Option<Instant>,
+}
+
+impl Default for ComplementaryFilter {
+    fn default() -> Self {
+        Self::new(ALPHA)
+    }
+}
+
+impl ComplementaryFilter {
+    /// Makes a filter trusting the gyro by the given amount from 0.0 to 1.0
+    /// each update. Something around 0.98 works well at 50-200Hz.
+    pub fn new(alpha: f64) -> Self {
+        ComplementaryFilter {
+            alpha: alpha.clamp(0.0, 1.0),
+            pitch: 0.0,
+            roll: 0.0,
+            last: None,
+        }
+    }
+    /// Current pitch which is the turn about the Y axis.
+    pub fn pitch(&self) -> f64 {
+        self.pitch
+    }
+    /// Current roll which is the turn about the X axis.
+    pub fn roll(&self) -> f64 {
+        self.roll
+    }
+    /// Feeds in a reading taken now giving the new pitch and roll.
+    pub fn update(&mut self, reading: &Reading) -> (f64, f64) {
+        let now = Instant::now();
+        match self.last.replace(now) {
+            Some(last) => self.update_with_dt(reading, now - last),
+            // Nothing to integrate yet so start from the accelerometer.
+            None => {
+                let (pitch, roll) = accel_angles(reading);
+                self.pitch = pitch;
+                self.roll = roll;
+                (pitch, roll)
+            }
+        }
+    }
+    /// Feeds in a reading taken the given time after the last one giving the
+    /// new pitch and roll.
+    pub fn update_with_dt(&mut self, reading: &Reading, dt: Duration) -> (f64, f64) {
+        let dt = dt.as_secs_f64();
+        let (pitch, roll) = accel_angles(reading);
+        self.pitch = self.alpha * (self.pitch + reading.gyro[1] * dt) + (1.0 - self.alpha) * pitch;
+        self.roll = self.alpha * (self.roll + reading.gyro[0] * dt) + (1.0 - self.alpha) * roll;
+        (self.pitch, self.roll)
+    }
+    /// Starts again from the next reading.
+    pub fn reset(&mut self) {
+        self.pitch = 0.0;
+        self.roll = 0.0;
+        self.last = None;
+    }
+}
+
+/// Internal function to work out pitch and roll from which way gravity pulls.
+fn accel_angles(reading: &Reading) -> (f64, f64) {
+    let [x, y, z] = reading.accel;
+    let pitch = (-x).atan2((y * y + z * z).sqrt()).to_degrees();
+    let roll = y.atan2(z).to_degrees();
+    (pitch, roll)
+}
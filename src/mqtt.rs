@@ -0,0 +1,285 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Connects the kit to an MQTT broker, like the one a home automation system
+//! already has, through an [EventBus].
+//!
+//! Every event on the bus is published to `<prefix>/<source>` and outputs
+//! added with [Mqtt::command_level()] and friends take commands from
+//! `<prefix>/<name>/set`, publishing the same set events the web dashboard
+//! does.
+//! Readings polled from sensors, like a temperature, are sent with
+//! [Mqtt::publish()].
+//! Only built with the `mqtt` feature.
+
+use crate::bus::{Event, EventBus, SubscriptionId};
+use crate::error::{Result, SuperKitError};
+use crate::Color;
+use log::{debug, info, warn};
+use rumqttc::{Client, Connection, Event as MqttEvent, MqttOptions, Outgoing, Packet, QoS};
+use std::{
+    collections::BTreeMap,
+    fmt, process,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    thread::{sleep, spawn, JoinHandle},
+    time::Duration,
+};
+
+/// Port used when the broker is given without one.
+const PORT: u16 = 1883;
+/// Seconds between keep alive pings.
+const KEEP_ALIVE: u64 = 30;
+/// Seconds to wait before trying to connect again after an error.
+const RETRY: u64 = 5;
+/// Number of requests queued for the broker before publishing fails.
+const QUEUE: usize = 64;
+
+/// What kind of command an output takes.
+#[derive(Clone, Copy, Debug)]
+enum Command {
+    Level,
+    Color,
+    Text,
+}
+
+/// Command topics and the output each one is for.
+type Commands = Arc<Mutex<BTreeMap<String, (String, Command)>>>;
+
+/// Structure holding a connection to an MQTT broker.
+///
+/// The connection is kept up from a background thread which reconnects
+/// whenever the broker goes away.
+/// State is published retained so something subscribing later still gets the
+/// last value.
+pub struct Mqtt {
+    client: Client,
+    prefix: String,
+    bus: EventBus,
+    commands: Commands,
+    subscription: SubscriptionId,
+    running: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Mqtt {
+    /// Connects to the broker at `host` or `host:port` and starts publishing
+    /// the events on the bus under the topic prefix, like `superkit`.
+    ///
+    /// Connecting happens in the background so this doesn't fail when the
+    /// broker can't be reached, it just keeps trying.
+    pub fn connect(broker: &str, prefix: &str, bus: EventBus) -> Result<Self> {
+        let (host, port) = match broker.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse().map_err(|e| SuperKitError::Parse {
+                    context: format!("Invalid MQTT broker port: {}", port),
+                    source: Box::new(e),
+                })?,
+            ),
+            None => (broker, PORT),
+        };
+        let prefix = prefix.trim_end_matches('/').to_string();
+        let mut options = MqttOptions::new(format!("superkit-{}", process::id()), host, port);
+        options.set_keep_alive(Duration::from_secs(KEEP_ALIVE));
+        let (client, connection) = Client::new(options, QUEUE);
+        debug!(target: "mqtt", "broker = {}:{}, prefix = {}", host, port, prefix);
+        let mut c = client.clone();
+        let p = prefix.clone();
+        let subscription = bus.on(
+            |_| true,
+            move |event| {
+                let topic = format!("{}/{}", p, event.source());
+                // Never blocks the bus, a full queue just drops the update.
+                if let Err(e) = c.try_publish(topic, QoS::AtLeastOnce, true, payload(event)) {
+                    warn!(target: "mqtt", "Failed to publish {:?}: {}", event, e);
+                }
+            },
+        );
+        let commands = Commands::default();
+        let running = Arc::new(AtomicBool::new(true));
+        let worker = spawn({
+            let (client, bus, commands, running) = (
+                client.clone(),
+                bus.clone(),
+                commands.clone(),
+                running.clone(),
+            );
+            move || run(connection, client, bus, commands, running)
+        });
+        Ok(Mqtt {
+            client,
+            prefix,
+            bus,
+            commands,
+            subscription,
+            running,
+            worker: Some(worker),
+        })
+    }
+    /// Publishes a reading, like a temperature, to `<prefix>/<name>`.
+    pub fn publish<P: fmt::Display>(&mut self, name: &str, payload: P) -> Result<()> {
+        let topic = self.topic(name);
+        self.client
+            .try_publish(topic.clone(), QoS::AtLeastOnce, true, payload.to_string())
+            .map_err(|source| SuperKitError::Mqtt {
+                context: format!("Failed to publish {}", topic),
+                source: Box::new(source),
+            })
+    }
+    /// Takes levels from 0.0 to 1.0, or on and off, for an output that
+    /// follows [EventBus::on_level()].
+    pub fn command_level(&mut self, name: &str) -> Result<()> {
+        self.command(name, Command::Level)
+    }
+    /// Takes colors for an output that follows [EventBus::on_color()].
+    pub fn command_color(&mut self, name: &str) -> Result<()> {
+        self.command(name, Command::Color)
+    }
+    /// Takes text for an output that follows [EventBus::on_text()].
+    pub fn command_text(&mut self, name: &str) -> Result<()> {
+        self.command(name, Command::Text)
+    }
+    /// Internal method to subscribe to the command topic of an output.
+    fn command(&mut self, name: &str, command: Command) -> Result<()> {
+        let topic = format!("{}/set", self.topic(name));
+        lock(&self.commands).insert(topic.clone(), (name.to_string(), command));
+        self.client
+            .try_subscribe(topic.clone(), QoS::AtLeastOnce)
+            .map_err(|source| SuperKitError::Mqtt {
+                context: format!("Failed to subscribe to {}", topic),
+                source: Box::new(source),
+            })
+    }
+    /// Internal method to get the topic for a name.
+    fn topic(&self, name: &str) -> String {
+        format!("{}/{}", self.prefix, name)
+    }
+}
+
+/// Insure the broker is told the kit is going away before exiting.
+impl Drop for Mqtt {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        self.bus.unsubscribe(self.subscription);
+        let _ = self.client.try_disconnect();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Internal function run by the background thread to keep the connection
+/// going and turn commands into events on the bus.
+fn run(
+    mut connection: Connection,
+    mut client: Client,
+    bus: EventBus,
+    commands: Commands,
+    running: Arc<AtomicBool>,
+) {
+    for notification in connection.iter() {
+        match notification {
+            Ok(MqttEvent::Incoming(Packet::ConnAck(_))) => {
+                info!(target: "mqtt", "Connected to broker");
+                // A new session starts without the old subscriptions.
+                let topics: Vec<String> = lock(&commands).keys().cloned().collect();
+                for topic in topics {
+                    if let Err(e) = client.try_subscribe(topic, QoS::AtLeastOnce) {
+                        warn!(target: "mqtt", "{}", e);
+                    }
+                }
+            }
+            Ok(MqttEvent::Incoming(Packet::Publish(publish))) => {
+                let command = lock(&commands).get(&publish.topic).cloned();
+                if let Some((source, command)) = command {
+                    let text = String::from_utf8_lossy(&publish.payload);
+                    match parse(source, command, text.trim()) {
+                        Ok(event) => bus.publish(event),
+                        Err(e) => warn!(target: "mqtt", "{}: {}", publish.topic, e),
+                    }
+                }
+            }
+            Ok(MqttEvent::Outgoing(Outgoing::Disconnect)) => break,
+            Ok(_) => {}
+            Err(e) => {
+                if !running.load(Ordering::SeqCst) {
+                    break;
+                }
+                warn!(target: "mqtt", "{}, trying again in {} seconds", e, RETRY);
+                sleep(Duration::from_secs(RETRY));
+            }
+        }
+    }
+}
+
+/// Internal function to turn a command into the set event for its output.
+fn parse(source: String, command: Command, text: &str) -> Result<Event> {
+    let event = match command {
+        Command::Level => {
+            let value = match text.to_ascii_lowercase().as_str() {
+                "on" | "true" => 1.0,
+                "off" | "false" => 0.0,
+                _ => text.parse().map_err(|e| SuperKitError::Parse {
+                    context: format!("Level \"{}\" is not a number, on, or off", text),
+                    source: Box::new(e),
+                })?,
+            };
+            if !(0.0..=1.0).contains(&value) {
+                return Err(SuperKitError::InvalidArgument(format!(
+                    "Level {} is outside of 0.0 to 1.0",
+                    value
+                )));
+            }
+            Event::LevelSet { source, value }
+        }
+        Command::Color => Event::ColorSet {
+            source,
+            color: Color::parse(text)?,
+        },
+        Command::Text => Event::TextSet {
+            source,
+            text: text.to_string(),
+        },
+    };
+    Ok(event)
+}
+
+/// Internal function to get the payload published for an event.
+fn payload(event: &Event) -> String {
+    match event {
+        Event::EncoderTurned { value, .. } => value.to_string(),
+        Event::Sample { value, .. } | Event::LevelSet { value, .. } => value.to_string(),
+        Event::ColorSet { color, .. } => format!("#{:06x}", color.to_hex()),
+        Event::TextSet { text, .. } => text.clone(),
+        _ => match event.is_active() {
+            Some(true) => "on".to_string(),
+            _ => "off".to_string(),
+        },
+    }
+}
+
+/// Internal function to get at the command topics.
+fn lock(commands: &Commands) -> MutexGuard<'_, BTreeMap<String, (String, Command)>> {
+    commands.lock().unwrap_or_else(PoisonError::into_inner)
+}
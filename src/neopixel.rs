@@ -0,0 +1,291 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::spi::{Bus, Mode, SlaveSelect, Spi};
+use crate::Color;
+use std::{thread::sleep, time::Duration};
+
+// Each bit sent to the strip becomes 4 SPI bits at this speed giving the
+// 1.25µs bit time the WS2812 wants.
+const CLOCK_SPEED: u32 = 3_200_000;
+// SPI patterns for a 0 and a 1 bit, a short or long high pulse.
+const ZERO: u8 = 0b1000;
+const ONE: u8 = 0b1110;
+// Low time that latches the data. Newer WS2812Bs need about 280µs which is 112
+// bytes at 3.2MHz.
+const RESET_BYTES: usize = 120;
+// Largest single SPI transfer spidev allows unless spidev.bufsiz is raised in
+// /boot/cmdline.txt.
+const MAX_TRANSFER: usize = 4096;
+
+/// Animations that [NeoPixel::play()] can show.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Effect {
+    /// The whole color wheel spread along the strip and rotating.
+    Rainbow,
+    /// A run of lit pixels of the given length moving along the strip.
+    Chase { color: Color, length: usize },
+    /// Every third pixel lit and stepping along like a theater marquee.
+    Theater(Color),
+    /// The strip filling up one pixel at a time then emptying again.
+    Wipe(Color),
+}
+
+impl Effect {
+    /// Number of frames in one cycle of the effect on a strip of the given
+    /// length.
+    pub fn frames(&self, len: usize) -> usize {
+        match self {
+            Effect::Rainbow => 256,
+            Effect::Chase { length, .. } => len + length,
+            Effect::Theater(_) => 3,
+            Effect::Wipe(_) => 2 * len,
+        }
+    }
+    /// Works out the colors for one frame of the effect.
+    pub fn frame(&self, len: usize, frame: usize) -> Vec<Color> {
+        (0..len)
+            .map(|i| match *self {
+                Effect::Rainbow => {
+                    let hue = 360.0 * (i as f64 / len as f64 + frame as f64 / 256.0);
+                    Color::from_hsv(hue, 1.0, 1.0)
+                }
+                Effect::Chase { color, length } => {
+                    // The head starts just off the start of the strip.
+                    if i < frame && frame - i <= length {
+                        color
+                    } else {
+                        Color::BLACK
+                    }
+                }
+                Effect::Theater(color) => {
+                    if i % 3 == frame % 3 {
+                        color
+                    } else {
+                        Color::BLACK
+                    }
+                }
+                Effect::Wipe(color) => {
+                    let lit = if frame < len {
+                        i <= frame
+                    } else {
+                        i > frame - len
+                    };
+                    if lit {
+                        color
+                    } else {
+                        Color::BLACK
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Structure used to model a strip of WS2812 or SK6812 addressable RGB LEDs,
+/// often called NeoPixels, connected to the SPI MOSI pin (GPIO 10).
+///
+/// The strip's timing is made by sending specially encoded bytes over SPI
+/// since the PWM hardware would need DMA to feed it fast enough which isn't
+/// available from user space.
+/// The whole strip has to go out in one transfer so the default spidev limit
+/// allows about 330 pixels.
+/// Most strips want 5V data but usually work from the Pi's 3.3V when the first
+/// pixel is close by, otherwise use a level shifter.
+/// Changes to the pixels only show after [NeoPixel::show()].
+pub struct NeoPixel {
+    spi: Spi,
+    pixels: Vec<Color>,
+    brightness: f64,
+    rgbw: bool,
+}
+
+impl NeoPixel {
+    /// Gets SPI0 for a strip with the given number of pixels.
+    pub fn new(len: usize) -> Result<Self> {
+        Self::with_spi(Bus::Spi0, SlaveSelect::Ss0, len)
+    }
+    /// Gets the given SPI bus for a strip with the given number of pixels.
+    ///
+    /// The chip select isn't used by the strip but is needed to open the bus.
+    pub fn with_spi(bus: Bus, slave_select: SlaveSelect, len: usize) -> Result<Self> {
+        if len == 0 {
+            return Err(SuperKitError::InvalidArgument(
+                "Need at least one pixel".to_string(),
+            ));
+        }
+        let spi = Spi::new(bus, slave_select, CLOCK_SPEED, Mode::Mode0)
+            .context("Failed to get SPI instance")?;
+        let mut strip = NeoPixel {
+            spi,
+            pixels: vec![Color::BLACK; len],
+            brightness: 1.0,
+            rgbw: false,
+        };
+        strip.check_size(false)?;
+        strip.show()?;
+        Ok(strip)
+    }
+    /// Sets if the strip is made of RGBW SK6812s which take an extra white
+    /// byte per pixel.
+    ///
+    /// The white LED is used for the part of the color shared by red, green,
+    /// and blue.
+    pub fn set_rgbw(&mut self, rgbw: bool) -> Result<()> {
+        self.check_size(rgbw)?;
+        self.rgbw = rgbw;
+        Ok(())
+    }
+    /// Number of pixels in the strip.
+    pub fn len(&self) -> usize {
+        self.pixels.len()
+    }
+    /// Always false since a strip needs at least one pixel.
+    pub fn is_empty(&self) -> bool {
+        self.pixels.is_empty()
+    }
+    /// Gives the color of a pixel before brightness scaling.
+    pub fn pixel(&self, index: usize) -> Option<Color> {
+        self.pixels.get(index).copied()
+    }
+    /// Changes the color of one pixel.
+    pub fn set_pixel(&mut self, index: usize, color: Color) -> Result<()> {
+        let len = self.len();
+        match self.pixels.get_mut(index) {
+            Some(pixel) => *pixel = color,
+            None => {
+                return Err(SuperKitError::InvalidArgument(format!(
+                    "Pixel {} is past the end of the {} pixel strip",
+                    index, len
+                )))
+            }
+        }
+        Ok(())
+    }
+    /// Replaces all the pixels starting from the first with the given colors.
+    pub fn set_pixels(&mut self, colors: &[Color]) {
+        for (pixel, color) in self.pixels.iter_mut().zip(colors.iter()) {
+            *pixel = *color;
+        }
+    }
+    /// Sets all the pixels to one color.
+    pub fn fill(&mut self, color: Color) {
+        for pixel in self.pixels.iter_mut() {
+            *pixel = color;
+        }
+    }
+    /// Turns all the pixels off.
+    pub fn clear(&mut self) {
+        self.fill(Color::BLACK);
+    }
+    /// Gives the brightness from 0.0 to 1.0.
+    pub fn brightness(&self) -> f64 {
+        self.brightness
+    }
+    /// Scales all the pixels from 0.0 (off) to 1.0 (full) when they are shown.
+    ///
+    /// A full strip at full brightness can draw 60mA a pixel so keeping this
+    /// down is kinder to the power supply and eyes.
+    pub fn set_brightness(&mut self, brightness: f64) {
+        self.brightness = brightness.clamp(0.0, 1.0);
+    }
+    /// Sends the pixels to the strip.
+    pub fn show(&mut self) -> Result<()> {
+        let mut buffer = Vec::with_capacity(self.buffer_size(self.rgbw));
+        for pixel in self.pixels.iter() {
+            let color = pixel.scale(self.brightness);
+            if self.rgbw {
+                let white = color.r.min(color.g).min(color.b);
+                for byte in [color.g - white, color.r - white, color.b - white, white].iter() {
+                    encode(*byte, &mut buffer);
+                }
+            } else {
+                // The strip wants green first.
+                for byte in [color.g, color.r, color.b].iter() {
+                    encode(*byte, &mut buffer);
+                }
+            }
+        }
+        buffer.resize(buffer.len() + RESET_BYTES, 0);
+        self.spi
+            .write(&buffer)
+            .context("Failed to send pixels to strip")?;
+        Ok(())
+    }
+    /// Shows one cycle of an effect with each frame lasting the given time.
+    ///
+    /// Call it in a loop to keep the effect going.
+    pub fn play(&mut self, effect: &Effect, step: Duration) -> Result<()> {
+        let len = self.len();
+        for frame in 0..effect.frames(len) {
+            let colors = effect.frame(len, frame);
+            self.set_pixels(&colors);
+            self.show()?;
+            sleep(step);
+        }
+        Ok(())
+    }
+    /// Internal method to work out the number of SPI bytes needed in the given
+    /// mode.
+    fn buffer_size(&self, rgbw: bool) -> usize {
+        let bytes_per_pixel = if rgbw { 4 } else { 3 };
+        self.pixels.len() * bytes_per_pixel * 4 + RESET_BYTES
+    }
+    /// Internal method to check the strip fits in a single SPI transfer in the
+    /// given mode.
+    fn check_size(&self, rgbw: bool) -> Result<()> {
+        if self.buffer_size(rgbw) > MAX_TRANSFER {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "{} pixels is too many for one SPI transfer, raise spidev.bufsiz",
+                self.len()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Turn all the pixels off before exiting.
+impl Drop for NeoPixel {
+    fn drop(&mut self) {
+        self.clear();
+        let _ = self.show();
+    }
+}
+
+/// Internal function to turn each bit of a byte, MSB first, into its 4 bit SPI
+/// pattern.
+fn encode(byte: u8, buffer: &mut Vec<u8>) {
+    for pair in (0..4).rev() {
+        let high = if byte & (1 << (pair * 2 + 1)) != 0 {
+            ONE
+        } else {
+            ZERO
+        };
+        let low = if byte & (1 << (pair * 2)) != 0 {
+            ONE
+        } else {
+            ZERO
+        };
+        buffer.push(high << 4 | low);
+    }
+}
@@ -0,0 +1,335 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::{
+    gpio::OutputPin,
+    pwm::{Channel, Pwm},
+};
+#[cfg(feature = "shift-register")]
+use crate::{SharedHC595, HC595};
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    thread::{sleep, spawn, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Frequency in Hz used when a plain output pin does software PWM.
+pub const SOFT_FREQUENCY: f64 = 1000.0;
+
+/// Finds which hardware PWM channel can drive a pin.
+///
+/// The Pi only has two channels, PWM0 on GPIO 12 or 18 and PWM1 on GPIO 13 or
+/// 19, so any other pin gives [SuperKitError::PwmUnsupported].
+pub fn hardware_channel(pin: u8) -> Result<Channel> {
+    match pin {
+        12 | 18 => Ok(Channel::Pwm0),
+        13 | 19 => Ok(Channel::Pwm1),
+        _ => Err(SuperKitError::PwmUnsupported { pin }),
+    }
+}
+
+/// Common interface for anything whose brightness or power can be set with a
+/// duty cycle so effects like [crate::Breather] work with any of them.
+pub trait PwmOutput {
+    /// Sets how much of the time the output is on from 0.0 to 1.0.
+    fn set_duty_cycle(&mut self, duty_cycle: f64) -> Result<()>;
+}
+
+/// Uses rppal's software PWM at [SOFT_FREQUENCY].
+impl PwmOutput for OutputPin {
+    fn set_duty_cycle(&mut self, duty_cycle: f64) -> Result<()> {
+        self.set_pwm_frequency(SOFT_FREQUENCY, duty_cycle.clamp(0.0, 1.0))
+            .context("Failed to change duty cycle")
+    }
+}
+
+/// Uses one of the hardware PWM channels at whatever frequency it was set up
+/// with.
+impl PwmOutput for Pwm {
+    fn set_duty_cycle(&mut self, duty_cycle: f64) -> Result<()> {
+        Pwm::set_duty_cycle(self, duty_cycle.clamp(0.0, 1.0))
+            .context("Failed to change hardware PWM duty cycle")
+    }
+}
+
+/// A group of on/off outputs that can all be updated together.
+///
+/// Used by [SoftPwm] so it can dim plain GPIO pins and shift register outputs
+/// the same way.
+pub trait OutputBank: Send + 'static {
+    /// Number of outputs in the bank.
+    fn len(&self) -> usize;
+    /// Checks if there are no outputs at all.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Sets every output with true being high.
+    fn write(&mut self, states: &[bool]);
+}
+
+impl OutputBank for Vec<OutputPin> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+    fn write(&mut self, states: &[bool]) {
+        for (pin, state) in self.iter_mut().zip(states.iter()) {
+            if *state {
+                pin.set_high();
+            } else {
+                pin.set_low();
+            }
+        }
+    }
+}
+
+/// Output 0 is Q0 of the chip.
+#[cfg(feature = "shift-register")]
+impl OutputBank for HC595 {
+    fn len(&self) -> usize {
+        8
+    }
+    fn write(&mut self, states: &[bool]) {
+        self.serial_in(to_byte(states));
+        self.parallel_out();
+    }
+}
+
+/// Output 0 is Q0 of the chip.
+#[cfg(feature = "shift-register")]
+impl OutputBank for SharedHC595 {
+    fn len(&self) -> usize {
+        8
+    }
+    fn write(&mut self, states: &[bool]) {
+        SharedHC595::write(self, to_byte(states));
+    }
+}
+
+/// Internal function to pack up to 8 states into a byte with the first one in
+/// bit 0.
+#[cfg(feature = "shift-register")]
+fn to_byte(states: &[bool]) -> u8 {
+    states
+        .iter()
+        .take(8)
+        .enumerate()
+        .fold(0, |byte, (bit, state)| byte | (*state as u8) << bit)
+}
+
+/// Settings shared with the worker thread.
+struct Channels {
+    duty_cycles: Vec<f64>,
+    active_low: bool,
+}
+
+/// Software PWM for a whole bank of outputs using a single thread.
+///
+/// rppal's software PWM starts a thread for every pin and only works on GPIO
+/// pins so dimming all 8 LEDs of lesson 3 or anything on a 74HC595 needs this
+/// instead.
+/// At the start of each period every channel with a duty cycle above zero is
+/// turned on and then each one is turned off again as its time runs out.
+pub struct SoftPwm {
+    channels: Arc<Mutex<Channels>>,
+    running: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl SoftPwm {
+    /// Starts the PWM worker for the outputs at the given frequency in Hz with
+    /// everything off.
+    ///
+    /// Linux can't sleep accurately for much less than 100µs so frequencies
+    /// above a few hundred Hz lose resolution.
+    pub fn new<B: OutputBank>(mut bank: B, frequency: f64) -> Result<Self> {
+        if bank.is_empty() {
+            return Err(SuperKitError::InvalidArgument(
+                "Software PWM needs at least one output".to_string(),
+            ));
+        }
+        if !(1.0..=10_000.0).contains(&frequency) {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Software PWM frequency {}Hz is out of range",
+                frequency
+            )));
+        }
+        let period = Duration::from_secs_f64(1.0 / frequency);
+        let channels = Arc::new(Mutex::new(Channels {
+            duty_cycles: vec![0.0; bank.len()],
+            active_low: false,
+        }));
+        let running = Arc::new(AtomicBool::new(true));
+        let c = channels.clone();
+        let r = running.clone();
+        let worker = spawn(move || {
+            let mut start = Instant::now();
+            while r.load(Ordering::SeqCst) {
+                let (duty_cycles, active_low) = {
+                    let channels = c.lock().unwrap_or_else(PoisonError::into_inner);
+                    (channels.duty_cycles.clone(), channels.active_low)
+                };
+                let mut states: Vec<bool> = duty_cycles.iter().map(|d| *d > 0.0).collect();
+                // When each partly on channel needs to be turned off.
+                let mut offs: Vec<(Duration, usize)> = duty_cycles
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, d)| **d > 0.0 && **d < 1.0)
+                    .map(|(i, d)| (period.mul_f64(*d), i))
+                    .collect();
+                offs.sort_by_key(|(at, _)| *at);
+                Self::write(&mut bank, &states, active_low);
+                let mut offs = offs.iter().peekable();
+                while let Some((at, channel)) = offs.next() {
+                    sleep_until(start + *at);
+                    states[*channel] = false;
+                    // Channels that turn off together only need one write.
+                    if !matches!(offs.peek(), Some((next, _)) if next == at) {
+                        Self::write(&mut bank, &states, active_low);
+                    }
+                }
+                start += period;
+                sleep_until(start);
+                // Don't try to catch up if we fell behind.
+                if start.elapsed() > period {
+                    start = Instant::now();
+                }
+            }
+            let active_low = c.lock().unwrap_or_else(PoisonError::into_inner).active_low;
+            let off = vec![false; bank.len()];
+            Self::write(&mut bank, &off, active_low);
+        });
+        Ok(SoftPwm {
+            channels,
+            running,
+            worker: Some(worker),
+        })
+    }
+    /// Number of channels.
+    pub fn len(&self) -> usize {
+        self.lock().duty_cycles.len()
+    }
+    /// Checks if there are no channels which can't happen after [SoftPwm::new()].
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Makes a duty cycle of 1.0 mean low instead of high for outputs like the
+    /// LEDs of lesson 3 which light up when the pin is low.
+    pub fn set_active_low(&self, active_low: bool) {
+        self.lock().active_low = active_low;
+    }
+    /// Sets how much of the time a channel is on from 0.0 to 1.0.
+    pub fn set_duty_cycle(&self, channel: usize, duty_cycle: f64) -> Result<()> {
+        let mut channels = self.lock();
+        match channels.duty_cycles.get_mut(channel) {
+            Some(duty) => *duty = duty_cycle.clamp(0.0, 1.0),
+            None => {
+                return Err(SuperKitError::InvalidArgument(format!(
+                    "Software PWM channel {} is out of range",
+                    channel
+                )))
+            }
+        }
+        Ok(())
+    }
+    /// Sets the duty cycles of all the channels starting from channel 0.
+    pub fn set_all(&self, duty_cycles: &[f64]) -> Result<()> {
+        let mut channels = self.lock();
+        if duty_cycles.len() > channels.duty_cycles.len() {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Got {} duty cycles but there are only {} channels",
+                duty_cycles.len(),
+                channels.duty_cycles.len()
+            )));
+        }
+        for (duty, new) in channels.duty_cycles.iter_mut().zip(duty_cycles.iter()) {
+            *duty = new.clamp(0.0, 1.0);
+        }
+        Ok(())
+    }
+    /// Gets a handle to one channel that can be used anywhere a [PwmOutput]
+    /// is wanted like with a [crate::Breather].
+    pub fn channel(&self, channel: usize) -> Result<SoftPwmChannel> {
+        if channel >= self.len() {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Software PWM channel {} is out of range",
+                channel
+            )));
+        }
+        Ok(SoftPwmChannel {
+            channels: self.channels.clone(),
+            channel,
+        })
+    }
+    /// Internal associative method (function) to write the states taking
+    /// active low into account.
+    fn write<B: OutputBank>(bank: &mut B, states: &[bool], active_low: bool) {
+        if active_low {
+            let inverted: Vec<bool> = states.iter().map(|s| !s).collect();
+            bank.write(&inverted);
+        } else {
+            bank.write(states);
+        }
+    }
+    /// Internal method used to get at the settings shared with worker thread.
+    fn lock(&self) -> MutexGuard<'_, Channels> {
+        self.channels.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Stop the worker thread and insure all the outputs are off before exiting.
+impl Drop for SoftPwm {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Handle to a single channel of a [SoftPwm].
+///
+/// It only changes the duty cycle so the [SoftPwm] itself has to be kept
+/// around for the channel to keep working.
+#[derive(Clone)]
+pub struct SoftPwmChannel {
+    channels: Arc<Mutex<Channels>>,
+    channel: usize,
+}
+
+impl PwmOutput for SoftPwmChannel {
+    fn set_duty_cycle(&mut self, duty_cycle: f64) -> Result<()> {
+        let mut channels = self.channels.lock().unwrap_or_else(PoisonError::into_inner);
+        channels.duty_cycles[self.channel] = duty_cycle.clamp(0.0, 1.0);
+        Ok(())
+    }
+}
+
+/// Internal function to sleep until a point in time if it hasn't already
+/// passed.
+fn sleep_until(deadline: Instant) {
+    let now = Instant::now();
+    if deadline > now {
+        sleep(deadline - now);
+    }
+}
@@ -0,0 +1,438 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::spi::{Bus, Mode, SlaveSelect, Spi};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    path::Path,
+    str::FromStr,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+// Registers.
+const COMMAND: u8 = 0x01;
+const COM_IRQ: u8 = 0x04;
+const ERROR: u8 = 0x06;
+const FIFO_DATA: u8 = 0x09;
+const FIFO_LEVEL: u8 = 0x0a;
+const BIT_FRAMING: u8 = 0x0d;
+const COLL: u8 = 0x0e;
+const MODE: u8 = 0x11;
+const TX_CONTROL: u8 = 0x14;
+const TX_ASK: u8 = 0x15;
+const T_MODE: u8 = 0x2a;
+const T_PRESCALER: u8 = 0x2b;
+const T_RELOAD_H: u8 = 0x2c;
+const T_RELOAD_L: u8 = 0x2d;
+const VERSION: u8 = 0x37;
+// Chip commands.
+const IDLE: u8 = 0x00;
+const TRANSCEIVE: u8 = 0x0c;
+const SOFT_RESET: u8 = 0x0f;
+// Commands sent to the card.
+const REQA: u8 = 0x26;
+const HLTA: u8 = 0x50;
+const SELECT: [u8; 3] = [0x93, 0x95, 0x97];
+// Anticollision cascade tag meaning more of the UID follows.
+const CASCADE_TAG: u8 = 0x88;
+// SAK bit meaning the UID isn't complete yet.
+const SAK_INCOMPLETE: u8 = 0x04;
+// COM_IRQ bits.
+const RX_IRQ: u8 = 0x20;
+const IDLE_IRQ: u8 = 0x10;
+const TIMER_IRQ: u8 = 0x01;
+// ERROR bits.
+const COLL_ERR: u8 = 0x08;
+const FATAL_ERRORS: u8 = 0x13;
+// COLL bit set when there was no collision position.
+const COLL_POS_NOT_VALID: u8 = 0x20;
+// Antenna driver bits in TX_CONTROL.
+const ANTENNA: u8 = 0x03;
+// Start sending bit in BIT_FRAMING.
+const START_SEND: u8 = 0x80;
+// The chip is good up to 10MHz but slower is kinder to long jumper wires.
+const CLOCK_SPEED: u32 = 1_000_000;
+// Backstop in case the chip's own 25ms timer never fires.
+const TIMEOUT: Duration = Duration::from_millis(40);
+
+/// Unique ID of a card which can be 4, 7, or 10 bytes long.
+///
+/// Shown and parsed as hex bytes with colons like `DE:AD:BE:EF`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Uid(Vec<u8>);
+
+impl Uid {
+    /// The bytes of the UID.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Uid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes: Vec<String> = self.0.iter().map(|b| format!("{:02X}", b)).collect();
+        write!(f, "{}", bytes.join(":"))
+    }
+}
+
+impl FromStr for Uid {
+    type Err = SuperKitError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let bytes = s
+            .split(&[':', ' ', '-'][..])
+            .filter(|part| !part.is_empty())
+            .map(|part| u8::from_str_radix(part, 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .context(format!("Invalid UID: {}", s))?;
+        if ![4, 7, 10].contains(&bytes.len()) {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "UID should be 4, 7, or 10 bytes but {} has {}",
+                s,
+                bytes.len()
+            )));
+        }
+        Ok(Uid(bytes))
+    }
+}
+
+impl std::convert::TryFrom<String> for Uid {
+    type Error = SuperKitError;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Uid> for String {
+    fn from(uid: Uid) -> Self {
+        uid.to_string()
+    }
+}
+
+/// A card allowed by a [Whitelist].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Card {
+    pub uid: Uid,
+    /// Who or what the card belongs to.
+    #[serde(default)]
+    pub name: String,
+}
+
+/// List of allowed cards normally loaded from a TOML file like this:
+///
+/// ```toml
+/// [[cards]]
+/// uid = "DE:AD:BE:EF"
+/// name = "Front door key"
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Whitelist {
+    #[serde(default)]
+    pub cards: Vec<Card>,
+}
+
+impl Whitelist {
+    /// Loads a whitelist from a TOML file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .context(format!("Failed to read whitelist file: {}", path.display()))?;
+        toml::from_str(&text).context(format!(
+            "Failed to parse whitelist file: {}",
+            path.display()
+        ))
+    }
+    /// Parses a whitelist from TOML text.
+    pub fn from_toml(text: &str) -> Result<Self> {
+        toml::from_str(text).context("Failed to parse whitelist")
+    }
+    /// Finds the card with the given UID if it is allowed.
+    pub fn find(&self, uid: &Uid) -> Option<&Card> {
+        self.cards.iter().find(|card| card.uid == *uid)
+    }
+}
+
+/// What came back from a card.
+struct Response {
+    data: Vec<u8>,
+    /// Bit position, counting from 1, of the first collision if more than one
+    /// card answered.
+    collision: Option<u8>,
+}
+
+/// Structure used to model an RC522 (MFRC522) 13.56MHz RFID reader on the SPI
+/// bus.
+///
+/// Reads the UIDs of ISO 14443A cards like MIFARE Classic and Ultralight tags.
+/// When several cards are in the field at once the anticollision loop picks
+/// one of them.
+pub struct Rc522 {
+    spi: Spi,
+}
+
+impl Rc522 {
+    /// Gets the reader on SPI0 using CE0.
+    pub fn new() -> Result<Self> {
+        Self::with_spi(Bus::Spi0, SlaveSelect::Ss0)
+    }
+    /// Gets the reader on the given SPI bus and chip select.
+    pub fn with_spi(bus: Bus, slave_select: SlaveSelect) -> Result<Self> {
+        let spi = Spi::new(bus, slave_select, CLOCK_SPEED, Mode::Mode0)
+            .context("Failed to get SPI instance")?;
+        let mut rc522 = Rc522 { spi };
+        rc522.write(COMMAND, SOFT_RESET)?;
+        sleep(Duration::from_millis(50));
+        // Timer ticks every 25µs with 1000 ticks making a 25ms timeout.
+        rc522.write(T_MODE, 0x80)?;
+        rc522.write(T_PRESCALER, 0xa9)?;
+        rc522.write(T_RELOAD_H, 0x03)?;
+        rc522.write(T_RELOAD_L, 0xe8)?;
+        // 100% ASK modulation which the cards need.
+        rc522.write(TX_ASK, 0x40)?;
+        // CRC preset of 0x6363 as ISO 14443A uses.
+        rc522.write(MODE, 0x3d)?;
+        rc522.set_antenna(true)?;
+        Ok(rc522)
+    }
+    /// Gives the chip's version which is 0x91 or 0x92 for real MFRC522s and
+    /// often 0x88 or 0x12 for clones.
+    pub fn version(&mut self) -> Result<u8> {
+        self.read(VERSION)
+    }
+    /// Turns the antenna field on or off.
+    pub fn set_antenna(&mut self, on: bool) -> Result<()> {
+        let control = self.read(TX_CONTROL)?;
+        let control = if on {
+            control | ANTENNA
+        } else {
+            control & !ANTENNA
+        };
+        self.write(TX_CONTROL, control)
+    }
+    /// Checks if there is a card in the field that hasn't been halted.
+    pub fn is_card_present(&mut self) -> Result<bool> {
+        Ok(self.request()?.is_some())
+    }
+    /// Reads the UID of a card in the field if there is one then halts it.
+    ///
+    /// A halted card doesn't answer again until it has been taken out of the
+    /// field so each card is only read once each time it is shown.
+    pub fn read_uid(&mut self) -> Result<Option<Uid>> {
+        if self.request()?.is_none() {
+            return Ok(None);
+        }
+        let mut uid = Vec::new();
+        for select in SELECT.iter() {
+            let (part, sak) = self.select_cascade(*select)?;
+            if sak & SAK_INCOMPLETE != 0 {
+                // The first byte is the cascade tag and not part of the UID.
+                if part[0] != CASCADE_TAG {
+                    return Err(SuperKitError::Device(
+                        "Card said its UID goes on but didn't send the cascade tag".to_string(),
+                    ));
+                }
+                uid.extend_from_slice(&part[1..]);
+            } else {
+                uid.extend_from_slice(&part);
+                self.halt()?;
+                return Ok(Some(Uid(uid)));
+            }
+        }
+        Err(SuperKitError::Device(
+            "Card UID is longer than 10 bytes".to_string(),
+        ))
+    }
+    /// Tells the selected card to stop answering.
+    pub fn halt(&mut self) -> Result<()> {
+        let mut frame = vec![HLTA, 0x00];
+        frame.extend_from_slice(&crc_a(&frame));
+        // The card doesn't answer a halt so a time out means it worked.
+        self.transceive(&frame, 0)?;
+        Ok(())
+    }
+    /// Internal method to wake any cards in the field giving their answer to
+    /// request.
+    fn request(&mut self) -> Result<Option<Vec<u8>>> {
+        // REQA is a short frame of only 7 bits.
+        match self.transceive(&[REQA], 7)? {
+            Some(response) if response.data.len() == 2 => Ok(Some(response.data)),
+            _ => Ok(None),
+        }
+    }
+    /// Internal method to run the anticollision loop and select at one cascade
+    /// level giving 4 UID bytes and the SAK.
+    fn select_cascade(&mut self, select: u8) -> Result<([u8; 4], u8)> {
+        // UID bytes plus the check byte.
+        let mut known = [0u8; 5];
+        let mut known_bits = 0usize;
+        loop {
+            let bytes = known_bits / 8;
+            let bits = known_bits % 8;
+            let sent = bytes + usize::from(bits > 0);
+            // NVB is the number of valid bytes then bits including these two.
+            let mut frame = vec![select, (((2 + bytes) << 4) | bits) as u8];
+            frame.extend_from_slice(&known[..sent]);
+            let response = self.transceive(&frame, bits as u8)?.ok_or_else(|| {
+                SuperKitError::Device("Card left during anticollision".to_string())
+            })?;
+            // The first byte back lines up with the last partial byte sent.
+            let start = bytes;
+            for (i, byte) in response.data.iter().enumerate() {
+                match known.get_mut(start + i) {
+                    Some(slot) if i == 0 && bits > 0 => {
+                        let mask = (1u8 << bits) - 1;
+                        *slot = (*slot & mask) | (byte & !mask);
+                    }
+                    Some(slot) => *slot = *byte,
+                    None => break,
+                }
+            }
+            match response.collision {
+                Some(position) => {
+                    let position = usize::from(position);
+                    if position <= known_bits || position > 40 {
+                        return Err(SuperKitError::Device(format!(
+                            "Anticollision got stuck at bit {}",
+                            position
+                        )));
+                    }
+                    // Pick the cards with a 1 in the colliding bit and go again.
+                    known_bits = position;
+                    known[(position - 1) / 8] |= 1 << ((position - 1) % 8);
+                }
+                None => break,
+            }
+        }
+        let check = known[..4].iter().fold(0, |acc, b| acc ^ b);
+        if check != known[4] {
+            return Err(SuperKitError::Device("UID check byte is wrong".to_string()));
+        }
+        let mut frame = vec![select, 0x70];
+        frame.extend_from_slice(&known);
+        frame.extend_from_slice(&crc_a(&frame));
+        let response = self
+            .transceive(&frame, 0)?
+            .ok_or_else(|| SuperKitError::Device("Card left during select".to_string()))?;
+        if response.data.len() != 3 || crc_a(&response.data[..1]) != response.data[1..] {
+            return Err(SuperKitError::Device("Bad answer to select".to_string()));
+        }
+        Ok(([known[0], known[1], known[2], known[3]], response.data[0]))
+    }
+    /// Internal method to send a frame to the card and get its answer, or None
+    /// if nothing answered in time.
+    ///
+    /// For a partial last byte give the number of bits to send from it, which
+    /// is also where the first bit of the answer is put.
+    fn transceive(&mut self, data: &[u8], last_bits: u8) -> Result<Option<Response>> {
+        self.write(COMMAND, IDLE)?;
+        // Clear the interrupt flags and the FIFO.
+        self.write(COM_IRQ, 0x7f)?;
+        self.write(FIFO_LEVEL, 0x80)?;
+        for byte in data.iter() {
+            self.write(FIFO_DATA, *byte)?;
+        }
+        let framing = (last_bits << 4) | last_bits;
+        self.write(BIT_FRAMING, framing)?;
+        self.write(COMMAND, TRANSCEIVE)?;
+        self.write(BIT_FRAMING, framing | START_SEND)?;
+        let deadline = Instant::now() + TIMEOUT;
+        loop {
+            let irq = self.read(COM_IRQ)?;
+            if irq & (RX_IRQ | IDLE_IRQ) != 0 {
+                break;
+            }
+            if irq & TIMER_IRQ != 0 || Instant::now() >= deadline {
+                self.write(BIT_FRAMING, 0)?;
+                return Ok(None);
+            }
+        }
+        self.write(BIT_FRAMING, 0)?;
+        let error = self.read(ERROR)?;
+        if error & FATAL_ERRORS != 0 {
+            return Err(SuperKitError::Device(format!(
+                "RC522 error while talking to the card: {:#04x}",
+                error
+            )));
+        }
+        let collision = if error & COLL_ERR != 0 {
+            let coll = self.read(COLL)?;
+            if coll & COLL_POS_NOT_VALID != 0 {
+                return Err(SuperKitError::Device(
+                    "Card collision outside the UID".to_string(),
+                ));
+            }
+            // 0 means the 32nd bit.
+            Some(match coll & 0x1f {
+                0 => 32,
+                position => position,
+            })
+        } else {
+            None
+        };
+        let level = self.read(FIFO_LEVEL)?;
+        let mut data = Vec::with_capacity(usize::from(level));
+        for _ in 0..level {
+            data.push(self.read(FIFO_DATA)?);
+        }
+        Ok(Some(Response { data, collision }))
+    }
+    /// Internal method to read a register.
+    fn read(&mut self, register: u8) -> Result<u8> {
+        let mut buffer = [0u8; 2];
+        self.spi
+            .transfer(&mut buffer, &[0x80 | (register << 1), 0])
+            .context(format!("Failed to read RC522 register: {:#04x}", register))?;
+        Ok(buffer[1])
+    }
+    /// Internal method to write a register.
+    fn write(&mut self, register: u8, value: u8) -> Result<()> {
+        self.spi
+            .write(&[register << 1, value])
+            .context(format!("Failed to write RC522 register: {:#04x}", register))?;
+        Ok(())
+    }
+}
+
+/// Turn the antenna off before exiting.
+impl Drop for Rc522 {
+    fn drop(&mut self) {
+        let _ = self.set_antenna(false);
+    }
+}
+
+/// Internal function to work out the ISO 14443A CRC of a frame, low byte
+/// first.
+fn crc_a(data: &[u8]) -> [u8; 2] {
+    let mut crc: u16 = 0x6363;
+    for byte in data.iter() {
+        let mut ch = byte ^ (crc & 0xff) as u8;
+        ch ^= ch << 4;
+        let ch = u16::from(ch);
+        crc = (crc >> 8) ^ (ch << 8) ^ (ch << 3) ^ (ch >> 4);
+    }
+    crc.to_le_bytes()
+}
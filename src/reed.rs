@@ -0,0 +1,211 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::Result;
+use crate::{Button, ButtonEvent};
+use std::{
+    sync::mpsc::{channel, RecvTimeoutError, Sender},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    thread::{spawn, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Things a [ReedSwitch] reports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DoorEvent {
+    Opened,
+    Closed,
+}
+
+/// Callback for a door left open too long with how long it has been open.
+type AlarmCallback = Box<dyn FnMut(Duration) + Send>;
+
+/// Open too long alarm shared with the worker thread.
+struct OpenAlarm {
+    after: Duration,
+    callback: AlarmCallback,
+}
+
+/// What the worker thread gets told.
+enum Message {
+    Event(ButtonEvent, Instant),
+    /// The alarm changed so the deadline needs working out again.
+    Wake,
+    Stop,
+}
+
+/// Structure used to model a reed switch used as a door or window sensor.
+///
+/// The switch is closed, connecting the pin to ground, while the magnet on the
+/// door is next to it so low means the door is closed.
+/// Can also call an alarm callback once each time the door has been left open
+/// too long.
+pub struct ReedSwitch {
+    button: Button,
+    alarm: Arc<Mutex<Option<OpenAlarm>>>,
+    tx: Option<Sender<Message>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl ReedSwitch {
+    /// Gets the GPIO pin with its pull-up turned on.
+    pub fn new(pin: u8) -> Result<Self> {
+        Ok(ReedSwitch {
+            button: Button::new(pin)?,
+            alarm: Arc::new(Mutex::new(None)),
+            tx: None,
+            worker: None,
+        })
+    }
+    /// Changes how long the switch has to stay still before a change is
+    /// believed. The default is 20ms.
+    pub fn set_debounce(&mut self, window: Duration) {
+        self.button.set_debounce(window);
+    }
+    /// Checks if the door is open.
+    pub fn is_open(&self) -> bool {
+        !self.button.is_pressed()
+    }
+    /// Reads the switch and gives any event now that it has settled.
+    ///
+    /// Needs to be called every few milliseconds.
+    /// Don't mix this with [ReedSwitch::on_event()] and note the open alarm
+    /// only works with that.
+    pub fn poll(&mut self) -> Option<DoorEvent> {
+        self.button.poll().map(door_event)
+    }
+    /// Calls the alarm callback from the background thread of
+    /// [ReedSwitch::on_event()] once the door has been open for the given
+    /// time, and again the next time it is left open.
+    ///
+    /// Replaces any alarm given before.
+    pub fn set_open_alarm<A>(&mut self, after: Duration, alarm: A)
+    where
+        A: FnMut(Duration) + Send + 'static,
+    {
+        *self.lock() = Some(OpenAlarm {
+            after,
+            callback: Box::new(alarm),
+        });
+        self.wake();
+    }
+    /// Stops calling the alarm callback.
+    pub fn clear_open_alarm(&mut self) {
+        *self.lock() = None;
+        self.wake();
+    }
+    /// Calls the callback from a background thread each time the door is
+    /// opened or closed.
+    ///
+    /// Replaces any callback given before.
+    pub fn on_event<C>(&mut self, mut callback: C) -> Result<()>
+    where
+        C: FnMut(DoorEvent) + Send + 'static,
+    {
+        self.clear_event()?;
+        let (tx, rx) = channel();
+        let events = tx.clone();
+        self.button.on_event(move |event| {
+            let _ = events.send(Message::Event(event, Instant::now()));
+        })?;
+        self.tx = Some(tx);
+        let alarm = self.alarm.clone();
+        let mut opened_at = if self.is_open() {
+            Some(Instant::now())
+        } else {
+            None
+        };
+        self.worker = Some(spawn(move || {
+            let lock = || alarm.lock().unwrap_or_else(PoisonError::into_inner);
+            let mut alarmed = false;
+            loop {
+                let deadline = match opened_at {
+                    Some(at) if !alarmed => lock().as_ref().map(|alarm| at + alarm.after),
+                    _ => None,
+                };
+                let received = match deadline {
+                    Some(deadline) => {
+                        rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    }
+                    None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                };
+                match received {
+                    Ok(Message::Event(event, at)) => {
+                        let event = door_event(event);
+                        opened_at = match event {
+                            DoorEvent::Opened => Some(at),
+                            DoorEvent::Closed => None,
+                        };
+                        alarmed = false;
+                        callback(event);
+                    }
+                    Ok(Message::Wake) => {}
+                    Ok(Message::Stop) | Err(RecvTimeoutError::Disconnected) => break,
+                    Err(RecvTimeoutError::Timeout) => {
+                        alarmed = true;
+                        if let (Some(at), Some(alarm)) = (opened_at, lock().as_mut()) {
+                            (alarm.callback)(at.elapsed());
+                        }
+                    }
+                }
+            }
+        }));
+        Ok(())
+    }
+    /// Stops calling the callback given to [ReedSwitch::on_event()] and the
+    /// open alarm.
+    pub fn clear_event(&mut self) -> Result<()> {
+        self.button.clear_event()?;
+        if let Some(tx) = self.tx.take() {
+            let _ = tx.send(Message::Stop);
+        }
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        Ok(())
+    }
+    /// Internal method to have the worker thread look at the alarm again.
+    fn wake(&self) {
+        if let Some(tx) = self.tx.as_ref() {
+            let _ = tx.send(Message::Wake);
+        }
+    }
+    /// Internal method used to get at the alarm shared with the worker thread.
+    fn lock(&self) -> MutexGuard<'_, Option<OpenAlarm>> {
+        self.alarm.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Stop the worker thread before exiting.
+impl Drop for ReedSwitch {
+    fn drop(&mut self) {
+        let _ = self.clear_event();
+    }
+}
+
+/// Internal function to turn a debounced button event into a door event.
+fn door_event(event: ButtonEvent) -> DoorEvent {
+    match event {
+        ButtonEvent::Pressed => DoorEvent::Closed,
+        ButtonEvent::Released => DoorEvent::Opened,
+    }
+}
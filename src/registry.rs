@@ -0,0 +1,171 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Keeps track of which driver has which GPIO pin.
+//!
+//! Two drivers wired to the same pin by mistake would otherwise only get a
+//! pin not available error from rppal without saying who has it.
+
+use crate::error::{Result, SuperKitError};
+use lazy_static::lazy_static;
+use log::debug;
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, MutexGuard, PoisonError,
+    },
+};
+
+lazy_static! {
+    static ref REGISTRY: PinRegistry = PinRegistry::default();
+}
+
+/// Who has a pin.
+#[derive(Clone, Copy, Debug)]
+struct Owner {
+    name: &'static str,
+    claim: u64,
+}
+
+/// Process-wide list of the GPIO pins drivers are using.
+///
+/// Every driver claims its pins here before getting them from rppal and the
+/// pins are given back when the driver is dropped.
+/// Hardware PWM set up by channel instead of pin, like
+/// [Servo::hardware()](crate::Servo::hardware), isn't tracked as the pin it is
+/// routed to depends on `/boot/config.txt`.
+#[derive(Debug, Default)]
+pub struct PinRegistry {
+    pins: Mutex<BTreeMap<u8, Owner>>,
+    next: AtomicU64,
+}
+
+impl PinRegistry {
+    /// The registry shared by all the drivers.
+    pub fn global() -> &'static PinRegistry {
+        &REGISTRY
+    }
+    /// Claims all the pins for the owner or none of them if any are already
+    /// claimed.
+    ///
+    /// The pins stay claimed until the returned [PinClaim] is dropped.
+    pub fn claim(&'static self, owner: &'static str, pins: &[u8]) -> Result<PinClaim> {
+        let mut claimed = self.lock();
+        for (i, pin) in pins.iter().enumerate() {
+            // The same pin given twice is just as much of a wiring mistake.
+            let other = match claimed.get(pin) {
+                Some(other) => other.name,
+                None if pins[..i].contains(pin) => owner,
+                None => continue,
+            };
+            debug!(
+                target: "pins",
+                "{} wanted {:?} with these already claimed:\n{}",
+                owner,
+                pins,
+                table(&claimed).trim_end()
+            );
+            return Err(SuperKitError::PinClaimed {
+                pin: *pin,
+                owner: other,
+            });
+        }
+        let claim = self.next.fetch_add(1, Ordering::SeqCst);
+        for pin in pins {
+            claimed.insert(*pin, Owner { name: owner, claim });
+        }
+        debug!(target: "pins", "{:?} claimed by {}", pins, owner);
+        Ok(PinClaim {
+            registry: self,
+            pins: pins.to_vec(),
+            claim,
+        })
+    }
+    /// Name of the driver using the pin if any.
+    pub fn owner(&self, pin: u8) -> Option<&'static str> {
+        self.lock().get(&pin).map(|owner| owner.name)
+    }
+    /// All the claimed pins with their owners in pin order.
+    pub fn assignments(&self) -> Vec<(u8, &'static str)> {
+        self.lock()
+            .iter()
+            .map(|(pin, owner)| (*pin, owner.name))
+            .collect()
+    }
+    /// Table of the claimed pins for debugging setups with a lot of devices.
+    pub fn dump(&self) -> String {
+        table(&self.lock())
+    }
+    /// Internal method to get at the pins.
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<u8, Owner>> {
+        self.pins.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Internal function to lay out the claimed pins one per line.
+fn table(claimed: &BTreeMap<u8, Owner>) -> String {
+    if claimed.is_empty() {
+        return "no pins claimed\n".to_string();
+    }
+    let mut table = String::new();
+    for (pin, owner) in claimed.iter() {
+        // Writing to a String can't fail.
+        let _ = writeln!(table, "GPIO {:>2}  {}", pin, owner.name);
+    }
+    table
+}
+
+/// Pins claimed from the [PinRegistry] which are given back when dropped.
+///
+/// Drivers keep this next to the pins they got from rppal.
+#[derive(Debug)]
+pub struct PinClaim {
+    registry: &'static PinRegistry,
+    pins: Vec<u8>,
+    claim: u64,
+}
+
+impl PinClaim {
+    /// The pins covered by the claim.
+    pub fn pins(&self) -> &[u8] {
+        &self.pins
+    }
+}
+
+/// Insure the pins can be claimed again once the driver is gone.
+impl Drop for PinClaim {
+    fn drop(&mut self) {
+        let mut claimed = self.registry.lock();
+        for pin in self.pins.iter() {
+            if matches!(claimed.get(pin), Some(owner) if owner.claim == self.claim) {
+                claimed.remove(pin);
+            }
+        }
+    }
+}
+
+/// Internal function to claim pins from the global registry.
+pub(crate) fn claim(owner: &'static str, pins: &[u8]) -> Result<PinClaim> {
+    PinRegistry::global().claim(owner, pins)
+}
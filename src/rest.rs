@@ -0,0 +1,356 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Small REST service so outputs can be driven with `curl` from a script.
+//!
+//! Unlike the [dashboard](crate::dashboard) it talks straight to the drivers
+//! it is given, taking a JSON body and answering with the new state of the
+//! part as JSON, or `{"error": "..."}` with a status code saying what went
+//! wrong.
+//!
+//! | Request | Body |
+//! | --- | --- |
+//! | `POST /lcd/text` | `{"text": "Hello\nWorld"}` or `{"text": "Hi", "row": 1}` |
+//! | `POST /rgb/color` | `{"color": "#ff8800"}` or a name like `{"color": "purple"}` |
+//! | `POST /ledbar/level` | `{"level": 5}` from 0 to 8 or `{"percent": 62.5}` |
+//! | `POST /relay/on` and `POST /relay/off` | none |
+//!
+//! `GET /` lists the endpoints of the parts that were given.
+
+use crate::dashboard::respond;
+use crate::error::{Context, Result, SuperKitError};
+use crate::led_bar::LEDS;
+use crate::runner::CancelToken;
+#[cfg(feature = "lcd")]
+use crate::Lcd1602;
+use crate::{Color, Led, LedBar, RgbPwm};
+use log::debug;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use std::{error::Error, io::Read, time::Duration};
+use tiny_http::{Method, Request, Server};
+
+/// Time in milliseconds to wait for a request before checking for Ctrl-C.
+const POLL_TIME: u64 = 100;
+/// Most bytes taken in the body of a request.
+const MAX_BODY: u64 = 4096;
+
+/// Body of `POST /lcd/text`.
+#[cfg(feature = "lcd")]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Text {
+    text: String,
+    /// Only replaces this row instead of the whole display.
+    row: Option<u8>,
+}
+
+/// Body of `POST /rgb/color`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ColorBody {
+    color: String,
+}
+
+/// Body of `POST /ledbar/level`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Level {
+    level: Option<u8>,
+    percent: Option<f32>,
+}
+
+/// Why a request failed and the status code to answer with.
+#[derive(Debug)]
+struct Failure {
+    status: u16,
+    message: String,
+}
+
+impl Failure {
+    fn new(status: u16, message: String) -> Self {
+        Failure { status, message }
+    }
+}
+
+/// Bad values are the caller's fault, anything else is a problem with the
+/// kit.
+impl From<SuperKitError> for Failure {
+    fn from(error: SuperKitError) -> Self {
+        let status = match error {
+            SuperKitError::InvalidArgument(_) | SuperKitError::Parse { .. } => 400,
+            _ => 500,
+        };
+        // Causes are added on so the caller sees more than just the context.
+        let mut message = error.to_string();
+        let mut source = error.source();
+        while let Some(cause) = source {
+            message = format!("{}: {}", message, cause);
+            source = cause.source();
+        }
+        Failure::new(status, message)
+    }
+}
+
+/// Structure holding the outputs the REST service drives.
+///
+/// Only the parts that are given are driven, asking for any others gives a
+/// 404 saying the part isn't connected.
+#[derive(Default)]
+pub struct RestApi {
+    #[cfg(feature = "lcd")]
+    lcd: Option<Lcd1602>,
+    rgb: Option<RgbPwm>,
+    led_bar: Option<LedBar>,
+    relay: Option<Led>,
+    /// What is on each row of the LCD.
+    #[cfg(feature = "lcd")]
+    lcd_rows: Vec<String>,
+}
+
+impl RestApi {
+    /// Makes a service without any parts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Drives the LCD from `POST /lcd/text`.
+    #[cfg(feature = "lcd")]
+    pub fn set_lcd(&mut self, lcd: Lcd1602) {
+        self.lcd_rows = vec![String::new(); lcd.geometry().rows() as usize];
+        self.lcd = Some(lcd);
+    }
+    /// Drives the RGB LED from `POST /rgb/color`.
+    pub fn set_rgb(&mut self, rgb: RgbPwm) {
+        self.rgb = Some(rgb);
+    }
+    /// Drives the LED bar from `POST /ledbar/level`.
+    pub fn set_led_bar(&mut self, led_bar: LedBar) {
+        self.led_bar = Some(led_bar);
+    }
+    /// Drives a relay, or anything else switched like an LED, from
+    /// `POST /relay/on` and `POST /relay/off`.
+    pub fn set_relay(&mut self, relay: Led) {
+        self.relay = Some(relay);
+    }
+    /// Serves the API on the given address, like `0.0.0.0:8080`, until the
+    /// token is cancelled.
+    ///
+    /// Requests are answered one at a time so two scripts can't fight over a
+    /// part half way through a change.
+    pub fn serve(&mut self, address: &str, token: &CancelToken) -> Result<()> {
+        let server = Server::http(address).map_err(|source| SuperKitError::Http {
+            context: format!("Failed to start REST service on {}", address),
+            source,
+        })?;
+        while token.is_running() {
+            if let Some(request) = server
+                .recv_timeout(Duration::from_millis(POLL_TIME))
+                .context("Failed to get web request")?
+            {
+                self.handle(request);
+            }
+        }
+        Ok(())
+    }
+    /// Internal method to answer a request.
+    fn handle(&mut self, mut request: Request) {
+        debug!(target: "rest", "{} {}", request.method(), request.url());
+        let method = request.method().clone();
+        let url = request.url().to_string();
+        let mut body = String::new();
+        let result = request
+            .as_reader()
+            .take(MAX_BODY + 1)
+            .read_to_string(&mut body)
+            .context("Failed to read request body")
+            .map_err(Failure::from)
+            .and_then(|size| {
+                if size as u64 > MAX_BODY {
+                    return Err(Failure::new(
+                        413,
+                        format!("Body is over the limit of {} bytes", MAX_BODY),
+                    ));
+                }
+                self.route(&method, &url, &body)
+            });
+        let (status, value) = match result {
+            Ok(value) => (200, value),
+            Err(failure) => {
+                debug!(target: "rest", "{} {}: {}", method, url, failure.message);
+                (failure.status, json!({ "error": failure.message }))
+            }
+        };
+        let response = respond(status, "application/json", value.to_string());
+        // The caller going away isn't a problem for the service.
+        if let Err(e) = request.respond(response) {
+            debug!(target: "rest", "Failed to answer {}: {}", url, e);
+        }
+    }
+    /// Internal method to send a request to the part it is for.
+    fn route(&mut self, method: &Method, url: &str, body: &str) -> Result<Value, Failure> {
+        let path = url.split('?').next().unwrap_or(url);
+        let allowed = match path {
+            "/" => Method::Get,
+            "/lcd/text" | "/rgb/color" | "/ledbar/level" | "/relay/on" | "/relay/off" => {
+                Method::Post
+            }
+            _ => return Err(Failure::new(404, format!("There is no endpoint {}", path))),
+        };
+        if *method != allowed {
+            return Err(Failure::new(
+                405,
+                format!("{} only takes {} but was sent {}", path, allowed, method),
+            ));
+        }
+        match path {
+            "/" => Ok(self.endpoints()),
+            #[cfg(feature = "lcd")]
+            "/lcd/text" => self.lcd_text(parse(body)?),
+            #[cfg(not(feature = "lcd"))]
+            "/lcd/text" => Err(missing("LCD")),
+            "/rgb/color" => self.rgb_color(parse(body)?),
+            "/ledbar/level" => self.led_bar_level(parse(body)?),
+            "/relay/on" => self.relay_set(true),
+            _ => self.relay_set(false),
+        }
+    }
+    /// Internal method to show text on the LCD.
+    #[cfg(feature = "lcd")]
+    fn lcd_text(&mut self, body: Text) -> Result<Value, Failure> {
+        let lcd = match self.lcd.as_mut() {
+            Some(lcd) => lcd,
+            None => return Err(missing("LCD")),
+        };
+        let geometry = lcd.geometry();
+        let columns = geometry.columns() as usize;
+        match body.row {
+            Some(row) if row >= geometry.rows() => {
+                return Err(Failure::new(
+                    400,
+                    format!("Row {} is past the last row {}", row, geometry.rows() - 1),
+                ))
+            }
+            Some(row) => {
+                let text: String = body.text.chars().take(columns).collect();
+                // Padded out so nothing is left over from the old text.
+                lcd.print_at(row, 0, &format!("{:<1$}", text, columns))?;
+                self.lcd_rows[row as usize] = text;
+            }
+            None => {
+                lcd.clear()?;
+                lcd.print(&body.text)?;
+                let mut lines = body.text.split('\n');
+                for row in self.lcd_rows.iter_mut() {
+                    *row = lines
+                        .next()
+                        .unwrap_or_default()
+                        .chars()
+                        .take(columns)
+                        .collect();
+                }
+            }
+        }
+        Ok(json!({ "rows": self.lcd_rows }))
+    }
+    /// Internal method to change the color of the RGB LED.
+    fn rgb_color(&mut self, body: ColorBody) -> Result<Value, Failure> {
+        let color = Color::parse(&body.color)?;
+        match self.rgb.as_mut() {
+            Some(rgb) => {
+                rgb.set_color(color)?;
+                Ok(json!({ "color": format!("#{:06x}", rgb.color().to_hex()) }))
+            }
+            None => Err(missing("RGB LED")),
+        }
+    }
+    /// Internal method to light part of the LED bar.
+    fn led_bar_level(&mut self, body: Level) -> Result<Value, Failure> {
+        let led_bar = match self.led_bar.as_mut() {
+            Some(led_bar) => led_bar,
+            None => return Err(missing("LED bar")),
+        };
+        match (body.level, body.percent) {
+            (Some(level), None) => led_bar.set_level(level)?,
+            (None, Some(percent)) if (0.0..=100.0).contains(&percent) => {
+                led_bar.set_percent(percent)?
+            }
+            (None, Some(percent)) => {
+                return Err(Failure::new(
+                    400,
+                    format!("Percent {} is outside of 0.0 to 100.0", percent),
+                ))
+            }
+            _ => {
+                return Err(Failure::new(
+                    400,
+                    format!("Need either a level from 0 to {} or a percent", LEDS),
+                ))
+            }
+        }
+        let mask = led_bar.mask();
+        Ok(json!({ "level": mask.count_ones(), "mask": mask }))
+    }
+    /// Internal method to switch the relay.
+    fn relay_set(&mut self, on: bool) -> Result<Value, Failure> {
+        match self.relay.as_mut() {
+            Some(relay) => {
+                relay.set(on)?;
+                Ok(json!({ "on": relay.is_on() }))
+            }
+            None => Err(missing("relay")),
+        }
+    }
+    /// Internal method to list the endpoints of the parts that were given.
+    fn endpoints(&self) -> Value {
+        json!({ "endpoints": self.endpoint_names() })
+    }
+    /// Internal method to get the paths of the parts that were given.
+    fn endpoint_names(&self) -> Vec<&'static str> {
+        let mut names = Vec::new();
+        #[cfg(feature = "lcd")]
+        {
+            if self.lcd.is_some() {
+                names.push("/lcd/text");
+            }
+        }
+        if self.rgb.is_some() {
+            names.push("/rgb/color");
+        }
+        if self.led_bar.is_some() {
+            names.push("/ledbar/level");
+        }
+        if self.relay.is_some() {
+            names.push("/relay/on");
+            names.push("/relay/off");
+        }
+        names
+    }
+}
+
+/// Internal function to read a JSON body.
+fn parse<T: DeserializeOwned>(body: &str) -> Result<T, Failure> {
+    serde_json::from_str(body).map_err(|e| Failure::new(400, format!("Bad JSON body: {}", e)))
+}
+
+/// Internal function to say a part wasn't given to the service.
+fn missing(part: &str) -> Failure {
+    Failure::new(404, format!("There is no {} connected to the kit", part))
+}
@@ -0,0 +1,348 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::{
+    gpio::{Gpio, OutputPin},
+    pwm::{self, Channel, Pwm},
+};
+use crate::registry::{self, PinClaim};
+use crate::{pwm::hardware_channel, Color, Easing};
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    thread::{sleep, spawn, JoinHandle},
+    time::{Duration, Instant},
+};
+
+// PWM frequency in Hz.
+const FREQUENCY: f64 = 2000.0;
+// Time in milliseconds between color changes during animations. Fast enough
+// that the steps can't be seen.
+const STEP_TIME: u64 = 10;
+// Gpio pin numbers used in the lesson.
+const PINS: [u8; 3] = [17, 18, 27];
+
+/// One color of the LED driven by either the PWM hardware or in software.
+enum Led {
+    Hardware(Pwm),
+    Software(OutputPin),
+}
+
+impl Led {
+    /// Internal associative method (function) to get a software PWM pin
+    /// starting with the LED off.
+    fn software(gpio: &Gpio, pin: u8, name: &str, polarity: Polarity) -> Result<Self> {
+        let mut output = gpio
+            .get(pin)
+            .context(format!("Failed to get {} LED", name))?
+            .into_output();
+        match polarity {
+            Polarity::CommonCathode => output.set_low(),
+            Polarity::CommonAnode => output.set_high(),
+        }
+        output
+            .set_pwm_frequency(FREQUENCY, polarity.duty_cycle(0.0))
+            .context(format!("Failed to initialize PWM for {} LED", name))?;
+        Ok(Led::Software(output))
+    }
+    /// Internal associative method (function) to get a hardware PWM channel
+    /// starting with the LED off.
+    fn hardware(channel: Channel, name: &str, polarity: Polarity) -> Result<Self> {
+        let pwm = Pwm::with_frequency(
+            channel,
+            FREQUENCY,
+            polarity.duty_cycle(0.0),
+            pwm::Polarity::Normal,
+            true,
+        )
+        .context(format!(
+            "Failed to get hardware PWM for {} LED, is the pwm-2chan overlay enabled",
+            name
+        ))?;
+        Ok(Led::Hardware(pwm))
+    }
+    /// Internal method to change the duty cycle.
+    fn set_duty_cycle(&mut self, duty_cycle: f64, name: &str) -> Result<()> {
+        let context = || format!("Failed to change {} duty cycle", name);
+        match self {
+            Led::Hardware(pwm) => pwm.set_duty_cycle(duty_cycle).with_context(context),
+            Led::Software(pin) => pin
+                .set_pwm_frequency(FREQUENCY, duty_cycle)
+                .with_context(context),
+        }
+    }
+}
+
+/// How the LED is wired up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Polarity {
+    /// The shared pin goes to ground and a high output turns a color on.
+    CommonCathode,
+    /// The shared pin goes to 3.3V and a low output turns a color on.
+    CommonAnode,
+}
+
+impl Polarity {
+    /// Internal method to turn how bright a color should be from 0.0 to 1.0
+    /// into the duty cycle that gives it.
+    fn duty_cycle(self, brightness: f64) -> f64 {
+        match self {
+            Polarity::CommonCathode => brightness,
+            Polarity::CommonAnode => 1.0 - brightness,
+        }
+    }
+}
+
+/// The three colors of the LED and the color they were last set to.
+struct Leds {
+    red: Led,
+    green: Led,
+    blue: Led,
+    color: Color,
+    polarity: Polarity,
+}
+
+impl Leds {
+    /// Internal method to change the duty cycles to show a color.
+    fn set_color(&mut self, color: Color) -> Result<()> {
+        // Each 0-255 part becomes a 0.0-1.0 duty cycle.
+        let [red, green, blue] = color.to_ratios();
+        let [red, green, blue] = [
+            self.polarity.duty_cycle(red),
+            self.polarity.duty_cycle(green),
+            self.polarity.duty_cycle(blue),
+        ];
+        // Set the new duty cycles.
+        self.red.set_duty_cycle(red, "red")?;
+        self.green.set_duty_cycle(green, "green")?;
+        self.blue.set_duty_cycle(blue, "blue")?;
+        self.color = color;
+        Ok(())
+    }
+}
+
+/// A fade running in the background.
+struct Fade {
+    running: Arc<AtomicBool>,
+    worker: JoinHandle<Result<()>>,
+}
+
+/// Structure for Pulse Width Modulated RGB led.
+///
+/// Used in Lesson 5.
+/// Software PWM is used by default which works on any pin but can flicker
+/// when the Pi is busy.
+/// See [RgbPwm::with_hardware_pwm()] for a steadier option.
+/// [RgbPwm::new()] assumes a common cathode LED like the lesson code always
+/// did, the other constructors take the polarity so a common anode LED also
+/// starts off.
+pub struct RgbPwm {
+    leds: Arc<Mutex<Leds>>,
+    fade: Option<Fade>,
+    _claim: PinClaim,
+}
+
+impl RgbPwm {
+    /// Gets the lesson's red, green, and blue pins for a common cathode LED
+    /// and starts them with the LED off.
+    pub fn new() -> Result<Self> {
+        Self::with_pins(PINS[0], PINS[1], PINS[2], Polarity::CommonCathode)
+    }
+    /// Gets the given red, green, and blue pins for an LED wired up the given
+    /// way and starts them with the LED off.
+    pub fn with_pins(red: u8, green: u8, blue: u8, polarity: Polarity) -> Result<Self> {
+        let claim = registry::claim("RgbPwm", &[red, green, blue])?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        #[cfg(feature = "mock")]
+        crate::simulator::attach(crate::simulator::Part::Rgb([red, green, blue]));
+        Ok(Self::from_leds(
+            Led::software(&gpio, red, "red", polarity)?,
+            Led::software(&gpio, green, "green", polarity)?,
+            Led::software(&gpio, blue, "blue", polarity)?,
+            polarity,
+            claim,
+        ))
+    }
+    /// Gets the given red, green, and blue pins using hardware PWM for any of
+    /// them that can and software PWM for the rest.
+    ///
+    /// The Pi only has two PWM channels, PWM0 on GPIO 12 or 18 and PWM1 on
+    /// GPIO 13 or 19, so at least one of the colors will always fall back to
+    /// software PWM.
+    /// The channels have to be routed to the pins first by adding something
+    /// like `dtoverlay=pwm-2chan,pin=18,func=2,pin2=19,func2=2` to
+    /// `/boot/config.txt`.
+    pub fn with_hardware_pwm(red: u8, green: u8, blue: u8, polarity: Polarity) -> Result<Self> {
+        let claim = registry::claim("RgbPwm", &[red, green, blue])?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        #[cfg(feature = "mock")]
+        crate::simulator::attach(crate::simulator::Part::Rgb([red, green, blue]));
+        let mut used = Vec::new();
+        let mut led = |pin: u8, name: &str| match hardware_channel(pin) {
+            Ok(channel) if !used.contains(&channel) => {
+                used.push(channel);
+                Led::hardware(channel, name, polarity)
+            }
+            _ => Led::software(&gpio, pin, name, polarity),
+        };
+        let red = led(red, "red")?;
+        let green = led(green, "green")?;
+        let blue = led(blue, "blue")?;
+        Ok(Self::from_leds(red, green, blue, polarity, claim))
+    }
+    /// How the LED is taken to be wired up.
+    pub fn polarity(&self) -> Polarity {
+        self.lock().polarity
+    }
+    /// Sets how the LED is wired up so colors come out right with either type.
+    ///
+    /// The current color is shown again using the new polarity.
+    pub fn set_polarity(&mut self, polarity: Polarity) -> Result<()> {
+        self.stop_fade()?;
+        let mut leds = self.lock();
+        leds.polarity = polarity;
+        let color = leds.color;
+        leds.set_color(color)
+    }
+    /// The color the LED was last set to.
+    ///
+    /// While fading this is the color part way through the fade.
+    pub fn color(&self) -> Color {
+        self.lock().color
+    }
+    /// Changes the color of the LED.
+    ///
+    /// Stops any fade that is still running first.
+    pub fn set_color(&mut self, color: Color) -> Result<()> {
+        self.stop_fade()?;
+        self.lock().set_color(color)
+    }
+    /// Changes the color of the LED using hue, saturation, and value.
+    ///
+    /// See [Color::from_hsv()] for the ranges.
+    pub fn set_hsv(&mut self, hue: f64, saturation: f64, value: f64) -> Result<()> {
+        self.set_color(Color::from_hsv(hue, saturation, value))
+    }
+    /// Smoothly sweeps the hue once all the way around the color wheel at full
+    /// saturation and brightness taking the given time.
+    pub fn rainbow(&mut self, cycle_time: Duration) -> Result<()> {
+        self.stop_fade()?;
+        let start = Instant::now();
+        loop {
+            let elapsed = start.elapsed();
+            if elapsed >= cycle_time {
+                break;
+            }
+            let hue = 360.0 * elapsed.as_secs_f64() / cycle_time.as_secs_f64();
+            self.lock().set_color(Color::from_hsv(hue, 1.0, 1.0))?;
+            sleep(Duration::from_millis(STEP_TIME));
+        }
+        self.set_hsv(0.0, 1.0, 1.0)
+    }
+    /// Starts fading from the current color to a new one over the given time
+    /// following an easing curve.
+    ///
+    /// The fade runs in the background so this returns straight away.
+    /// Use [RgbPwm::wait_fade()] to wait for it to finish.
+    /// Starting a new fade or setting a color stops any fade still running
+    /// where it is.
+    pub fn fade_to(&mut self, color: Color, duration: Duration, easing: Easing) -> Result<()> {
+        self.stop_fade()?;
+        let from = self.color();
+        let running = Arc::new(AtomicBool::new(true));
+        let r = running.clone();
+        let leds = self.leds.clone();
+        let worker = spawn(move || {
+            let start = Instant::now();
+            while r.load(Ordering::SeqCst) {
+                let elapsed = start.elapsed();
+                if elapsed >= duration {
+                    break;
+                }
+                let t = elapsed.as_secs_f64() / duration.as_secs_f64();
+                let step = from.lerp(color, easing.apply(t));
+                leds.lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .set_color(step)?;
+                sleep(Duration::from_millis(STEP_TIME));
+            }
+            if r.load(Ordering::SeqCst) {
+                leds.lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .set_color(color)?;
+            }
+            Ok(())
+        });
+        self.fade = Some(Fade { running, worker });
+        Ok(())
+    }
+    /// Checks if a fade is still running.
+    pub fn is_fading(&self) -> bool {
+        matches!(&self.fade, Some(fade) if !fade.worker.is_finished())
+    }
+    /// Waits for any running fade to finish.
+    pub fn wait_fade(&mut self) -> Result<()> {
+        match self.fade.take() {
+            Some(fade) => fade
+                .worker
+                .join()
+                .map_err(|_| SuperKitError::ThreadPanic("Fade".to_string()))?,
+            None => Ok(()),
+        }
+    }
+    /// Stops any running fade where it is.
+    pub fn stop_fade(&mut self) -> Result<()> {
+        if let Some(fade) = &self.fade {
+            fade.running.store(false, Ordering::SeqCst);
+        }
+        self.wait_fade()
+    }
+    /// Internal associative method (function) to finish construction once the
+    /// LEDs are set up.
+    fn from_leds(red: Led, green: Led, blue: Led, polarity: Polarity, claim: PinClaim) -> Self {
+        let leds = Leds {
+            red,
+            green,
+            blue,
+            color: Color::BLACK,
+            polarity,
+        };
+        RgbPwm {
+            leds: Arc::new(Mutex::new(leds)),
+            fade: None,
+            _claim: claim,
+        }
+    }
+    /// Internal method used to get at the LEDs shared with the fade worker.
+    fn lock(&self) -> MutexGuard<'_, Leds> {
+        self.leds.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Stop any fade and turn the LED off before exiting.
+impl Drop for RgbPwm {
+    fn drop(&mut self) {
+        let _ = self.stop_fade();
+        let _ = self.lock().set_color(Color::BLACK);
+    }
+}
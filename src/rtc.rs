@@ -0,0 +1,361 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::{
+    gpio::{Gpio, IoPin, Mode, OutputPin},
+    i2c::I2c,
+};
+use crate::registry::{self, PinClaim};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
+use std::{thread::sleep, time::Duration};
+
+/// I2C address of the DS3231.
+pub const DS3231_ADDRESS: u16 = 0x68;
+// DS3231 registers.
+const DS3231_TIME: u8 = 0x00;
+const DS3231_CONTROL: u8 = 0x0e;
+const DS3231_STATUS: u8 = 0x0f;
+const DS3231_TEMPERATURE: u8 = 0x11;
+// Oscillator stop flag in the status register.
+const OSF: u8 = 0x80;
+// Century bit in the month register.
+const CENTURY: u8 = 0x80;
+// 12 hour mode bit in the hours register.
+const HOUR_12: u8 = 0x40;
+// Pins used for the DS1302 which are free in the LCD and segment display
+// lessons.
+const DS1302_CLK: u8 = 5;
+const DS1302_IO: u8 = 6;
+const DS1302_CE: u8 = 13;
+// DS1302 commands.
+const DS1302_CLOCK_BURST_READ: u8 = 0xbf;
+const DS1302_CLOCK_BURST_WRITE: u8 = 0xbe;
+const DS1302_WRITE_PROTECT: u8 = 0x8e;
+// Clock halt bit in the DS1302 seconds register.
+const CLOCK_HALT: u8 = 0x80;
+// Clock half period in µs. The DS1302 is good up to 500kHz at 2V.
+const CLOCK_DELAY: u64 = 2;
+
+/// Common interface for real time clock chips so clock code doesn't care which
+/// one is used.
+///
+/// Times are local time since that is what the chips are normally set to.
+pub trait Rtc {
+    /// Reads the current date and time.
+    fn now(&mut self) -> Result<NaiveDateTime>;
+    /// Sets the date and time, which the backup battery then keeps going.
+    fn set(&mut self, time: &NaiveDateTime) -> Result<()>;
+    /// Checks if the clock stopped at some point, like the battery going flat
+    /// while the power was off, so the time can't be trusted.
+    fn lost_time(&mut self) -> Result<bool>;
+}
+
+/// Structure used to model a DS3231 temperature compensated real time clock on
+/// the I2C bus.
+pub struct Ds3231 {
+    i2c: I2c,
+}
+
+impl Ds3231 {
+    /// Gets the chip on I2C bus 1.
+    ///
+    /// The DS3231 uses the same address as an MPU6050 with AD0 low so they
+    /// can't share the bus unless the MPU6050 is moved.
+    pub fn new() -> Result<Self> {
+        let mut i2c = I2c::new().context("Failed to get I2C bus, is I2C enabled")?;
+        i2c.set_slave_address(DS3231_ADDRESS)
+            .context("Failed to set DS3231 address")?;
+        let mut ds3231 = Ds3231 { i2c };
+        // Make sure the oscillator keeps running on the battery.
+        let control = ds3231.read(DS3231_CONTROL, 1)?[0];
+        ds3231.write(DS3231_CONTROL, &[control & 0x7f])?;
+        Ok(ds3231)
+    }
+    /// Reads the chip's temperature sensor in °C which it uses to keep time
+    /// accurately.
+    ///
+    /// Only updated every 64 seconds and to the nearest 0.25°C.
+    pub fn temperature(&mut self) -> Result<f64> {
+        let buffer = self.read(DS3231_TEMPERATURE, 2)?;
+        let quarters = i16::from_be_bytes([buffer[0], buffer[1]]) >> 6;
+        Ok(f64::from(quarters) * 0.25)
+    }
+    /// Internal method to read registers starting at the given one.
+    fn read(&mut self, register: u8, len: usize) -> Result<Vec<u8>> {
+        let mut buffer = vec![0u8; len];
+        self.i2c
+            .write_read(&[register], &mut buffer)
+            .context(format!("Failed to read DS3231 register: {:#04x}", register))?;
+        Ok(buffer)
+    }
+    /// Internal method to write registers starting at the given one.
+    fn write(&mut self, register: u8, values: &[u8]) -> Result<()> {
+        let mut buffer = vec![register];
+        buffer.extend_from_slice(values);
+        self.i2c.write(&buffer).context(format!(
+            "Failed to write DS3231 register: {:#04x}",
+            register
+        ))?;
+        Ok(())
+    }
+}
+
+impl Rtc for Ds3231 {
+    fn now(&mut self) -> Result<NaiveDateTime> {
+        let r = self.read(DS3231_TIME, 7)?;
+        let century = if r[5] & CENTURY != 0 { 2100 } else { 2000 };
+        date_time(
+            century + from_bcd(r[6]) as i32,
+            from_bcd(r[5] & 0x1f),
+            from_bcd(r[4] & 0x3f),
+            hours(r[2]),
+            from_bcd(r[1] & 0x7f),
+            from_bcd(r[0] & 0x7f),
+        )
+    }
+    fn set(&mut self, time: &NaiveDateTime) -> Result<()> {
+        let (century, year) = split_year(time.year(), 2000, 2199)?;
+        let month = to_bcd(time.month()) | if century { CENTURY } else { 0 };
+        self.write(
+            DS3231_TIME,
+            &[
+                to_bcd(time.second()),
+                to_bcd(time.minute()),
+                to_bcd(time.hour()),
+                time.weekday().number_from_monday() as u8,
+                to_bcd(time.day()),
+                month,
+                year,
+            ],
+        )?;
+        // The time is good now.
+        let status = self.read(DS3231_STATUS, 1)?[0];
+        self.write(DS3231_STATUS, &[status & !OSF])
+    }
+    fn lost_time(&mut self) -> Result<bool> {
+        Ok(self.read(DS3231_STATUS, 1)?[0] & OSF != 0)
+    }
+}
+
+/// Structure used to model a DS1302 real time clock using its 3 wire serial
+/// interface.
+pub struct Ds1302 {
+    clk: OutputPin,
+    io: IoPin,
+    ce: OutputPin,
+    _claim: PinClaim,
+}
+
+impl Ds1302 {
+    /// Gets the default pins which are free in the LCD and segment display
+    /// lessons.
+    pub fn new() -> Result<Self> {
+        Self::with_pins(DS1302_CLK, DS1302_IO, DS1302_CE)
+    }
+    /// Gets the given GPIO pins for clock, data, and chip enable (sometimes
+    /// marked RST).
+    pub fn with_pins(clk: u8, io: u8, ce: u8) -> Result<Self> {
+        let claim = registry::claim("Ds1302", &[clk, io, ce])?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        let mut clk = gpio
+            .get(clk)
+            .context("Failed to get clock pin")?
+            .into_output();
+        clk.set_low();
+        let io = gpio
+            .get(io)
+            .context("Failed to get data pin")?
+            .into_io(Mode::Output);
+        let mut ce = gpio
+            .get(ce)
+            .context("Failed to get chip enable pin")?
+            .into_output();
+        ce.set_low();
+        Ok(Ds1302 {
+            clk,
+            io,
+            ce,
+            _claim: claim,
+        })
+    }
+    /// Internal method to send a command then read the given number of bytes.
+    fn read(&mut self, command: u8, len: usize) -> Vec<u8> {
+        self.ce.set_high();
+        sleep(Duration::from_micros(CLOCK_DELAY * 2));
+        self.write_byte(command);
+        // The chip puts the first bit out on the last falling edge of the
+        // command.
+        self.io.set_mode(Mode::Input);
+        let mut bytes = Vec::with_capacity(len);
+        for _ in 0..len {
+            let mut byte = 0u8;
+            for bit in 0..8 {
+                byte |= (self.io.is_high() as u8) << bit;
+                self.pulse();
+            }
+            bytes.push(byte);
+        }
+        self.io.set_mode(Mode::Output);
+        self.ce.set_low();
+        bytes
+    }
+    /// Internal method to send a command then the given bytes.
+    fn write(&mut self, command: u8, values: &[u8]) {
+        self.ce.set_high();
+        sleep(Duration::from_micros(CLOCK_DELAY * 2));
+        self.write_byte(command);
+        for value in values.iter() {
+            self.write_byte(*value);
+        }
+        self.ce.set_low();
+    }
+    /// Internal method to send one byte LSB first.
+    fn write_byte(&mut self, byte: u8) {
+        for bit in 0..8 {
+            if byte & (1 << bit) != 0 {
+                self.io.set_high();
+            } else {
+                self.io.set_low();
+            }
+            sleep(Duration::from_micros(CLOCK_DELAY));
+            self.pulse();
+        }
+    }
+    /// Internal method to give one clock pulse.
+    fn pulse(&mut self) {
+        self.clk.set_high();
+        sleep(Duration::from_micros(CLOCK_DELAY));
+        self.clk.set_low();
+        sleep(Duration::from_micros(CLOCK_DELAY));
+    }
+}
+
+impl Rtc for Ds1302 {
+    fn now(&mut self) -> Result<NaiveDateTime> {
+        let r = self.read(DS1302_CLOCK_BURST_READ, 8);
+        date_time(
+            2000 + from_bcd(r[6]) as i32,
+            from_bcd(r[4] & 0x1f),
+            from_bcd(r[3] & 0x3f),
+            hours(r[2]),
+            from_bcd(r[1] & 0x7f),
+            from_bcd(r[0] & 0x7f),
+        )
+    }
+    /// Also turns off write protection and starts the clock if it was halted.
+    fn set(&mut self, time: &NaiveDateTime) -> Result<()> {
+        let (_, year) = split_year(time.year(), 2000, 2099)?;
+        self.write(DS1302_WRITE_PROTECT, &[0]);
+        // The burst write has to include the write protect register last.
+        self.write(
+            DS1302_CLOCK_BURST_WRITE,
+            &[
+                to_bcd(time.second()),
+                to_bcd(time.minute()),
+                to_bcd(time.hour()),
+                to_bcd(time.day()),
+                to_bcd(time.month()),
+                time.weekday().number_from_monday() as u8,
+                year,
+                0,
+            ],
+        );
+        Ok(())
+    }
+    /// The DS1302 halts its clock when it loses all power so a halted clock
+    /// means the time is wrong.
+    fn lost_time(&mut self) -> Result<bool> {
+        Ok(self.read(DS1302_CLOCK_BURST_READ, 1)[0] & CLOCK_HALT != 0)
+    }
+}
+
+/// Internal function to turn packed BCD into a number.
+fn from_bcd(bcd: u8) -> u32 {
+    u32::from(bcd >> 4) * 10 + u32::from(bcd & 0x0f)
+}
+
+/// Internal function to turn a number below 100 into packed BCD.
+fn to_bcd(value: u32) -> u8 {
+    (((value / 10) << 4) | (value % 10)) as u8
+}
+
+/// Internal function to get the hours in 24 hour time from either 12 or 24
+/// hour mode.
+fn hours(register: u8) -> u32 {
+    if register & HOUR_12 == 0 {
+        return from_bcd(register & 0x3f);
+    }
+    let hour = from_bcd(register & 0x1f) % 12;
+    // Bit 5 is PM in 12 hour mode.
+    if register & 0x20 != 0 {
+        hour + 12
+    } else {
+        hour
+    }
+}
+
+/// Internal function to split a year into the century flag and the BCD year
+/// within the century checking it is one the chip can hold.
+fn split_year(year: i32, first: i32, last: i32) -> Result<(bool, u8)> {
+    if year < first || year > last {
+        return Err(SuperKitError::InvalidArgument(format!(
+            "Year {} is outside what the clock can hold, {}-{}",
+            year, first, last
+        )));
+    }
+    Ok((year >= first + 100, to_bcd(((year - first) % 100) as u32)))
+}
+
+/// Internal function to put the clock's registers together into a date and
+/// time.
+fn date_time(
+    year: i32,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+) -> Result<NaiveDateTime> {
+    NaiveDate::from_ymd_opt(year, month, day)
+        .and_then(|date| date.and_hms_opt(hour, minute, second))
+        .ok_or_else(|| {
+            SuperKitError::Device(format!(
+                "Clock has an invalid time: {}-{:02}-{:02} {:02}:{:02}:{:02}",
+                year, month, day, hour, minute, second
+            ))
+        })
+}
+
+/// Gets a clock chip by name, either `ds3231` or `ds1302` on its default pins.
+///
+/// Handy for picking the chip with a command line option.
+pub fn open(chip: &str) -> Result<Box<dyn Rtc>> {
+    match chip.trim().to_ascii_lowercase().as_str() {
+        "ds3231" => Ok(Box::new(Ds3231::new()?)),
+        "ds1302" => Ok(Box::new(Ds1302::new()?)),
+        _ => Err(SuperKitError::InvalidArgument(format!(
+            "Unknown clock chip \"{}\", use ds3231 or ds1302",
+            chip
+        ))),
+    }
+}
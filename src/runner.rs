@@ -0,0 +1,262 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Runs the body of a lesson so each one doesn't need to repeat the startup
+//! banner and signal handling.
+//!
+//! Ctrl-C (SIGINT) and SIGTERM, like from `kill` or stopping a service, both
+//! stop the lesson the same way so everything it set up is dropped and its
+//! pins let go of.
+//! SIGHUP asks for the settings to be reloaded in programs that can, like
+//! `superkitd`, and stops everything else the same as the other two.
+//! Those programs also reload when the config file is saved, see
+//! [LessonContext::watch_config()].
+
+use crate::cli::Common;
+use crate::config::Watcher;
+use crate::error::SuperKitError;
+use crate::hal::system::DeviceInfo;
+use crate::say;
+use log::{debug, info, warn};
+use signal_hook::{
+    consts::{SIGHUP, SIGINT, SIGTERM},
+    iterator::Signals,
+};
+use std::{
+    fmt::Display,
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex, MutexGuard, PoisonError,
+    },
+    thread::spawn,
+    time::Duration,
+};
+
+/// Flag shared between a lesson and anything that needs to know when it has
+/// been told to stop, like worker threads.
+///
+/// Cloning is cheap and all the clones share the same flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    inner: Arc<(Mutex<State>, Condvar)>,
+}
+
+/// Internal structure of what has been asked of a [CancelToken].
+#[derive(Debug, Default)]
+struct State {
+    cancelled: bool,
+    reload: bool,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Tells everything using the token to stop and wakes up any
+    /// [sleep()](CancelToken::sleep) right away.
+    pub fn cancel(&self) {
+        self.lock().cancelled = true;
+        self.inner.1.notify_all();
+    }
+    /// Checks if [cancel()](CancelToken::cancel) has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.lock().cancelled
+    }
+    /// Asks whatever is using the token to reload its settings and wakes up
+    /// any [sleep()](CancelToken::sleep) right away.
+    ///
+    /// Sleeps keep being cut short until it is taken with
+    /// [take_reload()](CancelToken::take_reload).
+    pub fn request_reload(&self) {
+        self.lock().reload = true;
+        self.inner.1.notify_all();
+    }
+    /// Checks for a reload asked for since the last time without clearing it.
+    pub fn is_reload_requested(&self) -> bool {
+        self.lock().reload
+    }
+    /// Checks for a reload asked for since the last time, clearing it.
+    pub fn take_reload(&self) -> bool {
+        mem::replace(&mut self.lock().reload, false)
+    }
+    /// Opposite of [is_cancelled()](CancelToken::is_cancelled) to read better
+    /// in loop conditions.
+    pub fn is_running(&self) -> bool {
+        !self.is_cancelled()
+    }
+    /// Sleeps for the given time or until cancelled or a reload is asked
+    /// for.
+    ///
+    /// Returns true if it slept the whole time and false if it was cut short.
+    pub fn sleep(&self, duration: Duration) -> bool {
+        let (state, _) = self
+            .inner
+            .1
+            .wait_timeout_while(self.lock(), duration, |state| {
+                !state.cancelled && !state.reload
+            })
+            .unwrap_or_else(PoisonError::into_inner);
+        !state.cancelled && !state.reload
+    }
+    /// Internal method to get at the state.
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.inner.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// What a lesson gets from [run_lesson()].
+pub struct LessonContext {
+    token: CancelToken,
+    reloadable: Arc<AtomicBool>,
+    watcher: Option<Watcher>,
+    teardown: Vec<Box<dyn FnOnce()>>,
+}
+
+impl LessonContext {
+    /// Checks that Ctrl-C hasn't been pressed.
+    pub fn is_running(&self) -> bool {
+        self.token.is_running()
+    }
+    /// Sleeps for the given time or until Ctrl-C is pressed.
+    ///
+    /// Returns true if it slept the whole time and false if it was cut short.
+    pub fn sleep(&self, duration: Duration) -> bool {
+        self.token.sleep(duration)
+    }
+    /// Stops the lesson the same as Ctrl-C would, like when a quit key is
+    /// pressed.
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+    /// Gets a token to hand to threads or callbacks that need to know when
+    /// the lesson stops.
+    pub fn token(&self) -> CancelToken {
+        self.token.clone()
+    }
+    /// Has SIGHUP ask for a reload through the token, see
+    /// [CancelToken::take_reload()], instead of stopping the lesson.
+    pub fn enable_reload(&self) {
+        self.reloadable.store(true, Ordering::SeqCst);
+    }
+    /// Asks for a reload whenever the config file is saved, as well as on
+    /// SIGHUP like [enable_reload()](LessonContext::enable_reload).
+    ///
+    /// Does nothing more than that when there isn't a config file to watch.
+    pub fn watch_config(&mut self, common: &Common) {
+        self.enable_reload();
+        match common.config_path() {
+            Some(path) => self.watcher = Some(Watcher::new(path, self.token())),
+            None => debug!(target: "runner", "no config file to watch"),
+        }
+    }
+    /// Takes any reload that has been asked for and gets the new settings
+    /// with `load`.
+    ///
+    /// Gives None when there wasn't a reload or when the new settings
+    /// couldn't be loaded, which is logged so the lesson can keep going with
+    /// the old ones.
+    pub fn reload<T, E, F>(&self, load: F) -> Option<T>
+    where
+        E: Display,
+        F: FnOnce() -> Result<T, E>,
+    {
+        if !self.token.take_reload() {
+            return None;
+        }
+        match load() {
+            Ok(settings) => {
+                info!(target: "runner", "Reloaded");
+                Some(settings)
+            }
+            Err(e) => {
+                warn!(target: "runner", "Keeping the old settings: {:#}", e);
+                None
+            }
+        }
+    }
+    /// Adds something to do once the lesson is over even if it returned an
+    /// error or panicked.
+    ///
+    /// They are done in the reverse order they were added, like drops.
+    pub fn on_teardown<F: FnOnce() + 'static>(&mut self, teardown: F) {
+        self.teardown.push(Box::new(teardown));
+    }
+}
+
+/// Does the teardowns.
+impl Drop for LessonContext {
+    fn drop(&mut self) {
+        while let Some(teardown) = self.teardown.pop() {
+            teardown();
+        }
+    }
+}
+
+/// Runs a lesson between the usual `started on a` and `stopped` messages with
+/// Ctrl-C, SIGTERM, and SIGHUP hooked up to its [LessonContext].
+///
+/// Anything the lesson sets up inside the closure is dropped first, then the
+/// [teardowns](LessonContext::on_teardown) are done, and last comes the
+/// stopped message.
+/// Call it after the config is loaded so `--quiet` is already in effect.
+pub fn run_lesson<F, E>(name: &str, lesson: F) -> Result<(), E>
+where
+    F: FnOnce(&mut LessonContext) -> Result<(), E>,
+    E: From<SuperKitError>,
+{
+    let info = DeviceInfo::new()
+        .map_err(|e| SuperKitError::Device(format!("Failed to get new DeviceInfo: {}", e)))?;
+    say!("{} started on a {}", name, info.model());
+    let token = CancelToken::new();
+    let reloadable = Arc::new(AtomicBool::new(false));
+    watch_signals(token.clone(), reloadable.clone())?;
+    let mut ctx = LessonContext {
+        token,
+        reloadable,
+        watcher: None,
+        teardown: Vec::new(),
+    };
+    let result = lesson(&mut ctx);
+    drop(ctx);
+    say!("\n{} stopped", name);
+    #[cfg(feature = "mock")]
+    let result = result.and(crate::session::save_recording().map_err(E::from));
+    result
+}
+
+/// Internal function to pass signals on to the token from a thread, where
+/// anything can be done safely unlike in a signal handler.
+fn watch_signals(token: CancelToken, reloadable: Arc<AtomicBool>) -> Result<(), SuperKitError> {
+    let mut signals = Signals::new([SIGINT, SIGTERM, SIGHUP]).map_err(SuperKitError::Signals)?;
+    spawn(move || {
+        for signal in signals.forever() {
+            if signal == SIGHUP && reloadable.load(Ordering::SeqCst) {
+                info!(target: "runner", "Reloading");
+                token.request_reload();
+            } else {
+                token.cancel();
+            }
+        }
+    });
+    Ok(())
+}
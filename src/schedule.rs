@@ -0,0 +1,375 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Does things on time rules, like turning a relay on every day at 18:00 or
+//! showing the temperature on the LCD every 10 minutes.
+//!
+//! Rules are read from the config file and act on outputs through an
+//! [EventBus] by publishing the same set events the dashboard and MQTT
+//! bridge do, or by running a [Script].
+//!
+//! ```toml
+//! [[superkitd.schedule]]
+//! at = "18:00"
+//! level = { output = "relay", value = 1.0 }
+//!
+//! [[superkitd.schedule]]
+//! at = "07:30"
+//! days = ["sat", "sun"]
+//! color = { output = "rgb", color = "warm_white" }
+//!
+//! [[superkitd.schedule]]
+//! every = 600
+//! text = { output = "lcd", text = "Temp {temperature}C" }
+//!
+//! [[superkitd.schedule]]
+//! every = 3600
+//! script = "chime.toml"
+//! ```
+//!
+//! Text can show the last [Event::Sample] from a sensor on the bus by putting
+//! its name in braces.
+
+use crate::bus::{Event, EventBus, SubscriptionId};
+use crate::config::Lesson;
+use crate::error::Result;
+use crate::runner::CancelToken;
+use crate::{Color, Script};
+use chrono::{DateTime, Datelike, Local, NaiveTime, TimeZone, Weekday};
+use log::{debug, info, warn};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    convert::TryFrom,
+    path::PathBuf,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+/// Longest time to sleep before checking the clock again, so a clock that
+/// jumps, like after NTP syncs at boot, is noticed.
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+/// Longest `every` allowed in seconds, a year is plenty for a kit.
+const MAX_EVERY: u64 = 366 * 24 * 60 * 60;
+
+/// When a [Rule] is due.
+#[derive(Clone, Debug, PartialEq)]
+pub enum When {
+    /// Every day at a time, or only on the given days if there are any.
+    At { time: NaiveTime, days: Vec<Weekday> },
+    /// Repeatedly with this much time in between, starting from when the
+    /// scheduler starts.
+    Every(Duration),
+}
+
+impl When {
+    /// Gives the first time after `now` this is due.
+    pub fn next_after(&self, now: DateTime<Local>) -> DateTime<Local> {
+        match self {
+            When::At { time, days } => {
+                let today = now.naive_local().date();
+                // Only a week needs checking to find an allowed day.
+                for offset in 0..=7 {
+                    let date = today + chrono::Duration::days(offset);
+                    if !days.is_empty() && !days.contains(&date.weekday()) {
+                        continue;
+                    }
+                    // Times skipped by daylight saving don't happen that day.
+                    if let Some(next) = Local.from_local_datetime(&date.and_time(*time)).earliest()
+                    {
+                        if next > now {
+                            return next;
+                        }
+                    }
+                }
+                now + chrono::Duration::days(1)
+            }
+            When::Every(interval) => chrono::Duration::from_std(*interval)
+                .ok()
+                .and_then(|interval| now.checked_add_signed(interval))
+                .unwrap_or_else(|| now + chrono::Duration::days(1)),
+        }
+    }
+}
+
+/// What a [Rule] does when it is due.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
+    /// Publishes [Event::LevelSet] for the output.
+    Level { output: String, value: f64 },
+    /// Publishes [Event::ColorSet] for the output.
+    Color { output: String, color: Color },
+    /// Publishes [Event::TextSet] for the output after filling in any
+    /// `{sample}` names.
+    Text { output: String, text: String },
+    /// Runs a script file.
+    Script(Script),
+}
+
+/// One time rule and what it does.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(try_from = "RuleTable")]
+pub struct Rule {
+    pub when: When,
+    pub action: Action,
+}
+
+/// How a [Rule] looks in the config file, the same way as a script
+/// [Step](crate::script::Step) is read.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleTable {
+    #[serde(default)]
+    at: Option<String>,
+    #[serde(default)]
+    days: Vec<String>,
+    /// Seconds between runs.
+    #[serde(default)]
+    every: Option<u64>,
+    #[serde(default)]
+    level: Option<LevelTable>,
+    #[serde(default)]
+    color: Option<ColorTable>,
+    #[serde(default)]
+    text: Option<TextTable>,
+    #[serde(default)]
+    script: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LevelTable {
+    output: String,
+    value: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ColorTable {
+    output: String,
+    color: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TextTable {
+    output: String,
+    text: String,
+}
+
+impl TryFrom<RuleTable> for Rule {
+    type Error = String;
+
+    fn try_from(table: RuleTable) -> std::result::Result<Self, Self::Error> {
+        let RuleTable {
+            at,
+            days,
+            every,
+            level,
+            color,
+            text,
+            script,
+        } = table;
+        let when = match (at, every) {
+            (Some(at), None) => When::At {
+                time: NaiveTime::parse_from_str(&at, "%H:%M")
+                    .or_else(|_| NaiveTime::parse_from_str(&at, "%H:%M:%S"))
+                    .map_err(|_| format!("time \"{}\" isn't like 18:00", at))?,
+                days: days
+                    .iter()
+                    .map(|day| {
+                        day.parse()
+                            .map_err(|_| format!("\"{}\" isn't a day like mon", day))
+                    })
+                    .collect::<std::result::Result<_, _>>()?,
+            },
+            (None, Some(0)) => return Err("every needs to be at least 1 second".to_string()),
+            (None, Some(every)) if every > MAX_EVERY => {
+                return Err(format!("every {} is more than a year", every))
+            }
+            (None, Some(every)) if days.is_empty() => When::Every(Duration::from_secs(every)),
+            (None, Some(_)) => return Err("days only goes with at".to_string()),
+            _ => return Err("each rule needs exactly one of at or every".to_string()),
+        };
+        let mut actions = Vec::new();
+        if let Some(LevelTable { output, value }) = level {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!("level {} is outside of 0.0 to 1.0", value));
+            }
+            actions.push(Action::Level { output, value });
+        }
+        if let Some(ColorTable { output, color }) = color {
+            let color = Color::parse(&color).map_err(|e| e.to_string())?;
+            actions.push(Action::Color { output, color });
+        }
+        actions.extend(text.map(|TextTable { output, text }| Action::Text { output, text }));
+        if let Some(path) = script {
+            actions.push(Action::Script(
+                Script::load(&path).map_err(|e| e.to_string())?,
+            ));
+        }
+        match actions.len() {
+            1 => Ok(Rule {
+                when,
+                action: actions.remove(0),
+            }),
+            _ => Err("each rule needs exactly one of level, color, text, or script".to_string()),
+        }
+    }
+}
+
+/// Last value of each sample seen on the bus.
+type Samples = Arc<Mutex<BTreeMap<String, f64>>>;
+
+/// Structure that runs [Rule]s as they come due.
+pub struct Scheduler {
+    bus: EventBus,
+    rules: Vec<(Rule, DateTime<Local>)>,
+    samples: Samples,
+    subscription: SubscriptionId,
+}
+
+impl Scheduler {
+    /// Starts keeping track of when each rule is next due and of the samples
+    /// published on the bus.
+    pub fn new(bus: EventBus, rules: Vec<Rule>) -> Self {
+        let now = Local::now();
+        let rules = rules
+            .into_iter()
+            .map(|rule| {
+                let next = rule.when.next_after(now);
+                (rule, next)
+            })
+            .collect();
+        let samples = Samples::default();
+        let s = samples.clone();
+        let subscription = bus.on(
+            |event| matches!(event, Event::Sample { .. }),
+            move |event| {
+                if let Event::Sample { source, value } = event {
+                    lock(&s).insert(source.clone(), *value);
+                }
+            },
+        );
+        Scheduler {
+            bus,
+            rules,
+            samples,
+            subscription,
+        }
+    }
+    /// Reads the rules from the `schedule` setting of the config table.
+    pub fn from_config(bus: EventBus, config: &Lesson) -> Result<Self> {
+        let rules: Vec<Rule> = config.get("schedule", Vec::new())?;
+        Ok(Self::new(bus, rules))
+    }
+    /// Gives the rules and when each is next due.
+    pub fn rules(&self) -> &[(Rule, DateTime<Local>)] {
+        &self.rules
+    }
+    /// Runs rules as they come due until the token is cancelled.
+    pub fn run(&mut self, token: &CancelToken) {
+        info!(target: "schedule", "{} rules", self.rules.len());
+        while token.is_running() {
+            let now = Local::now();
+            self.run_due(now, token);
+            let wait = self
+                .rules
+                .iter()
+                .map(|(_, next)| *next)
+                .min()
+                .and_then(|next| (next - now).to_std().ok())
+                .map_or(MAX_SLEEP, |wait| wait.min(MAX_SLEEP));
+            token.sleep(wait);
+        }
+    }
+    /// Runs every rule due at or before `now` once then works out when it is
+    /// next due.
+    ///
+    /// A rule that fails is logged and tried again next time.
+    pub fn run_due(&mut self, now: DateTime<Local>, token: &CancelToken) {
+        for i in 0..self.rules.len() {
+            if self.rules[i].1 > now {
+                continue;
+            }
+            let action = self.rules[i].0.action.clone();
+            debug!(target: "schedule", "Running {:?}", action);
+            if let Err(e) = self.act(action, token) {
+                warn!(target: "schedule", "{}", e);
+            }
+            let rule = &mut self.rules[i];
+            rule.1 = rule.0.when.next_after(now);
+        }
+    }
+    /// Internal method to do what a rule says.
+    fn act(&self, action: Action, token: &CancelToken) -> Result<()> {
+        let event = match action {
+            Action::Level { output, value } => Event::LevelSet {
+                source: output,
+                value,
+            },
+            Action::Color { output, color } => Event::ColorSet {
+                source: output,
+                color,
+            },
+            Action::Text { output, text } => Event::TextSet {
+                source: output,
+                text: self.fill(&text),
+            },
+            Action::Script(script) => return script.run(token),
+        };
+        self.bus.publish(event);
+        Ok(())
+    }
+    /// Internal method to replace `{name}` with the last sample of that
+    /// name, or `?` if there hasn't been one yet.
+    fn fill(&self, text: &str) -> String {
+        let samples = lock(&self.samples);
+        let mut filled = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find('{') {
+            let end = match rest[start..].find('}') {
+                Some(end) => start + end,
+                None => break,
+            };
+            filled.push_str(&rest[..start]);
+            match samples.get(&rest[start + 1..end]) {
+                Some(value) => filled.push_str(&format!("{:.1}", value)),
+                None => filled.push('?'),
+            }
+            rest = &rest[end + 1..];
+        }
+        filled.push_str(rest);
+        filled
+    }
+}
+
+/// Insure the bus stops sending samples to the scheduler once it is gone.
+impl Drop for Scheduler {
+    fn drop(&mut self) {
+        self.bus.unsubscribe(self.subscription);
+    }
+}
+
+/// Internal function to get at the samples.
+fn lock(samples: &Samples) -> MutexGuard<'_, BTreeMap<String, f64>> {
+    samples.lock().unwrap_or_else(PoisonError::into_inner)
+}
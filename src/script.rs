@@ -0,0 +1,321 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Runs simple GPIO sequences written in TOML so new ones can be made without
+//! touching any Rust.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::gpio::{Gpio, OutputPin};
+use crate::registry::{self, PinClaim};
+use crate::runner::CancelToken;
+use crate::say;
+#[cfg(feature = "shift-register")]
+use crate::HC595;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    convert::TryFrom,
+    fs,
+    path::Path,
+    time::Duration,
+};
+
+/// Level a [Step::Set] puts a pin at.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    High,
+    Low,
+}
+
+/// One thing for a [Script] to do.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(try_from = "StepTable", into = "StepTable")]
+pub enum Step {
+    /// Sets a GPIO pin high or low, like `set = { pin = 17, level = "low" }`.
+    Set { pin: u8, level: Level },
+    /// Shifts a byte out of the 74HC595 and latches it, like
+    /// `shift = 0x81`.
+    Shift(u8),
+    /// Waits for the given milliseconds, like `wait = 500`.
+    Wait(u64),
+    /// Does the steps the given number of times, like
+    /// `repeat = { times = 3, steps = [{ wait = 100 }] }`.
+    Repeat { times: u32, steps: Vec<Step> },
+    /// Shows a message, like `print = "LED on"`.
+    Print(String),
+}
+
+/// How a [Step] looks in the file, a table with one key naming the step.
+///
+/// The toml crate can only read an enum from an inline table, not from a
+/// `[[steps]]` table, so the steps are read through this instead.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct StepTable {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    set: Option<SetTable>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shift: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    wait: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    repeat: Option<RepeatTable>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    print: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct SetTable {
+    pin: u8,
+    level: Level,
+}
+
+#[derive(Deserialize, Serialize)]
+struct RepeatTable {
+    times: u32,
+    steps: Vec<Step>,
+}
+
+impl TryFrom<StepTable> for Step {
+    type Error = String;
+
+    fn try_from(table: StepTable) -> std::result::Result<Self, Self::Error> {
+        let StepTable {
+            set,
+            shift,
+            wait,
+            repeat,
+            print,
+        } = table;
+        let mut steps = Vec::new();
+        steps.extend(set.map(|SetTable { pin, level }| Step::Set { pin, level }));
+        steps.extend(shift.map(Step::Shift));
+        steps.extend(wait.map(Step::Wait));
+        steps.extend(repeat.map(|RepeatTable { times, steps }| Step::Repeat { times, steps }));
+        steps.extend(print.map(Step::Print));
+        match steps.len() {
+            1 => Ok(steps.remove(0)),
+            _ => {
+                Err("each step needs exactly one of set, shift, wait, repeat, or print".to_string())
+            }
+        }
+    }
+}
+
+impl From<Step> for StepTable {
+    fn from(step: Step) -> Self {
+        let mut table = StepTable::default();
+        match step {
+            Step::Set { pin, level } => table.set = Some(SetTable { pin, level }),
+            Step::Shift(byte) => table.shift = Some(byte),
+            Step::Wait(ms) => table.wait = Some(ms),
+            Step::Repeat { times, steps } => table.repeat = Some(RepeatTable { times, steps }),
+            Step::Print(text) => table.print = Some(text),
+        }
+        table
+    }
+}
+
+/// Pins of the 74HC595 used by [Step::Shift].
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct ShiftPins {
+    pub sdi: u8,
+    pub rclk: u8,
+    pub srclk: u8,
+}
+
+/// Same pins as lesson 10.
+impl Default for ShiftPins {
+    fn default() -> Self {
+        ShiftPins {
+            sdi: 17,
+            rclk: 18,
+            srclk: 27,
+        }
+    }
+}
+
+/// Structure holding a sequence of steps to run on the GPIO pins.
+///
+/// Scripts are normally loaded from a TOML file like this:
+///
+/// ```toml
+/// name = "Blink"
+///
+/// [[steps]]
+/// print = "Blinking"
+///
+/// [[steps]]
+/// repeat = { times = 5, steps = [
+///     { set = { pin = 17, level = "low" } },
+///     { wait = 500 },
+///     { set = { pin = 17, level = "high" } },
+///     { wait = 500 },
+/// ] }
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Script {
+    /// Shown when the script starts, the file name is used if left out.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Only needed if the chip isn't on the lesson 10 pins.
+    #[serde(default)]
+    pub hc595: Option<ShiftPins>,
+    pub steps: Vec<Step>,
+}
+
+impl Script {
+    /// Loads a script from a TOML file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .context(format!("Failed to read script file: {}", path.display()))?;
+        let mut script = Self::from_toml(&text)?;
+        if script.name.is_none() {
+            script.name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned());
+        }
+        Ok(script)
+    }
+    /// Parses a script from TOML text.
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let script: Script = toml::from_str(text).context("Failed to parse script")?;
+        if script.steps.is_empty() {
+            return Err(SuperKitError::InvalidArgument(
+                "Script has no steps".to_string(),
+            ));
+        }
+        Ok(script)
+    }
+    /// Gives the name of the script or `script` if it doesn't have one.
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or("script")
+    }
+    /// Runs all the steps once.
+    ///
+    /// Stops early once the token is cancelled, including part way through a
+    /// wait.
+    /// The pins are only taken while the script runs and are left low
+    /// afterwards.
+    pub fn run(&self, token: &CancelToken) -> Result<()> {
+        let mut machine = Machine {
+            token,
+            #[cfg(feature = "shift-register")]
+            hc595_pins: self.hc595.unwrap_or_default(),
+            #[cfg(feature = "shift-register")]
+            hc595: None,
+            pins: BTreeMap::new(),
+        };
+        machine.run(&self.steps)
+    }
+}
+
+/// Internal state of a running [Script].
+struct Machine<'a> {
+    token: &'a CancelToken,
+    #[cfg(feature = "shift-register")]
+    hc595_pins: ShiftPins,
+    #[cfg(feature = "shift-register")]
+    hc595: Option<HC595>,
+    pins: BTreeMap<u8, (OutputPin, PinClaim)>,
+}
+
+impl Machine<'_> {
+    /// Internal method to run a list of steps.
+    fn run(&mut self, steps: &[Step]) -> Result<()> {
+        for step in steps {
+            if self.token.is_cancelled() {
+                break;
+            }
+            match step {
+                Step::Set { pin, level } => {
+                    let pin = self.pin(*pin)?;
+                    match level {
+                        Level::High => pin.set_high(),
+                        Level::Low => pin.set_low(),
+                    }
+                }
+                #[cfg(feature = "shift-register")]
+                Step::Shift(byte) => {
+                    let hc595 = self.hc595()?;
+                    hc595.serial_in(*byte);
+                    hc595.parallel_out();
+                }
+                #[cfg(not(feature = "shift-register"))]
+                Step::Shift(_) => {
+                    return Err(SuperKitError::InvalidArgument(
+                        "Shift steps need the shift-register feature".to_string(),
+                    ))
+                }
+                Step::Wait(ms) => {
+                    self.token.sleep(Duration::from_millis(*ms));
+                }
+                Step::Repeat { times, steps } => {
+                    for _ in 0..*times {
+                        self.run(steps)?;
+                    }
+                }
+                Step::Print(text) => say!("{}", text),
+            }
+        }
+        Ok(())
+    }
+    /// Internal method to get a pin the first time it is used.
+    fn pin(&mut self, number: u8) -> Result<&mut OutputPin> {
+        let (pin, _) = match self.pins.entry(number) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let claim = registry::claim("Script", &[number])?;
+                let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+                let pin = gpio
+                    .get(number)
+                    .context(format!("Failed to get script pin: {}", number))?
+                    .into_output();
+                entry.insert((pin, claim))
+            }
+        };
+        Ok(pin)
+    }
+    /// Internal method to get the 74HC595 the first time it is used.
+    #[cfg(feature = "shift-register")]
+    fn hc595(&mut self) -> Result<&mut HC595> {
+        let hc595 = match self.hc595.take() {
+            Some(hc595) => hc595,
+            None => {
+                let ShiftPins { sdi, rclk, srclk } = self.hc595_pins;
+                HC595::with_pins(sdi, rclk, srclk)?
+            }
+        };
+        Ok(self.hc595.get_or_insert(hc595))
+    }
+}
+
+/// Insure the pins the script used are left low before exiting.
+impl Drop for Machine<'_> {
+    fn drop(&mut self) {
+        for (pin, _) in self.pins.values_mut() {
+            pin.set_low();
+        }
+    }
+}
@@ -0,0 +1,313 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::gpio::{Gpio, OutputPin};
+use crate::registry::{self, PinClaim};
+use crate::HC595;
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    thread::{sleep, spawn, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// How long each digit is lit during a refresh cycle.
+///
+/// With 4 digits this gives a full refresh rate of about 125Hz which is well
+/// above where flicker can be seen.
+const DIGIT_TIME: u64 = 2;
+/// Time in milliseconds blinking digits spend on and then off.
+const BLINK_TIME: u128 = 500;
+/// Decimal point segment bit.
+const POINT: u8 = 0x80;
+/// Segment code with all the segments off.
+pub const BLANK: u8 = 0x00;
+
+/// Converts an ASCII character into the segment code used to show it.
+///
+/// Bits 0-6 are segments a-g and bit 7 is the decimal point which matches the
+/// codes used in lesson 11.
+/// Letters that only have one reasonable shape are accepted in either case.
+/// Characters like K, M, W, or X that can't be made out on seven segments give
+/// an error instead of something misleading.
+pub fn encode_char(c: char) -> Result<u8> {
+    let code = match c {
+        '0' | 'O' => 0x3f,
+        '1' | 'I' => 0x06,
+        '2' => 0x5b,
+        '3' => 0x4f,
+        '4' => 0x66,
+        '5' | 'S' | 's' => 0x6d,
+        '6' => 0x7d,
+        '7' => 0x07,
+        '8' => 0x7f,
+        '9' | 'g' => 0x6f,
+        'A' | 'a' => 0x77,
+        'B' | 'b' => 0x7c,
+        'C' => 0x39,
+        'c' => 0x58,
+        'D' | 'd' => 0x5e,
+        'E' | 'e' => 0x79,
+        'F' | 'f' => 0x71,
+        'G' => 0x3d,
+        'H' => 0x76,
+        'h' => 0x74,
+        'i' => 0x04,
+        'J' | 'j' => 0x1e,
+        'L' => 0x38,
+        'l' => 0x30,
+        'N' | 'n' => 0x54,
+        'o' => 0x5c,
+        'P' | 'p' => 0x73,
+        'Q' | 'q' => 0x67,
+        'R' | 'r' => 0x50,
+        'T' | 't' => 0x78,
+        'U' => 0x3e,
+        'u' | 'v' => 0x1c,
+        'Y' | 'y' => 0x6e,
+        '-' => 0x40,
+        '_' => 0x08,
+        '=' => 0x48,
+        '"' => 0x22,
+        '\'' => 0x02,
+        '[' => 0x39,
+        ']' => 0x0f,
+        '°' => 0x63,
+        '.' => 0x80,
+        ' ' => BLANK,
+        _ => {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Character '{}' can not be shown on a seven segment display",
+                c
+            )))
+        }
+    };
+    Ok(code)
+}
+
+/// Converts a string into segment codes, one per character.
+pub fn encode_str(text: &str) -> Result<Vec<u8>> {
+    text.chars().map(encode_char).collect()
+}
+
+/// Per digit state shared with the refresh thread.
+#[derive(Clone, Copy)]
+struct Digits<const N: usize> {
+    codes: [u8; N],
+    points: [bool; N],
+    blinks: [bool; N],
+}
+
+/// Structure used to model a multiplexed multi-digit common cathode seven
+/// segment display.
+///
+/// The segments of all the digits are wired in parallel to the outputs of a
+/// 74HC595 and each digit's common cathode has its own select pin.
+/// A background thread lights one digit at a time fast enough that they all
+/// look like they are on together.
+pub struct SegmentDisplay<const N: usize> {
+    digits: Arc<Mutex<Digits<N>>>,
+    running: Arc<AtomicBool>,
+    refresh: Option<JoinHandle<()>>,
+    _claim: PinClaim,
+}
+
+impl<const N: usize> SegmentDisplay<N> {
+    /// Gets the digit select pins and starts the refresh thread.
+    ///
+    /// The digit pins are given left to right.
+    pub fn new(digit_pins: [u8; N]) -> Result<Self> {
+        let claim = registry::claim("SegmentDisplay", &digit_pins)?;
+        let mut hc595 = HC595::new()?;
+        #[cfg(feature = "mock")]
+        crate::simulator::attach(crate::simulator::Part::Segment {
+            hc595: hc595.pins(),
+            selects: digit_pins.to_vec(),
+        });
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        let mut selects = Vec::new();
+        for pin in digit_pins.iter() {
+            let mut select = gpio
+                .get(*pin)
+                .context(format!("Failed to get digit select pin: {}", pin))?
+                .into_output();
+            // Pulling the cathode high turns the digit off.
+            select.set_high();
+            selects.push(select);
+        }
+        let digits = Arc::new(Mutex::new(Digits {
+            codes: [BLANK; N],
+            points: [false; N],
+            blinks: [false; N],
+        }));
+        let running = Arc::new(AtomicBool::new(true));
+        let d = digits.clone();
+        let r = running.clone();
+        let refresh = spawn(move || {
+            let start = Instant::now();
+            while r.load(Ordering::SeqCst) {
+                let digits = *d.lock().unwrap_or_else(PoisonError::into_inner);
+                // Blinking digits are on for the first half of each period.
+                let blink_off = start.elapsed().as_millis() % (2 * BLINK_TIME) >= BLINK_TIME;
+                let mut codes = digits.codes;
+                for (i, code) in codes.iter_mut().enumerate() {
+                    if digits.points[i] {
+                        *code |= POINT;
+                    }
+                    if digits.blinks[i] && blink_off {
+                        *code = BLANK;
+                    }
+                }
+                Self::scan(&mut hc595, &mut selects, &codes);
+            }
+            for select in selects.iter_mut() {
+                select.set_high();
+            }
+        });
+        Ok(SegmentDisplay {
+            digits,
+            running,
+            refresh: Some(refresh),
+            _claim: claim,
+        })
+    }
+    /// Shows a decimal number right aligned with a leading minus sign if it is
+    /// negative.
+    pub fn show_number(&mut self, number: i32) -> Result<()> {
+        let text = number.to_string();
+        if text.len() > N {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Number {} is too wide for a {} digit display",
+                number, N
+            )));
+        }
+        self.show_text(&format!("{:>width$}", text, width = N))
+    }
+    /// Shows a number right aligned with a fixed number of decimal places.
+    ///
+    /// The decimal point doesn't use up a digit so 4 digits can show `12.34`.
+    pub fn show_float(&mut self, number: f64, places: usize) -> Result<()> {
+        let text = format!("{:.*}", places, number);
+        let width = text.chars().filter(|c| *c != '.').count();
+        if width > N {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Number {} is too wide for a {} digit display",
+                text, N
+            )));
+        }
+        self.show_text(&format!("{:>width$}", text, width = text.len() + N - width))
+    }
+    /// Shows text left aligned on the display.
+    ///
+    /// A '.' following another character turns on that digit's decimal point
+    /// instead of using up a digit of its own.
+    /// Any decimal points set before are replaced but blinking is left alone.
+    pub fn show_text(&mut self, text: &str) -> Result<()> {
+        let mut codes = [BLANK; N];
+        let mut points = [false; N];
+        let mut count = 0;
+        for c in text.chars() {
+            if c == '.' && count > 0 && !points[count - 1] {
+                points[count - 1] = true;
+                continue;
+            }
+            if count == N {
+                return Err(SuperKitError::InvalidArgument(format!(
+                    "Text \"{}\" is too long for a {} digit display",
+                    text, N
+                )));
+            }
+            codes[count] = encode_char(c)?;
+            count += 1;
+        }
+        let mut digits = self.lock();
+        digits.codes = codes;
+        digits.points = points;
+        Ok(())
+    }
+    /// Directly sets the segment codes for all the digits.
+    pub fn set_raw(&mut self, codes: [u8; N]) {
+        self.lock().codes = codes;
+    }
+    /// Turns the decimal point of a digit on or off.
+    ///
+    /// Digits are numbered left to right starting from 0.
+    pub fn set_decimal_point(&mut self, digit: usize, on: bool) -> Result<()> {
+        Self::check_digit(digit)?;
+        self.lock().points[digit] = on;
+        Ok(())
+    }
+    /// Makes a digit blink on and off or stay steady.
+    ///
+    /// Digits are numbered left to right starting from 0.
+    pub fn set_blink(&mut self, digit: usize, on: bool) -> Result<()> {
+        Self::check_digit(digit)?;
+        self.lock().blinks[digit] = on;
+        Ok(())
+    }
+    /// Makes all the digits blink on and off or stay steady.
+    pub fn set_blink_all(&mut self, on: bool) {
+        self.lock().blinks = [on; N];
+    }
+    /// Blanks all the digits and turns off the decimal points.
+    pub fn clear(&mut self) {
+        let mut digits = self.lock();
+        digits.codes = [BLANK; N];
+        digits.points = [false; N];
+    }
+    /// Internal associative method (function) to validate a digit number.
+    fn check_digit(digit: usize) -> Result<()> {
+        if digit >= N {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Digit {} is out of range for a {} digit display",
+                digit, N
+            )));
+        }
+        Ok(())
+    }
+    /// Internal method used to get at the state shared with refresh thread.
+    fn lock(&self) -> MutexGuard<'_, Digits<N>> {
+        self.digits.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+    /// Internal associative method (function) used by the refresh thread for
+    /// one pass over all the digits.
+    fn scan(hc595: &mut HC595, selects: &mut [OutputPin], codes: &[u8; N]) {
+        for (select, code) in selects.iter_mut().zip(codes.iter()) {
+            hc595.serial_in(*code);
+            hc595.parallel_out();
+            select.set_low();
+            sleep(Duration::from_millis(DIGIT_TIME));
+            select.set_high();
+        }
+    }
+}
+
+/// Stop the refresh thread and insure all the digits are off before exiting.
+impl<const N: usize> Drop for SegmentDisplay<N> {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(refresh) = self.refresh.take() {
+            let _ = refresh.join();
+        }
+    }
+}
@@ -0,0 +1,162 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::{
+    gpio::{Gpio, OutputPin},
+    pwm::{Channel, Polarity, Pwm},
+};
+use crate::registry::{self, PinClaim};
+use std::time::Duration;
+
+// Servos expect a pulse every 20ms (50Hz).
+const PERIOD: Duration = Duration::from_millis(20);
+// Pulse widths in µs for 0° and 180° that suit most SG90s.
+const MIN_PULSE: u64 = 500;
+const MAX_PULSE: u64 = 2400;
+/// Largest angle accepted by [Servo::set_angle()].
+pub const MAX_ANGLE: f64 = 180.0;
+
+/// Where the pulses come from.
+enum Output {
+    Hardware(Pwm),
+    Software(OutputPin),
+}
+
+/// Structure used to model a hobby servo like the SG90.
+///
+/// The angle is set by the width of a pulse sent 50 times a second.
+/// Hardware PWM gives rock steady pulses while software PWM works on any pin
+/// but can make the servo twitch a little when the Pi is busy.
+pub struct Servo {
+    output: Output,
+    min_pulse: Duration,
+    max_pulse: Duration,
+    angle: Option<f64>,
+    _claim: Option<PinClaim>,
+}
+
+impl Servo {
+    /// Uses one of the hardware PWM channels which need to be routed to a pin
+    /// with a pwm overlay in `/boot/config.txt` first.
+    ///
+    /// No pulses are sent until an angle is set.
+    pub fn hardware(channel: Channel) -> Result<Self> {
+        let pwm = Pwm::with_period(
+            channel,
+            PERIOD,
+            Duration::from_secs(0),
+            Polarity::Normal,
+            false,
+        )
+        .context("Failed to get hardware PWM for servo, is the pwm overlay enabled")?;
+        Ok(Self::with_output(Output::Hardware(pwm), None))
+    }
+    /// Uses software PWM on any GPIO pin.
+    ///
+    /// No pulses are sent until an angle is set.
+    pub fn software(pin: u8) -> Result<Self> {
+        let claim = registry::claim("Servo", &[pin])?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        let mut pin = gpio
+            .get(pin)
+            .context(format!("Failed to get servo pin: {}", pin))?
+            .into_output();
+        pin.set_low();
+        Ok(Self::with_output(Output::Software(pin), Some(claim)))
+    }
+    /// Calibrates the pulse widths for 0° and 180°.
+    ///
+    /// Every servo is a little different so these are worth tweaking until the
+    /// end points are right without the servo straining against its stops.
+    /// The defaults are 500µs and 2400µs.
+    pub fn set_pulse_range(&mut self, min: Duration, max: Duration) -> Result<()> {
+        if min >= max || max >= PERIOD {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Servo pulse range {:?} to {:?} is not usable",
+                min, max
+            )));
+        }
+        self.min_pulse = min;
+        self.max_pulse = max;
+        if let Some(angle) = self.angle {
+            self.set_angle(angle)?;
+        }
+        Ok(())
+    }
+    /// The angle last set or None if detached.
+    pub fn angle(&self) -> Option<f64> {
+        self.angle
+    }
+    /// Moves to the given angle from 0.0 to 180.0.
+    pub fn set_angle(&mut self, angle: f64) -> Result<()> {
+        if !(0.0..=MAX_ANGLE).contains(&angle) {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Servo angle {} is outside of 0 to {}",
+                angle, MAX_ANGLE
+            )));
+        }
+        let range = self.max_pulse - self.min_pulse;
+        let pulse = self.min_pulse + range.mul_f64(angle / MAX_ANGLE);
+        match &mut self.output {
+            Output::Hardware(pwm) => {
+                pwm.set_pulse_width(pulse)
+                    .context("Failed to change servo pulse width")?;
+                pwm.enable().context("Failed to enable servo PWM")?;
+            }
+            Output::Software(pin) => pin
+                .set_pwm(PERIOD, pulse)
+                .context("Failed to change servo pulse width")?,
+        }
+        self.angle = Some(angle);
+        Ok(())
+    }
+    /// Stops sending pulses so the servo goes limp and stops buzzing.
+    pub fn detach(&mut self) -> Result<()> {
+        match &mut self.output {
+            Output::Hardware(pwm) => pwm.disable().context("Failed to disable servo PWM")?,
+            Output::Software(pin) => {
+                pin.clear_pwm().context("Failed to stop servo PWM")?;
+                pin.set_low();
+            }
+        }
+        self.angle = None;
+        Ok(())
+    }
+    /// Internal associative method (function) to finish construction.
+    fn with_output(output: Output, claim: Option<PinClaim>) -> Self {
+        Servo {
+            output,
+            min_pulse: Duration::from_micros(MIN_PULSE),
+            max_pulse: Duration::from_micros(MAX_PULSE),
+            angle: None,
+            _claim: claim,
+        }
+    }
+}
+
+/// Insure the pulses have stopped before exiting.
+impl Drop for Servo {
+    fn drop(&mut self) {
+        let _ = self.detach();
+    }
+}
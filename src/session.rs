@@ -0,0 +1,409 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Records what is done to the GPIO pins so it can be saved, played back, or
+//! checked against a later run.
+//!
+//! A [Recorder] follows every pin write and read on the mock backend and
+//! turns them into a [Session], which is saved as one line per event like
+//! `0.012500 write 17 low` so it is easy to read and to diff.
+//! A saved session can be played back onto real or fake pins, or compared
+//! with a new recording by [Session::verify()] to check something like
+//! lesson 10 still shifting out the same bytes without a Raspberry Pi.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::gpio::{Gpio, Level, OutputPin};
+use crate::registry;
+use crate::runner::CancelToken;
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::Path,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+#[cfg(feature = "mock")]
+use {
+    crate::hal::mock::{self, Operation},
+    lazy_static::lazy_static,
+    std::{
+        path::PathBuf,
+        sync::{Arc, Mutex, PoisonError},
+    },
+};
+
+/// Whether a [PinEvent] drove the pin or read it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    Write,
+    Read,
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Access::Write => write!(f, "write"),
+            Access::Read => write!(f, "read"),
+        }
+    }
+}
+
+/// One write or read of a pin and when it happened.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PinEvent {
+    /// Time since the recording started.
+    pub elapsed: Duration,
+    pub access: Access,
+    pub pin: u8,
+    pub level: Level,
+}
+
+impl PinEvent {
+    /// Internal method to describe the event without its time for
+    /// [Session::verify()].
+    fn action(&self) -> String {
+        format!("{} {} {}", self.access, self.pin, level_name(self.level))
+    }
+}
+
+/// Same as a line in a saved [Session].
+impl fmt::Display for PinEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.6} {}", self.elapsed.as_secs_f64(), self.action())
+    }
+}
+
+impl FromStr for PinEvent {
+    type Err = SuperKitError;
+
+    /// Parses a line like `0.012500 write 17 low`.
+    fn from_str(line: &str) -> Result<Self> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (elapsed, access, pin, level) = match words.as_slice() {
+            [elapsed, access, pin, level] => (elapsed, access, pin, level),
+            _ => {
+                return Err(SuperKitError::InvalidArgument(format!(
+                    "Expected <seconds> <write|read> <pin> <high|low> but found: {}",
+                    line
+                )))
+            }
+        };
+        let elapsed = elapsed
+            .parse::<f64>()
+            .context(format!("Invalid time: {}", elapsed))?;
+        if !elapsed.is_finite() || elapsed < 0.0 {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Invalid time: {}",
+                elapsed
+            )));
+        }
+        let access = match *access {
+            "write" => Access::Write,
+            "read" => Access::Read,
+            _ => {
+                return Err(SuperKitError::InvalidArgument(format!(
+                    "Expected write or read but found: {}",
+                    access
+                )))
+            }
+        };
+        let pin = pin.parse().context(format!("Invalid pin: {}", pin))?;
+        let level = match *level {
+            "high" => Level::High,
+            "low" => Level::Low,
+            _ => {
+                return Err(SuperKitError::InvalidArgument(format!(
+                    "Expected high or low but found: {}",
+                    level
+                )))
+            }
+        };
+        Ok(PinEvent {
+            elapsed: Duration::from_secs_f64(elapsed),
+            access,
+            pin,
+            level,
+        })
+    }
+}
+
+/// Structure holding the pin writes and reads of a recorded run in the order
+/// they happened.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Session {
+    events: Vec<PinEvent>,
+}
+
+impl Session {
+    pub fn new(events: Vec<PinEvent>) -> Self {
+        Session { events }
+    }
+    /// Loads a session saved by [save()](Session::save).
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .context(format!("Failed to read session file: {}", path.display()))?;
+        Self::parse(&text)
+            .map_err(|e| SuperKitError::InvalidArgument(format!("{}: {}", path.display(), e)))
+    }
+    /// Parses a session from text with one event per line.
+    ///
+    /// Blank lines and lines starting with `#` are skipped.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut events = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let event = line.parse().map_err(|e| {
+                SuperKitError::InvalidArgument(format!("line {}: {}", number + 1, e))
+            })?;
+            events.push(event);
+        }
+        Ok(Session { events })
+    }
+    /// Saves the session to a file, replacing anything already in it.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_string())
+            .context(format!("Failed to write session file: {}", path.display()))
+    }
+    pub fn events(&self) -> &[PinEvent] {
+        &self.events
+    }
+    /// Gets just the writes, which is all a program does that can be seen.
+    pub fn writes(&self) -> impl Iterator<Item = &PinEvent> {
+        self.events
+            .iter()
+            .filter(|event| event.access == Access::Write)
+    }
+    /// Checks another run wrote the same levels to the same pins in the same
+    /// order as this one.
+    ///
+    /// Times and reads are ignored as they change from run to run even when
+    /// nothing is wrong.
+    pub fn verify(&self, actual: &Session) -> Result<()> {
+        let mut expected_writes = self.writes();
+        let mut actual_writes = actual.writes();
+        let mut index = 0;
+        loop {
+            match (expected_writes.next(), actual_writes.next()) {
+                (None, None) => return Ok(()),
+                (Some(expected), Some(found)) if expected.action() == found.action() => {}
+                (expected, found) => {
+                    let describe = |event: Option<&PinEvent>| match event {
+                        Some(event) => event.action(),
+                        None => "nothing more".to_string(),
+                    };
+                    return Err(SuperKitError::Mismatch(format!(
+                        "Write {} differs, expected {} but found {}",
+                        index + 1,
+                        describe(expected),
+                        describe(found)
+                    )));
+                }
+            }
+            index += 1;
+        }
+    }
+    /// Drives the written pins the same way and with the same timing as the
+    /// recording.
+    ///
+    /// Stops early once the token is cancelled.
+    /// The pins are only taken while playing back and are left low
+    /// afterwards.
+    pub fn replay(&self, token: &CancelToken) -> Result<()> {
+        let mut numbers: Vec<u8> = self.writes().map(|event| event.pin).collect();
+        numbers.sort_unstable();
+        numbers.dedup();
+        let _claim = registry::claim("Replay", &numbers)?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        let mut pins = BTreeMap::new();
+        for number in numbers {
+            let pin = gpio
+                .get(number)
+                .context(format!("Failed to get replay pin: {}", number))?
+                .into_output();
+            pins.insert(number, pin);
+        }
+        let mut pins = LowOnDrop(pins);
+        let start = Instant::now();
+        for event in self.writes() {
+            if !wait_until(token, start, event.elapsed) {
+                break;
+            }
+            if let Some(pin) = pins.0.get_mut(&event.pin) {
+                pin.write(event.level);
+            }
+        }
+        Ok(())
+    }
+    /// Sets the fake input pins to the levels that were read, with the same
+    /// timing as the recording, so a program run at the same time sees the
+    /// same inputs.
+    ///
+    /// Pins the recording also wrote are left alone.
+    /// Stops early once the token is cancelled.
+    #[cfg(feature = "mock")]
+    pub fn feed_inputs(&self, token: &CancelToken) {
+        let outputs: Vec<u8> = self.writes().map(|event| event.pin).collect();
+        let mut levels = BTreeMap::new();
+        let start = Instant::now();
+        let reads = self
+            .events
+            .iter()
+            .filter(|event| event.access == Access::Read && !outputs.contains(&event.pin));
+        for event in reads {
+            // Polling reads the same level over and over so only the changes
+            // matter.
+            if levels.insert(event.pin, event.level) == Some(event.level) {
+                continue;
+            }
+            if !wait_until(token, start, event.elapsed) {
+                break;
+            }
+            crate::hal::gpio::set_level(event.pin, event.level);
+        }
+    }
+}
+
+/// Same as a saved session file.
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# seconds access pin level")?;
+        for event in self.events.iter() {
+            writeln!(f, "{}", event)?;
+        }
+        Ok(())
+    }
+}
+
+/// Internal wrapper of the pins being played back that leaves them low when
+/// done or on an error.
+struct LowOnDrop(BTreeMap<u8, OutputPin>);
+
+/// Insure the pins are left low before exiting.
+impl Drop for LowOnDrop {
+    fn drop(&mut self) {
+        for pin in self.0.values_mut() {
+            pin.set_low();
+        }
+    }
+}
+
+/// Internal function to sleep until the given time after the start.
+///
+/// Returns false if the token was cancelled.
+fn wait_until(token: &CancelToken, start: Instant, at: Duration) -> bool {
+    match at.checked_sub(start.elapsed()) {
+        Some(remaining) => token.sleep(remaining),
+        None => token.is_running(),
+    }
+}
+
+/// Internal function to give the name of a level used in session files.
+fn level_name(level: Level) -> &'static str {
+    match level {
+        Level::High => "high",
+        Level::Low => "low",
+    }
+}
+
+/// Follows the fake pins and collects what is done to them into a [Session].
+///
+/// Only the mock backend can be recorded as rppal has no way to watch pins
+/// being used.
+#[cfg(feature = "mock")]
+pub struct Recorder {
+    events: Arc<Mutex<Option<Vec<PinEvent>>>>,
+}
+
+#[cfg(feature = "mock")]
+impl Recorder {
+    /// Starts recording every pin write and read from now on.
+    pub fn start() -> Self {
+        let start = mock::elapsed();
+        let events = Arc::new(Mutex::new(Some(Vec::new())));
+        let e = events.clone();
+        mock::watch(move |record| {
+            let (access, pin, level) = match record.operation {
+                Operation::Write { pin, level } => (Access::Write, pin, level),
+                Operation::Read { pin, level } => (Access::Read, pin, level),
+                _ => return,
+            };
+            if let Some(events) = e.lock().unwrap_or_else(PoisonError::into_inner).as_mut() {
+                events.push(PinEvent {
+                    elapsed: record.elapsed.checked_sub(start).unwrap_or_default(),
+                    access,
+                    pin,
+                    level,
+                });
+            }
+        });
+        Recorder { events }
+    }
+    /// Gets a copy of what has been recorded so far.
+    pub fn session(&self) -> Session {
+        Session::new(self.lock().clone().unwrap_or_default())
+    }
+    /// Stops recording and gives back everything recorded.
+    pub fn stop(self) -> Session {
+        Session::new(self.lock().take().unwrap_or_default())
+    }
+    /// Internal method to get at the events.
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Vec<PinEvent>>> {
+        self.events.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Insure the watcher stops collecting before exiting.
+#[cfg(feature = "mock")]
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        self.lock().take();
+    }
+}
+
+#[cfg(feature = "mock")]
+lazy_static! {
+    static ref RECORDING: Mutex<Option<(Recorder, PathBuf)>> = Mutex::new(None);
+}
+
+/// Internal function to start the recording asked for by `--record`.
+#[cfg(feature = "mock")]
+pub(crate) fn record_to(path: PathBuf) {
+    *RECORDING.lock().unwrap_or_else(PoisonError::into_inner) = Some((Recorder::start(), path));
+}
+
+/// Internal function to save the recording asked for by `--record`, if there
+/// is one, once the lesson stops.
+#[cfg(feature = "mock")]
+pub(crate) fn save_recording() -> Result<()> {
+    let recording = RECORDING
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take();
+    match recording {
+        Some((recorder, path)) => recorder.stop().save(path),
+        None => Ok(()),
+    }
+}
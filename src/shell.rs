@@ -0,0 +1,394 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Commands for trying the kit's parts one at a time, used by
+//! `superkit shell` to check the wiring before running a whole lesson.
+//!
+//! A line like `pin 17 high`, `pwm 18 50%`, `hc595 0xAA`, `lcd "hello"`, or
+//! `adc read 1` is run with [Shell::execute()] and whatever it sets up is
+//! kept so outputs stay how they were left until they are released.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::gpio::{Gpio, InputPin};
+use crate::pwm::PwmOutput;
+use crate::registry::{self, PinClaim, PinRegistry};
+#[cfg(feature = "lcd")]
+use crate::Lcd1602;
+use crate::Led;
+#[cfg(feature = "shift-register")]
+use crate::HC595;
+#[cfg(feature = "analog")]
+use crate::{adc::Adc, Adc0832};
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    mem,
+};
+
+/// The commands with how they are used and what they do, as shown by `help`.
+pub const COMMANDS: [(&str, &str, &str); 9] = [
+    (
+        "pin",
+        "pin <n> high|low|read",
+        "Sets a GPIO pin or reads it",
+    ),
+    (
+        "pwm",
+        "pwm <n> <duty>",
+        "Software PWM on a pin, like 50% or 0.5, off stops it",
+    ),
+    (
+        "hc595",
+        "hc595 <byte>",
+        "Latches a byte, like 0xAA or 0b1010, out of the 74HC595 on the lesson 10 pins",
+    ),
+    (
+        "lcd",
+        "lcd <text>|clear",
+        "Shows text on the LCD1602 from lesson 13, | starts the second row",
+    ),
+    (
+        "adc",
+        "adc read <channel>",
+        "Reads a channel of the ADC0832 on the lesson 15 pins",
+    ),
+    ("pins", "pins", "Lists which pins are in use and by what"),
+    (
+        "release",
+        "release <n>|hc595|lcd|adc|all",
+        "Lets go of a pin or part leaving it off",
+    ),
+    ("help", "help", "Shows this list"),
+    (
+        "quit",
+        "quit",
+        "Lets go of everything and leaves, so does Ctrl-D",
+    ),
+];
+
+/// What to do after a command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Reply {
+    /// Nothing to show.
+    Done,
+    /// Text to show.
+    Show(String),
+    /// The shell should stop.
+    Quit,
+}
+
+/// Structure holding the parts the commands have used so far.
+///
+/// Each part is only set up the first time a command needs it.
+#[derive(Default)]
+pub struct Shell {
+    outputs: BTreeMap<u8, Led>,
+    inputs: BTreeMap<u8, (InputPin, PinClaim)>,
+    #[cfg(feature = "shift-register")]
+    hc595: Option<HC595>,
+    #[cfg(feature = "lcd")]
+    lcd: Option<Lcd1602>,
+    #[cfg(feature = "analog")]
+    adc: Option<Adc0832>,
+}
+
+impl Shell {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Runs one command line.
+    ///
+    /// A mistake in the command or a part that can't be used is given as an
+    /// error and leaves everything else alone, so the shell can carry on.
+    pub fn execute(&mut self, line: &str) -> Result<Reply> {
+        let words = split(line)?;
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        match words.as_slice() {
+            [] => Ok(Reply::Done),
+            ["help"] => Ok(Reply::Show(help())),
+            ["quit"] | ["exit"] => Ok(Reply::Quit),
+            ["pins"] => Ok(Reply::Show(
+                PinRegistry::global().dump().trim_end().to_string(),
+            )),
+            ["pin", pin] | ["pin", pin, "read"] => self.read_pin(parse_pin(pin)?),
+            ["pin", pin, level] => {
+                let on = parse_level(level)?;
+                self.output(parse_pin(pin)?)?.set(on)?;
+                Ok(Reply::Done)
+            }
+            ["pwm", pin, duty] => {
+                let duty = parse_duty(duty)?;
+                self.output(parse_pin(pin)?)?.set_duty_cycle(duty)?;
+                Ok(Reply::Done)
+            }
+            ["hc595", byte] => self.shift(parse_byte(byte)?),
+            ["lcd", "clear"] => self.lcd(None),
+            ["lcd", text @ ..] if !text.is_empty() => self.lcd(Some(&text.join(" "))),
+            ["adc", "read", channel] | ["adc", channel] => self.read_adc(parse_byte(channel)?),
+            ["release", what] => self.release(what),
+            [command, ..] => match COMMANDS.iter().find(|(name, _, _)| name == command) {
+                Some((_, usage, _)) => Err(SuperKitError::InvalidArgument(format!(
+                    "Use it like: {}",
+                    usage
+                ))),
+                None => Err(SuperKitError::InvalidArgument(format!(
+                    "Unknown command {}, try help",
+                    command
+                ))),
+            },
+        }
+    }
+    /// Internal method to read a pin, or say how it was set if it is an
+    /// output.
+    fn read_pin(&mut self, pin: u8) -> Result<Reply> {
+        if let Some(led) = self.outputs.get(&pin) {
+            let level = if led.is_on() { "high" } else { "low" };
+            return Ok(Reply::Show(format!("GPIO {} is set {}", pin, level)));
+        }
+        let (input, _) = match self.inputs.entry(pin) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let claim = registry::claim("Shell", &[pin])?;
+                let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+                let input = gpio
+                    .get(pin)
+                    .context(format!("Failed to get shell pin: {}", pin))?
+                    .into_input();
+                entry.insert((input, claim))
+            }
+        };
+        let level = if input.is_high() { "high" } else { "low" };
+        Ok(Reply::Show(format!("GPIO {} is {}", pin, level)))
+    }
+    /// Internal method to get a pin as an output, letting go of it as an
+    /// input first if it was read before.
+    fn output(&mut self, pin: u8) -> Result<&mut Led> {
+        self.inputs.remove(&pin);
+        let led = match self.outputs.entry(pin) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(Led::new(pin)?),
+        };
+        Ok(led)
+    }
+    /// Internal method to latch a byte out of the 74HC595.
+    #[cfg(feature = "shift-register")]
+    fn shift(&mut self, byte: u8) -> Result<Reply> {
+        let hc595 = match self.hc595.take() {
+            Some(hc595) => hc595,
+            None => HC595::new()?,
+        };
+        let hc595 = self.hc595.get_or_insert(hc595);
+        hc595.serial_in(byte);
+        hc595.parallel_out();
+        Ok(Reply::Show(format!(
+            "latched {:#04x} = {:#010b}",
+            byte, byte
+        )))
+    }
+    #[cfg(not(feature = "shift-register"))]
+    fn shift(&mut self, _byte: u8) -> Result<Reply> {
+        Err(missing("hc595", "shift-register"))
+    }
+    /// Internal method to show text on the LCD or clear it.
+    #[cfg(feature = "lcd")]
+    fn lcd(&mut self, text: Option<&str>) -> Result<Reply> {
+        let lcd = match self.lcd.take() {
+            Some(lcd) => lcd,
+            None => Lcd1602::new()?,
+        };
+        let lcd = self.lcd.get_or_insert(lcd);
+        lcd.clear()?;
+        if let Some(text) = text {
+            lcd.print(&text.replace('|', "\n"))?;
+        }
+        Ok(Reply::Done)
+    }
+    #[cfg(not(feature = "lcd"))]
+    fn lcd(&mut self, _text: Option<&str>) -> Result<Reply> {
+        Err(missing("lcd", "lcd"))
+    }
+    /// Internal method to read a channel of the ADC.
+    #[cfg(feature = "analog")]
+    fn read_adc(&mut self, channel: u8) -> Result<Reply> {
+        let adc = match self.adc.take() {
+            Some(adc) => adc,
+            None => Adc0832::new()?,
+        };
+        let adc = self.adc.get_or_insert(adc);
+        let value = adc.read(channel)?;
+        let percent = f64::from(value) * 100.0 / f64::from(adc.max_value());
+        Ok(Reply::Show(format!(
+            "channel {} = {} ({:.1}%)",
+            channel, value, percent
+        )))
+    }
+    #[cfg(not(feature = "analog"))]
+    fn read_adc(&mut self, _channel: u8) -> Result<Reply> {
+        Err(missing("adc", "analog"))
+    }
+    /// Internal method to let go of a pin, a part, or everything.
+    fn release(&mut self, what: &str) -> Result<Reply> {
+        match what {
+            "all" => drop(mem::take(self)),
+            #[cfg(feature = "shift-register")]
+            "hc595" => self.hc595 = None,
+            #[cfg(feature = "lcd")]
+            "lcd" => self.lcd = None,
+            #[cfg(feature = "analog")]
+            "adc" => self.adc = None,
+            _ => {
+                let pin = parse_pin(what)?;
+                self.outputs.remove(&pin);
+                self.inputs.remove(&pin);
+            }
+        }
+        Ok(Reply::Done)
+    }
+}
+
+/// Gives the start of the word being typed at the end of `line` and the
+/// ways it could be finished, for tab completion.
+pub fn complete(line: &str) -> (usize, Vec<String>) {
+    let start = line
+        .rfind(char::is_whitespace)
+        .map(|space| space + 1)
+        .unwrap_or(0);
+    let (before, word) = line.split_at(start);
+    let before: Vec<&str> = before.split_whitespace().collect();
+    let choices: Vec<&str> = match before.as_slice() {
+        [] => COMMANDS.iter().map(|(name, _, _)| *name).collect(),
+        ["pin", _] => vec!["high", "low", "read"],
+        ["pwm", _] => vec!["off", "50%", "100%"],
+        ["lcd"] => vec!["clear"],
+        ["adc"] => vec!["read"],
+        ["release"] => vec!["all", "hc595", "lcd", "adc"],
+        _ => Vec::new(),
+    };
+    let choices = choices
+        .into_iter()
+        .filter(|choice| choice.starts_with(word))
+        .map(str::to_string)
+        .collect();
+    (start, choices)
+}
+
+/// Splits a command line into words with anything in double quotes kept
+/// together as one word.
+pub fn split(line: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_word = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_word {
+                    words.push(mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quoted {
+        return Err(SuperKitError::InvalidArgument(
+            "Missing closing quote".to_string(),
+        ));
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// Internal function to lay out the help text.
+fn help() -> String {
+    COMMANDS
+        .iter()
+        .map(|(_, usage, about)| format!("{:<32}{}", usage, about))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Internal function to parse a GPIO pin number.
+fn parse_pin(text: &str) -> Result<u8> {
+    text.parse()
+        .context(format!("\"{}\" isn't a GPIO pin number", text))
+}
+
+/// Internal function to parse a pin level.
+fn parse_level(text: &str) -> Result<bool> {
+    match text.to_ascii_lowercase().as_str() {
+        "high" | "on" | "1" => Ok(true),
+        "low" | "off" | "0" => Ok(false),
+        _ => Err(SuperKitError::InvalidArgument(format!(
+            "\"{}\" isn't high, low, or read",
+            text
+        ))),
+    }
+}
+
+/// Internal function to parse a duty cycle as a percentage or a fraction.
+fn parse_duty(text: &str) -> Result<f64> {
+    if text.eq_ignore_ascii_case("off") {
+        return Ok(0.0);
+    }
+    let duty = match text.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().map(|percent| percent / 100.0),
+        None => text.parse::<f64>(),
+    }
+    .context(format!("\"{}\" isn't a duty cycle like 50% or 0.5", text))?;
+    if !(0.0..=1.0).contains(&duty) {
+        return Err(SuperKitError::InvalidArgument(format!(
+            "Duty cycle {} is outside of 0% to 100%",
+            text
+        )));
+    }
+    Ok(duty)
+}
+
+/// Internal function to parse a byte in decimal, `0x` hex, or `0b` binary.
+fn parse_byte(text: &str) -> Result<u8> {
+    let byte = if let Some(hex) = text.strip_prefix("0x") {
+        u8::from_str_radix(hex, 16)
+    } else if let Some(binary) = text.strip_prefix("0b") {
+        u8::from_str_radix(binary, 2)
+    } else {
+        text.parse()
+    };
+    byte.context(format!("\"{}\" isn't a number from 0 to 255", text))
+}
+
+/// Internal function for the error when a part's driver wasn't built.
+#[cfg(not(all(feature = "shift-register", feature = "lcd", feature = "analog")))]
+fn missing(command: &str, feature: &str) -> SuperKitError {
+    SuperKitError::InvalidArgument(format!(
+        "The {} command needs the {} feature",
+        command, feature
+    ))
+}
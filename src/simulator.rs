@@ -0,0 +1,704 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Shows the output parts of the kit in a terminal while a lesson runs on the
+//! mock backend.
+//!
+//! The `simulator` binary starts a lesson with [ADDRESS_VAR] set and the
+//! lesson connects back to it from [connect()], which the common command line
+//! options call for every lesson.
+//! The lesson side decodes what its drivers do to the fake pins into a
+//! [Panel] which it sends several times a second for the binary to draw, so
+//! the lessons themselves need no changes to be simulated.
+
+use crate::error::{Context, Result};
+use crate::hal::{
+    gpio::{Level, Mode},
+    mock::{self, Operation, Record},
+    pwm::Channel,
+};
+use crate::pwm::hardware_channel;
+use crossterm::style::{style, Color};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    env, fmt,
+    io::{BufRead, Write},
+    net::TcpStream,
+    sync::{Mutex, MutexGuard, Once, PoisonError},
+    thread::{sleep, spawn},
+    time::Duration,
+};
+
+/// Environment variable with the address a lesson sends its [Panel] to.
+pub const ADDRESS_VAR: &str = "SUPERKIT_SIMULATOR";
+/// Number of GPIO pins on the 40 pin header.
+const PINS: usize = 28;
+/// Time in milliseconds between updates sent to the simulator.
+const UPDATE_TIME: u64 = 50;
+/// Byte used to mark the end of each [Panel] sent.
+const END: u8 = 0;
+/// Number of bytes of display data RAM in the LCD controller.
+const LCD_RAM: usize = 0x68;
+/// Characters in each line of the LCD controller memory.
+const LCD_LINE: u8 = 40;
+
+/// A part of the kit a driver tells the simulator about when it is set up so
+/// its pins can be decoded.
+///
+/// Parts whose drivers were left out by the features are never told about but
+/// can still be drawn.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    not(all(feature = "shift-register", feature = "lcd")),
+    allow(dead_code)
+)]
+pub(crate) enum Part {
+    /// A 74HC595 with its serial data, latch clock, and shift clock pins.
+    Hc595([u8; 3]),
+    /// Dot matrices driven through a chain of 74HC595s.
+    Matrix { hc595: [u8; 3], modules: usize },
+    /// Multiplexed seven segment digits driven through a 74HC595 with the
+    /// select pin of each digit.
+    Segment { hc595: [u8; 3], selects: Vec<u8> },
+    /// LCD with its register select, enable, D4 to D7 pins, and the memory
+    /// address at the start of each row.
+    Lcd {
+        rs: u8,
+        e: u8,
+        data: [u8; 4],
+        columns: u8,
+        rows: Vec<u8>,
+    },
+    /// Active low LED bar.
+    LedBar([u8; 8]),
+    /// Red, green, and blue pins of a RGB LED.
+    Rgb([u8; 3]),
+}
+
+/// Snapshot of everything the simulator shows.
+///
+/// Empty parts weren't used by the lesson and aren't shown.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Panel {
+    /// GPIO pins currently used as outputs.
+    pub outputs: Vec<u8>,
+    /// Brightness from 0.0 to 1.0 of an LED on each GPIO pin.
+    pub brightness: Vec<f64>,
+    /// Which LEDs of the LED bar are on.
+    pub led_bar: Vec<bool>,
+    /// Red, green, and blue of the RGB LED.
+    pub rgb: Vec<u8>,
+    /// Outputs of each 74HC595 not used by one of the other parts, bit 0 is
+    /// Q0.
+    pub shift_registers: Vec<u8>,
+    /// Rows of each dot matrix, bit 0 being the left most column.
+    pub matrices: Vec<Vec<u8>>,
+    /// Segment codes of each seven segment digit.
+    pub digits: Vec<u8>,
+    /// Text of each row of the LCD.
+    pub lcd: Vec<String>,
+}
+
+impl Panel {
+    /// Reads the next panel sent by a lesson.
+    ///
+    /// Gives `None` once the lesson has closed the connection.
+    pub fn read_from<R: BufRead>(reader: &mut R) -> Result<Option<Panel>> {
+        let mut buffer = Vec::new();
+        reader
+            .read_until(END, &mut buffer)
+            .context("Failed to read from lesson")?;
+        if buffer.pop() != Some(END) {
+            return Ok(None);
+        }
+        let text = String::from_utf8(buffer).context("Lesson sent bad text")?;
+        let panel = toml::from_str(&text).context("Lesson sent a bad panel")?;
+        Ok(Some(panel))
+    }
+    /// Sends the panel to the simulator.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let text = toml::to_string(self).context("Failed to encode panel")?;
+        writer
+            .write_all(text.as_bytes())
+            .and_then(|_| writer.write_all(&[END]))
+            .context("Failed to send panel to simulator")
+    }
+    /// Internal method to draw the GPIO pins.
+    fn fmt_gpio(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "GPIO     0         1         2")?;
+        writeln!(f, "         0123456789012345678901234567")?;
+        write!(f, "         ")?;
+        for pin in 0..PINS {
+            if !self.outputs.contains(&(pin as u8)) {
+                write!(f, "·")?;
+                continue;
+            }
+            let brightness = self.brightness.get(pin).copied().unwrap_or(0.0);
+            let (c, level) = match brightness {
+                b if b < 0.05 => ('○', 80),
+                b if b < 0.95 => ('◐', 80 + (175.0 * b) as u8),
+                _ => ('●', 255),
+            };
+            write!(
+                f,
+                "{}",
+                style(c).with(Color::Rgb {
+                    r: level,
+                    g: 0,
+                    b: 0
+                })
+            )?;
+        }
+        writeln!(f)
+    }
+    /// Internal method to draw the seven segment digits, three rows of text
+    /// each.
+    fn fmt_digits(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in 0..3 {
+            write!(f, "{}", if row == 1 { "Digits   " } else { "         " })?;
+            for code in self.digits.iter() {
+                let lit = |bit: u8, c: char| if code & (1 << bit) != 0 { c } else { ' ' };
+                // Segments a-g are bits 0-6 and the decimal point is bit 7.
+                let text = match row {
+                    0 => format!(" {}  ", lit(0, '_')),
+                    1 => format!("{}{}{} ", lit(5, '|'), lit(6, '_'), lit(1, '|')),
+                    _ => format!(
+                        "{}{}{}{}",
+                        lit(4, '|'),
+                        lit(3, '_'),
+                        lit(2, '|'),
+                        lit(7, '.')
+                    ),
+                };
+                write!(f, "{}", style(text).with(Color::Red))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Draws the panel as lines of text for a terminal.
+impl fmt::Display for Panel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_gpio(f)?;
+        if !self.led_bar.is_empty() {
+            write!(f, "\nLED bar  ")?;
+            for on in self.led_bar.iter() {
+                let (c, color) = if *on {
+                    ('█', Color::Red)
+                } else {
+                    ('░', Color::DarkGrey)
+                };
+                write!(f, "{}", style(c).with(color))?;
+            }
+            writeln!(f)?;
+        }
+        if let [r, g, b] = self.rgb[..] {
+            writeln!(
+                f,
+                "\nRGB LED  {} #{:02x}{:02x}{:02x}",
+                style("██████").with(Color::Rgb { r, g, b }),
+                r,
+                g,
+                b
+            )?;
+        }
+        for (chip, outputs) in self.shift_registers.iter().enumerate() {
+            write!(f, "\n74HC595  ")?;
+            for bit in (0..8).rev() {
+                let c = if outputs & (1 << bit) != 0 {
+                    '●'
+                } else {
+                    '○'
+                };
+                write!(f, "{}", style(c).with(Color::Red))?;
+            }
+            writeln!(f, " Q7-Q0 of chip {}", chip)?;
+        }
+        if !self.matrices.is_empty() {
+            writeln!(f)?;
+            for row in 0..8 {
+                write!(f, "{}", if row == 0 { "Matrix   " } else { "         " })?;
+                for rows in self.matrices.iter() {
+                    for column in 0..8 {
+                        if rows.get(row).copied().unwrap_or(0) & (1 << column) != 0 {
+                            write!(f, "{}", style("● ").with(Color::Red))?;
+                        } else {
+                            write!(f, "{}", style("· ").with(Color::DarkGrey))?;
+                        }
+                    }
+                    write!(f, " ")?;
+                }
+                writeln!(f)?;
+            }
+        }
+        if !self.digits.is_empty() {
+            writeln!(f)?;
+            self.fmt_digits(f)?;
+        }
+        if let Some(first) = self.lcd.first() {
+            let border = "─".repeat(first.chars().count());
+            writeln!(f, "\nLCD      ┌{}┐", border)?;
+            for row in self.lcd.iter() {
+                writeln!(
+                    f,
+                    "         │{}│",
+                    style(row).with(Color::Black).on(Color::Green)
+                )?;
+            }
+            writeln!(f, "         └{}┘", border)?;
+        }
+        Ok(())
+    }
+}
+
+/// What the outputs of a chain of 74HC595s are used for.
+enum Use {
+    Raw,
+    Matrix(usize),
+    Segment(Vec<u8>),
+}
+
+/// Internal structure to follow a chain of 74HC595s.
+struct Chain {
+    /// Serial data, latch clock, and shift clock pins.
+    pins: [u8; 3],
+    usage: Use,
+    /// Shift register bits of all the chips, bit 0 of the first chip first.
+    register: Vec<bool>,
+    /// Latched outputs of each chip, nearest the Raspberry Pi first.
+    outputs: Vec<u8>,
+    /// Rows being shown on each dot matrix.
+    frame: Vec<[u8; 8]>,
+    /// Segment codes being shown on each digit.
+    digits: Vec<u8>,
+}
+
+impl Chain {
+    fn new(pins: [u8; 3], usage: Use) -> Self {
+        let (chips, modules, digits) = match &usage {
+            Use::Raw => (1, 0, 0),
+            Use::Matrix(modules) => (modules + 1, *modules, 0),
+            Use::Segment(selects) => (1, 0, selects.len()),
+        };
+        Chain {
+            pins,
+            usage,
+            register: vec![false; chips * 8],
+            outputs: vec![0; chips],
+            frame: vec![[0; 8]; modules],
+            digits: vec![0; digits],
+        }
+    }
+    /// Internal method to follow a pin changing level.
+    fn edge(&mut self, levels: &[Level; PINS], pin: u8, rising: bool) {
+        let [sdi, rclk, srclk] = self.pins;
+        if pin == srclk && rising {
+            self.register.pop();
+            self.register.insert(0, levels[sdi as usize] == Level::High);
+        } else if pin == rclk && rising {
+            self.latch(levels);
+        } else if let Use::Segment(selects) = &self.usage {
+            // A digit shows the latched segments while it is selected.
+            if let Some(digit) = selects.iter().position(|select| *select == pin) {
+                if !rising {
+                    self.digits[digit] = self.outputs[0];
+                }
+            }
+        }
+    }
+    /// Internal method to copy the shift register to the outputs.
+    fn latch(&mut self, levels: &[Level; PINS]) {
+        for (chip, outputs) in self.outputs.iter_mut().enumerate() {
+            *outputs = self.register[chip * 8..chip * 8 + 8]
+                .iter()
+                .enumerate()
+                .fold(0, |byte, (bit, on)| byte | ((*on as u8) << bit));
+        }
+        match &self.usage {
+            Use::Raw => {}
+            Use::Matrix(_) => {
+                // First chip selects the rows and the others are the active
+                // low columns of each matrix from left to right.
+                let rows = self.outputs[0];
+                if rows == 0 {
+                    self.frame.iter_mut().for_each(|frame| *frame = [0; 8]);
+                }
+                for (frame, columns) in self.frame.iter_mut().zip(self.outputs[1..].iter()) {
+                    for (row, pixels) in frame.iter_mut().enumerate() {
+                        if rows & (1 << row) != 0 {
+                            *pixels = !columns;
+                        }
+                    }
+                }
+            }
+            Use::Segment(selects) => {
+                for (digit, select) in selects.iter().enumerate() {
+                    if levels[*select as usize] == Level::Low {
+                        self.digits[digit] = self.outputs[0];
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Internal structure to follow a HD44780 LCD controller.
+struct Lcd {
+    rs: u8,
+    e: u8,
+    data: [u8; 4],
+    columns: u8,
+    rows: Vec<u8>,
+    ram: [u8; LCD_RAM],
+    address: u8,
+    increment: bool,
+    /// Data is going to the custom character memory instead of the display.
+    custom: bool,
+    on: bool,
+    /// How far the display has been shifted left.
+    shift: u8,
+    /// High nibble while waiting for the low one.
+    high: Option<u8>,
+}
+
+impl Lcd {
+    /// Internal method to follow the enable pin going low.
+    fn strobe(&mut self, levels: &[Level; PINS]) {
+        let nibble = self.data.iter().enumerate().fold(0, |nibble, (bit, pin)| {
+            nibble | (((levels[*pin as usize] == Level::High) as u8) << bit)
+        });
+        match self.high.take() {
+            Some(high) => self.byte(
+                (high << 4) | nibble,
+                levels[self.rs as usize] == Level::High,
+            ),
+            None => self.high = Some(nibble),
+        }
+    }
+    /// Internal method to act on a whole byte of data or an instruction.
+    ///
+    /// The 8 bit mode nibbles sent at power on pair up into function set
+    /// instructions so they can be ignored like any other.
+    fn byte(&mut self, byte: u8, data: bool) {
+        if data {
+            if !self.custom {
+                self.ram[self.address as usize] = byte;
+                self.address = self.next_address();
+            }
+            return;
+        }
+        match byte {
+            b if b & 0x80 != 0 => {
+                self.address = (b & 0x7f).min(LCD_RAM as u8 - 1);
+                self.custom = false;
+            }
+            b if b & 0x40 != 0 => self.custom = true,
+            b if b & 0x20 != 0 => {}
+            // Only shifting the display matters, not moving the cursor.
+            b if b & 0x10 != 0 && b & 0x08 != 0 => {
+                self.shift = if b & 0x04 != 0 {
+                    (self.shift + LCD_LINE - 1) % LCD_LINE
+                } else {
+                    (self.shift + 1) % LCD_LINE
+                };
+            }
+            b if b & 0x10 != 0 => {}
+            b if b & 0x08 != 0 => self.on = b & 0x04 != 0,
+            b if b & 0x04 != 0 => self.increment = b & 0x02 != 0,
+            b if b & 0x02 != 0 => {
+                self.address = 0;
+                self.shift = 0;
+            }
+            0x01 => {
+                self.ram = [b' '; LCD_RAM];
+                self.address = 0;
+                self.shift = 0;
+                self.increment = true;
+            }
+            _ => {}
+        }
+    }
+    /// Internal method to find where the next character goes, wrapping
+    /// between the two lines of memory.
+    fn next_address(&self) -> u8 {
+        match (self.increment, self.address) {
+            (true, 0x27) => 0x40,
+            (true, 0x67) => 0x00,
+            (true, address) => address + 1,
+            (false, 0x00) => 0x67,
+            (false, 0x40) => 0x27,
+            (false, address) => address - 1,
+        }
+    }
+    /// Internal method to get what each row of the display shows.
+    fn text(&self) -> Vec<String> {
+        self.rows
+            .iter()
+            .map(|start| {
+                let line = start & 0x40;
+                let offset = start & 0x3f;
+                (0..self.columns)
+                    .map(|column| {
+                        let address = line + (offset + column + self.shift) % LCD_LINE;
+                        match self.ram[address as usize] {
+                            _ if !self.on => ' ',
+                            c @ 0x20..=0x7e => c as char,
+                            // Custom characters.
+                            0..=7 => '▒',
+                            _ => '?',
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Internal structure with everything decoded from the fake pins so far.
+struct Decoder {
+    levels: [Level; PINS],
+    outputs: [bool; PINS],
+    /// Software PWM duty cycle of each pin while it is on.
+    duty_cycles: [Option<f64>; PINS],
+    /// Duty cycle of each hardware PWM channel while it is enabled.
+    channels: HashMap<Channel, f64>,
+    chains: Vec<Chain>,
+    lcds: Vec<Lcd>,
+    led_bar: Option<[u8; 8]>,
+    rgb: Option<[u8; 3]>,
+}
+
+impl Decoder {
+    fn new() -> Self {
+        Decoder {
+            levels: [Level::Low; PINS],
+            outputs: [false; PINS],
+            duty_cycles: [None; PINS],
+            channels: HashMap::new(),
+            chains: Vec::new(),
+            lcds: Vec::new(),
+            led_bar: None,
+            rgb: None,
+        }
+    }
+    /// Internal method to start decoding the pins of a part.
+    fn attach(&mut self, part: Part) {
+        let chain = |decoder: &mut Decoder, pins: [u8; 3], usage: Use| {
+            decoder.chains.retain(|chain| chain.pins != pins);
+            decoder.chains.push(Chain::new(pins, usage));
+        };
+        match part {
+            Part::Hc595(pins) => {
+                if !self.chains.iter().any(|chain| chain.pins == pins) {
+                    chain(self, pins, Use::Raw);
+                }
+            }
+            Part::Matrix { hc595, modules } => chain(self, hc595, Use::Matrix(modules)),
+            Part::Segment { hc595, selects } => chain(self, hc595, Use::Segment(selects)),
+            Part::Lcd {
+                rs,
+                e,
+                data,
+                columns,
+                rows,
+            } => {
+                self.lcds.retain(|lcd| lcd.e != e);
+                self.lcds.push(Lcd {
+                    rs,
+                    e,
+                    data,
+                    columns,
+                    rows,
+                    ram: [b' '; LCD_RAM],
+                    address: 0,
+                    increment: true,
+                    custom: false,
+                    on: true,
+                    shift: 0,
+                    high: None,
+                });
+            }
+            Part::LedBar(pins) => self.led_bar = Some(pins),
+            Part::Rgb(pins) => self.rgb = Some(pins),
+        }
+    }
+    /// Internal method to follow one thing done to the fakes.
+    fn record(&mut self, record: &Record) {
+        match record.operation {
+            Operation::Claim { pin, mode } => self.outputs[pin as usize] = mode == Mode::Output,
+            Operation::Release { pin } => {
+                self.outputs[pin as usize] = false;
+                self.duty_cycles[pin as usize] = None;
+            }
+            Operation::Write { pin, level } => {
+                let from = self.levels[pin as usize];
+                self.levels[pin as usize] = level;
+                self.duty_cycles[pin as usize] = None;
+                if from == level {
+                    return;
+                }
+                let rising = level == Level::High;
+                for chain in self.chains.iter_mut() {
+                    chain.edge(&self.levels, pin, rising);
+                }
+                for lcd in self.lcds.iter_mut().filter(|lcd| lcd.e == pin && !rising) {
+                    lcd.strobe(&self.levels);
+                }
+            }
+            Operation::SoftPwm {
+                pin, duty_cycle, ..
+            } => self.duty_cycles[pin as usize] = Some(duty_cycle),
+            Operation::ClearSoftPwm { pin } => self.duty_cycles[pin as usize] = None,
+            Operation::Pwm {
+                channel,
+                duty_cycle,
+                enabled,
+                ..
+            } => {
+                self.channels
+                    .insert(channel, if enabled { duty_cycle } else { 0.0 });
+            }
+            _ => {}
+        }
+    }
+    /// Internal method to get how bright an LED on a pin would be.
+    fn brightness(&self, pin: u8) -> f64 {
+        let pin = pin as usize;
+        match self.duty_cycles.get(pin) {
+            Some(Some(duty_cycle)) => *duty_cycle,
+            Some(None) if self.levels[pin] == Level::High => 1.0,
+            _ => 0.0,
+        }
+    }
+    /// Internal method to take a snapshot of all the parts.
+    fn panel(&self) -> Panel {
+        let mut panel = Panel {
+            outputs: (0..PINS as u8)
+                .filter(|pin| self.outputs[*pin as usize])
+                .collect(),
+            brightness: (0..PINS as u8).map(|pin| self.brightness(pin)).collect(),
+            ..Panel::default()
+        };
+        if let Some(pins) = self.led_bar {
+            panel.led_bar = pins.iter().map(|pin| self.brightness(*pin) < 0.5).collect();
+        }
+        if let Some(pins) = self.rgb {
+            // Shown the way a common cathode LED would look.
+            panel.rgb = pins
+                .iter()
+                .map(|pin| {
+                    let duty_cycle = match hardware_channel(*pin)
+                        .ok()
+                        .and_then(|channel| self.channels.get(&channel))
+                    {
+                        Some(duty_cycle) => *duty_cycle,
+                        None => self.brightness(*pin),
+                    };
+                    (duty_cycle.clamp(0.0, 1.0) * 255.0).round() as u8
+                })
+                .collect();
+        }
+        for chain in self.chains.iter() {
+            match chain.usage {
+                Use::Raw => panel.shift_registers.extend_from_slice(&chain.outputs),
+                Use::Matrix(_) => panel
+                    .matrices
+                    .extend(chain.frame.iter().map(|rows| rows.to_vec())),
+                Use::Segment(_) => panel.digits.extend_from_slice(&chain.digits),
+            }
+        }
+        if let Some(lcd) = self.lcds.last() {
+            panel.lcd = lcd.text();
+        }
+        panel
+    }
+}
+
+lazy_static! {
+    static ref DECODER: Mutex<Decoder> = Mutex::new(Decoder::new());
+}
+
+static FOLLOW: Once = Once::new();
+
+/// Internal function to get at the decoder shared with the mock watcher and
+/// the sending thread.
+fn decoder() -> MutexGuard<'static, Decoder> {
+    DECODER.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Used by the drivers to say which pins a part is on.
+pub(crate) fn attach(part: Part) {
+    decoder().attach(part);
+}
+
+/// Connects to the simulator if the lesson was started by one and starts
+/// sending it updates.
+///
+/// Does nothing when [ADDRESS_VAR] isn't set.
+/// The mock log is turned off as the simulator can run for a long time.
+pub fn connect() -> Result<()> {
+    let address = match env::var(ADDRESS_VAR) {
+        Ok(address) => address,
+        Err(_) => return Ok(()),
+    };
+    let mut stream = TcpStream::connect(&address)
+        .context(format!("Failed to connect to simulator at {}", address))?;
+    follow();
+    spawn(move || {
+        let mut sent = None;
+        loop {
+            let panel = panel();
+            if sent.as_ref() != Some(&panel) {
+                // Stop quietly once the simulator has gone away.
+                if panel.write_to(&mut stream).is_err() {
+                    break;
+                }
+                sent = Some(panel);
+            }
+            sleep(Duration::from_millis(UPDATE_TIME));
+        }
+    });
+    Ok(())
+}
+
+/// Starts decoding what the drivers do to the fake pins into a [Panel] for
+/// [panel()] to give, like the web dashboard shows.
+///
+/// The mock log is turned off as this is for programs that run for a long
+/// time.
+/// Only the first call does anything so it is safe to call again.
+pub fn follow() {
+    FOLLOW.call_once(|| {
+        mock::set_recording(false);
+        mock::watch(|record| decoder().record(record));
+    });
+}
+
+/// Gets a snapshot of the parts, empty until [follow()] has been called.
+pub fn panel() -> Panel {
+    decoder().panel()
+}
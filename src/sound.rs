@@ -0,0 +1,172 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Result, SuperKitError};
+use crate::Adc;
+use std::{
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+/// Default number of samples a second.
+const SAMPLE_RATE: f64 = 1000.0;
+/// Default time each measurement covers.
+const WINDOW: Duration = Duration::from_millis(50);
+// Shortest time between samples so very high rates still move time along.
+const MIN_PERIOD: Duration = Duration::from_micros(1);
+// How quickly the DC offset estimate follows the signal from 0.0 to 1.0.
+const DC_SMOOTHING: f64 = 0.01;
+/// Default fraction of full scale the held peak falls each second.
+const PEAK_DECAY: f64 = 0.5;
+// Quietest level in dB below full scale that [Envelope::db()] gives.
+const FLOOR_DB: f64 = -60.0;
+
+/// Loudness over one measurement window as fractions of full scale from 0.0
+/// to 1.0 with the microphone module's DC offset taken out.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Envelope {
+    /// Root mean square which follows how loud things sound.
+    pub rms: f64,
+    /// Largest swing from the middle.
+    pub peak: f64,
+    /// Peak that drops slowly after a loud sound like on a VU meter.
+    pub peak_hold: f64,
+}
+
+impl Envelope {
+    /// The RMS level in dB below full scale, down to -60dB for silence.
+    pub fn db(&self) -> f64 {
+        to_db(self.rms)
+    }
+    /// The held peak in dB below full scale, down to -60dB for silence.
+    pub fn peak_db(&self) -> f64 {
+        to_db(self.peak_hold)
+    }
+}
+
+/// Structure used to model the sound sensor module's analog output read by
+/// one channel of an [Adc].
+///
+/// The microphone's signal sits around the middle of the ADC's range so the
+/// middle is tracked and taken out before working out the loudness.
+/// The ADC is passed in for each measurement so other sensors can share its
+/// other channels.
+#[derive(Clone, Copy, Debug)]
+pub struct SoundSensor {
+    channel: u8,
+    sample_rate: f64,
+    window: Duration,
+    peak_decay: f64,
+    /// Estimate of the middle of the signal, None until the first sample.
+    dc: Option<f64>,
+    peak_hold: f64,
+    last: Option<Instant>,
+}
+
+impl SoundSensor {
+    /// Makes a sensor on the given ADC channel sampling at 1kHz over 50ms
+    /// windows.
+    pub fn new(channel: u8) -> Self {
+        SoundSensor {
+            channel,
+            sample_rate: SAMPLE_RATE,
+            window: WINDOW,
+            peak_decay: PEAK_DECAY,
+            dc: None,
+            peak_hold: 0.0,
+            last: None,
+        }
+    }
+    /// The ADC channel being read.
+    pub fn channel(&self) -> u8 {
+        self.channel
+    }
+    /// Changes how many samples are taken a second.
+    ///
+    /// A bit banged ADC like the ADC0832 tops out at a few kHz so higher rates
+    /// just sample as fast as they can.
+    pub fn set_sample_rate(&mut self, sample_rate: f64) -> Result<()> {
+        if !sample_rate.is_finite() || sample_rate <= 0.0 {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Sample rate {} needs to be a number above zero",
+                sample_rate
+            )));
+        }
+        self.sample_rate = sample_rate;
+        Ok(())
+    }
+    /// Changes the time each measurement covers. Shorter reacts faster but
+    /// jumps about more.
+    pub fn set_window(&mut self, window: Duration) {
+        self.window = window;
+    }
+    /// Changes the fraction of full scale the held peak falls each second.
+    pub fn set_peak_decay(&mut self, peak_decay: f64) {
+        self.peak_decay = peak_decay.max(0.0);
+    }
+    /// Samples for one window and gives how loud it was.
+    pub fn measure<A: Adc>(&mut self, adc: &mut A) -> Result<Envelope> {
+        let period = Duration::from_secs_f64(1.0 / self.sample_rate).max(MIN_PERIOD);
+        let start = Instant::now();
+        let mut next = start;
+        let mut sum_squares = 0.0;
+        let mut peak: f64 = 0.0;
+        let mut count = 0u32;
+        while next - start < self.window {
+            let sample = adc.read_ratio(self.channel)?;
+            let dc = self.dc.get_or_insert(sample);
+            *dc += (sample - *dc) * DC_SMOOTHING;
+            // Twice the swing from the middle so full scale is 1.0.
+            let ac = 2.0 * (sample - *dc);
+            sum_squares += ac * ac;
+            peak = peak.max(ac.abs());
+            count += 1;
+            next += period;
+            let now = Instant::now();
+            if next > now {
+                sleep(next - now);
+            }
+        }
+        let rms = (sum_squares / f64::from(count.max(1))).sqrt().min(1.0);
+        let peak = peak.min(1.0);
+        let now = Instant::now();
+        let elapsed = self
+            .last
+            .replace(now)
+            .map_or(0.0, |last| (now - last).as_secs_f64());
+        self.peak_hold = (self.peak_hold - self.peak_decay * elapsed).max(peak);
+        Ok(Envelope {
+            rms,
+            peak,
+            peak_hold: self.peak_hold,
+        })
+    }
+}
+
+/// Internal function to turn a fraction of full scale into dB.
+fn to_db(level: f64) -> f64 {
+    if level <= 0.0 {
+        FLOOR_DB
+    } else {
+        (20.0 * level.log10()).max(FLOOR_DB)
+    }
+}
@@ -0,0 +1,194 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::gpio::{Gpio, OutputPin};
+use crate::registry::{self, PinClaim};
+use std::{thread::sleep, time::Duration};
+
+/// Full steps for one turn of the output shaft of a 28BYJ-48.
+///
+/// 32 steps for the motor itself times the 1:64 gearbox.
+pub const STEPS_PER_REVOLUTION: u32 = 2048;
+// Coils energised for each half step with bit 0 being IN1.
+const HALF_STEPS: [u8; 8] = [
+    0b0001, 0b0011, 0b0010, 0b0110, 0b0100, 0b1100, 0b1000, 0b1001,
+];
+// Fastest the 28BYJ-48 can reliably go in RPM when full stepping.
+const MAX_RPM: f64 = 15.0;
+// Default speed in RPM.
+const RPM: f64 = 10.0;
+
+/// How the coils are driven.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepMode {
+    /// One coil at a time. Uses the least power but has the least torque.
+    Wave,
+    /// Two coils at a time for full torque.
+    Full,
+    /// Alternates between one and two coils for twice the resolution and
+    /// smoother movement.
+    Half,
+}
+
+impl StepMode {
+    /// Internal method to get how many half steps make up one step.
+    fn half_steps(self) -> usize {
+        match self {
+            StepMode::Half => 1,
+            _ => 2,
+        }
+    }
+}
+
+/// Structure used to model a 28BYJ-48 stepper motor driven through a ULN2003
+/// board like the ones that come with many of the SunFounder kits.
+pub struct Stepper {
+    coils: Vec<OutputPin>,
+    mode: StepMode,
+    /// Index into HALF_STEPS of the coils that are on.
+    phase: usize,
+    /// Steps moved from where it started, in the current mode's steps.
+    position: i64,
+    delay: Duration,
+    _claim: PinClaim,
+}
+
+impl Stepper {
+    /// Gets the pins connected to IN1 through IN4 with all the coils off and
+    /// full stepping at 10 RPM.
+    pub fn new(pins: [u8; 4]) -> Result<Self> {
+        let claim = registry::claim("Stepper", &pins)?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        let mut coils = Vec::new();
+        for pin in pins.iter() {
+            let mut coil = gpio
+                .get(*pin)
+                .context(format!("Failed to get stepper pin: {}", pin))?
+                .into_output();
+            coil.set_low();
+            coils.push(coil);
+        }
+        let mut stepper = Stepper {
+            coils,
+            mode: StepMode::Full,
+            phase: 1,
+            position: 0,
+            delay: Duration::from_secs(0),
+            _claim: claim,
+        };
+        stepper.set_speed(RPM)?;
+        Ok(stepper)
+    }
+    /// Steps moved since starting in the current mode's steps.
+    pub fn position(&self) -> i64 {
+        self.position
+    }
+    /// Changes how the coils are driven.
+    ///
+    /// The speed in RPM is kept the same.
+    pub fn set_mode(&mut self, mode: StepMode) -> Result<()> {
+        let rpm = self.rpm();
+        // Half steps count twice as many steps for the same movement.
+        self.position = match (self.mode, mode) {
+            (StepMode::Half, StepMode::Half) => self.position,
+            (StepMode::Half, _) => self.position / 2,
+            (_, StepMode::Half) => self.position * 2,
+            _ => self.position,
+        };
+        self.mode = mode;
+        // Wave uses the single coil phases and full the double ones.
+        match (mode, self.phase & 1) {
+            (StepMode::Wave, 1) => self.phase -= 1,
+            (StepMode::Full, 0) => self.phase += 1,
+            _ => {}
+        }
+        self.set_speed(rpm)
+    }
+    /// Sets the speed in revolutions per minute of the output shaft.
+    pub fn set_speed(&mut self, rpm: f64) -> Result<()> {
+        if !(rpm > 0.0 && rpm <= MAX_RPM) {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Stepper speed {} RPM is outside of 0 to {} RPM",
+                rpm, MAX_RPM
+            )));
+        }
+        let steps_per_second = rpm / 60.0 * f64::from(self.steps_per_revolution());
+        self.delay = Duration::from_secs_f64(1.0 / steps_per_second);
+        Ok(())
+    }
+    /// The speed in revolutions per minute.
+    pub fn rpm(&self) -> f64 {
+        60.0 / (self.delay.as_secs_f64() * f64::from(self.steps_per_revolution()))
+    }
+    /// Steps for one turn of the output shaft in the current mode.
+    pub fn steps_per_revolution(&self) -> u32 {
+        STEPS_PER_REVOLUTION * 2 / self.mode.half_steps() as u32
+    }
+    /// Moves the given number of steps with positive being clockwise when
+    /// looking at the shaft.
+    ///
+    /// The coils are left on afterwards to hold the position.
+    pub fn step(&mut self, steps: i64) -> Result<()> {
+        let stride = self.mode.half_steps();
+        for _ in 0..steps.abs() {
+            self.phase = if steps > 0 {
+                (self.phase + stride) % HALF_STEPS.len()
+            } else {
+                (self.phase + HALF_STEPS.len() - stride) % HALF_STEPS.len()
+            };
+            self.energise(HALF_STEPS[self.phase]);
+            sleep(self.delay);
+        }
+        self.position += steps;
+        Ok(())
+    }
+    /// Turns the output shaft by the given angle, rounded to the nearest step.
+    pub fn rotate_degrees(&mut self, degrees: f64) -> Result<()> {
+        let steps = degrees / 360.0 * f64::from(self.steps_per_revolution());
+        self.step(steps.round() as i64)
+    }
+    /// Turns off all the coils so the motor stops using power and can be
+    /// turned by hand.
+    ///
+    /// The gearbox still holds it fairly well for light loads.
+    pub fn release(&mut self) {
+        self.energise(0);
+    }
+    /// Internal method to turn on exactly the coils whose bits are set.
+    fn energise(&mut self, coils: u8) {
+        for (bit, coil) in self.coils.iter_mut().enumerate() {
+            if coils & (1 << bit) != 0 {
+                coil.set_high();
+            } else {
+                coil.set_low();
+            }
+        }
+    }
+}
+
+/// Insure the coils are off before exiting.
+impl Drop for Stepper {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
@@ -0,0 +1,93 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Tells systemd how a service is doing through the socket it gives units
+//! with `Type=notify`.
+//!
+//! Outside of systemd, where `NOTIFY_SOCKET` isn't set, nothing is sent so
+//! the same program runs fine from a terminal.
+//! Nothing is sent on anything but Linux either as systemd only runs there.
+
+#[cfg(target_os = "linux")]
+use crate::error::Context;
+use crate::error::Result;
+#[cfg(target_os = "linux")]
+use log::debug;
+#[cfg(target_os = "linux")]
+use std::{env, os::unix::net::UnixDatagram};
+
+/// Environment variable systemd puts the path of its socket in.
+pub const SOCKET_VAR: &str = "NOTIFY_SOCKET";
+
+/// Sends a state like `READY=1` to systemd.
+///
+/// Gives false when not started by systemd.
+#[cfg(target_os = "linux")]
+pub fn notify(state: &str) -> Result<bool> {
+    let path = match env::var_os(SOCKET_VAR) {
+        Some(path) => path,
+        None => return Ok(false),
+    };
+    debug!(target: "systemd", "Sending {}", state);
+    let socket = UnixDatagram::unbound().context("Failed to make systemd notify socket")?;
+    let bytes = path.to_string_lossy().into_owned();
+    // A leading @ means a socket in the abstract namespace.
+    match bytes.strip_prefix('@') {
+        Some(name) => {
+            use std::os::{linux::net::SocketAddrExt, unix::net::SocketAddr};
+            let address = SocketAddr::from_abstract_name(name.as_bytes())
+                .context("Failed to get systemd notify socket address")?;
+            socket.send_to_addr(state.as_bytes(), &address)
+        }
+        None => socket.send_to(state.as_bytes(), &bytes),
+    }
+    .context(format!("Failed to tell systemd {}", state))?;
+    Ok(true)
+}
+
+/// Sends a state like `READY=1` to systemd.
+///
+/// Always gives false as there is no systemd to tell here.
+#[cfg(not(target_os = "linux"))]
+pub fn notify(_state: &str) -> Result<bool> {
+    Ok(false)
+}
+
+/// Tells systemd the service has finished starting.
+pub fn ready() -> Result<bool> {
+    notify("READY=1")
+}
+
+/// Tells systemd the service is reloading its settings, it has to send
+/// [ready()] again once done.
+pub fn reloading() -> Result<bool> {
+    notify("RELOADING=1")
+}
+
+/// Tells systemd the service is shutting down.
+pub fn stopping() -> Result<bool> {
+    notify("STOPPING=1")
+}
+
+/// Gives systemd a line of text to show in `systemctl status`.
+pub fn status(text: &str) -> Result<bool> {
+    notify(&format!("STATUS={}", text))
+}
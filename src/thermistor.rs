@@ -0,0 +1,146 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Result, SuperKitError};
+use crate::Adc;
+
+// 0°C in kelvin.
+const ZERO_CELSIUS: f64 = 273.15;
+// Values for the kit's 10kΩ NTC thermistor and series resistor.
+const BETA: f64 = 3950.0;
+const NOMINAL_RESISTANCE: f64 = 10_000.0;
+const NOMINAL_TEMPERATURE: f64 = 25.0;
+const SERIES_RESISTANCE: f64 = 10_000.0;
+
+/// Which side of the voltage divider the thermistor is on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Divider {
+    /// Series resistor to the supply and thermistor to ground like in the kit
+    /// so the reading goes down as it warms up.
+    ThermistorLow,
+    /// Thermistor to the supply and series resistor to ground.
+    ThermistorHigh,
+}
+
+/// How resistance is turned into temperature.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Conversion {
+    /// Simple Beta equation from the data sheet's β, and the resistance at a
+    /// nominal temperature in °C (usually 25°C).
+    Beta {
+        beta: f64,
+        nominal_resistance: f64,
+        nominal_temperature: f64,
+    },
+    /// Full Steinhart–Hart equation `1/T = A + B ln(R) + C ln(R)³` which is
+    /// more accurate over a wide range once the coefficients are known.
+    SteinhartHart { a: f64, b: f64, c: f64 },
+}
+
+impl Conversion {
+    /// Temperature in kelvin for a resistance in ohms.
+    pub fn kelvin(&self, resistance: f64) -> f64 {
+        match *self {
+            Conversion::Beta {
+                beta,
+                nominal_resistance,
+                nominal_temperature,
+            } => {
+                1.0 / ((resistance / nominal_resistance).ln() / beta
+                    + 1.0 / (nominal_temperature + ZERO_CELSIUS))
+            }
+            Conversion::SteinhartHart { a, b, c } => {
+                let ln_r = resistance.ln();
+                1.0 / (a + b * ln_r + c * ln_r.powi(3))
+            }
+        }
+    }
+}
+
+/// Structure used to model a NTC thermistor in a voltage divider read by one
+/// channel of an [Adc].
+///
+/// The ADC is passed in for each reading so other sensors can share its other
+/// channels.
+/// Defaults suit the kit's 10kΩ β=3950 thermistor with a 10kΩ series resistor.
+#[derive(Clone, Copy, Debug)]
+pub struct Thermistor {
+    channel: u8,
+    series_resistance: f64,
+    divider: Divider,
+    conversion: Conversion,
+}
+
+impl Thermistor {
+    /// Makes a thermistor on the given ADC channel with the default values.
+    pub fn new(channel: u8) -> Self {
+        Thermistor {
+            channel,
+            series_resistance: SERIES_RESISTANCE,
+            divider: Divider::ThermistorLow,
+            conversion: Conversion::Beta {
+                beta: BETA,
+                nominal_resistance: NOMINAL_RESISTANCE,
+                nominal_temperature: NOMINAL_TEMPERATURE,
+            },
+        }
+    }
+    /// Changes the value in ohms of the other resistor in the divider.
+    pub fn set_series_resistance(&mut self, ohms: f64) -> Result<()> {
+        if !ohms.is_finite() || ohms <= 0.0 {
+            return Err(SuperKitError::InvalidArgument(format!(
+                "Series resistance {} has to be above zero",
+                ohms
+            )));
+        }
+        self.series_resistance = ohms;
+        Ok(())
+    }
+    /// Changes which side of the divider the thermistor is on.
+    pub fn set_divider(&mut self, divider: Divider) {
+        self.divider = divider;
+    }
+    /// Changes how resistance is turned into temperature.
+    pub fn set_conversion(&mut self, conversion: Conversion) {
+        self.conversion = conversion;
+    }
+    /// Reads the thermistor's resistance in ohms.
+    pub fn resistance<A: Adc>(&self, adc: &mut A) -> Result<f64> {
+        let ratio = adc.read_ratio(self.channel)?;
+        if ratio <= 0.0 || ratio >= 1.0 {
+            return Err(SuperKitError::InvalidArgument(format!("Thermistor reading {} is at the end of the range, check for an open or short circuit",
+                ratio)));
+        }
+        Ok(match self.divider {
+            Divider::ThermistorLow => self.series_resistance * ratio / (1.0 - ratio),
+            Divider::ThermistorHigh => self.series_resistance * (1.0 - ratio) / ratio,
+        })
+    }
+    /// Reads the temperature in °C.
+    pub fn read_celsius<A: Adc>(&self, adc: &mut A) -> Result<f64> {
+        Ok(self.conversion.kelvin(self.resistance(adc)?) - ZERO_CELSIUS)
+    }
+    /// Reads the temperature in °F.
+    pub fn read_fahrenheit<A: Adc>(&self, adc: &mut A) -> Result<f64> {
+        Ok(self.read_celsius(adc)? * 9.0 / 5.0 + 32.0)
+    }
+}
@@ -0,0 +1,100 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::Result;
+use crate::{Button, ButtonEvent};
+use std::time::Duration;
+
+/// Default time the ball has to stay put before a change is believed. Much
+/// longer than for a button since the ball rolls and bounces for a while.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Which way up a [TiltSwitch] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    Upright,
+    Tilted,
+}
+
+impl From<ButtonEvent> for Orientation {
+    fn from(event: ButtonEvent) -> Self {
+        match event {
+            ButtonEvent::Pressed => Orientation::Upright,
+            ButtonEvent::Released => Orientation::Tilted,
+        }
+    }
+}
+
+/// Structure used to model a ball tilt switch.
+///
+/// The switch is closed, connecting the pin to ground, while upright and opens
+/// when tilted far enough for the ball to roll away from the contacts.
+/// The ball chatters a lot as it moves so changes are only reported once it
+/// has settled.
+pub struct TiltSwitch {
+    button: Button,
+}
+
+impl TiltSwitch {
+    /// Gets the GPIO pin with its pull-up turned on.
+    pub fn new(pin: u8) -> Result<Self> {
+        let mut button = Button::new(pin)?;
+        button.set_debounce(DEBOUNCE);
+        Ok(TiltSwitch { button })
+    }
+    /// Changes how long the ball has to stay put before a change is believed.
+    /// The default is 100ms.
+    pub fn set_debounce(&mut self, window: Duration) {
+        self.button.set_debounce(window);
+    }
+    /// Gives the settled orientation.
+    pub fn orientation(&self) -> Orientation {
+        if self.button.is_pressed() {
+            Orientation::Upright
+        } else {
+            Orientation::Tilted
+        }
+    }
+    /// Reads the switch and gives the new orientation if it has changed and
+    /// settled.
+    ///
+    /// Needs to be called every few milliseconds.
+    /// Don't mix this with [TiltSwitch::on_change()].
+    pub fn poll(&mut self) -> Option<Orientation> {
+        self.button.poll().map(Orientation::from)
+    }
+    /// Calls the callback from a background thread each time the orientation
+    /// changes.
+    ///
+    /// Replaces any callback given before.
+    pub fn on_change<C>(&mut self, mut callback: C) -> Result<()>
+    where
+        C: FnMut(Orientation) + Send + 'static,
+    {
+        self.button
+            .on_event(move |event| callback(Orientation::from(event)))
+    }
+    /// Stops calling the callback given to [TiltSwitch::on_change()].
+    pub fn clear_change(&mut self) -> Result<()> {
+        self.button.clear_event()
+    }
+}
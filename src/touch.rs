@@ -0,0 +1,125 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::Result;
+use crate::{Button, ButtonEvent};
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    sync::Arc,
+    time::Duration,
+};
+
+/// Things a [TouchSensor] reports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TouchEvent {
+    Touched,
+    Released,
+    /// In toggle mode each touch flips the state instead, on or off.
+    Toggled(bool),
+}
+
+/// Structure used to model a TTP223 capacitive touch sensor.
+///
+/// The sensor drives its output high while touched so no pull-up is needed.
+/// It is debounced the same way as a [Button] since a finger hovering at the
+/// edge of the pad can make the output chatter.
+pub struct TouchSensor {
+    button: Button,
+    toggle: bool,
+    on: Arc<AtomicBool>,
+}
+
+impl TouchSensor {
+    /// Gets the GPIO pin the sensor's output is connected to.
+    pub fn new(pin: u8) -> Result<Self> {
+        Ok(TouchSensor {
+            button: Button::with_polarity(pin, false)?,
+            toggle: false,
+            on: Arc::new(AtomicBool::new(false)),
+        })
+    }
+    /// Changes how long the output has to stay still before a change is
+    /// believed. The default is 20ms.
+    pub fn set_debounce(&mut self, window: Duration) {
+        self.button.set_debounce(window);
+    }
+    /// Turns toggle mode on or off.
+    ///
+    /// In toggle mode each touch gives [TouchEvent::Toggled] and releases are
+    /// ignored, turning the sensor into an on/off switch.
+    /// Takes effect for the next call to [TouchSensor::on_event()].
+    pub fn set_toggle_mode(&mut self, toggle: bool) {
+        self.toggle = toggle;
+    }
+    /// Checks if the pad is being touched right now.
+    pub fn is_touched(&self) -> bool {
+        self.button.is_pressed()
+    }
+    /// Checks the toggle mode state.
+    pub fn is_on(&self) -> bool {
+        self.on.load(Ordering::SeqCst)
+    }
+    /// Sets the toggle mode state without a touch.
+    pub fn set_on(&self, on: bool) {
+        self.on.store(on, Ordering::SeqCst);
+    }
+    /// Reads the sensor and gives any event now that it has settled.
+    ///
+    /// Needs to be called every few milliseconds.
+    /// Don't mix this with [TouchSensor::on_event()].
+    pub fn poll(&mut self) -> Option<TouchEvent> {
+        let event = self.button.poll()?;
+        translate(event, self.toggle, &self.on)
+    }
+    /// Calls the callback from a background thread for each event.
+    ///
+    /// Replaces any callback given before.
+    pub fn on_event<C>(&mut self, mut callback: C) -> Result<()>
+    where
+        C: FnMut(TouchEvent) + Send + 'static,
+    {
+        let toggle = self.toggle;
+        let on = self.on.clone();
+        self.button.on_event(move |event| {
+            if let Some(event) = translate(event, toggle, &on) {
+                callback(event);
+            }
+        })
+    }
+    /// Stops calling the callback given to [TouchSensor::on_event()].
+    pub fn clear_event(&mut self) -> Result<()> {
+        self.button.clear_event()
+    }
+}
+
+/// Internal function to turn a debounced button event into a touch event,
+/// flipping the toggle state in toggle mode.
+fn translate(event: ButtonEvent, toggle: bool, on: &AtomicBool) -> Option<TouchEvent> {
+    match (event, toggle) {
+        (ButtonEvent::Pressed, false) => Some(TouchEvent::Touched),
+        (ButtonEvent::Released, false) => Some(TouchEvent::Released),
+        (ButtonEvent::Pressed, true) => {
+            Some(TouchEvent::Toggled(!on.fetch_xor(true, Ordering::SeqCst)))
+        }
+        (ButtonEvent::Released, true) => None,
+    }
+}
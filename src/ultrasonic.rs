@@ -0,0 +1,140 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{Context, Result, SuperKitError};
+use crate::hal::gpio::{Gpio, InputPin, OutputPin};
+use crate::registry::{self, PinClaim};
+use std::{
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+// Length of the trigger pulse, the sensor needs at least 10µs.
+const TRIGGER_PULSE: Duration = Duration::from_micros(10);
+// Shortest time between pings so echoes from the last one have died away.
+const MIN_INTERVAL: Duration = Duration::from_millis(60);
+/// Default longest echo waited for, a little over the sensor's 4m range.
+const TIMEOUT: Duration = Duration::from_millis(30);
+// Default air temperature in °C used for the speed of sound.
+const TEMPERATURE: f64 = 20.0;
+
+/// Structure used to model a HC-SR04 ultrasonic distance sensor.
+///
+/// The echo pin gives 5V so needs a voltage divider, like 1kΩ and 2kΩ, before
+/// it goes to the Pi.
+/// The echo pulse is timed by watching the pin in a tight loop which gives
+/// about millimetre precision as long as Linux doesn't step in part way
+/// through.
+pub struct Ultrasonic {
+    trigger: OutputPin,
+    echo: InputPin,
+    temperature: f64,
+    timeout: Duration,
+    last_ping: Option<Instant>,
+    _claim: PinClaim,
+}
+
+impl Ultrasonic {
+    /// Gets the given trigger and echo pins.
+    pub fn new(trigger: u8, echo: u8) -> Result<Self> {
+        let claim = registry::claim("Ultrasonic", &[trigger, echo])?;
+        let gpio = Gpio::new().map_err(SuperKitError::GpioInit)?;
+        let mut trigger = gpio
+            .get(trigger)
+            .context("Failed to get trigger pin")?
+            .into_output();
+        trigger.set_low();
+        let echo = gpio
+            .get(echo)
+            .context("Failed to get echo pin")?
+            .into_input();
+        Ok(Ultrasonic {
+            trigger,
+            echo,
+            temperature: TEMPERATURE,
+            timeout: TIMEOUT,
+            last_ping: None,
+            _claim: claim,
+        })
+    }
+    /// Sets the air temperature in °C used to work out the speed of sound.
+    ///
+    /// Sound goes about 0.6m/s faster for each degree warmer so this is worth
+    /// doing with a temperature sensor handy. The default is 20°C.
+    pub fn set_temperature(&mut self, celsius: f64) {
+        self.temperature = celsius;
+    }
+    /// Changes the longest time an echo is waited for before giving up.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+    /// Speed of sound in m/s at the current temperature.
+    pub fn speed_of_sound(&self) -> f64 {
+        331.3 + 0.606 * self.temperature
+    }
+    /// Sends a ping and times how long the echo pulse is.
+    pub fn measure(&mut self) -> Result<Duration> {
+        if let Some(last) = self.last_ping {
+            if let Some(wait) = MIN_INTERVAL.checked_sub(last.elapsed()) {
+                sleep(wait);
+            }
+        }
+        self.last_ping = Some(Instant::now());
+        if self.echo.is_high() {
+            return Err(SuperKitError::Device(
+                "Echo pin is still high from an earlier ping".to_string(),
+            ));
+        }
+        self.trigger.set_high();
+        Self::spin(TRIGGER_PULSE);
+        self.trigger.set_low();
+        let start = Instant::now();
+        while self.echo.is_low() {
+            if start.elapsed() > self.timeout {
+                return Err(SuperKitError::Timeout(
+                    "No echo pulse started, check the wiring".to_string(),
+                ));
+            }
+        }
+        let rise = Instant::now();
+        while self.echo.is_high() {
+            if rise.elapsed() > self.timeout {
+                return Err(SuperKitError::Timeout(
+                    "No echo came back, nothing in range".to_string(),
+                ));
+            }
+        }
+        Ok(rise.elapsed())
+    }
+    /// Measures the distance to whatever is in front in cm.
+    pub fn distance_cm(&mut self) -> Result<f64> {
+        let echo = self.measure()?;
+        // The sound goes there and back so only half the time counts.
+        Ok(echo.as_secs_f64() * self.speed_of_sound() * 100.0 / 2.0)
+    }
+    /// Internal associative method (function) to wait a very short time more
+    /// exactly than sleep() can.
+    fn spin(duration: Duration) {
+        let start = Instant::now();
+        while start.elapsed() < duration {}
+    }
+}
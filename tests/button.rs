@@ -0,0 +1,175 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+#![cfg(feature = "mock")]
+
+mod common;
+
+use std::time::Duration;
+use superkit_rust_code_for_raspberrypi::{
+    hal::{
+        gpio::Level,
+        mock::{clock, gpio},
+    },
+    Button, ButtonEvent, Gesture,
+};
+
+const PIN: u8 = 22;
+
+/// Internal function to move the fake clock on by some milliseconds.
+fn advance(ms: u64) {
+    clock::advance(Duration::from_millis(ms));
+}
+
+/// Internal function to get a kit button with the clock stopped.
+fn button() -> Button {
+    clock::freeze();
+    Button::new(PIN).unwrap()
+}
+
+#[test]
+fn starts_released_with_pull_up() {
+    let _guard = common::setup();
+    let mut button = button();
+    assert!(!button.is_pressed());
+    assert_eq!(button.poll(), None);
+}
+
+#[test]
+fn press_counts_after_debounce_window() {
+    let _guard = common::setup();
+    let mut button = button();
+    gpio::set_level(PIN, Level::Low);
+    assert_eq!(button.poll(), None);
+    advance(19);
+    assert_eq!(button.poll(), None);
+    assert!(!button.is_pressed());
+    advance(1);
+    assert_eq!(button.poll(), Some(ButtonEvent::Pressed));
+    assert!(button.is_pressed());
+    // Only reported once.
+    advance(100);
+    assert_eq!(button.poll(), None);
+}
+
+#[test]
+fn bounce_restarts_the_window() {
+    let _guard = common::setup();
+    let mut button = button();
+    for _ in 0..3 {
+        gpio::set_level(PIN, Level::Low);
+        assert_eq!(button.poll(), None);
+        advance(5);
+        gpio::set_level(PIN, Level::High);
+        assert_eq!(button.poll(), None);
+        advance(5);
+    }
+    gpio::set_level(PIN, Level::Low);
+    assert_eq!(button.poll(), None);
+    advance(15);
+    assert_eq!(button.poll(), None);
+    advance(5);
+    assert_eq!(button.poll(), Some(ButtonEvent::Pressed));
+}
+
+#[test]
+fn release_is_debounced_too() {
+    let _guard = common::setup();
+    let mut button = button();
+    gpio::set_level(PIN, Level::Low);
+    button.poll();
+    advance(20);
+    assert_eq!(button.poll(), Some(ButtonEvent::Pressed));
+    gpio::set_level(PIN, Level::High);
+    assert_eq!(button.poll(), None);
+    advance(10);
+    assert_eq!(button.poll(), None);
+    advance(10);
+    assert_eq!(button.poll(), Some(ButtonEvent::Released));
+}
+
+#[test]
+fn debounce_window_can_be_changed() {
+    let _guard = common::setup();
+    let mut button = button();
+    button.set_debounce(Duration::from_millis(50));
+    gpio::set_level(PIN, Level::Low);
+    button.poll();
+    advance(49);
+    assert_eq!(button.poll(), None);
+    advance(1);
+    assert_eq!(button.poll(), Some(ButtonEvent::Pressed));
+}
+
+#[test]
+fn active_high_inputs() {
+    let _guard = common::setup();
+    clock::freeze();
+    let mut button = Button::with_polarity(PIN, false).unwrap();
+    gpio::set_level(PIN, Level::High);
+    button.poll();
+    advance(20);
+    assert_eq!(button.poll(), Some(ButtonEvent::Pressed));
+}
+
+/// Internal function to press or release the button and let it settle.
+fn settle(button: &mut Button, level: Level) -> Option<Gesture> {
+    gpio::set_level(PIN, level);
+    button.poll_gesture();
+    advance(20);
+    button.poll_gesture()
+}
+
+#[test]
+fn short_press_is_a_click_once_double_click_time_passes() {
+    let _guard = common::setup();
+    let mut button = button();
+    assert_eq!(settle(&mut button, Level::Low), None);
+    advance(100);
+    assert_eq!(settle(&mut button, Level::High), None);
+    advance(300);
+    assert_eq!(button.poll_gesture(), None);
+    advance(1);
+    assert_eq!(button.poll_gesture(), Some(Gesture::Click));
+}
+
+#[test]
+fn two_quick_presses_are_a_double_click() {
+    let _guard = common::setup();
+    let mut button = button();
+    settle(&mut button, Level::Low);
+    settle(&mut button, Level::High);
+    advance(100);
+    settle(&mut button, Level::Low);
+    assert_eq!(settle(&mut button, Level::High), Some(Gesture::DoubleClick));
+}
+
+#[test]
+fn holding_down_is_a_long_press() {
+    let _guard = common::setup();
+    let mut button = button();
+    settle(&mut button, Level::Low);
+    advance(700);
+    assert_eq!(button.poll_gesture(), None);
+    advance(100);
+    assert_eq!(button.poll_gesture(), Some(Gesture::LongPress));
+    assert_eq!(settle(&mut button, Level::High), None);
+}
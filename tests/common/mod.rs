@@ -0,0 +1,70 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Setup shared by the tests that run against the mock backend.
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use superkit_rust_code_for_raspberrypi::hal::{
+    gpio::Level,
+    mock::{self, Operation},
+};
+
+lazy_static::lazy_static! {
+    static ref LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Gives a test the fakes to itself starting from a clean slate.
+///
+/// The fakes are shared by the whole test program while tests run in
+/// parallel, so hold on to the guard until the test is done.
+pub fn setup() -> MutexGuard<'static, ()> {
+    let guard = LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    mock::reset();
+    guard
+}
+
+/// Decodes the bytes a 74HC595 on the given pins latched from the records
+/// so far.
+#[allow(dead_code)]
+pub fn latched_bytes(sdi: u8, rclk: u8, srclk: u8) -> Vec<u8> {
+    let mut data = Level::Low;
+    let mut shift = 0u8;
+    let mut latched = Vec::new();
+    let mut levels = [Level::Low; 2];
+    for operation in mock::operations() {
+        if let Operation::Write { pin, level } = operation {
+            if pin == sdi {
+                data = level;
+            } else if pin == srclk {
+                if levels[0] == Level::Low && level == Level::High {
+                    shift = shift << 1 | (data == Level::High) as u8;
+                }
+                levels[0] = level;
+            } else if pin == rclk {
+                if levels[1] == Level::Low && level == Level::High {
+                    latched.push(shift);
+                }
+                levels[1] = level;
+            }
+        }
+    }
+    latched
+}
@@ -0,0 +1,157 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+#![cfg(feature = "mock")]
+
+mod common;
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use superkit_rust_code_for_raspberrypi::{
+    encoder::{Decoder, Direction},
+    hal::{
+        gpio::Level,
+        mock::{clock, gpio},
+    },
+    RotaryEncoder,
+};
+
+const CLK: u8 = 18;
+const DT: u8 = 17;
+// Pin levels (clk, dt) for one detent clockwise starting from both low.
+const CLOCKWISE: [(bool, bool); 4] = [(true, false), (true, true), (false, true), (false, false)];
+
+/// Internal function to feed levels into a decoder and collect the steps.
+fn decode(decoder: &mut Decoder, levels: &[(bool, bool)]) -> Vec<Direction> {
+    levels
+        .iter()
+        .filter_map(|(clk, dt)| decoder.update(*clk, *dt))
+        .collect()
+}
+
+#[test]
+fn every_edge_is_a_count() {
+    let mut decoder = Decoder::new(false, false, 1);
+    assert_eq!(
+        decode(&mut decoder, &CLOCKWISE),
+        vec![Direction::Clockwise; 4]
+    );
+    let mut backwards = CLOCKWISE;
+    backwards.reverse();
+    let backwards = [&backwards[1..], &[(false, false)]].concat();
+    assert_eq!(
+        decode(&mut decoder, &backwards),
+        vec![Direction::CounterClockwise; 4]
+    );
+}
+
+#[test]
+fn divider_gives_one_step_per_detent() {
+    let mut decoder = Decoder::new(false, false, 4);
+    assert_eq!(decode(&mut decoder, &CLOCKWISE), vec![Direction::Clockwise]);
+    assert_eq!(decode(&mut decoder, &CLOCKWISE[..3]), vec![]);
+}
+
+#[test]
+fn large_divider_counts_every_edge() {
+    let mut decoder = Decoder::new(false, false, 200);
+    let mut steps = Vec::new();
+    for _ in 0..50 {
+        steps.extend(decode(&mut decoder, &CLOCKWISE));
+    }
+    assert_eq!(steps, vec![Direction::Clockwise]);
+    let mut decoder = Decoder::new(false, false, 255);
+    for _ in 0..63 {
+        assert_eq!(decode(&mut decoder, &CLOCKWISE), vec![]);
+    }
+    assert_eq!(
+        decode(&mut decoder, &CLOCKWISE[..3]),
+        vec![Direction::Clockwise]
+    );
+}
+
+#[test]
+fn bounce_on_one_pin_cancels_out() {
+    let mut decoder = Decoder::new(false, false, 1);
+    let levels = [(true, false), (false, false), (true, false), (false, false)];
+    assert_eq!(
+        decode(&mut decoder, &levels),
+        vec![
+            Direction::Clockwise,
+            Direction::CounterClockwise,
+            Direction::Clockwise,
+            Direction::CounterClockwise
+        ]
+    );
+}
+
+#[test]
+fn missed_states_are_ignored() {
+    let mut decoder = Decoder::new(false, false, 1);
+    assert_eq!(decoder.update(true, true), None);
+    assert_eq!(decoder.invalid(), 1);
+    assert_eq!(decoder.update(false, true), Some(Direction::Clockwise));
+}
+
+/// Internal function to turn the fake knob through a list of levels.
+fn turn(levels: &[(bool, bool)]) {
+    let level = |high: bool| if high { Level::High } else { Level::Low };
+    for (clk, dt) in levels.iter() {
+        gpio::set_level(CLK, level(*clk));
+        gpio::set_level(DT, level(*dt));
+    }
+}
+
+#[test]
+fn encoder_counts_from_interrupts() {
+    let _guard = common::setup();
+    let mut encoder = RotaryEncoder::with_pins(CLK, DT).unwrap();
+    let turns = Arc::new(Mutex::new(Vec::new()));
+    let t = turns.clone();
+    encoder.on_turn(move |direction, value| t.lock().unwrap().push((direction, value)));
+    turn(&CLOCKWISE);
+    assert_eq!(encoder.value(), 4);
+    encoder.set_divider(4).unwrap();
+    turn(&CLOCKWISE);
+    turn(&CLOCKWISE);
+    assert_eq!(encoder.value(), 6);
+    let turns = turns.lock().unwrap();
+    assert_eq!(turns.len(), 6);
+    assert_eq!(turns[5], (Direction::Clockwise, 6));
+}
+
+#[test]
+fn velocity_follows_the_clock() {
+    let _guard = common::setup();
+    clock::freeze();
+    let mut encoder = RotaryEncoder::with_pins(CLK, DT).unwrap();
+    encoder.set_divider(4).unwrap();
+    assert_eq!(encoder.velocity(), 0.0);
+    for _ in 0..4 {
+        clock::advance(Duration::from_millis(50));
+        turn(&CLOCKWISE);
+    }
+    assert!(encoder.velocity() > 4.0);
+    clock::advance(Duration::from_millis(300));
+    assert_eq!(encoder.velocity(), 0.0);
+}
@@ -0,0 +1,112 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+#![cfg(all(feature = "mock", feature = "shift-register"))]
+
+mod common;
+
+use superkit_rust_code_for_raspberrypi::{
+    hal::{
+        gpio::Level,
+        mock::{self, Operation},
+    },
+    SharedHC595, HC595,
+};
+
+const SDI: u8 = 17;
+const RCLK: u8 = 18;
+const SRCLK: u8 = 27;
+
+#[test]
+fn serial_in_shifts_most_significant_bit_first() {
+    let _guard = common::setup();
+    let mut hc595 = HC595::with_pins(SDI, RCLK, SRCLK).unwrap();
+    mock::clear_records();
+    hc595.serial_in(0b1010_0001);
+    // Data has to be on sdi each time the shift clock rises.
+    let mut data = Level::Low;
+    let mut bits = Vec::new();
+    for operation in mock::operations() {
+        match operation {
+            Operation::Write { pin: SDI, level } => data = level,
+            Operation::Write {
+                pin: SRCLK,
+                level: Level::High,
+            } => bits.push(data == Level::High),
+            _ => {}
+        }
+    }
+    assert_eq!(
+        bits,
+        [true, false, true, false, false, false, false, true].to_vec()
+    );
+}
+
+#[test]
+fn outputs_only_change_when_latched() {
+    let _guard = common::setup();
+    let mut hc595 = HC595::with_pins(SDI, RCLK, SRCLK).unwrap();
+    hc595.serial_in(0x3c);
+    assert!(common::latched_bytes(SDI, RCLK, SRCLK).is_empty());
+    hc595.parallel_out();
+    assert_eq!(common::latched_bytes(SDI, RCLK, SRCLK), vec![0x3c]);
+}
+
+#[test]
+fn lesson_10_byte_sequence() {
+    let _guard = common::setup();
+    let mut hc595 = HC595::with_pins(SDI, RCLK, SRCLK).unwrap();
+    let bytes = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80];
+    for byte in bytes.iter() {
+        hc595.serial_in(*byte);
+        hc595.parallel_out();
+    }
+    assert_eq!(common::latched_bytes(SDI, RCLK, SRCLK), bytes.to_vec());
+}
+
+#[test]
+fn drop_clears_outputs_and_pins() {
+    let _guard = common::setup();
+    let mut hc595 = HC595::with_pins(SDI, RCLK, SRCLK).unwrap();
+    hc595.serial_in(0xff);
+    hc595.parallel_out();
+    drop(hc595);
+    assert_eq!(common::latched_bytes(SDI, RCLK, SRCLK), vec![0xff, 0x00]);
+    for pin in [SDI, RCLK, SRCLK].iter() {
+        assert_eq!(mock::gpio::level(*pin), Level::Low);
+    }
+}
+
+#[test]
+fn write_all_latches_once_after_every_byte() {
+    let _guard = common::setup();
+    let hc595 = SharedHC595::with_pins(SDI, RCLK, SRCLK).unwrap();
+    hc595.write_all(&[0x12, 0x34]);
+    // A single chip only keeps the last byte shifted in.
+    assert_eq!(common::latched_bytes(SDI, RCLK, SRCLK), vec![0x34]);
+}
+
+#[test]
+fn pins_already_claimed_are_refused() {
+    let _guard = common::setup();
+    let _hc595 = HC595::with_pins(SDI, RCLK, SRCLK).unwrap();
+    assert!(HC595::with_pins(SDI, 5, 6).is_err());
+}
@@ -0,0 +1,60 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+#![cfg(feature = "mock")]
+
+mod common;
+
+use superkit_rust_code_for_raspberrypi::{
+    hal::mock::{self, Operation},
+    NeoPixel,
+};
+
+/// Internal function to get the size of the last SPI transfer.
+fn last_transfer() -> usize {
+    mock::operations()
+        .into_iter()
+        .rev()
+        .find_map(|operation| match operation {
+            Operation::SpiTransfer { written, .. } => Some(written.len()),
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn rgbw_too_big_leaves_mode_alone() {
+    let _guard = common::setup();
+    // 300 pixels fit in one transfer as RGB but not as RGBW.
+    let mut strip = NeoPixel::new(300).unwrap();
+    assert!(strip.set_rgbw(true).is_err());
+    strip.show().unwrap();
+    assert_eq!(last_transfer(), 300 * 3 * 4 + 120);
+}
+
+#[test]
+fn rgbw_sends_a_white_byte() {
+    let _guard = common::setup();
+    let mut strip = NeoPixel::new(10).unwrap();
+    strip.set_rgbw(true).unwrap();
+    strip.show().unwrap();
+    assert_eq!(last_transfer(), 10 * 4 * 4 + 120);
+}
@@ -0,0 +1,141 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+#![cfg(all(feature = "mock", feature = "shift-register"))]
+
+mod common;
+
+use std::{
+    thread::sleep,
+    time::{Duration, Instant},
+};
+use superkit_rust_code_for_raspberrypi::{
+    hal::{
+        gpio::Level,
+        mock::{self, Operation},
+    },
+    segment::{encode_char, encode_str, BLANK},
+    SegmentDisplay, SuperKitError,
+};
+
+// Digit select pins, left to right.
+const SELECTS: [u8; 4] = [5, 6, 13, 19];
+// Default 74HC595 pins used by the display.
+const SDI: u8 = 17;
+const RCLK: u8 = 18;
+const SRCLK: u8 = 27;
+
+#[test]
+fn digits_match_lesson_11() {
+    let codes = encode_str("0123456789").unwrap();
+    assert_eq!(
+        codes,
+        vec![0x3f, 0x06, 0x5b, 0x4f, 0x66, 0x6d, 0x7d, 0x07, 0x7f, 0x6f]
+    );
+}
+
+#[test]
+fn hex_digits_and_words() {
+    assert_eq!(
+        encode_str("AbCdEF").unwrap(),
+        vec![0x77, 0x7c, 0x39, 0x5e, 0x79, 0x71]
+    );
+    assert_eq!(encode_str("HELP").unwrap(), vec![0x76, 0x79, 0x38, 0x73]);
+    assert_eq!(encode_str("-").unwrap(), vec![0x40]);
+    assert_eq!(encode_char(' ').unwrap(), BLANK);
+}
+
+#[test]
+fn undisplayable_characters_are_errors() {
+    for c in ['W', 'M', 'X', 'K', '?'].iter() {
+        match encode_char(*c) {
+            Err(SuperKitError::InvalidArgument(_)) => {}
+            other => panic!("'{}' gave {:?}", c, other),
+        }
+    }
+    assert!(encode_str("HOW").is_err());
+}
+
+/// Watches the refresh thread until every digit has been lit, giving the
+/// code last seen on each.
+fn scanned() -> [Option<u8>; 4] {
+    // Let any scan started before the change finish first.
+    sleep(Duration::from_millis(20));
+    mock::clear_records();
+    let deadline = Instant::now() + Duration::from_secs(2);
+    loop {
+        let mut seen = [None; 4];
+        let latched = common::latched_bytes(SDI, RCLK, SRCLK);
+        let mut latches = 0;
+        let mut rclk = Level::Low;
+        for operation in mock::operations() {
+            match operation {
+                Operation::Write { pin: RCLK, level } => {
+                    if rclk == Level::Low && level == Level::High {
+                        latches += 1;
+                    }
+                    rclk = level;
+                }
+                Operation::Write {
+                    pin,
+                    level: Level::Low,
+                } if latches > 0 => {
+                    if let Some(digit) = SELECTS.iter().position(|select| *select == pin) {
+                        seen[digit] = Some(latched[latches - 1]);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if seen.iter().all(Option::is_some) || Instant::now() > deadline {
+            return seen;
+        }
+        sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn show_text_scans_each_digit() {
+    let _guard = common::setup();
+    let mut display = SegmentDisplay::new(SELECTS).unwrap();
+    display.show_text("HELP").unwrap();
+    assert_eq!(scanned(), [Some(0x76), Some(0x79), Some(0x38), Some(0x73)]);
+}
+
+#[test]
+fn decimal_point_joins_the_digit_before() {
+    let _guard = common::setup();
+    let mut display = SegmentDisplay::new(SELECTS).unwrap();
+    display.show_text("1.2").unwrap();
+    assert_eq!(
+        scanned(),
+        [Some(0x06 | 0x80), Some(0x5b), Some(BLANK), Some(BLANK)]
+    );
+}
+
+#[test]
+fn numbers_are_right_aligned() {
+    let _guard = common::setup();
+    let mut display = SegmentDisplay::new(SELECTS).unwrap();
+    display.show_number(-42).unwrap();
+    assert_eq!(scanned(), [Some(BLANK), Some(0x40), Some(0x66), Some(0x5b)]);
+    assert!(display.show_number(12345).is_err());
+}
@@ -0,0 +1,90 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+#![cfg(all(feature = "mock", feature = "shell"))]
+
+mod common;
+
+use superkit_rust_code_for_raspberrypi::{
+    hal::{
+        gpio::Level,
+        mock::{self, gpio, Operation},
+    },
+    shell::{self, Reply},
+    Shell,
+};
+
+const PIN: u8 = 17;
+
+#[test]
+fn pin_commands_set_and_read_pins() {
+    let _guard = common::setup();
+    let mut shell = Shell::new();
+    assert_eq!(shell.execute("pin 17 high").unwrap(), Reply::Done);
+    assert!(mock::operations().contains(&Operation::Write {
+        pin: PIN,
+        level: Level::High,
+    }));
+    assert_eq!(
+        shell.execute("pin 17").unwrap(),
+        Reply::Show("GPIO 17 is set high".to_string())
+    );
+    // Reading lets go of the pin as an output first.
+    shell.execute("release 17").unwrap();
+    gpio::set_level(PIN, Level::High);
+    assert_eq!(
+        shell.execute("pin 17 read").unwrap(),
+        Reply::Show("GPIO 17 is high".to_string())
+    );
+}
+
+#[test]
+fn mistakes_are_errors_not_panics() {
+    let _guard = common::setup();
+    let mut shell = Shell::new();
+    assert!(shell.execute("pin 17 sideways").is_err());
+    assert!(shell.execute("pwm 18 150%").is_err());
+    assert!(shell.execute("lcd \"unclosed").is_err());
+    assert!(shell.execute("frobnicate").is_err());
+    assert_eq!(shell.execute("  ").unwrap(), Reply::Done);
+    assert_eq!(shell.execute("quit").unwrap(), Reply::Quit);
+}
+
+#[test]
+fn quotes_keep_words_together() {
+    assert_eq!(
+        shell::split(r#"lcd "hello  world" now"#).unwrap(),
+        vec!["lcd", "hello  world", "now"]
+    );
+}
+
+#[test]
+fn completes_commands_and_their_words() {
+    assert_eq!(
+        shell::complete("p"),
+        (
+            0,
+            vec!["pin".to_string(), "pwm".to_string(), "pins".to_string()]
+        )
+    );
+    assert_eq!(shell::complete("pin 17 h"), (7, vec!["high".to_string()]));
+    assert_eq!(shell::complete("adc "), (4, vec!["read".to_string()]));
+}
@@ -0,0 +1,59 @@
+// MIT License
+//
+// Copyright © 2020-present, Michael Cummings <mgcummings@yahoo.com>.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+#![cfg(feature = "analog")]
+
+use std::time::Duration;
+use superkit_rust_code_for_raspberrypi::{error::Result, Adc, SoundSensor};
+
+/// Internal structure for an ADC that always reads the middle of its range.
+struct Quiet;
+
+impl Adc for Quiet {
+    fn channels(&self) -> u8 {
+        1
+    }
+    fn max_value(&self) -> u16 {
+        255
+    }
+    fn read(&mut self, _channel: u8) -> Result<u16> {
+        Ok(128)
+    }
+}
+
+#[test]
+fn sample_rate_has_to_be_a_number_above_zero() {
+    let mut sensor = SoundSensor::new(0);
+    assert!(sensor.set_sample_rate(0.0).is_err());
+    assert!(sensor.set_sample_rate(-1.0).is_err());
+    assert!(sensor.set_sample_rate(f64::NAN).is_err());
+    assert!(sensor.set_sample_rate(f64::INFINITY).is_err());
+    assert!(sensor.set_sample_rate(2000.0).is_ok());
+}
+
+#[test]
+fn huge_sample_rate_still_finishes() {
+    let mut sensor = SoundSensor::new(0);
+    sensor.set_sample_rate(f64::MAX).unwrap();
+    sensor.set_window(Duration::from_millis(1));
+    let envelope = sensor.measure(&mut Quiet).unwrap();
+    assert_eq!(envelope.rms, 0.0);
+}